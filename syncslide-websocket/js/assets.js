@@ -0,0 +1,13 @@
+// Copy buttons for the Markdown of uploaded images and media on the edit page.
+document.querySelectorAll('.copy-asset').forEach((btn) => {
+	btn.addEventListener('click', async () => {
+		const markdown = document.getElementById(btn.dataset.target).textContent;
+		const announce = document.getElementById('asset-announce');
+		try {
+			await navigator.clipboard.writeText(markdown);
+			announce.textContent = 'Markdown copied.';
+		} catch (e) {
+			announce.textContent = 'Copying failed; select the Markdown and copy it by hand.';
+		}
+	});
+});
@@ -9,6 +9,23 @@ if (qrToggleBtn && qrOverlay) {
 }
 
 let TEXT_TO_RENDER = "";
+// Server revision of TEXT_TO_RENDER; null until a snapshot says which one it is.
+let TEXT_REVISION = null;
+// The slide shown and how many of its fragment steps are revealed.
+let SLIDE = 0;
+let FRAGMENT = 0;
+// The block the presenter points at, as sent by the server, or null.
+let HIGHLIGHT = null;
+// Language tag of TEXT_TO_RENDER, if the presenters set one.
+let LANG = null;
+// Translations of TEXT_TO_RENDER by language tag, slide for slide.
+let VARIANTS = {};
+// The translation this viewer follows, or null for the deck itself.
+let VIEW_LANG = null;
+// The translation the slide on screen comes from, or null for the deck itself.
+let SHOWN_VARIANT = null;
+// Whether a slide has been shown yet; until then there is nothing to redraw.
+let SLIDE_SHOWN = false;
 const presNameEl = document.getElementById('pres-name');
 function getPresName() { return presNameEl ? presNameEl.textContent.trim() : ''; }
 
@@ -18,6 +35,39 @@ function stringToDOM(htmlString) {
 return tempElement;
 }
 
+// What the block pointed at shows in the slide on screen; translations have their own.
+function highlightTarget() {
+	if (SHOWN_VARIANT !== null) return (HIGHLIGHT.variants || {})[SHOWN_VARIANT] || {};
+	return HIGHLIGHT.target || {};
+}
+
+// Marks the block the presenter points at on the slide shown; with announce, also
+// tells screen readers what it is.
+function showHighlight(announce) {
+	const htmlOutput = document.getElementById("currentSlide");
+	if (!htmlOutput) return;
+	for (const el of htmlOutput.querySelectorAll('.highlighted')) {
+		el.classList.remove('highlighted');
+		el.removeAttribute('aria-current');
+	}
+	const el = HIGHLIGHT && HIGHLIGHT.slide === SLIDE ? findBlock(htmlOutput, highlightTarget()) : null;
+	if (el) {
+		el.classList.add('highlighted');
+		el.setAttribute('aria-current', 'true');
+		el.scrollIntoView({ block: 'nearest' });
+	}
+	const announcer = document.getElementById('highlight-announce');
+	if (announce && announcer) {
+		announcer.textContent = el ? 'Presenter points at: ' + el.textContent.trim() : '';
+	}
+	if (typeof onHighlightShown === 'function') onHighlightShown();
+}
+
+// Patch offsets are string indices (UTF-16 code units), so plain slicing applies them.
+function applyPatch(text, patch) {
+	return text.slice(0, patch.start) + patch.text + text.slice(patch.end);
+}
+
 const handleUpdate = (message) => {
 	try {
 		message = JSON.parse(message.data);
@@ -30,8 +80,69 @@ const handleUpdate = (message) => {
 		}
 		return;
 	}
+	if (message.type === "question" || message.type === "questions" || message.type === "pinned_questions") {
+		if (typeof handleQuestionMessage === 'function') {
+			handleQuestionMessage(message.type, message.data);
+		}
+		return;
+	}
+	if (message.type === "cursor" || message.type === "cursor_gone") {
+		if (typeof handleCursorMessage === 'function') {
+			handleCursorMessage(message.type, message.data);
+		}
+		return;
+	}
+	if (message.type === "presence") {
+		if (typeof handlePresenceMessage === 'function') {
+			handlePresenceMessage(message.data);
+		}
+		return;
+	}
+	if (message.type === "pacing") {
+		if (typeof handlePacingMessage === 'function') {
+			handlePacingMessage(message.data);
+		}
+		return;
+	}
+	if (message.type === "caption") {
+		if (typeof handleCaptionMessage === 'function') {
+			handleCaptionMessage(message.data);
+		}
+		return;
+	}
+	if (message.type === "poll") {
+		if (typeof handlePollMessage === 'function') {
+			handlePollMessage(message.data);
+		}
+		return;
+	}
 	if (message.type === "text") {
 		TEXT_TO_RENDER = message.data;
+		TEXT_REVISION = null;
+		return;
+	}
+	if (message.type === "revision") {
+		TEXT_REVISION = message.data;
+		if (typeof onTextSnapshot === 'function') onTextSnapshot();
+		return;
+	}
+	if (message.type === "patch") {
+		const patch = message.data;
+		// Patches older than the last snapshot are already part of it.
+		if (TEXT_REVISION === null || patch.base < TEXT_REVISION) return;
+		if (patch.base > TEXT_REVISION) {
+			// Missed a patch; the text is unreliable until the next snapshot.
+			TEXT_REVISION = null;
+			return;
+		}
+		TEXT_TO_RENDER = applyPatch(TEXT_TO_RENDER, patch);
+		TEXT_REVISION = patch.base + 1;
+		if (typeof onTextPatched === 'function') onTextPatched(patch);
+		return;
+	}
+	if (message.type === "notes") {
+		const notesEl = document.getElementById('speakerNotes');
+		if (notesEl) notesEl.textContent = message.data;
 		return;
 	}
 	if (message.type === "name") {
@@ -48,16 +159,68 @@ const handleUpdate = (message) => {
 		    : `${message.data} - SyncSlide`;
 		return;
 	}
-	const slideIndex = message.data;
-	const htmlString = md.render(TEXT_TO_RENDER);
+	if (message.type === "language" || message.type === "variant") {
+		if (message.type === "language") {
+			LANG = message.data;
+		} else if (message.data.text === null) {
+			delete VARIANTS[message.data.lang];
+		} else {
+			VARIANTS[message.data.lang] = message.data.text;
+		}
+		if (typeof onLanguagesChanged === 'function') onLanguagesChanged();
+		if (SLIDE_SHOWN) renderSlide();
+		return;
+	}
+	if (message.type === "highlight") {
+		HIGHLIGHT = message.data.block === null ? null : message.data;
+		showHighlight(true);
+		return;
+	}
+	const previousSlide = SLIDE;
+	if (message.type === "fragment") {
+		SLIDE = message.data.slide;
+		FRAGMENT = message.data.fragment;
+	} else {
+		SLIDE = message.data;
+		FRAGMENT = 0;
+	}
+	// Moving to another slide drops the pointer, as it does on the server.
+	if (SLIDE !== previousSlide) HIGHLIGHT = null;
+	renderSlide();
+}
+
+// Shows the current slide in the translation this viewer follows, or in the deck itself
+// if the translation lacks the slide.
+function renderSlide() {
+	const htmlString = md.render(stripNotes(TEXT_TO_RENDER));
 	const allHtml = stringToDOM(htmlString);
 	if (typeof getH2s === 'function') {
 		getH2s(allHtml)
 	}
-	const newHtml = addSiblings(allHtml)[slideIndex];
+	let slides = addSiblings(allHtml);
+	let lang = LANG;
+	let variant = null;
+	if (VIEW_LANG !== null && VARIANTS[VIEW_LANG] !== undefined) {
+		const translated = addSiblings(stringToDOM(md.render(stripNotes(VARIANTS[VIEW_LANG]))));
+		if (translated[SLIDE]) {
+			slides = translated;
+			lang = VIEW_LANG;
+			variant = VIEW_LANG;
+		}
+	}
+	const slideHtml = slides[SLIDE];
+	markBlocks(slideHtml);
+	const newHtml = revealFragments(slideHtml, FRAGMENT).shown;
 	const htmlOutput = document.getElementById("currentSlide");
 	if (!htmlOutput) return;
+	SLIDE_SHOWN = true;
+	SHOWN_VARIANT = variant;
 	htmlOutput.innerHTML = "";
+	if (lang) {
+		htmlOutput.setAttribute('lang', lang);
+	} else {
+		htmlOutput.removeAttribute('lang');
+	}
 	const presName = getPresName();
 	if (presName) {
 		const h1 = document.createElement('h1');
@@ -69,6 +232,7 @@ const handleUpdate = (message) => {
 	}
 	updateRender();
 	markExternalLinks(htmlOutput);
+	showHighlight(false);
 }
 
 wsRegisterMessageHandler(handleUpdate);
@@ -0,0 +1,40 @@
+// Live captions from a CART captioner.
+// Captions arrive over the shared WebSocket (set up by common.js), one line at a time,
+// and are shown to everyone. Captioners send them from the form under the log.
+
+(function () {
+  const panel = document.getElementById('captions-panel');
+  const log = document.getElementById('captions-log');
+  const readAloud = document.getElementById('captions-read-aloud');
+  const form = document.getElementById('caption-form');
+  const input = document.getElementById('caption-text');
+
+  if (!panel) return;
+
+  // Lines kept on screen; older ones scroll away.
+  const MAX_LINES = 50;
+
+  // Screen readers hear the speaker already, so captions are only read out on request.
+  readAloud.addEventListener('change', function () {
+    log.setAttribute('aria-live', readAloud.checked ? 'polite' : 'off');
+  });
+
+  window.handleCaptionMessage = function (text) {
+    const line = document.createElement('p');
+    line.textContent = text;
+    log.appendChild(line);
+    while (log.childElementCount > MAX_LINES) log.firstElementChild.remove();
+    log.scrollTop = log.scrollHeight;
+    panel.hidden = false;
+  };
+
+  if (!form) return;
+
+  form.addEventListener('submit', function (e) {
+    e.preventDefault();
+    const text = input.value.trim();
+    if (!text || typeof socket === 'undefined' || socket.readyState !== WebSocket.OPEN) return;
+    socket.send(JSON.stringify({ type: 'caption', data: text }));
+    input.value = '';
+  });
+})();
@@ -17,6 +17,66 @@ let _wsMessageHandler = null;
 let _wsReconnectDelay = 1000;
 const _wsMaxDelay = 30000;
 
+// Position in the presentation's stream of broadcasts: the stream id from the last
+// "resume" message and the highest "seq" seen. A reconnect sends it back and receives
+// only what it missed, instead of the whole state being announced again.
+let _wsStream = null;
+let _wsSeq = null;
+
+// Version of the WebSocket protocol this page speaks; must match PROTOCOL_VERSION on the server.
+const WS_PROTOCOL_VERSION = 1;
+
+// The last "hello": the role this connection was granted and the message types it may send.
+var wsHello = null;
+
+// Why the server closed the connection, if it said; shown until it reconnects.
+let _wsFatalError = null;
+
+function _wsOnMessage(event) {
+    try {
+        const message = JSON.parse(event.data);
+        if (message.type === 'resume') {
+            _wsStream = message.data.stream;
+            _wsSeq = message.data.seq;
+        } else if (typeof message.seq === 'number') {
+            _wsSeq = message.seq;
+        }
+        // Hello and error are about the connection, not the presentation; pages do not see them.
+        if (message.type === 'hello') {
+            _wsOnHello(message.data);
+            return;
+        }
+        if (message.type === 'error') {
+            _wsOnError(message.data);
+            return;
+        }
+    } catch (e) {
+        // Not JSON; the registered handler decides what to do with it.
+    }
+    if (_wsMessageHandler) _wsMessageHandler(event);
+}
+
+function _wsOnHello(hello) {
+    wsHello = hello;
+    if (hello.protocol !== WS_PROTOCOL_VERSION) {
+        _wsShowStatus('SyncSlide was updated. Reload the page to keep following the presentation.');
+    }
+}
+
+// Tells the user why the server refused something they sent, through the status region
+// screen readers announce. Pages may also react through handleProtocolError(error).
+function _wsOnError(error) {
+    if (error.fatal) _wsFatalError = error.message;
+    _wsShowStatus(error.message);
+    if (!error.fatal) {
+        setTimeout(() => {
+            const el = document.getElementById('ws-status');
+            if (el && el.textContent === error.message) _wsSetStatus(true);
+        }, 8000);
+    }
+    if (typeof handleProtocolError === 'function') handleProtocolError(error);
+}
+
 /**
  * Register the onmessage handler and open the first connection.
  * Must be called instead of socket.onmessage = directly, so the handler
@@ -26,12 +86,19 @@ const _wsMaxDelay = 30000;
 function wsRegisterMessageHandler(fn) {
     _wsMessageHandler = fn;
     if (socket) {
-        socket.onmessage = fn;
+        socket.onmessage = _wsOnMessage;
     } else {
         _wsConnect();
     }
 }
 
+function _wsShowStatus(text) {
+    const el = document.getElementById('ws-status');
+    if (!el) return;
+    el.hidden = false;
+    el.textContent = text;
+}
+
 function _wsSetStatus(connected) {
     const el = document.getElementById('ws-status');
     if (!el) return;
@@ -39,17 +106,24 @@ function _wsSetStatus(connected) {
         el.hidden = true;
         el.textContent = '';
     } else {
-        el.hidden = false;
-        el.textContent = 'Connection lost \u2014 reconnecting\u2026';
+        const reason = _wsFatalError ? _wsFatalError + ' ' : '';
+        _wsShowStatus(reason + 'Connection lost \u2014 reconnecting\u2026');
     }
 }
 
 function _wsConnect() {
-    socket = new WebSocket(wsUrl.href);
-    if (_wsMessageHandler) socket.onmessage = _wsMessageHandler;
+    const url = new URL(wsUrl.href);
+    // A page may veto resuming, e.g. while an edit it sent is unacknowledged.
+    if (_wsStream !== null && (typeof wsCanResume !== 'function' || wsCanResume())) {
+        url.searchParams.set('stream', _wsStream);
+        url.searchParams.set('seq', _wsSeq);
+    }
+    socket = new WebSocket(url.href);
+    socket.onmessage = _wsOnMessage;
 
     socket.onopen = function () {
         _wsReconnectDelay = 1000;
+        _wsFatalError = null;
         _wsSetStatus(true);
     };
 
@@ -70,6 +144,15 @@ const md = new remarkable.Remarkable({
 	html: true,
 });
 
+// Speaker notes start at a line containing only "???" and run to the next "## " heading.
+// They are presenter-only, so slides are always rendered with the notes removed.
+function stripNotes(markdown) {
+	return markdown
+		.split(/^(?=##\s)/m)
+		.map(section => section.split(/^\?\?\?[ \t]*$/m)[0])
+		.join('');
+}
+
 function addSiblings(allHtml) {
 	const h2s = allHtml.querySelectorAll('h2');
 	const result = [];
@@ -86,6 +169,81 @@ function addSiblings(allHtml) {
 	return result;
 }
 
+// A slide reveals its content in fragment steps: after a paragraph of just "--" the
+// rest comes one step later, and after one of just "++" each item of the list that
+// follows is a step of its own. Returns the elements of a slide shown once its first
+// `fragment` steps are revealed, without the markers, and how many steps it has.
+// Must match Deck::reveal on the server.
+function revealFragments(elements, fragment) {
+	const shown = [];
+	let step = 0;
+	let pendingSteps = 0;
+	let itemsNext = false;
+	for (const el of elements) {
+		const marker = el.tagName === 'P' ? el.textContent.trim() : '';
+		if (marker === '--') {
+			pendingSteps++;
+			continue;
+		}
+		if (marker === '++') {
+			itemsNext = true;
+			continue;
+		}
+		step += pendingSteps;
+		pendingSteps = 0;
+		const steppedList = itemsNext && (el.tagName === 'UL' || el.tagName === 'OL');
+		// A list shown item by item appears with its first item.
+		const visible = (steppedList ? step + 1 : step) <= fragment;
+		if (steppedList) {
+			for (const li of Array.from(el.children)) {
+				step++;
+				if (step > fragment) li.remove();
+			}
+		}
+		itemsNext = false;
+		if (visible) shown.push(el);
+	}
+	return { shown, steps: step };
+}
+
+// Elements a presenter can point at: headings, paragraphs, list items, table rows and
+// cells, and code blocks.
+const BLOCK_SELECTOR = 'h1, h2, h3, h4, h5, h6, p, li, tr, th, td, pre';
+
+// What each block element of the slide shown stands for, as { tag, text, occurrence };
+// the server names the block a presenter points at the same way (BlockTarget).
+let BLOCK_TARGETS = new WeakMap();
+
+// Notes what each block of a slide's elements stands for, skipping fragment markers.
+// Call before revealFragments hides any and before math is rendered; the text is kept
+// to letters and digits, as Deck::targets on the server keeps it.
+function markBlocks(elements) {
+	BLOCK_TARGETS = new WeakMap();
+	const seen = {};
+	for (const el of elements) {
+		const marker = el.tagName === 'P' ? el.textContent.trim() : '';
+		if (marker === '--' || marker === '++') continue;
+		for (const b of [el, ...el.querySelectorAll(BLOCK_SELECTOR)]) {
+			if (!b.matches(BLOCK_SELECTOR)) continue;
+			const tag = b.tagName.toLowerCase();
+			const text = b.textContent.replace(/[^\p{Alphabetic}\p{N}]/gu, '');
+			const key = tag + ' ' + text;
+			const occurrence = seen[key] || 0;
+			seen[key] = occurrence + 1;
+			BLOCK_TARGETS.set(b, { tag, text, occurrence });
+		}
+	}
+}
+
+// The block element within container that target names, or null if none is shown.
+function findBlock(container, target) {
+	for (const el of container.querySelectorAll(BLOCK_SELECTOR)) {
+		const t = BLOCK_TARGETS.get(el);
+		if (t && t.tag === target.tag && t.text === target.text && t.occurrence === target.occurrence) return el;
+	}
+	return null;
+}
+
 const updateRender = async () => {
 	const htmlDiv = document.getElementById("currentSlide");
 	renderMathInElement(htmlDiv, {
@@ -9,14 +9,241 @@ let dialogMode = 'insert'; // 'insert' | 'edit'
 
 const textInput = document.getElementById("markdown-input");
 
+// Collaborative editing. TEXT_TO_RENDER (audience.js) is the server's text at TEXT_REVISION.
+// At most one patch is in flight; localBase is the server text with it applied, and
+// savedMarkdown is the deck as last saved here, which may be ahead of localBase.
+// Remote patches are transformed over the local edits, exactly as the server
+// transforms late patches, so every editor ends with the same text.
+let inflight = null;
+let localBase = textInput ? textInput.value : '';
+let savedMarkdown = localBase;
+
+const isHighSurrogate = (code) => code >= 0xD800 && code <= 0xDBFF;
+const isLowSurrogate = (code) => code >= 0xDC00 && code <= 0xDFFF;
+
+// The single range replacement turning oldText into newText, in string indices.
+// The range never splits a surrogate pair: the server rejects offsets inside a character.
+function diffPatch(oldText, newText, base) {
+    const max = Math.min(oldText.length, newText.length);
+    let start = 0;
+    while (start < max && oldText[start] === newText[start]) start++;
+    if (start > 0 && isHighSurrogate(oldText.charCodeAt(start - 1))) start--;
+    let oldEnd = oldText.length;
+    let newEnd = newText.length;
+    while (oldEnd > start && newEnd > start && oldText[oldEnd - 1] === newText[newEnd - 1]) {
+        oldEnd--;
+        newEnd--;
+    }
+    if (oldEnd < oldText.length && isLowSurrogate(oldText.charCodeAt(oldEnd))) {
+        oldEnd++;
+        newEnd++;
+    }
+    return { base, start, end: oldEnd, text: newText.slice(start, newEnd) };
+}
+
+// Rewrites patch to apply after other, made against the same text.
+// Must match TextPatch::transform on the server.
+function transformPatch(patch, other, later) {
+    const shift = (pos) => pos + other.text.length - (other.end - other.start);
+    const moved = (start, end, text) => ({ base: other.base + 1, start, end, text });
+    if (patch.start === patch.end && other.start === other.end && patch.start === other.start) {
+        return later ? moved(shift(patch.start), shift(patch.end), patch.text) : moved(patch.start, patch.end, patch.text);
+    }
+    if (patch.end <= other.start) return moved(patch.start, patch.end, patch.text);
+    if (patch.start >= other.end) return moved(shift(patch.start), shift(patch.end), patch.text);
+    const covers = patch.start <= other.start && patch.end >= other.end;
+    const covered = other.start <= patch.start && other.end >= patch.end;
+    if (covers && (!covered || later)) return moved(patch.start, shift(patch.end), patch.text);
+    if (covered) return moved(other.start, other.start, '');
+    if (patch.start > other.start) return moved(other.start + other.text.length, shift(patch.end), patch.text);
+    return moved(patch.start, other.start, patch.text);
+}
+
+// Where a position in the text ends up after patch is applied.
+function mapOffset(pos, patch) {
+    if (pos <= patch.start) return pos;
+    if (pos >= patch.end) return pos + patch.text.length - (patch.end - patch.start);
+    return patch.start + patch.text.length;
+}
+
+// Applies patch, made against base, to text, which has local changes on top of base.
+// Returns the new text and the patch as it was applied to it.
+function applyUnder(base, text, patch) {
+    const rebased = transformPatch(patch, diffPatch(base, text, 0), false);
+    return { text: applyPatch(text, rebased), patch: rebased };
+}
+
+// Sends what was saved here since the last acknowledged edit, one patch at a time,
+// or the whole deck while the server revision is unknown.
+function sendMarkdown(markdown) {
+    savedMarkdown = markdown;
+    if (!socket || socket.readyState !== WebSocket.OPEN) return;
+    if (TEXT_REVISION === null) {
+        socket.send(JSON.stringify({ type: "text", data: markdown }));
+        return;
+    }
+    // The rest follows once the patch in flight is acknowledged.
+    if (inflight || markdown === localBase) return;
+    inflight = diffPatch(localBase, markdown, TEXT_REVISION);
+    localBase = markdown;
+    socket.send(JSON.stringify({ type: "patch", data: inflight }));
+}
+
+// Called by audience.js once a patch is applied to TEXT_TO_RENDER.
+function onTextPatched(patch) {
+    if (patch.mine) {
+        inflight = null;
+        localBase = TEXT_TO_RENDER;
+        sendMarkdown(savedMarkdown);
+        return;
+    }
+    let remote = patch;
+    if (inflight) {
+        remote = transformPatch(patch, inflight, false);
+        inflight = transformPatch(inflight, patch, true);
+    }
+    const oldBase = localBase;
+    localBase = applyPatch(localBase, remote);
+    const oldSaved = savedMarkdown;
+    const saved = applyUnder(oldBase, savedMarkdown, remote);
+    savedMarkdown = saved.text;
+    showRemoteEdit(oldSaved, saved.patch);
+    announceRemoteEdit(patch);
+}
+
+// Called by common.js before reconnecting. The server cannot tell a replayed patch
+// of ours from someone else's, so with one in flight a full snapshot is safer.
+function wsCanResume() {
+    return inflight === null;
+}
+
+// Called by audience.js after a snapshot: on connect, or in reply to a patch the
+// server could not rebase. A lost local edit is sent again over the new text.
+function onTextSnapshot() {
+    const lost = inflight !== null || savedMarkdown !== localBase;
+    inflight = null;
+    localBase = TEXT_TO_RENDER;
+    if (lost) {
+        sendMarkdown(savedMarkdown);
+        return;
+    }
+    const oldSaved = savedMarkdown;
+    savedMarkdown = TEXT_TO_RENDER;
+    showRemoteEdit(oldSaved, diffPatch(oldSaved, TEXT_TO_RENDER, 0));
+}
+
+// Brings the textarea, keeping any unsaved changes and the selection, and the slide
+// list up to date with a remote patch made against oldSaved.
+function showRemoteEdit(oldSaved, patch) {
+    if (!textInput) return;
+    const shown = applyUnder(oldSaved, textInput.value, patch);
+    if (shown.text !== textInput.value) {
+        const start = mapOffset(textInput.selectionStart, shown.patch);
+        const end = mapOffset(textInput.selectionEnd, shown.patch);
+        textInput.value = shown.text;
+        textInput.setSelectionRange(start, end);
+    }
+    if (markdownDialog && markdownDialog.open) markdownSnapshot = savedMarkdown;
+    for (const cursor of remoteCursors.values()) {
+        cursor.start = mapOffset(cursor.start, patch);
+        cursor.end = mapOffset(cursor.end, patch);
+    }
+    renderCoeditors();
+    const slideTableBody = document.getElementById("slideTableBody");
+    if (slideTableBody && !slideTableBody.contains(document.activeElement)) renderSlideTable();
+    if (typeof getH2s === 'function') getH2s(stringToDOM(md.render(savedMarkdown)));
+}
+
+// Index of the slide containing offset; slides start at "## " headings.
+function slideAt(text, offset) {
+    const headings = text.slice(0, offset).match(/^##\s/gm);
+    const count = headings ? headings.length : 0;
+    return /^##\s/.test(text) ? Math.max(count - 1, 0) : count;
+}
+
+// Offset at which slide idx starts.
+function slideOffset(text, idx) {
+    const starts = [...text.matchAll(/^##\s/gm)].map(m => m.index);
+    if (!/^##\s/.test(text)) starts.unshift(0);
+    return starts[idx] !== undefined ? starts[idx] : text.length;
+}
+
+// The slide this editor is working on: the one under the caret in the Markdown
+// dialog, the one open in the slide dialog, or the one being presented.
+function currentEditSlide() {
+    const slideDialog = document.getElementById('slideDialog');
+    if (markdownDialog && markdownDialog.open) return slideAt(textInput.value, textInput.selectionStart);
+    if (slideDialog && slideDialog.open && dialogMode === 'edit') return dialogRefIdx;
+    const goTo = document.getElementById('goTo');
+    return goTo ? Number(goTo.value) : null;
+}
+
+function announce(message) {
+    // Content outside an open modal dialog is inert, so announce inside it.
+    const region = document.querySelector('dialog[open] .coedit-announce') || document.getElementById('coedit-announce');
+    if (!region) return;
+    region.textContent = '';
+    setTimeout(() => { region.textContent = message; }, 50);
+}
+
+function announceRemoteEdit(patch) {
+    if (!patch.by) return;
+    const slide = slideAt(TEXT_TO_RENDER, patch.start);
+    if (slide === currentEditSlide()) {
+        announce(`${patch.by} edited slide ${slide + 1}, the slide you are on.`);
+    }
+}
+
+// Other editors' cursors by connection id, as offsets into savedMarkdown.
+const remoteCursors = new Map();
+
+function renderCoeditors() {
+    const section = document.getElementById('coeditors');
+    const list = document.getElementById('coeditorList');
+    if (!section || !list) return;
+    const slides = markdownToSlides(savedMarkdown);
+    list.innerHTML = '';
+    for (const cursor of remoteCursors.values()) {
+        const idx = slideAt(savedMarkdown, cursor.start);
+        const title = slides[idx] ? slides[idx].title : '';
+        const li = document.createElement('li');
+        const what = cursor.start === cursor.end ? 'editing' : 'selecting text on';
+        li.textContent = `${cursor.user || 'Someone'}: ${what} slide ${idx + 1}${title ? ', ' + title : ''}`;
+        list.appendChild(li);
+    }
+    section.hidden = remoteCursors.size === 0;
+}
+
+function handleCursorMessage(type, data) {
+    if (type === 'cursor_gone') {
+        remoteCursors.delete(data);
+    } else {
+        remoteCursors.set(data.id, data);
+    }
+    renderCoeditors();
+}
+
+let cursorTimer = null;
+function sendCursor(start, end) {
+    clearTimeout(cursorTimer);
+    cursorTimer = setTimeout(() => {
+        if (socket && socket.readyState === WebSocket.OPEN) {
+            socket.send(JSON.stringify({ type: "cursor", data: { start, end } }));
+        }
+    }, 300);
+}
+
+if (textInput) {
+    const sendCaret = () => sendCursor(textInput.selectionStart, textInput.selectionEnd);
+    for (const event of ['select', 'keyup', 'click', 'focus']) textInput.addEventListener(event, sendCaret);
+}
+
 function updateMarkdown() {
     const markdownInput = textInput.value;
     const render = md.render(markdownInput);
     const dom = stringToDOM(render);
     if (typeof getH2s === 'function') getH2s(dom);
-    if (socket && socket.readyState === WebSocket.OPEN) {
-        socket.send(JSON.stringify({ type: "text", data: markdownInput }));
-    }
+    sendMarkdown(markdownInput);
     if (typeof updateSlide === 'function') updateSlide();
     renderSlideTable();
 }
@@ -44,6 +271,25 @@ function markdownToSlides(markdown) {
 	});
 }
 
+// A slide's planned time is a "Time:" line in its speaker notes, after "???".
+const NOTES_LINE = /^\?\?\?[ \t]*$/m;
+const TIME_LINE = /^[ \t]*time:(.*)(\n|$)/im;
+
+function slideTime(body) {
+	const at = body.search(NOTES_LINE);
+	const match = at === -1 ? null : body.slice(at).match(TIME_LINE);
+	return match ? match[1].trim() : '';
+}
+
+// Returns body with its planned time set to time, or removed when time is empty.
+function withSlideTime(body, time) {
+	const at = body.search(NOTES_LINE);
+	const content = (at === -1 ? body : body.slice(0, at)).trimEnd();
+	const rest = at === -1 ? '' : body.slice(at).replace(NOTES_LINE, '').replace(TIME_LINE, '').trim();
+	const notes = time ? `Time: ${time}\n${rest}`.trim() : rest;
+	return notes ? `${content}\n\n???\n${notes}` : content;
+}
+
 function slidesToMarkdown(slides) {
 	return slides.map(s => `## ${s.title}\n${s.body}`).join('\n\n');
 }
@@ -54,9 +300,7 @@ function syncFromSlides(slides) {
 	const d = document.createElement('div');
 	d.innerHTML = DOMPurify.sanitize(md.render(markdown));
 	if (typeof getH2s === 'function') getH2s(d);
-	if (socket && socket.readyState === WebSocket.OPEN) {
-		socket.send(JSON.stringify({ type: "text", data: markdown }));
-	}
+	sendMarkdown(markdown);
 	if (typeof updateSlide === 'function') updateSlide();
 }
 
@@ -94,8 +338,11 @@ function openSlideDialog(mode, idx) {
 	const applyBtn = document.getElementById('slideDialogApply');
 	const slides = markdownToSlides(textInput.value);
 	if (mode === 'edit') {
+		const offset = slideOffset(textInput.value, idx);
+		sendCursor(offset, offset);
 		document.getElementById('insertTitle').value = slides[idx].title;
 		document.getElementById('insertBody').value = slides[idx].body;
+		document.getElementById('insertTime').value = slideTime(slides[idx].body);
 		posFieldset.hidden = true;
 		refLabel.hidden = true;
 		heading.textContent = 'Edit Slide';
@@ -103,6 +350,7 @@ function openSlideDialog(mode, idx) {
 	} else {
 		document.getElementById('insertTitle').value = 'New Slide';
 		document.getElementById('insertBody').value = '';
+		document.getElementById('insertTime').value = '';
 		document.querySelector('input[name="insertPos"][value="after"]').checked = true;
 		const refSelect = document.getElementById('insertRefSlide');
 		refSelect.innerHTML = '';
@@ -235,7 +483,7 @@ const slideDialog = document.getElementById('slideDialog');
 if (slideDialog) {
 	document.getElementById('slideDialogApply').addEventListener('click', () => {
 		const title = document.getElementById('insertTitle').value;
-		const body = document.getElementById('insertBody').value;
+		const body = withSlideTime(document.getElementById('insertBody').value, document.getElementById('insertTime').value.trim());
 		const slides = markdownToSlides(textInput.value);
 		if (dialogMode === 'edit') {
 			slides[dialogRefIdx].title = title;
@@ -441,3 +689,6 @@ if (slideTableBody) {
         });
     }
 }
+
+// A snapshot that arrived before this script ran still has to reach the textarea.
+if (TEXT_REVISION !== null) onTextSnapshot();
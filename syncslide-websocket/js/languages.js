@@ -0,0 +1,63 @@
+// Lets each viewer follow the presentation in one of its translations.
+// The deck and its translations arrive over the shared WebSocket (set up by common.js);
+// the slide shown is the same in every language, only its text differs. The choice is
+// remembered across visits and can be given in the URL as ?lang=fr.
+
+(function () {
+  const STORAGE_KEY = 'syncslide-lang';
+
+  // A language's name in itself, such as "français" for fr; the tag if the browser cannot name it.
+  function languageName(tag) {
+    try {
+      return new Intl.DisplayNames([tag], { type: 'language' }).of(tag) || tag;
+    } catch (e) {
+      return tag;
+    }
+  }
+
+  // Links between the languages of a recording.
+  for (const el of document.querySelectorAll('.language-name[data-lang]')) {
+    el.textContent = languageName(el.dataset.lang);
+  }
+
+  const choice = document.getElementById('language-choice');
+  const select = document.getElementById('language-select');
+  if (!choice || !select) return;
+
+  let wanted = new URLSearchParams(location.search).get('lang') || localStorage.getItem(STORAGE_KEY);
+
+  function option(value, label, lang) {
+    const opt = document.createElement('option');
+    opt.value = value;
+    opt.textContent = label;
+    if (lang) opt.lang = lang;
+    return opt;
+  }
+
+  window.onLanguagesChanged = function () {
+    const langs = Object.keys(VARIANTS).sort();
+    // Follow the language asked for as soon as the presentation has it.
+    if (wanted && VARIANTS[wanted] !== undefined) {
+      VIEW_LANG = wanted;
+      wanted = null;
+    }
+    if (VIEW_LANG !== null && VARIANTS[VIEW_LANG] === undefined) VIEW_LANG = null;
+    select.replaceChildren(
+      option('', LANG ? languageName(LANG) : 'Original', LANG),
+      ...langs.map(function (lang) { return option(lang, languageName(lang), lang); })
+    );
+    select.value = VIEW_LANG || '';
+    choice.hidden = langs.length === 0;
+  };
+
+  select.addEventListener('change', function () {
+    VIEW_LANG = select.value || null;
+    wanted = null;
+    if (VIEW_LANG) {
+      localStorage.setItem(STORAGE_KEY, VIEW_LANG);
+    } else {
+      localStorage.removeItem(STORAGE_KEY);
+    }
+    if (SLIDE_SHOWN) renderSlide();
+  });
+})();
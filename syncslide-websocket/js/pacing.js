@@ -0,0 +1,87 @@
+// Pacing against planned slide timings, for presenters.
+// The server sends where the session stands on each move to another slide, and again
+// when the slide or the whole session runs over its plan; the clock ticks here in between.
+
+(function () {
+  const clockEl = document.getElementById('pacing-clock');
+  const slideEl = document.getElementById('pacing-slide');
+  const warningEl = document.getElementById('pacing-warning');
+  const announceEl = document.getElementById('pacing-announce');
+  const reportBtn = document.getElementById('pacing-report-show');
+  const reportTable = document.getElementById('pacing-report');
+
+  if (!clockEl) return;
+
+  let latest = null;
+  let receivedAt = 0;
+  // 'slide' or 'session' while a warning is shown.
+  let warning = null;
+
+  function formatTime(seconds) {
+    const h = Math.floor(seconds / 3600);
+    const m = Math.floor(seconds / 60) % 60;
+    const s = String(seconds % 60).padStart(2, '0');
+    return h > 0 ? `${h}:${String(m).padStart(2, '0')}:${s}` : `${m}:${s}`;
+  }
+
+  function ofPlanned(seconds, planned) {
+    return planned ? `${formatTime(seconds)} of ${formatTime(planned)}` : formatTime(seconds);
+  }
+
+  function render() {
+    if (!latest) return;
+    const passed = Math.floor((Date.now() - receivedAt) / 1000);
+    clockEl.textContent = ofPlanned(latest.session_seconds + passed, latest.session_planned);
+    slideEl.textContent = `Slide ${latest.slide + 1}: ${ofPlanned(latest.slide_seconds + passed, latest.slide_planned)}`;
+  }
+
+  function warn(text, kind) {
+    warning = kind;
+    warningEl.textContent = text;
+    warningEl.hidden = false;
+    if (announceEl) announceEl.textContent = text;
+  }
+
+  window.handlePacingMessage = function (pacing) {
+    latest = pacing;
+    receivedAt = Date.now();
+    if (pacing.over === 'slide') {
+      warn(`Slide ${pacing.slide + 1} is over its planned ${formatTime(pacing.slide_planned)}.`, 'slide');
+    } else if (pacing.over === 'session') {
+      warn(`The session is over its planned ${formatTime(pacing.session_planned)}.`, 'session');
+    } else if (warning === 'slide') {
+      // Moved on from the slide that ran over.
+      warning = null;
+      warningEl.hidden = true;
+    }
+    render();
+  };
+
+  setInterval(render, 1000);
+
+  function reportRow(label, planned, actual) {
+    const tr = document.createElement('tr');
+    const th = document.createElement('th');
+    th.scope = 'row';
+    th.textContent = label;
+    const plannedTd = document.createElement('td');
+    plannedTd.textContent = planned ? formatTime(planned) : 'None';
+    const actualTd = document.createElement('td');
+    actualTd.textContent = formatTime(actual);
+    tr.append(th, plannedTd, actualTd);
+    return tr;
+  }
+
+  reportBtn.addEventListener('click', async () => {
+    const resp = await fetch(`/${parts.join('/')}/pacing`);
+    if (!resp.ok) return;
+    const report = await resp.json();
+    const body = reportTable.querySelector('tbody');
+    body.innerHTML = '';
+    for (const s of report.slides) {
+      body.appendChild(reportRow(`${s.slide + 1}: ${s.title}`, s.planned_seconds, s.actual_seconds));
+    }
+    body.appendChild(reportRow('Whole session', report.planned_seconds, report.session_seconds));
+    reportTable.hidden = false;
+  });
+})();
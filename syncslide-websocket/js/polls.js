@@ -0,0 +1,100 @@
+// Live poll client.
+// Shows the current poll and its tally from the shared WebSocket (set up by common.js).
+// Audience members vote; owners and editors open and close polls from the stage.
+
+(function () {
+  const panel = document.getElementById('poll-panel');
+  const questionEl = document.getElementById('poll-question');
+  const stateEl = document.getElementById('poll-state');
+  const results = document.getElementById('poll-results');
+  const announceEl = document.getElementById('poll-announce');
+  const voteForm = document.getElementById('poll-vote-form');
+  const optionsEl = document.getElementById('poll-options');
+  const btnClose = document.getElementById('pollClose');
+  const btnOpenInline = document.getElementById('pollOpenInline');
+  const openForm = document.getElementById('poll-open-form');
+  const newQuestion = document.getElementById('poll-new-question');
+  const newOptions = document.getElementById('poll-new-options');
+
+  if (!panel) return;
+
+  let currentPoll = null;
+  // Polls this page has voted in; the server enforces one vote per connection as well.
+  const voted = new Set();
+
+  function send(message) {
+    if (typeof socket !== 'undefined' && socket.readyState === WebSocket.OPEN) {
+      socket.send(JSON.stringify(message));
+    }
+  }
+
+  function renderOptions(poll) {
+    if (!optionsEl) return;
+    optionsEl.innerHTML = '';
+    poll.options.forEach(function (option, i) {
+      const label = document.createElement('label');
+      const input = document.createElement('input');
+      input.type = 'radio';
+      input.name = 'poll-option';
+      input.value = String(i);
+      input.required = true;
+      label.append(input, ' ' + option);
+      optionsEl.appendChild(label);
+    });
+  }
+
+  function renderResults(poll) {
+    const total = poll.counts.reduce(function (a, b) { return a + b; }, 0);
+    results.innerHTML = '';
+    poll.options.forEach(function (option, i) {
+      const li = document.createElement('li');
+      const pct = total ? Math.round(poll.counts[i] * 100 / total) : 0;
+      li.textContent = option + ': ' + poll.counts[i] + ' (' + pct + '%)';
+      results.appendChild(li);
+    });
+  }
+
+  window.handlePollMessage = function (poll) {
+    const isNew = !currentPoll || currentPoll.id !== poll.id;
+    const justClosed = currentPoll && currentPoll.id === poll.id && currentPoll.open && !poll.open;
+    currentPoll = poll;
+    panel.hidden = false;
+    questionEl.textContent = poll.question;
+    stateEl.textContent = poll.open ? '' : '(closed)';
+    if (isNew) renderOptions(poll);
+    if (voteForm) voteForm.hidden = !poll.open || voted.has(poll.id);
+    if (btnClose) btnClose.hidden = !poll.open;
+    renderResults(poll);
+    if (isNew && poll.open) {
+      announceEl.textContent = 'New poll: ' + poll.question;
+    } else if (justClosed) {
+      announceEl.textContent = 'Poll closed';
+    }
+  };
+
+  if (voteForm) {
+    voteForm.addEventListener('submit', function (e) {
+      e.preventDefault();
+      const checked = voteForm.querySelector('input[name="poll-option"]:checked');
+      if (!checked || !currentPoll) return;
+      send({ type: 'poll_vote', poll_id: currentPoll.id, option: Number(checked.value) });
+      voted.add(currentPoll.id);
+      voteForm.hidden = true;
+      announceEl.textContent = 'Vote sent';
+    });
+  }
+
+  if (btnClose) {
+    btnClose.addEventListener('click', function () { send({ type: 'poll_close' }); });
+  }
+  if (btnOpenInline) {
+    btnOpenInline.addEventListener('click', function () { send({ type: 'poll_open_inline' }); });
+  }
+  if (openForm) {
+    openForm.addEventListener('submit', function (e) {
+      e.preventDefault();
+      const options = newOptions.value.split('\n').map(function (o) { return o.trim(); }).filter(Boolean);
+      send({ type: 'poll_open', question: newQuestion.value.trim(), options: options });
+    });
+  }
+}());
@@ -0,0 +1,39 @@
+// Audience presence for presenters.
+// The server sends who is connected over the shared WebSocket (set up by common.js),
+// to presenter connections only and at most every couple of seconds.
+
+(function () {
+  const countEl = document.getElementById('presence-count');
+  const users = document.getElementById('presence-users');
+  const usersEmpty = document.getElementById('presence-empty');
+  const announceEl = document.getElementById('presence-announce');
+  const captionersEl = document.getElementById('presence-captioners');
+
+  if (!countEl) return;
+
+  let lastViewers = null;
+
+  function viewersText(n) {
+    return n === 1 ? '1 viewer' : n + ' viewers';
+  }
+
+  window.handlePresenceMessage = function (presence) {
+    countEl.textContent = viewersText(presence.viewers);
+    users.innerHTML = '';
+    for (const name of presence.users) {
+      const li = document.createElement('li');
+      li.textContent = name;
+      users.appendChild(li);
+    }
+    usersEmpty.hidden = presence.users.length > 0;
+    captionersEl.textContent = presence.captioners === 1
+      ? '1 captioner connected.'
+      : presence.captioners + ' captioners connected.';
+    captionersEl.hidden = presence.captioners === 0;
+    // The first update only sets the count; later changes are announced.
+    if (lastViewers !== null && presence.viewers !== lastViewers && announceEl) {
+      announceEl.textContent = viewersText(presence.viewers) + ' connected';
+    }
+    lastViewers = presence.viewers;
+  };
+})();
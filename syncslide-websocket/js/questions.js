@@ -0,0 +1,133 @@
+// Audience Q&A client.
+// Audience members submit questions over the shared WebSocket (set up by common.js);
+// presenters see the moderation queue on the stage. Pinned questions are shown to everyone.
+
+(function () {
+  const pinnedSection = document.getElementById('pinned-questions');
+  const pinnedList = document.getElementById('pinned-list');
+  const askForm = document.getElementById('ask-form');
+  const askText = document.getElementById('question-text');
+  const askAnonymous = document.getElementById('question-anonymous');
+  const askStatus = document.getElementById('ask-status');
+  const queue = document.getElementById('question-queue');
+  const queueEmpty = document.getElementById('question-empty');
+  const announceEl = document.getElementById('question-announce');
+
+  // Must match question_within_size_limit on the server, which disconnects on excess.
+  const MAX_QUESTION_BYTES = 1024;
+  // Must match QUESTION_COOLDOWN on the server, which drops questions sent sooner.
+  const QUESTION_COOLDOWN_MS = 10000;
+  let lastAskedAt = 0;
+
+  const STATUS_LABELS = { open: 'Open', answered: 'Answered', pinned: 'Pinned' };
+
+  // Questions in the moderation queue, keyed by id.
+  const questions = new Map();
+
+  function send(message) {
+    if (typeof socket !== 'undefined' && socket.readyState === WebSocket.OPEN) {
+      socket.send(JSON.stringify(message));
+      return true;
+    }
+    return false;
+  }
+
+  function authorLabel(q) {
+    return q.author ? q.author : 'Anonymous';
+  }
+
+  function renderPinned(pinned) {
+    if (!pinnedSection) return;
+    pinnedList.innerHTML = '';
+    for (const q of pinned) {
+      const li = document.createElement('li');
+      li.textContent = q.text + ' — ' + authorLabel(q);
+      pinnedList.appendChild(li);
+    }
+    pinnedSection.hidden = pinned.length === 0;
+  }
+
+  function moderateButton(q, status, label) {
+    const btn = document.createElement('button');
+    btn.type = 'button';
+    btn.textContent = label;
+    btn.setAttribute('aria-label', label + ': ' + q.text);
+    btn.addEventListener('click', function () {
+      send({ type: 'question_moderate', id: q.id, status: status });
+    });
+    return btn;
+  }
+
+  function renderQueue() {
+    if (!queue) return;
+    queue.innerHTML = '';
+    for (const q of questions.values()) {
+      const li = document.createElement('li');
+      const text = document.createElement('p');
+      text.textContent = q.text;
+      const meta = document.createElement('p');
+      meta.textContent = authorLabel(q) + ' – ' + STATUS_LABELS[q.status];
+      li.append(text, meta);
+      if (q.status === 'pinned') {
+        li.append(moderateButton(q, 'open', 'Unpin'));
+      } else {
+        li.append(moderateButton(q, 'pinned', 'Pin'));
+      }
+      if (q.status !== 'answered') {
+        li.append(moderateButton(q, 'answered', 'Mark answered'));
+      }
+      li.append(moderateButton(q, 'dismissed', 'Dismiss'));
+      queue.appendChild(li);
+    }
+    queueEmpty.hidden = questions.size > 0;
+  }
+
+  window.handleQuestionMessage = function (type, data) {
+    if (type === 'pinned_questions') {
+      renderPinned(data);
+    } else if (type === 'questions') {
+      questions.clear();
+      for (const q of data) questions.set(q.id, q);
+      renderQueue();
+    } else if (type === 'question') {
+      const isNew = !questions.has(data.id);
+      if (data.status === 'dismissed') {
+        questions.delete(data.id);
+      } else {
+        questions.set(data.id, data);
+      }
+      renderQueue();
+      if (isNew && announceEl) {
+        announceEl.textContent = 'New question from ' + authorLabel(data);
+      }
+    }
+  };
+
+  if (askForm) {
+    askForm.addEventListener('submit', function (e) {
+      e.preventDefault();
+      const text = askText.value.trim();
+      if (!text) return;
+      if (new TextEncoder().encode(text).length > MAX_QUESTION_BYTES) {
+        askStatus.textContent = 'Your question is too long.';
+        return;
+      }
+      if (Date.now() - lastAskedAt < QUESTION_COOLDOWN_MS) {
+        askStatus.textContent = 'Please wait a few seconds before asking another question.';
+        return;
+      }
+      const sent = send({
+        type: 'question_ask',
+        text: text,
+        anonymous: askAnonymous ? askAnonymous.checked : true,
+      });
+      if (sent) {
+        lastAskedAt = Date.now();
+        askText.value = '';
+        askStatus.textContent = 'Question sent.';
+      } else {
+        askStatus.textContent = 'Not connected — please try again.';
+      }
+    });
+  }
+}());
@@ -27,16 +27,71 @@ const updateSlide = async () => {
 const goTo = document.getElementById("goTo");
 goTo.addEventListener('input', updateSlide);
 
+// Names of the blocks listed to point at, by tag.
+const BLOCK_KINDS = { P: "Paragraph", LI: "Item", TR: "Row", TH: "Cell", TD: "Cell", PRE: "Code" };
+
+// The blocks listed to point at, by option value.
+let POINTABLE = [];
+
+// Lists the blocks shown on the current slide to point the audience at, keeping the
+// one pointed at selected. Called by audience.js whenever the slide or pointer changes.
+function onHighlightShown() {
+	const pointAt = document.getElementById("pointAt");
+	const slideEl = document.getElementById("currentSlide");
+	pointAt.innerHTML = "";
+	pointAt.appendChild(new Option("Nothing", ""));
+	POINTABLE = Array.from(slideEl.querySelectorAll(BLOCK_SELECTOR)).filter((el) => BLOCK_TARGETS.has(el));
+	POINTABLE.forEach((el, i) => {
+		const text = el.textContent.trim().replace(/\s+/g, " ");
+		const label = text.length > 60 ? text.slice(0, 59) + "\u2026" : text;
+		const kind = BLOCK_KINDS[el.tagName] || "Heading";
+		pointAt.appendChild(new Option(`${kind}: ${label}`, String(i)));
+	});
+	const current = HIGHLIGHT && HIGHLIGHT.slide === SLIDE ? findBlock(slideEl, highlightTarget()) : null;
+	pointAt.value = current ? String(POINTABLE.indexOf(current)) : "";
+}
+
+// The server works out which block the target names; clients never send their own count.
+document.getElementById("pointAt").addEventListener('input', (e) => {
+	const data = e.target.value === ""
+		? { slide: SLIDE, block: null }
+		: { slide: SLIDE, block: null, target: BLOCK_TARGETS.get(POINTABLE[Number(e.target.value)]) };
+	if (socket && socket.readyState === WebSocket.OPEN) {
+		socket.send(JSON.stringify({ type: "highlight", data }));
+	}
+});
+
+// Number of fragment steps of slide idx.
+function slideSteps(idx) {
+	const slides = addSiblings(stringToDOM(md.render(stripNotes(TEXT_TO_RENDER))));
+	return slides[idx] ? revealFragments(slides[idx], Infinity).steps : 0;
+}
+
+// F8 reveals the next fragment step of the slide, or moves on once all are shown;
+// Shift+F8 hides the last step, or goes back to the previous slide with all shown.
 document.addEventListener("keydown", (e) => {
 	if (e.key !== "F8") return;
 	e.preventDefault();
 	const goTo = document.getElementById("goTo");
-	const current = Number(goTo.value);
 	const max = goTo.options.length - 1;
+	let slide = SLIDE;
+	let fragment = FRAGMENT;
 	if (e.shiftKey) {
-		if (current > 0) goTo.value = current - 1;
-	} else {
-		if (current < max) goTo.value = current + 1;
+		if (fragment > 0) {
+			fragment--;
+		} else if (slide > 0) {
+			slide--;
+			fragment = slideSteps(slide);
+		}
+	} else if (fragment < slideSteps(slide)) {
+		fragment++;
+	} else if (slide < max) {
+		slide++;
+		fragment = 0;
+	}
+	if (slide === SLIDE && fragment === FRAGMENT) return;
+	goTo.value = slide;
+	if (socket && socket.readyState === WebSocket.OPEN) {
+		socket.send(JSON.stringify({ type: "fragment", data: { slide, fragment } }));
 	}
-	updateSlide();
 });
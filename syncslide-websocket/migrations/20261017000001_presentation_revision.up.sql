@@ -0,0 +1,10 @@
+CREATE TABLE presentation_revision (
+    id INTEGER NOT NULL PRIMARY KEY,
+    presentation_id INTEGER NOT NULL REFERENCES presentation(id) ON DELETE CASCADE,
+    user_id INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    role TEXT NOT NULL,
+    content TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT (strftime('%s', 'now'))
+);
+CREATE INDEX presentation_revision_by_presentation
+ON presentation_revision(presentation_id, id);
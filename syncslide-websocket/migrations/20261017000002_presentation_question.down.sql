@@ -0,0 +1 @@
+DROP TABLE IF EXISTS presentation_question;
@@ -0,0 +1,10 @@
+CREATE TABLE presentation_question (
+    id INTEGER NOT NULL PRIMARY KEY,
+    presentation_id INTEGER NOT NULL REFERENCES presentation(id) ON DELETE CASCADE,
+    user_id INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    text TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'open' CHECK(status IN ('open', 'answered', 'dismissed', 'pinned')),
+    created_at DATETIME NOT NULL DEFAULT (strftime('%s', 'now'))
+);
+CREATE INDEX presentation_question_by_presentation
+ON presentation_question(presentation_id, id);
@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS recording_poll;
+DROP TABLE IF EXISTS poll_vote;
+DROP TABLE IF EXISTS poll;
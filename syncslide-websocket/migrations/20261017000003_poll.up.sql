@@ -0,0 +1,25 @@
+CREATE TABLE poll (
+    id INTEGER NOT NULL PRIMARY KEY,
+    presentation_id INTEGER NOT NULL REFERENCES presentation(id) ON DELETE CASCADE,
+    question TEXT NOT NULL,
+    -- JSON array of option labels, in display order.
+    options TEXT NOT NULL,
+    opened_at DATETIME NOT NULL DEFAULT (strftime('%s', 'now')),
+    closed_at DATETIME
+);
+CREATE INDEX poll_by_presentation ON poll(presentation_id, id);
+CREATE TABLE poll_vote (
+    id INTEGER NOT NULL PRIMARY KEY,
+    poll_id INTEGER NOT NULL REFERENCES poll(id) ON DELETE CASCADE,
+    option_index INTEGER NOT NULL,
+    -- NULL for anonymous voters, who are limited to one vote per connection instead.
+    user_id INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    UNIQUE(poll_id, user_id)
+);
+CREATE TABLE recording_poll (
+    id INTEGER NOT NULL PRIMARY KEY,
+    recording_id INTEGER NOT NULL REFERENCES recording(id) ON DELETE CASCADE,
+    poll_id INTEGER NOT NULL REFERENCES poll(id) ON DELETE CASCADE,
+    start_seconds REAL NOT NULL,
+    event TEXT NOT NULL CHECK(event IN ('open', 'close'))
+);
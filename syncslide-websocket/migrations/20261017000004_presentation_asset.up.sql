@@ -0,0 +1,9 @@
+CREATE TABLE presentation_asset (
+    id INTEGER NOT NULL PRIMARY KEY,
+    presentation_id INTEGER NOT NULL REFERENCES presentation(id) ON DELETE CASCADE,
+    -- Name on disk under presentation_assets/{presentation_id}/.
+    file_name TEXT NOT NULL,
+    alt_text TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT (strftime('%s', 'now')),
+    UNIQUE(presentation_id, file_name)
+);
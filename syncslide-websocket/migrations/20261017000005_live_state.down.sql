@@ -0,0 +1,2 @@
+ALTER TABLE recording DROP COLUMN interrupted;
+DROP TABLE IF EXISTS live_state;
@@ -0,0 +1,26 @@
+-- What a live presentation needs to pick up where it left off after a restart.
+CREATE TABLE live_state (
+    presentation_id INTEGER NOT NULL PRIMARY KEY REFERENCES presentation(id) ON DELETE CASCADE,
+    slide INTEGER NOT NULL DEFAULT 0,
+    -- How many fragment steps of the current slide are revealed.
+    fragment INTEGER NOT NULL DEFAULT 0,
+    -- The recording in progress, if any, and its timeline so far.
+    recording_id INTEGER REFERENCES recording(id) ON DELETE SET NULL,
+    active_ms INTEGER NOT NULL DEFAULT 0,
+    -- JSON arrays of [offset_ms, slide, fragment, highlight], [offset_ms, poll_id, opened]
+    -- and [offset_ms, text] events, oldest first.
+    recording_slides TEXT NOT NULL DEFAULT '[]',
+    recording_polls TEXT NOT NULL DEFAULT '[]',
+    recording_captions TEXT NOT NULL DEFAULT '[]',
+    -- The process serving the presentation and until when, in Unix seconds, it holds the
+    -- state; a recording is only taken to be interrupted once its process restarted or
+    -- stopped renewing the lease.
+    node INTEGER NOT NULL,
+    lease_until INTEGER NOT NULL,
+    -- The session clock of the presentation's latest session: milliseconds it ran, NULL
+    -- before any session started, and a JSON array of [offset_ms, slide] for each move.
+    clock_ms INTEGER,
+    clock_steps TEXT NOT NULL DEFAULT '[]'
+);
+-- Set on recordings that were in progress when the server stopped.
+ALTER TABLE recording ADD COLUMN interrupted BOOLEAN NOT NULL DEFAULT 0;
@@ -0,0 +1,3 @@
+DROP TABLE recording_slide_variant;
+DROP TABLE presentation_variant;
+DROP TABLE presentation_language;
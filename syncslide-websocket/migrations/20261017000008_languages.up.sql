@@ -0,0 +1,23 @@
+-- The language a presentation's own Markdown is written in, once its presenters set it.
+CREATE TABLE presentation_language (
+    presentation_id INTEGER NOT NULL PRIMARY KEY REFERENCES presentation(id) ON DELETE CASCADE,
+    lang TEXT NOT NULL
+);
+
+-- Translations of a presentation's Markdown, slide for slide.
+CREATE TABLE presentation_variant (
+    presentation_id INTEGER NOT NULL REFERENCES presentation(id) ON DELETE CASCADE,
+    lang TEXT NOT NULL,
+    content TEXT NOT NULL,
+    PRIMARY KEY (presentation_id, lang)
+);
+
+-- Recorded slides in each translation, by their position in the recording.
+CREATE TABLE recording_slide_variant (
+    recording_id INTEGER NOT NULL REFERENCES recording(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    lang TEXT NOT NULL,
+    title TEXT NOT NULL,
+    content TEXT NOT NULL,
+    PRIMARY KEY (recording_id, position, lang)
+);
@@ -0,0 +1,53 @@
+//! Live captions from a CART captioner, and the `WebVTT` file a recording gets from them.
+//!
+//! Each caption is one line of text, shown to everyone as it arrives. While a recording
+//! runs, captions are timed against its clock; once it stops they become the cues of
+//! its `captions.vtt`.
+
+use std::fmt::Write;
+
+/// Longest caption line, in bytes. Captioners send a line at a time, far shorter than this.
+pub const MAX_CAPTION_BYTES: usize = 1024;
+
+/// Longest a cue stays on screen when no caption follows it sooner, in milliseconds.
+const MAX_CUE_MS: u64 = 6000;
+
+/// The caption to show for `text`: whitespace, line breaks included, collapsed to single
+/// spaces and control characters dropped. `None` if nothing is left or it is too long.
+#[must_use]
+pub fn clean(text: &str) -> Option<String> {
+    if text.len() > MAX_CAPTION_BYTES {
+        return None;
+    }
+    let words: Vec<String> = text
+        .split_whitespace()
+        .map(|word| word.chars().filter(|c| !c.is_control()).collect::<String>())
+        .filter(|word| !word.is_empty())
+        .collect();
+    (!words.is_empty()).then(|| words.join(" "))
+}
+
+/// `HH:MM:SS.mmm` for an offset in milliseconds.
+fn timestamp(ms: u64) -> String {
+    let (h, m, s) = (ms / 3_600_000, ms / 60_000 % 60, ms / 1000 % 60);
+    format!("{h:02}:{m:02}:{s:02}.{:03}", ms % 1000)
+}
+
+/// A `WebVTT` file of `captions`, `(offset_ms, text)` oldest first, for a recording
+/// `length_ms` long.
+///
+/// Each caption shows until the next one, for at most [`MAX_CUE_MS`], and never past
+/// the end of the recording.
+#[must_use]
+pub fn webvtt(captions: &[(u64, String)], length_ms: u64) -> String {
+    let mut vtt = String::from("WEBVTT\n");
+    for (i, (start, text)) in captions.iter().enumerate() {
+        let next = captions.get(i + 1).map_or(u64::MAX, |(next, _)| *next);
+        // A caption at the very end still shows, however briefly.
+        let end = next.min(start.saturating_add(MAX_CUE_MS)).min(length_ms).max(start + 1);
+        let text = text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+        // Writing to a String cannot fail.
+        let _ = write!(vtt, "\n{} --> {}\n{text}\n", timestamp(*start), timestamp(end));
+    }
+    vtt
+}
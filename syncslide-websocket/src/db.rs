@@ -1,6 +1,7 @@
 use argon2::password_hash::{SaltString, rand_core::OsRng};
 use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
 use axum_login::{AuthUser, AuthnBackend, AuthzBackend, UserId};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use sqlx::types::time::OffsetDateTime;
 use sqlx::{self, FromRow, SqlitePool};
@@ -31,6 +32,8 @@ pub struct Recording {
     #[serde(with = "time::serde::rfc3339::option")]
     pub last_edited: Option<OffsetDateTime>,
     pub access_mode: Option<String>,
+    /// Set if the recording was in progress when the server stopped.
+    pub interrupted: bool,
 }
 impl Recording {
     pub async fn get_by_presentation(
@@ -66,11 +69,16 @@ impl Recording {
             .map_err(Error::from)
     }
     pub async fn delete(id: i64, db: &SqlitePool) -> Result<(), Error> {
+        LiveState::clear_recording(id, db).await?;
         sqlx::query("DELETE FROM recording_slide WHERE recording_id = ?;")
             .bind(id)
             .execute(db)
             .await
             .map_err(Error::from)?;
+        sqlx::query!("DELETE FROM recording_poll WHERE recording_id = ?;", id)
+            .execute(db)
+            .await
+            .map_err(Error::from)?;
         sqlx::query("DELETE FROM recording WHERE id = ?;")
             .bind(id)
             .execute(db)
@@ -99,6 +107,32 @@ impl Recording {
         .map_err(Error::from)
         .map(|_| ())
     }
+    /// Points the recording at captions file `path` in its asset directory.
+    pub async fn set_captions_path(id: i64, path: &str, db: &SqlitePool) -> Result<(), Error> {
+        sqlx::query!("UPDATE recording SET captions_path = ? WHERE id = ?;", path, id)
+            .execute(db)
+            .await
+            .map_err(Error::from)
+            .map(|_| ())
+    }
+    /// Marks as interrupted the recordings in progress whose process stopped: those
+    /// saved by process `node`, which has just started, and those whose process let its
+    /// lease on them lapse. Returns how many were not marked before.
+    pub async fn mark_interrupted(node: Option<u64>, db: &SqlitePool) -> Result<u64, Error> {
+        let node = node.map(u64::cast_signed);
+        sqlx::query!(
+            "UPDATE recording SET interrupted = 1
+             WHERE NOT interrupted AND id IN (
+                 SELECT recording_id FROM live_state WHERE recording_id IS NOT NULL
+                 AND (node IS ? OR lease_until < strftime('%s', 'now'))
+             );",
+            node
+        )
+        .execute(db)
+        .await
+        .map_err(Error::from)
+        .map(|result| result.rows_affected())
+    }
     pub async fn create(
         presentation_id: i64,
         name: String,
@@ -178,6 +212,59 @@ impl RecordingSlide {
         }
         tx.commit().await.map_err(Error::from)
     }
+    /// Returns the slides of a recording as translated into `lang`, falling back to the
+    /// recorded slide wherever there is no translation.
+    pub async fn get_by_recording_in(recording_id: i64, lang: &str, db: &SqlitePool) -> Result<Vec<Self>, Error> {
+        sqlx::query_as!(
+            RecordingSlide,
+            r#"SELECT s.id, s.recording_id, s.start_seconds, s.position,
+                      COALESCE(v.title, s.title) AS "title!: String",
+                      COALESCE(v.content, s.content) AS "content!: String"
+               FROM recording_slide s
+               LEFT JOIN recording_slide_variant v
+                 ON v.recording_id = s.recording_id AND v.position = s.position AND v.lang = ?
+               WHERE s.recording_id = ? ORDER BY s.position;"#,
+            lang,
+            recording_id
+        )
+        .fetch_all(db)
+        .await
+        .map_err(Error::from)
+    }
+    /// Saves the `(position, title, content)` of recorded slides translated into `lang`.
+    pub async fn create_variants(
+        recording_id: i64,
+        lang: &str,
+        slides: Vec<(i64, String, String)>,
+        db: &SqlitePool,
+    ) -> Result<(), Error> {
+        let mut tx = db.begin().await.map_err(Error::from)?;
+        for (position, title, content) in slides {
+            sqlx::query!(
+                "INSERT OR REPLACE INTO recording_slide_variant (recording_id, position, lang, title, content)
+                 VALUES (?, ?, ?, ?, ?);",
+                recording_id,
+                position,
+                lang,
+                title,
+                content
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(Error::from)?;
+        }
+        tx.commit().await.map_err(Error::from)
+    }
+    /// The languages a recording's slides were translated into, sorted.
+    pub async fn languages(recording_id: i64, db: &SqlitePool) -> Result<Vec<String>, Error> {
+        sqlx::query_scalar!(
+            "SELECT DISTINCT lang FROM recording_slide_variant WHERE recording_id = ? ORDER BY lang;",
+            recording_id
+        )
+        .fetch_all(db)
+        .await
+        .map_err(Error::from)
+    }
     pub async fn update_start_seconds(
         id: i64,
         start_seconds: f64,
@@ -193,6 +280,46 @@ impl RecordingSlide {
     }
 }
 
+/// A poll opened or closed while a recording was running.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct RecordingPoll {
+    pub id: i64,
+    pub recording_id: i64,
+    pub poll_id: i64,
+    pub start_seconds: f64,
+    /// `open` or `close`.
+    pub event: String,
+}
+#[derive(Debug)]
+pub struct RecordingPollInput {
+    pub poll_id: i64,
+    pub start_seconds: f64,
+    pub event: &'static str,
+}
+impl RecordingPoll {
+    pub async fn create_batch(
+        recording_id: i64,
+        events: Vec<RecordingPollInput>,
+        db: &SqlitePool,
+    ) -> Result<(), Error> {
+        let mut tx = db.begin().await.map_err(Error::from)?;
+        for event in events {
+            sqlx::query!(
+                "INSERT INTO recording_poll (recording_id, poll_id, start_seconds, event)
+                 VALUES (?, ?, ?, ?);",
+                recording_id,
+                event.poll_id,
+                event.start_seconds,
+                event.event
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(Error::from)?;
+        }
+        tx.commit().await.map_err(Error::from)
+    }
+}
+
 #[derive(sqlx::Type, Copy, Clone, Hash, Eq, PartialEq, Serialize, Deserialize)]
 #[sqlx(rename_all = "lowercase")]
 #[serde(rename_all = "lowercase")]
@@ -337,7 +464,33 @@ impl Presentation {
             .map(|_| ())
     }
 
+    /// The language tag of the presentation's own Markdown, if its presenters set one.
+    pub async fn get_language(id: i64, db: &SqlitePool) -> Result<Option<String>, Error> {
+        sqlx::query_scalar!("SELECT lang FROM presentation_language WHERE presentation_id = ?", id)
+            .fetch_optional(db)
+            .await
+            .map_err(Error::from)
+    }
+
+    /// Sets the language tag of the presentation's own Markdown; `None` forgets it.
+    pub async fn set_language(id: i64, lang: Option<&str>, db: &SqlitePool) -> Result<(), Error> {
+        match lang {
+            Some(lang) => {
+                sqlx::query!("INSERT OR REPLACE INTO presentation_language (presentation_id, lang) VALUES (?, ?)", id, lang)
+                    .execute(db)
+                    .await
+            }
+            None => sqlx::query!("DELETE FROM presentation_language WHERE presentation_id = ?", id).execute(db).await,
+        }
+        .map_err(Error::from)
+        .map(|_| ())
+    }
+
     pub async fn delete(id: i64, user_id: i64, db: &SqlitePool) -> Result<(), Error> {
+        sqlx::query!("DELETE FROM live_state WHERE presentation_id = ?", id)
+            .execute(db)
+            .await
+            .map_err(Error::from)?;
         sqlx::query(
             "DELETE FROM recording_slide WHERE recording_id IN \
              (SELECT id FROM recording WHERE presentation_id = ?)",
@@ -452,6 +605,611 @@ impl PresentationAccess {
     }
 }
 
+/// A saved snapshot of a presentation's Markdown.
+///
+/// NOTE: `author` is not a database column — it is populated by the queries in
+/// this impl, which LEFT JOIN the `users` table.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PresentationRevision {
+    pub id: i64,
+    pub presentation_id: i64,
+    pub user_id: Option<i64>,
+    pub role: String,
+    pub content: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    pub author: Option<String>, // populated by JOIN — see struct doc
+}
+
+impl PresentationRevision {
+    /// Saves `content` as a new revision, unless it matches the latest revision already.
+    pub async fn create(
+        db: &SqlitePool,
+        presentation_id: i64,
+        user_id: Option<i64>,
+        role: &str,
+        content: String,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            "INSERT INTO presentation_revision (presentation_id, user_id, role, content)
+             SELECT ?, ?, ?, ?
+             WHERE NOT EXISTS (
+                 SELECT 1 FROM presentation_revision
+                 WHERE id = (SELECT MAX(id) FROM presentation_revision WHERE presentation_id = ?)
+                   AND content = ?
+             )",
+            presentation_id,
+            user_id,
+            role,
+            content,
+            presentation_id,
+            content
+        )
+        .execute(db)
+        .await
+        .map_err(Error::from)
+        .map(|_| ())
+    }
+
+    /// Returns all revisions of a presentation, newest first.
+    pub async fn get_for_presentation(
+        db: &SqlitePool,
+        presentation_id: i64,
+    ) -> Result<Vec<Self>, Error> {
+        sqlx::query_as!(
+            PresentationRevision,
+            r#"SELECT r.id, r.presentation_id, r.user_id, r.role, r.content,
+                      r.created_at as "created_at: OffsetDateTime", u.name as "author?"
+               FROM presentation_revision r
+               LEFT JOIN users u ON u.id = r.user_id
+               WHERE r.presentation_id = ?
+               ORDER BY r.id DESC"#,
+            presentation_id
+        )
+        .fetch_all(db)
+        .await
+        .map_err(Error::from)
+    }
+
+    pub async fn get_by_id(db: &SqlitePool, id: i64) -> Result<Option<Self>, Error> {
+        sqlx::query_as!(
+            PresentationRevision,
+            r#"SELECT r.id, r.presentation_id, r.user_id, r.role, r.content,
+                      r.created_at as "created_at: OffsetDateTime", u.name as "author?"
+               FROM presentation_revision r
+               LEFT JOIN users u ON u.id = r.user_id
+               WHERE r.id = ?"#,
+            id
+        )
+        .fetch_optional(db)
+        .await
+        .map_err(Error::from)
+    }
+}
+
+/// Moderation state of an audience question.
+#[derive(sqlx::Type, Debug, Copy, Clone, Hash, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[sqlx(rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum QuestionStatus {
+    Open,
+    Answered,
+    Dismissed,
+    Pinned,
+}
+
+/// A question asked by the audience during a presentation.
+///
+/// NOTE: `author` is not a database column — it is populated by the queries in
+/// this impl, which LEFT JOIN the `users` table. Anonymous questions store no
+/// `user_id`, so their author is always `None`.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, JsonSchema)]
+pub struct Question {
+    pub id: i64,
+    pub presentation_id: i64,
+    pub text: String,
+    pub status: QuestionStatus,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schemars(with = "String")]
+    pub created_at: OffsetDateTime,
+    pub author: Option<String>, // populated by JOIN — see struct doc
+}
+
+impl Question {
+    /// Saves a new open question and returns it.
+    pub async fn create(
+        db: &SqlitePool,
+        presentation_id: i64,
+        user_id: Option<i64>,
+        text: &str,
+    ) -> Result<Self, Error> {
+        let id = sqlx::query!(
+            "INSERT INTO presentation_question (presentation_id, user_id, text) VALUES (?, ?, ?)",
+            presentation_id,
+            user_id,
+            text
+        )
+        .execute(db)
+        .await?
+        .last_insert_rowid();
+        Self::get_by_id(db, id)
+            .await?
+            .ok_or(Error::Sqlx(sqlx::Error::RowNotFound))
+    }
+
+    pub async fn get_by_id(db: &SqlitePool, id: i64) -> Result<Option<Self>, Error> {
+        sqlx::query_as!(
+            Question,
+            r#"SELECT q.id, q.presentation_id, q.text, q.status as "status: QuestionStatus",
+                      q.created_at as "created_at: OffsetDateTime", u.name as "author?"
+               FROM presentation_question q
+               LEFT JOIN users u ON u.id = q.user_id
+               WHERE q.id = ?"#,
+            id
+        )
+        .fetch_optional(db)
+        .await
+        .map_err(Error::from)
+    }
+
+    /// Returns the moderation queue of a presentation (every question not dismissed), oldest first.
+    pub async fn get_for_presentation(
+        db: &SqlitePool,
+        presentation_id: i64,
+    ) -> Result<Vec<Self>, Error> {
+        sqlx::query_as!(
+            Question,
+            r#"SELECT q.id, q.presentation_id, q.text, q.status as "status: QuestionStatus",
+                      q.created_at as "created_at: OffsetDateTime", u.name as "author?"
+               FROM presentation_question q
+               LEFT JOIN users u ON u.id = q.user_id
+               WHERE q.presentation_id = ? AND q.status != 'dismissed'
+               ORDER BY q.id"#,
+            presentation_id
+        )
+        .fetch_all(db)
+        .await
+        .map_err(Error::from)
+    }
+
+    /// Returns the pinned questions of a presentation, oldest first.
+    pub async fn get_pinned(db: &SqlitePool, presentation_id: i64) -> Result<Vec<Self>, Error> {
+        sqlx::query_as!(
+            Question,
+            r#"SELECT q.id, q.presentation_id, q.text, q.status as "status: QuestionStatus",
+                      q.created_at as "created_at: OffsetDateTime", u.name as "author?"
+               FROM presentation_question q
+               LEFT JOIN users u ON u.id = q.user_id
+               WHERE q.presentation_id = ? AND q.status = 'pinned'
+               ORDER BY q.id"#,
+            presentation_id
+        )
+        .fetch_all(db)
+        .await
+        .map_err(Error::from)
+    }
+
+    /// Changes the status of a question belonging to `presentation_id`.
+    pub async fn set_status(
+        db: &SqlitePool,
+        presentation_id: i64,
+        id: i64,
+        status: QuestionStatus,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            "UPDATE presentation_question SET status = ? WHERE id = ? AND presentation_id = ?",
+            status,
+            id,
+            presentation_id
+        )
+        .execute(db)
+            .await
+            .map_err(Error::from)
+            .map(|_| ())
+    }
+}
+
+/// A poll run during a presentation, with its current tally.
+///
+/// NOTE: `counts` is not a database column — it is aggregated from `poll_vote`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Poll {
+    pub id: i64,
+    pub question: String,
+    pub options: Vec<String>,
+    /// Number of votes for each option, in the order of `options`.
+    pub counts: Vec<i64>,
+    pub open: bool,
+}
+
+struct PollRow {
+    id: i64,
+    question: String,
+    /// JSON array of option labels.
+    options: String,
+    closed_at: Option<OffsetDateTime>,
+}
+
+impl Poll {
+    /// Saves a new open poll and returns it with an empty tally.
+    pub async fn create(
+        db: &SqlitePool,
+        presentation_id: i64,
+        question: &str,
+        options: &[String],
+    ) -> Result<Self, Error> {
+        // A list of strings always serialises.
+        let options_json = serde_json::to_string(options).expect("options are serializable");
+        let id = sqlx::query!(
+            "INSERT INTO poll (presentation_id, question, options) VALUES (?, ?, ?)",
+            presentation_id,
+            question,
+            options_json
+        )
+        .execute(db)
+        .await?
+        .last_insert_rowid();
+        Ok(Poll {
+            id,
+            question: question.to_string(),
+            options: options.to_vec(),
+            counts: vec![0; options.len()],
+            open: true,
+        })
+    }
+
+    pub async fn close(db: &SqlitePool, id: i64) -> Result<(), Error> {
+        sqlx::query!("UPDATE poll SET closed_at = strftime('%s', 'now') WHERE id = ? AND closed_at IS NULL", id)
+            .execute(db)
+            .await
+            .map_err(Error::from)
+            .map(|_| ())
+    }
+
+    /// Records a vote. Returns `false` if `user_id` has already voted in this poll.
+    pub async fn vote(
+        db: &SqlitePool,
+        id: i64,
+        option_index: usize,
+        user_id: Option<i64>,
+    ) -> Result<bool, Error> {
+        let option_index = i64::try_from(option_index).unwrap_or(i64::MAX);
+        let result = sqlx::query!(
+            "INSERT OR IGNORE INTO poll_vote (poll_id, option_index, user_id) VALUES (?, ?, ?)",
+            id,
+            option_index,
+            user_id
+        )
+        .execute(db)
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Returns every poll of a presentation with its final tally, oldest first.
+    pub async fn get_for_presentation(
+        db: &SqlitePool,
+        presentation_id: i64,
+    ) -> Result<Vec<Self>, Error> {
+        let rows = sqlx::query_as!(
+            PollRow,
+            r#"SELECT id, question, options, closed_at as "closed_at: OffsetDateTime"
+               FROM poll WHERE presentation_id = ? ORDER BY id"#,
+            presentation_id
+        )
+        .fetch_all(db)
+        .await?;
+        let mut polls = Vec::with_capacity(rows.len());
+        for row in rows {
+            polls.push(Self::from_row(db, row).await?);
+        }
+        Ok(polls)
+    }
+
+    async fn from_row(db: &SqlitePool, row: PollRow) -> Result<Self, Error> {
+        let options: Vec<String> = serde_json::from_str(&row.options)
+            .map_err(|e| Error::Sqlx(sqlx::Error::Decode(Box::new(e))))?;
+        let mut counts = vec![0; options.len()];
+        let tally = sqlx::query!(
+            r#"SELECT option_index, COUNT(*) as "count!: i64" FROM poll_vote WHERE poll_id = ? GROUP BY option_index"#,
+            row.id
+        )
+        .fetch_all(db)
+        .await?;
+        for vote in tally {
+            if let Some(c) = usize::try_from(vote.option_index).ok().and_then(|i| counts.get_mut(i)) {
+                *c = vote.count;
+            }
+        }
+        Ok(Poll {
+            id: row.id,
+            question: row.question,
+            options,
+            counts,
+            open: row.closed_at.is_none(),
+        })
+    }
+}
+
+/// Seconds a process holds the live state it saved; it saves the state again more often
+/// while it records, and a recording whose lease lapsed is taken to be interrupted.
+pub const LIVE_LEASE_SECS: i64 = 30;
+
+/// What a live presentation needs to pick up where it left off after a restart: the
+/// slide shown and the recording in progress, with the session clock its pacing report
+/// is drawn from.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LiveState {
+    pub slide: u32,
+    /// Fragment steps of the slide revealed so far.
+    pub fragment: u32,
+    pub recording: Option<RecordingCheckpoint>,
+    /// `(elapsed_ms, [(offset_ms, slide)])` of the latest session clock. Saving `None`
+    /// keeps the clock saved before, so the last session's report outlives it.
+    pub clock: Option<(u64, Vec<(u64, u32)>)>,
+}
+
+/// The timeline so far of a recording in progress.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordingCheckpoint {
+    pub recording_id: i64,
+    /// Time recorded so far, without pauses.
+    pub active_ms: u64,
+    /// `(offset_ms, slide, fragment, highlight)` for each slide, fragment step or highlight
+    /// shown, oldest first.
+    pub slides: Vec<(u64, u32, u32, Option<u32>)>,
+    /// `(offset_ms, poll_id, opened)` for each poll opened or closed, oldest first.
+    pub polls: Vec<(u64, i64, bool)>,
+    /// `(offset_ms, text)` for each live caption, oldest first.
+    pub captions: Vec<(u64, String)>,
+}
+
+struct LiveStateRow {
+    presentation_id: i64,
+    slide: i64,
+    fragment: i64,
+    recording_id: Option<i64>,
+    active_ms: i64,
+    /// JSON array of `[offset_ms, slide, fragment, highlight]`.
+    recording_slides: String,
+    /// JSON array of `[offset_ms, poll_id, opened]`.
+    recording_polls: String,
+    /// JSON array of `[offset_ms, text]`.
+    recording_captions: String,
+    clock_ms: Option<i64>,
+    /// JSON array of `[offset_ms, slide]`.
+    clock_steps: String,
+}
+
+impl LiveState {
+    /// Returns the saved state of presentation `presentation_id`, if any.
+    pub async fn get(presentation_id: i64, db: &SqlitePool) -> Result<Option<Self>, Error> {
+        let row = sqlx::query_as!(
+            LiveStateRow,
+            "SELECT presentation_id, slide, fragment, recording_id, active_ms, recording_slides, recording_polls,
+                    recording_captions, clock_ms, clock_steps
+             FROM live_state WHERE presentation_id = ?",
+            presentation_id
+        )
+        .fetch_optional(db)
+        .await?;
+        row.as_ref().map(Self::from_row).transpose()
+    }
+
+    /// Returns the presentation and saved timeline of recording `recording_id`, if it
+    /// was in progress.
+    pub async fn get_for_recording(
+        recording_id: i64,
+        db: &SqlitePool,
+    ) -> Result<Option<(i64, RecordingCheckpoint)>, Error> {
+        let row = sqlx::query_as!(
+            LiveStateRow,
+            "SELECT presentation_id, slide, fragment, recording_id, active_ms, recording_slides, recording_polls,
+                    recording_captions, clock_ms, clock_steps
+             FROM live_state WHERE recording_id = ?",
+            recording_id
+        )
+        .fetch_optional(db)
+        .await?;
+        let Some(row) = row else { return Ok(None); };
+        let presentation_id = row.presentation_id;
+        Ok(Self::from_row(&row)?.recording.map(|recording| (presentation_id, recording)))
+    }
+
+    /// Saves the state of presentation `presentation_id`, served by process `node`,
+    /// replacing what was saved before; the process holds it for [`LIVE_LEASE_SECS`].
+    pub async fn save(&self, presentation_id: i64, node: u64, db: &SqlitePool) -> Result<(), Error> {
+        let recording = self.recording.as_ref();
+        // Lists of integers always serialise.
+        let slides = serde_json::to_string(&recording.map_or(&[][..], |r| &r.slides[..])).expect("events are serializable");
+        let polls = serde_json::to_string(&recording.map_or(&[][..], |r| &r.polls[..])).expect("events are serializable");
+        let captions = serde_json::to_string(&recording.map_or(&[][..], |r| &r.captions[..])).expect("captions are serializable");
+        let clock_steps = serde_json::to_string(&self.clock.as_ref().map_or(&[][..], |(_, steps)| &steps[..])).expect("steps are serializable");
+        let slide = i64::from(self.slide);
+        let fragment = i64::from(self.fragment);
+        let recording_id = recording.map(|r| r.recording_id);
+        let active_ms = recording.map_or(0, |r| i64::try_from(r.active_ms).unwrap_or(i64::MAX));
+        let node = node.cast_signed();
+        let clock_ms = self.clock.as_ref().map(|(elapsed_ms, _)| i64::try_from(*elapsed_ms).unwrap_or(i64::MAX));
+        sqlx::query!(
+            "INSERT INTO live_state
+             (presentation_id, slide, fragment, recording_id, active_ms, recording_slides, recording_polls, recording_captions,
+              node, lease_until, clock_ms, clock_steps)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, strftime('%s', 'now') + ?, ?, ?)
+             ON CONFLICT (presentation_id) DO UPDATE SET
+              slide = excluded.slide, fragment = excluded.fragment, recording_id = excluded.recording_id,
+              active_ms = excluded.active_ms, recording_slides = excluded.recording_slides,
+              recording_polls = excluded.recording_polls, recording_captions = excluded.recording_captions,
+              node = excluded.node, lease_until = excluded.lease_until,
+              clock_ms = COALESCE(excluded.clock_ms, clock_ms),
+              clock_steps = CASE WHEN excluded.clock_ms IS NULL THEN clock_steps ELSE excluded.clock_steps END",
+            presentation_id,
+            slide,
+            fragment,
+            recording_id,
+            active_ms,
+            slides,
+            polls,
+            captions,
+            node,
+            LIVE_LEASE_SECS,
+            clock_ms,
+            clock_steps
+        )
+        .execute(db)
+        .await
+        .map_err(Error::from)
+        .map(|_| ())
+    }
+
+    /// Forgets the timeline of recording `recording_id` once it is saved or deleted.
+    pub async fn clear_recording(recording_id: i64, db: &SqlitePool) -> Result<(), Error> {
+        sqlx::query!(
+            "UPDATE live_state SET recording_id = NULL, active_ms = 0, recording_slides = '[]', recording_polls = '[]',
+             recording_captions = '[]'
+             WHERE recording_id = ?",
+            recording_id
+        )
+        .execute(db)
+        .await
+        .map_err(Error::from)
+        .map(|_| ())
+    }
+
+    fn from_row(row: &LiveStateRow) -> Result<Self, Error> {
+        let decode = |e: serde_json::Error| Error::Sqlx(sqlx::Error::Decode(Box::new(e)));
+        let recording = match row.recording_id {
+            Some(recording_id) => Some(RecordingCheckpoint {
+                recording_id,
+                active_ms: u64::try_from(row.active_ms).unwrap_or(0),
+                slides: serde_json::from_str(&row.recording_slides).map_err(decode)?,
+                polls: serde_json::from_str(&row.recording_polls).map_err(decode)?,
+                captions: serde_json::from_str(&row.recording_captions).map_err(decode)?,
+            }),
+            None => None,
+        };
+        let clock = match row.clock_ms {
+            Some(elapsed_ms) => Some((u64::try_from(elapsed_ms).unwrap_or(0), serde_json::from_str(&row.clock_steps).map_err(decode)?)),
+            None => None,
+        };
+        Ok(LiveState {
+            slide: u32::try_from(row.slide).unwrap_or(0),
+            fragment: u32::try_from(row.fragment).unwrap_or(0),
+            recording,
+            clock,
+        })
+    }
+}
+
+/// An image or media file uploaded to a presentation.
+///
+/// The file itself lives in `presentation_assets/{presentation_id}/{file_name}` and is
+/// served at `/assets/presentations/{presentation_id}/{file_name}`.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PresentationAsset {
+    pub id: i64,
+    pub presentation_id: i64,
+    pub file_name: String,
+    pub alt_text: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+impl PresentationAsset {
+    pub async fn create(
+        db: &SqlitePool,
+        presentation_id: i64,
+        file_name: &str,
+        alt_text: &str,
+    ) -> Result<Self, Error> {
+        sqlx::query_as!(
+            PresentationAsset,
+            r#"INSERT INTO presentation_asset (presentation_id, file_name, alt_text) VALUES (?, ?, ?)
+               RETURNING id, presentation_id, file_name, alt_text, created_at as "created_at: OffsetDateTime""#,
+            presentation_id,
+            file_name,
+            alt_text
+        )
+        .fetch_one(db)
+        .await
+        .map_err(Error::from)
+    }
+
+    /// Returns every upload of a presentation, oldest first.
+    pub async fn get_for_presentation(
+        db: &SqlitePool,
+        presentation_id: i64,
+    ) -> Result<Vec<Self>, Error> {
+        sqlx::query_as!(
+            PresentationAsset,
+            r#"SELECT id, presentation_id, file_name, alt_text, created_at as "created_at: OffsetDateTime"
+               FROM presentation_asset WHERE presentation_id = ? ORDER BY id"#,
+            presentation_id
+        )
+        .fetch_all(db)
+        .await
+        .map_err(Error::from)
+    }
+}
+
+/// A translation of a presentation's Markdown, slide for slide.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PresentationVariant {
+    pub presentation_id: i64,
+    /// Language tag of the translation, such as `fr` or `pt-BR`.
+    pub lang: String,
+    pub content: String,
+}
+
+impl PresentationVariant {
+    /// Returns every translation of a presentation, by language.
+    pub async fn get_for_presentation(db: &SqlitePool, presentation_id: i64) -> Result<Vec<Self>, Error> {
+        sqlx::query_as!(
+            PresentationVariant,
+            "SELECT * FROM presentation_variant WHERE presentation_id = ? ORDER BY lang",
+            presentation_id
+        )
+        .fetch_all(db)
+        .await
+        .map_err(Error::from)
+    }
+
+    /// Returns the translation of a presentation into `lang`, if it has one.
+    pub async fn get(db: &SqlitePool, presentation_id: i64, lang: &str) -> Result<Option<Self>, Error> {
+        sqlx::query_as!(
+            PresentationVariant,
+            "SELECT * FROM presentation_variant WHERE presentation_id = ? AND lang = ?",
+            presentation_id,
+            lang
+        )
+        .fetch_optional(db)
+        .await
+        .map_err(Error::from)
+    }
+
+    /// Saves the translation into `lang`, replacing any saved before.
+    pub async fn save(db: &SqlitePool, presentation_id: i64, lang: &str, content: &str) -> Result<(), Error> {
+        sqlx::query!(
+            "INSERT OR REPLACE INTO presentation_variant (presentation_id, lang, content) VALUES (?, ?, ?)",
+            presentation_id,
+            lang,
+            content
+        )
+        .execute(db)
+            .await
+            .map_err(Error::from)
+            .map(|_| ())
+    }
+
+    /// Removes the translation into `lang`.
+    pub async fn delete(db: &SqlitePool, presentation_id: i64, lang: &str) -> Result<(), Error> {
+        sqlx::query!("DELETE FROM presentation_variant WHERE presentation_id = ? AND lang = ?", presentation_id, lang)
+            .execute(db)
+            .await
+            .map_err(Error::from)
+            .map(|_| ())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct User {
     pub id: i64,
@@ -603,8 +1361,8 @@ impl AuthnBackend for Backend {
 
 /// The result of an access check for a presentation or recording.
 ///
-/// Priority order: Owner > Editor > Controller > Audience > PublicOk > Denied.
-/// Owners, editors, and controllers bypass the visibility mode check entirely.
+/// Priority order: Owner > Editor > Controller > Captioner > Audience > PublicOk > Denied.
+/// Owners, editors, controllers and captioners bypass the visibility mode check entirely.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AccessResult {
     /// The user owns the presentation.
@@ -613,6 +1371,8 @@ pub enum AccessResult {
     Editor,
     /// The user has controller access (can move between slides only).
     Controller,
+    /// The user sends live captions, and otherwise follows along like the audience.
+    Captioner,
     /// The user is explicitly listed as an audience member.
     Audience,
     /// The presentation is public and the user has no named role.
@@ -621,6 +1381,23 @@ pub enum AccessResult {
     Denied,
 }
 
+impl AccessResult {
+    /// The role name as stored in `presentation_access.role`, or a label for the
+    /// roles that have no row there (`owner`, `public`, `denied`).
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccessResult::Owner => "owner",
+            AccessResult::Editor => "editor",
+            AccessResult::Controller => "controller",
+            AccessResult::Captioner => "captioner",
+            AccessResult::Audience => "audience",
+            AccessResult::PublicOk => "public",
+            AccessResult::Denied => "denied",
+        }
+    }
+}
+
 /// Checks what level of access a user (or unauthenticated visitor) has to a
 /// presentation, optionally scoped to a specific recording.
 ///
@@ -630,7 +1407,7 @@ pub enum AccessResult {
 ///   presentation's when non-NULL (recording-level visibility). Pass `None`
 ///   for pure presentation access checks.
 ///
-/// Priority: Owner > Editor > Controller > Audience (when mode allows) > PublicOk > Denied.
+/// Priority: Owner > Editor > Controller > Captioner > Audience (when mode allows) > PublicOk > Denied.
 pub async fn check_access(
     db: &SqlitePool,
     user: Option<&User>,
@@ -679,6 +1456,7 @@ pub async fn check_access(
             return match row.role.as_str() {
                 "editor" => Ok(AccessResult::Editor),
                 "controller" => Ok(AccessResult::Controller),
+                "captioner" => Ok(AccessResult::Captioner),
                 // Audience role is ignored in private mode
                 "audience" if effective_mode != "private" => Ok(AccessResult::Audience),
                 _ => Ok(AccessResult::Denied),
@@ -859,6 +1637,26 @@ mod access_tests {
         );
     }
 
+    /// A captioner must get `AccessResult::Captioner`, even on a private presentation.
+    #[tokio::test]
+    async fn check_access_captioner() {
+        let pool = setup_pool().await;
+        let owner = make_user(&pool, "owner_cap").await;
+        let captioner = make_user(&pool, "captioner_cap").await;
+        let pres = make_presentation(&owner, &pool).await;
+        sqlx::query("UPDATE presentation SET access_mode = 'private' WHERE id = ?")
+            .bind(pres.id).execute(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO presentation_access (presentation_id, user_id, role) VALUES (?, ?, 'captioner')",
+        )
+        .bind(pres.id).bind(captioner.id).execute(&pool).await.unwrap();
+        let result = check_access(&pool, Some(&captioner), pres.id, None).await.unwrap();
+        assert!(
+            matches!(result, AccessResult::Captioner),
+            "captioner must get Captioner on a private presentation"
+        );
+    }
+
     /// An unrelated authenticated user on a private presentation must get Denied.
     #[tokio::test]
     async fn check_access_unrelated_user_denied() {
@@ -1080,4 +1878,92 @@ mod access_tests {
         let updated = Recording::get_by_id(rec.id, &pool).await.unwrap().unwrap();
         assert!(updated.last_edited.is_some());
     }
+
+    /// A checkpoint must read back as saved, be found by its recording, and mark the
+    /// recording interrupted until its timeline is cleared.
+    #[tokio::test]
+    async fn live_state_round_trips_and_marks_interrupted() {
+        let pool = setup_pool().await;
+        let owner = make_user(&pool, "owner").await;
+        let pres = make_presentation(&owner, &pool).await;
+        let rec = Recording::create(pres.id, "Live".to_string(), None, String::new(), &pool)
+            .await
+            .unwrap();
+        assert_eq!(LiveState::get(pres.id, &pool).await.unwrap(), None);
+        let state = LiveState {
+            slide: 3,
+            fragment: 1,
+            recording: Some(RecordingCheckpoint {
+                recording_id: rec.id,
+                active_ms: 4200,
+                slides: vec![(0, 0, 0, None), (1500, 3, 0, None), (1800, 3, 1, None), (2100, 3, 1, Some(2))],
+                polls: vec![(2000, 7, true)],
+                captions: vec![(2500, "Welcome, everyone".to_string())],
+            }),
+            clock: Some((9000, vec![(0, 0), (1500, 3)])),
+        };
+        state.save(pres.id, 1, &pool).await.unwrap();
+        assert_eq!(LiveState::get(pres.id, &pool).await.unwrap(), Some(state.clone()));
+        assert_eq!(
+            LiveState::get_for_recording(rec.id, &pool).await.unwrap(),
+            Some((pres.id, state.recording.clone().unwrap()))
+        );
+
+
+        assert_eq!(Recording::mark_interrupted(Some(2), &pool).await.unwrap(), 0, "process 1 still holds it");
+        assert_eq!(Recording::mark_interrupted(Some(1), &pool).await.unwrap(), 1, "process 1 restarted");
+        assert!(Recording::get_by_id(rec.id, &pool).await.unwrap().unwrap().interrupted);
+        sqlx::query("UPDATE recording SET interrupted = 0").execute(&pool).await.unwrap();
+        sqlx::query("UPDATE live_state SET lease_until = strftime('%s', 'now') - 1").execute(&pool).await.unwrap();
+        assert_eq!(Recording::mark_interrupted(None, &pool).await.unwrap(), 1, "process 1 stopped renewing its lease");
+
+        LiveState::clear_recording(rec.id, &pool).await.unwrap();
+        let cleared = LiveState { slide: 3, fragment: 1, recording: None, clock: state.clock.clone() };
+        assert_eq!(LiveState::get(pres.id, &pool).await.unwrap(), Some(cleared));
+        LiveState { slide: 0, fragment: 0, recording: None, clock: None }.save(pres.id, 1, &pool).await.unwrap();
+        let saved = LiveState::get(pres.id, &pool).await.unwrap().unwrap();
+        assert_eq!((saved.slide, saved.clock), (0, state.clock.clone()), "a new session keeps the last one's clock until its own starts");
+        assert_eq!(LiveState::get_for_recording(rec.id, &pool).await.unwrap(), None);
+        assert_eq!(Recording::mark_interrupted(Some(1), &pool).await.unwrap(), 0);
+    }
+
+    /// Languages and translations must read back as saved, and recorded slides must
+    /// fall back to the recorded deck where a translation has no slide.
+    #[tokio::test]
+    async fn languages_round_trip() {
+        let pool = setup_pool().await;
+        let owner = make_user(&pool, "owner").await;
+        let pres = make_presentation(&owner, &pool).await;
+        assert_eq!(Presentation::get_language(pres.id, &pool).await.unwrap(), None);
+        Presentation::set_language(pres.id, Some("en"), &pool).await.unwrap();
+        assert_eq!(Presentation::get_language(pres.id, &pool).await.unwrap().as_deref(), Some("en"));
+        Presentation::set_language(pres.id, None, &pool).await.unwrap();
+        assert_eq!(Presentation::get_language(pres.id, &pool).await.unwrap(), None);
+
+        PresentationVariant::save(&pool, pres.id, "fr", "## Bonjour").await.unwrap();
+        PresentationVariant::save(&pool, pres.id, "de", "## Hallo").await.unwrap();
+        PresentationVariant::save(&pool, pres.id, "fr", "## Salut").await.unwrap();
+        let langs: Vec<(String, String)> = PresentationVariant::get_for_presentation(&pool, pres.id)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|v| (v.lang, v.content))
+            .collect();
+        assert_eq!(langs, [("de".to_string(), "## Hallo".to_string()), ("fr".to_string(), "## Salut".to_string())]);
+        PresentationVariant::delete(&pool, pres.id, "de").await.unwrap();
+        assert!(PresentationVariant::get(&pool, pres.id, "de").await.unwrap().is_none());
+
+        let rec = Recording::create(pres.id, "Live".to_string(), None, String::new(), &pool)
+            .await
+            .unwrap();
+        let slide = |title: &str| RecordingSlideInput { start_seconds: 0.0, title: title.to_string(), content: title.to_string() };
+        RecordingSlide::create_batch(rec.id, vec![slide("Hello"), slide("Bye")], &pool).await.unwrap();
+        RecordingSlide::create_variants(rec.id, "fr", vec![(0, "Bonjour".to_string(), "Bonjour".to_string())], &pool)
+            .await
+            .unwrap();
+        assert_eq!(RecordingSlide::languages(rec.id, &pool).await.unwrap(), ["fr"]);
+        let titles: Vec<String> =
+            RecordingSlide::get_by_recording_in(rec.id, "fr", &pool).await.unwrap().into_iter().map(|s| s.title).collect();
+        assert_eq!(titles, ["Bonjour", "Bye"]);
+    }
 }
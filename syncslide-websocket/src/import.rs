@@ -0,0 +1,854 @@
+//! Converts uploaded `.pptx` and `.odp` decks into slide Markdown.
+//!
+//! Titles become `## ` headings, bullet hierarchies nested lists, tables GFM tables and
+//! speaker notes a [`NOTES_MARKER`](super::NOTES_MARKER) section. Embedded images are
+//! extracted with their alt text. Anything else is reported in
+//! [`ImportedDeck::warnings`] instead of being dropped silently.
+
+use quick_xml::{Reader, XmlVersion, escape::resolve_predefined_entity, events::BytesStart, events::Event};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::{Cursor, Read};
+use zip::{ZipArchive, result::ZipError};
+
+/// Total bytes an import may unpack; stops zip bombs long before memory runs out.
+const MAX_UNPACKED_BYTES: u64 = 200 * 1024 * 1024;
+
+/// Deepest element nesting a part may have. Real slides stay far below it; the tree
+/// is walked and dropped recursively, so unbounded nesting would overflow the stack.
+const MAX_XML_DEPTH: usize = 256;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ImportError {
+    #[error("This is not a PowerPoint (.pptx) or OpenDocument (.odp) presentation.")]
+    UnknownFormat,
+    #[error("The presentation file is damaged: {0}.")]
+    Damaged(String),
+    #[error("The presentation unpacks to more than 200 MB.")]
+    TooLarge,
+}
+
+/// A converted deck whose images still need to be written next to it.
+pub struct ImportedDeck {
+    slides: Vec<Slide>,
+    /// Extracted images as `(file name, bytes)`; names are unique and pass `asset_file_name`.
+    pub images: Vec<(String, Vec<u8>)>,
+    /// Alt text of each image file, as first given on a slide.
+    alts: HashMap<String, String>,
+    /// One human-readable line per element that could not be converted.
+    pub warnings: Vec<String>,
+}
+
+impl ImportedDeck {
+    /// Returns the alt text the slides give image `file`, or `""` if they give none.
+    pub fn alt_text(&self, file: &str) -> &str {
+        self.alts.get(file).map_or("", String::as_str)
+    }
+
+    /// Renders the deck as Markdown, pointing images at `asset_prefix` + file name.
+    pub fn markdown(&self, asset_prefix: &str) -> String {
+        let mut out = String::new();
+        for (i, slide) in self.slides.iter().enumerate() {
+            if i > 0 {
+                out.push('\n');
+            }
+            match &slide.title {
+                Some(title) => {
+                    let _ = writeln!(out, "## {title}");
+                }
+                None => {
+                    let _ = writeln!(out, "## Slide {}", i + 1);
+                }
+            }
+            push_blocks(&mut out, &slide.blocks, asset_prefix);
+            if !slide.notes.is_empty() {
+                let _ = writeln!(out, "\n{}", super::NOTES_MARKER);
+                out.push_str(&slide.notes.join("\n\n"));
+                out.push('\n');
+            }
+        }
+        out
+    }
+}
+
+#[derive(Default)]
+struct Slide {
+    /// Escaped title text, if the slide has one.
+    title: Option<String>,
+    blocks: Vec<Block>,
+    /// Escaped speaker-note paragraphs.
+    notes: Vec<String>,
+}
+
+/// Slide content in source order; all text is already Markdown-escaped.
+enum Block {
+    Item { level: usize, ordered: bool, text: String },
+    Paragraph(String),
+    Image { alt: String, file: String },
+    Table(Vec<Vec<String>>),
+}
+
+/// Writes `blocks` as Markdown, indenting nested list items under their parent's content.
+fn push_blocks(out: &mut String, blocks: &[Block], asset_prefix: &str) {
+    // Column where each open list level's item content starts.
+    let mut indents: Vec<usize> = Vec::new();
+    for block in blocks {
+        if let Block::Item { level, ordered, text } = block {
+            if indents.is_empty() {
+                out.push('\n');
+            }
+            // A level can only be one deeper than the item before it.
+            indents.truncate((*level).min(indents.len()));
+            let column = indents.last().copied().unwrap_or(0);
+            let marker = if *ordered { "1. " } else { "- " };
+            let _ = writeln!(out, "{:column$}{marker}{text}", "");
+            indents.push(column + marker.len());
+            continue;
+        }
+        indents.clear();
+        out.push('\n');
+        match block {
+            Block::Paragraph(text) => {
+                let _ = writeln!(out, "{text}");
+            }
+            Block::Image { alt, file } => {
+                let _ = writeln!(out, "![{alt}]({asset_prefix}{file})");
+            }
+            Block::Table(rows) => push_table(out, rows),
+            Block::Item { .. } => {}
+        }
+    }
+}
+
+/// Writes `rows` as a GFM table whose first row is the header.
+fn push_table(out: &mut String, rows: &[Vec<String>]) {
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    for (i, row) in rows.iter().enumerate() {
+        out.push('|');
+        for col in 0..width {
+            let cell = row.get(col).map_or("", String::as_str);
+            let _ = write!(out, " {cell} |");
+        }
+        out.push('\n');
+        if i == 0 {
+            out.push('|');
+            out.push_str(&" --- |".repeat(width));
+            out.push('\n');
+        }
+    }
+}
+
+/// Escapes text so it renders literally, whatever block it ends up in.
+fn escape_markdown(text: &str) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut out = String::with_capacity(text.len());
+    for (i, c) in text.char_indices() {
+        let starts_block = i == 0 && matches!(c, '-' | '+' | '=' | ':' | '?');
+        if starts_block || "\\`*_[]<>#|${}~^&".contains(c) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    // `1. text` and `1) text` would start an ordered list.
+    let digits = out.bytes().take_while(u8::is_ascii_digit).count();
+    if digits > 0 && matches!(out.as_bytes().get(digits), Some(b'.' | b')')) {
+        out.insert(digits, '\\');
+    }
+    out
+}
+
+/// An XML element with its children, enough of a DOM to walk slide parts.
+#[derive(Default)]
+struct Element {
+    /// Qualified name, e.g. `a:p`.
+    name: String,
+    /// Attributes by qualified name.
+    attrs: Vec<(String, String)>,
+    children: Vec<Node>,
+}
+
+enum Node {
+    Element(Element),
+    Text(String),
+}
+
+impl Element {
+    fn from_start(start: &BytesStart<'_>) -> Result<Self, ImportError> {
+        let mut attrs = Vec::new();
+        for attr in start.attributes() {
+            let attr = attr.map_err(|e| ImportError::Damaged(e.to_string()))?;
+            let value = attr
+                .normalized_value(XmlVersion::Implicit1_0)
+                .map_err(|e| ImportError::Damaged(e.to_string()))?;
+            attrs.push((attr.key.as_ref().to_string(), value.into_owned()));
+        }
+        Ok(Element {
+            name: start.name().as_ref().to_string(),
+            attrs,
+            children: Vec::new(),
+        })
+    }
+
+    /// Name without its namespace prefix; prefixes are conventional, not fixed.
+    fn local(&self) -> &str {
+        self.name.rsplit_once(':').map_or(&self.name, |(_, local)| local)
+    }
+
+    fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|node| match node {
+            Node::Element(e) => Some(e),
+            Node::Text(_) => None,
+        })
+    }
+
+    fn child(&self, local: &str) -> Option<&Element> {
+        self.elements().find(|e| e.local() == local)
+    }
+
+    /// First descendant named `local`, depth first.
+    fn find(&self, local: &str) -> Option<&Element> {
+        self.elements()
+            .find_map(|e| if e.local() == local { Some(e) } else { e.find(local) })
+    }
+
+    /// Every descendant named one of `locals`, in document order.
+    fn descendants<'a>(&'a self, locals: &[&str], out: &mut Vec<&'a Element>) {
+        for e in self.elements() {
+            if locals.contains(&e.local()) {
+                out.push(e);
+            } else {
+                e.descendants(locals, out);
+            }
+        }
+    }
+}
+
+fn parse_xml(bytes: &[u8]) -> Result<Element, ImportError> {
+    fn damaged(e: &impl ToString) -> ImportError {
+        ImportError::Damaged(e.to_string())
+    }
+    fn push(stack: &mut [Element], node: Node) {
+        if let Some(parent) = stack.last_mut() {
+            parent.children.push(node);
+        }
+    }
+    let text = std::str::from_utf8(bytes).map_err(|e| damaged(&e))?;
+    let mut reader = Reader::from_str(text);
+    // The bottom entry collects the document's root element.
+    let mut stack = vec![Element::default()];
+    loop {
+        match reader.read_event().map_err(|e| damaged(&e))? {
+            Event::Start(start) => {
+                if stack.len() > MAX_XML_DEPTH {
+                    return Err(damaged(&"XML nested too deeply"));
+                }
+                stack.push(Element::from_start(&start)?);
+            }
+            Event::Empty(start) => {
+                let element = Element::from_start(&start)?;
+                push(&mut stack, Node::Element(element));
+            }
+            Event::End(_) => {
+                let element = stack.pop().filter(|_| !stack.is_empty());
+                let element = element.ok_or_else(|| damaged(&"unbalanced XML"))?;
+                push(&mut stack, Node::Element(element));
+            }
+            Event::Text(t) => push(&mut stack, Node::Text(t.xml10_content().into_owned())),
+            Event::CData(t) => push(&mut stack, Node::Text(t.xml10_content().into_owned())),
+            Event::GeneralRef(r) => {
+                let resolved = match r.resolve_char_ref().map_err(|e| damaged(&e))? {
+                    Some(c) => c.to_string(),
+                    None => resolve_predefined_entity(&r.xml10_content())
+                        .ok_or_else(|| damaged(&"unknown XML entity"))?
+                        .to_string(),
+                };
+                push(&mut stack, Node::Text(resolved));
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    let root = stack.pop().filter(|_| stack.is_empty());
+    root.and_then(|root| root.children.into_iter().find_map(|node| match node {
+        Node::Element(e) => Some(e),
+        Node::Text(_) => None,
+    }))
+    .ok_or_else(|| damaged(&"unbalanced XML"))
+}
+
+/// A zip package read under a shared [`MAX_UNPACKED_BYTES`] budget.
+struct Package<'a> {
+    archive: ZipArchive<Cursor<&'a [u8]>>,
+    budget: u64,
+}
+
+impl Package<'_> {
+    fn contains(&self, name: &str) -> bool {
+        self.archive.index_for_name(name).is_some()
+    }
+
+    fn read(&mut self, name: &str) -> Result<Option<Vec<u8>>, ImportError> {
+        let file = match self.archive.by_name(name) {
+            Ok(file) => file,
+            Err(ZipError::FileNotFound) => return Ok(None),
+            Err(e) => return Err(ImportError::Damaged(e.to_string())),
+        };
+        let mut bytes = Vec::new();
+        // Read one byte past the budget so an overrun is detectable.
+        file.take(self.budget + 1)
+            .read_to_end(&mut bytes)
+            .map_err(|e| ImportError::Damaged(e.to_string()))?;
+        let len = u64::try_from(bytes.len()).unwrap_or(u64::MAX);
+        if len > self.budget {
+            return Err(ImportError::TooLarge);
+        }
+        self.budget -= len;
+        Ok(Some(bytes))
+    }
+
+    fn read_xml(&mut self, name: &str) -> Result<Option<Element>, ImportError> {
+        self.read(name)?.map(|bytes| parse_xml(&bytes)).transpose()
+    }
+}
+
+/// Resolves a relationship or link `target` against the part `base` it appears in.
+fn resolve_part(base: &str, target: &str) -> String {
+    let mut parts: Vec<&str> = match target.strip_prefix('/') {
+        Some(_) => Vec::new(),
+        None => base.split('/').collect(),
+    };
+    // Drop the base's file name; targets are relative to its directory.
+    parts.pop();
+    for segment in target.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            segment => parts.push(segment),
+        }
+    }
+    parts.join("/")
+}
+
+/// Shared conversion state for both formats.
+struct Converter<'a> {
+    package: Package<'a>,
+    images: Vec<(String, Vec<u8>)>,
+    /// Package part of each extracted image → its file name, so reuse shares one file.
+    image_files: HashMap<String, String>,
+    alts: HashMap<String, String>,
+    warnings: Vec<String>,
+}
+
+impl Converter<'_> {
+    fn warn(&mut self, slide: usize, what: &str) {
+        let warning = format!("Slide {slide}: {what} could not be imported.");
+        if !self.warnings.contains(&warning) {
+            self.warnings.push(warning);
+        }
+    }
+
+    /// Extracts the image at package `part` and returns the block showing it.
+    fn image(&mut self, slide: usize, part: &str, alt: Option<&str>) -> Result<Option<Block>, ImportError> {
+        let file_name = part.rsplit('/').next().unwrap_or(part);
+        let kind = super::image_mime_type(std::path::Path::new(file_name));
+        if kind == "application/octet-stream" {
+            self.warn(slide, &format!("The image {file_name} (a format browsers cannot show)"));
+            return Ok(None);
+        }
+        let file = if let Some(file) = self.image_files.get(part) {
+            file.clone()
+        } else {
+            let Some(bytes) = self.package.read(part)? else {
+                self.warn(slide, &format!("The missing image {file_name}"));
+                return Ok(None);
+            };
+            let mut file = super::asset_file_name(file_name);
+            if self.images.iter().any(|(taken, _)| *taken == file) {
+                file = format!("{}-{file}", self.images.len() + 1);
+            }
+            self.images.push((file.clone(), bytes));
+            self.image_files.insert(part.to_string(), file.clone());
+            file
+        };
+        let alt = alt.map(str::trim).filter(|alt| !alt.is_empty());
+        if let Some(alt) = alt {
+            self.alts.entry(file.clone()).or_insert_with(|| alt.to_string());
+        } else {
+            self.warnings.push(format!(
+                "Slide {slide}: The image {file} has no alt text; add a description."
+            ));
+        }
+        Ok(Some(Block::Image { alt: alt.map(escape_markdown).unwrap_or_default(), file }))
+    }
+}
+
+/// Converts a `.pptx` or `.odp` file.
+pub fn import_deck(bytes: &[u8]) -> Result<ImportedDeck, ImportError> {
+    let archive = ZipArchive::new(Cursor::new(bytes)).map_err(|_| ImportError::UnknownFormat)?;
+    let mut converter = Converter {
+        package: Package { archive, budget: MAX_UNPACKED_BYTES },
+        images: Vec::new(),
+        image_files: HashMap::new(),
+        alts: HashMap::new(),
+        warnings: Vec::new(),
+    };
+    let slides = if converter.package.contains("ppt/presentation.xml") {
+        pptx::slides(&mut converter)?
+    } else if converter.package.contains("content.xml")
+        && converter
+            .package
+            .read("mimetype")?
+            .is_some_and(|m| m.starts_with(b"application/vnd.oasis.opendocument.presentation"))
+    {
+        odp::slides(&mut converter)?
+    } else {
+        return Err(ImportError::UnknownFormat);
+    };
+    Ok(ImportedDeck { slides, images: converter.images, alts: converter.alts, warnings: converter.warnings })
+}
+
+/// Office Open XML (`PowerPoint`) packages.
+mod pptx {
+    use super::{Block, Converter, Element, ImportError, Slide, escape_markdown, resolve_part};
+    use std::collections::HashMap;
+
+    /// Relationship id → (type, resolved part) for one part.
+    type Relationships = HashMap<String, (String, String)>;
+
+    fn relationships(converter: &mut Converter<'_>, part: &str) -> Result<Relationships, ImportError> {
+        let (dir, file) = part.rsplit_once('/').unwrap_or(("", part));
+        let Some(rels) = converter.package.read_xml(&format!("{dir}/_rels/{file}.rels"))? else {
+            return Ok(HashMap::new());
+        };
+        Ok(rels
+            .elements()
+            .filter(|rel| rel.attr("TargetMode") != Some("External"))
+            .filter_map(|rel| {
+                let id = rel.attr("Id")?.to_string();
+                let kind = rel.attr("Type").unwrap_or_default().to_string();
+                Some((id, (kind, resolve_part(part, rel.attr("Target")?))))
+            })
+            .collect())
+    }
+
+    pub(super) fn slides(converter: &mut Converter<'_>) -> Result<Vec<Slide>, ImportError> {
+        const MAIN: &str = "ppt/presentation.xml";
+        let Some(presentation) = converter.package.read_xml(MAIN)? else {
+            return Err(ImportError::UnknownFormat);
+        };
+        let rels = relationships(converter, MAIN)?;
+        let mut slides = Vec::new();
+        let ids = presentation.child("sldIdLst").map(|list| list.elements().collect::<Vec<_>>());
+        for id in ids.unwrap_or_default() {
+            let Some((_, part)) = id.attr("r:id").and_then(|id| rels.get(id)) else {
+                return Err(ImportError::Damaged("a slide is missing".to_string()));
+            };
+            let number = slides.len() + 1;
+            slides.push(slide(converter, number, part)?);
+        }
+        Ok(slides)
+    }
+
+    fn slide(converter: &mut Converter<'_>, number: usize, part: &str) -> Result<Slide, ImportError> {
+        let Some(xml) = converter.package.read_xml(part)? else {
+            return Err(ImportError::Damaged(format!("slide {number} is missing")));
+        };
+        let rels = relationships(converter, part)?;
+        let mut slide = Slide::default();
+        if let Some(tree) = xml.find("spTree") {
+            for shape in tree.elements() {
+                self::shape(converter, number, shape, &rels, &mut slide)?;
+            }
+        }
+        let notes_part = rels.values().find(|(kind, _)| kind.ends_with("/notesSlide"));
+        if let Some((_, notes_part)) = notes_part
+            && let Some(notes) = converter.package.read_xml(notes_part)?
+            && let Some(tree) = notes.find("spTree")
+        {
+            let body = tree
+                .elements()
+                .find(|sp| placeholder(sp).is_some_and(|ph| ph.attr("type") == Some("body")));
+            if let Some(txt) = body.and_then(|sp| sp.child("txBody")) {
+                slide.notes = txt
+                    .elements()
+                    .filter(|p| p.local() == "p")
+                    .map(text)
+                    .filter(|t| !t.is_empty())
+                    .collect();
+            }
+        }
+        Ok(slide)
+    }
+
+    fn placeholder(shape: &Element) -> Option<&Element> {
+        shape.elements().next()?.child("nvPr")?.child("ph")
+    }
+
+    fn shape(
+        converter: &mut Converter<'_>,
+        number: usize,
+        shape: &Element,
+        rels: &Relationships,
+        slide: &mut Slide,
+    ) -> Result<(), ImportError> {
+        match shape.local() {
+            "sp" => {
+                // A placeholder without a type is a content ("obj") placeholder.
+                let kind = placeholder(shape).map(|ph| ph.attr("type").unwrap_or("obj"));
+                let Some(body) = shape.child("txBody") else {
+                    return Ok(());
+                };
+                match kind {
+                    Some("title" | "ctrTitle") => {
+                        let title: Vec<String> = body
+                            .elements()
+                            .filter(|p| p.local() == "p")
+                            .map(text)
+                            .filter(|t| !t.is_empty())
+                            .collect();
+                        if !title.is_empty() {
+                            slide.title = Some(title.join(" "));
+                        }
+                    }
+                    // Filled in by the application, not written by the author.
+                    Some("sldNum" | "dt" | "ftr" | "hdr") => {}
+                    _ => {
+                        let bullets = kind.is_some_and(|kind| kind != "subTitle");
+                        for p in body.elements().filter(|p| p.local() == "p") {
+                            slide.blocks.extend(paragraph(p, bullets));
+                        }
+                    }
+                }
+            }
+            "pic" => {
+                let media = shape.find("nvPr").is_some_and(|nv| {
+                    nv.elements().any(|e| matches!(e.local(), "videoFile" | "audioFile" | "media"))
+                });
+                if media {
+                    converter.warn(number, "A video or audio clip");
+                    return Ok(());
+                }
+                let alt = shape.find("cNvPr").and_then(|c| c.attr("descr"));
+                let part = shape
+                    .find("blip")
+                    .and_then(|blip| blip.attr("r:embed"))
+                    .and_then(|id| rels.get(id));
+                match part {
+                    Some((_, part)) => slide.blocks.extend(converter.image(number, part, alt)?),
+                    None => converter.warn(number, "A linked image"),
+                }
+            }
+            "graphicFrame" => {
+                if let Some(table) = shape.find("tbl") {
+                    let rows: Vec<Vec<String>> = table
+                        .elements()
+                        .filter(|tr| tr.local() == "tr")
+                        .map(|tr| tr.elements().filter(|tc| tc.local() == "tc").map(cell).collect())
+                        .collect();
+                    if !rows.is_empty() {
+                        slide.blocks.push(Block::Table(rows));
+                    }
+                    return Ok(());
+                }
+                let uri = shape.find("graphicData").and_then(|d| d.attr("uri")).unwrap_or_default();
+                if uri.ends_with("/chart") {
+                    converter.warn(number, "A chart");
+                } else if uri.ends_with("/diagram") {
+                    converter.warn(number, "A SmartArt diagram");
+                } else {
+                    converter.warn(number, "An embedded object");
+                }
+            }
+            "grpSp" => {
+                for child in shape.elements() {
+                    self::shape(converter, number, child, rels, slide)?;
+                }
+            }
+            // Newer features ship with a fallback older readers understand.
+            "AlternateContent" => {
+                if let Some(fallback) = shape.child("Fallback") {
+                    for child in fallback.elements() {
+                        self::shape(converter, number, child, rels, slide)?;
+                    }
+                }
+            }
+            // Group properties and connector lines carry no content.
+            "nvGrpSpPr" | "grpSpPr" | "cxnSp" | "extLst" => {}
+            "contentPart" => converter.warn(number, "A drawing made with ink"),
+            _ => converter.warn(number, "An unsupported shape"),
+        }
+        Ok(())
+    }
+
+    fn cell(tc: &Element) -> String {
+        if tc.attr("hMerge").is_some() || tc.attr("vMerge").is_some() {
+            return String::new();
+        }
+        let mut paragraphs = Vec::new();
+        tc.descendants(&["p"], &mut paragraphs);
+        let texts: Vec<String> = paragraphs.into_iter().map(text).filter(|t| !t.is_empty()).collect();
+        texts.join(" ")
+    }
+
+    /// Converts `a:p`; `bullets` is whether the shape's paragraphs are bulleted by default.
+    fn paragraph(p: &Element, bullets: bool) -> Option<Block> {
+        let text = text(p);
+        if text.is_empty() {
+            return None;
+        }
+        let properties = p.child("pPr");
+        let level = properties
+            .and_then(|pr| pr.attr("lvl"))
+            .and_then(|lvl| lvl.parse().ok())
+            .unwrap_or(0);
+        let bullet = properties.and_then(|pr| {
+            pr.elements()
+                .map(Element::local)
+                .find(|name| matches!(*name, "buNone" | "buChar" | "buAutoNum" | "buBlip"))
+        });
+        match bullet {
+            Some("buNone") => Some(Block::Paragraph(text)),
+            Some("buAutoNum") => Some(Block::Item { level, ordered: true, text }),
+            Some(_) => Some(Block::Item { level, ordered: false, text }),
+            None if bullets || level > 0 => Some(Block::Item { level, ordered: false, text }),
+            None => Some(Block::Paragraph(text)),
+        }
+    }
+
+    /// Escaped text of `a:p`, line breaks folded into spaces.
+    fn text(p: &Element) -> String {
+        let mut raw = String::new();
+        for run in p.elements() {
+            match run.local() {
+                "r" | "fld" => {
+                    for t in run.elements().filter(|t| t.local() == "t") {
+                        for node in &t.children {
+                            if let super::Node::Text(s) = node {
+                                raw.push_str(s);
+                            }
+                        }
+                    }
+                }
+                "br" => raw.push(' '),
+                _ => {}
+            }
+        }
+        escape_markdown(&raw)
+    }
+}
+
+/// `OpenDocument` presentations.
+mod odp {
+    use super::{Block, Converter, Element, ImportError, Node, Slide, escape_markdown};
+    use std::collections::HashSet;
+
+    /// `(list style, level)` pairs that number their items; levels start at 1.
+    type NumberedLevels = HashSet<(String, usize)>;
+
+    pub(super) fn slides(converter: &mut Converter<'_>) -> Result<Vec<Slide>, ImportError> {
+        let Some(content) = converter.package.read_xml("content.xml")? else {
+            return Err(ImportError::UnknownFormat);
+        };
+        let mut numbered = NumberedLevels::new();
+        if let Some(styles) = content.child("automatic-styles") {
+            for style in styles.elements().filter(|s| s.local() == "list-style") {
+                let Some(name) = style.attr("style:name") else { continue };
+                for level in style.elements().filter(|l| l.local() == "list-level-style-number") {
+                    if let Some(n) = level.attr("text:level").and_then(|n| n.parse().ok()) {
+                        numbered.insert((name.to_string(), n));
+                    }
+                }
+            }
+        }
+        let mut pages = Vec::new();
+        if let Some(presentation) = content.child("body").and_then(|b| b.child("presentation")) {
+            pages.extend(presentation.elements().filter(|p| p.local() == "page"));
+        }
+        let mut slides = Vec::new();
+        for (i, page) in pages.into_iter().enumerate() {
+            let mut slide = Slide::default();
+            for shape in page.elements() {
+                self::shape(converter, i + 1, shape, &numbered, &mut slide)?;
+            }
+            slides.push(slide);
+        }
+        Ok(slides)
+    }
+
+    fn shape(
+        converter: &mut Converter<'_>,
+        number: usize,
+        shape: &Element,
+        numbered: &NumberedLevels,
+        slide: &mut Slide,
+    ) -> Result<(), ImportError> {
+        match shape.local() {
+            "frame" => frame(converter, number, shape, numbered, slide)?,
+            "g" => {
+                for child in shape.elements() {
+                    self::shape(converter, number, child, numbered, slide)?;
+                }
+            }
+            "notes" => {
+                let mut paragraphs = Vec::new();
+                for frame in shape.elements().filter(|f| f.attr("presentation:class") == Some("notes")) {
+                    frame.descendants(&["p", "h"], &mut paragraphs);
+                }
+                slide.notes = paragraphs.into_iter().map(text).filter(|t| !t.is_empty()).collect();
+            }
+            // Drawing shapes only matter for the text typed into them.
+            "custom-shape" | "rect" | "ellipse" | "circle" | "polygon" | "polyline" | "path"
+            | "line" | "connector" | "measure" => {
+                text_blocks(shape, false, numbered, &mut slide.blocks);
+            }
+            // Animations and form controls have no static content.
+            "par" | "seq" | "forms" => {}
+            _ => converter.warn(number, "An unsupported shape"),
+        }
+        Ok(())
+    }
+
+    fn frame(
+        converter: &mut Converter<'_>,
+        number: usize,
+        frame: &Element,
+        numbered: &NumberedLevels,
+        slide: &mut Slide,
+    ) -> Result<(), ImportError> {
+        let class = frame.attr("presentation:class");
+        if matches!(class, Some("page-number" | "footer" | "date-time" | "header")) {
+            return Ok(());
+        }
+        // A frame holds one piece of content, possibly followed by fallbacks for it.
+        let Some(content) = frame.elements().find(|e| !matches!(e.local(), "title" | "desc")) else {
+            return Ok(());
+        };
+        match content.local() {
+            "text-box" if class == Some("title") => {
+                let mut paragraphs = Vec::new();
+                content.descendants(&["p", "h"], &mut paragraphs);
+                let title: Vec<String> = paragraphs.into_iter().map(text).filter(|t| !t.is_empty()).collect();
+                if !title.is_empty() {
+                    slide.title = Some(title.join(" "));
+                }
+            }
+            "text-box" => text_blocks(content, class == Some("outline"), numbered, &mut slide.blocks),
+            "image" => {
+                let alt = ["title", "desc"]
+                    .iter()
+                    .filter_map(|name| frame.child(name))
+                    .map(raw_text)
+                    .find(|alt| !alt.trim().is_empty());
+                match content.attr("xlink:href") {
+                    Some(href) if !href.contains("://") => {
+                        let part = super::resolve_part("content.xml", href);
+                        slide.blocks.extend(converter.image(number, &part, alt.as_deref())?);
+                    }
+                    _ => converter.warn(number, "A linked image"),
+                }
+            }
+            "table" => {
+                let mut rows = Vec::new();
+                content.descendants(&["table-row"], &mut rows);
+                let rows: Vec<Vec<String>> = rows
+                    .into_iter()
+                    .map(|row| {
+                        row.elements()
+                            .filter(|c| matches!(c.local(), "table-cell" | "covered-table-cell"))
+                            .map(|c| {
+                                let mut paragraphs = Vec::new();
+                                c.descendants(&["p", "h"], &mut paragraphs);
+                                let texts: Vec<String> =
+                                    paragraphs.into_iter().map(text).filter(|t| !t.is_empty()).collect();
+                                texts.join(" ")
+                            })
+                            .collect()
+                    })
+                    .collect();
+                if !rows.is_empty() {
+                    slide.blocks.push(Block::Table(rows));
+                }
+            }
+            "object" | "object-ole" => converter.warn(number, "A chart or embedded object"),
+            "plugin" => converter.warn(number, "A video or audio clip"),
+            _ => converter.warn(number, "An unsupported frame"),
+        }
+        Ok(())
+    }
+
+    /// Converts the paragraphs and lists directly inside `container`.
+    fn text_blocks(container: &Element, bullets: bool, numbered: &NumberedLevels, out: &mut Vec<Block>) {
+        for child in container.elements() {
+            match child.local() {
+                "p" | "h" => {
+                    let text = text(child);
+                    if text.is_empty() {
+                        continue;
+                    }
+                    out.push(if bullets {
+                        Block::Item { level: 0, ordered: false, text }
+                    } else {
+                        Block::Paragraph(text)
+                    });
+                }
+                "list" => list(child, 0, None, numbered, out),
+                _ => {}
+            }
+        }
+    }
+
+    /// Converts a `text:list`; nested lists inherit the outermost list's style.
+    fn list(list: &Element, level: usize, style: Option<&str>, numbered: &NumberedLevels, out: &mut Vec<Block>) {
+        let style = list.attr("text:style-name").or(style);
+        let ordered = style.is_some_and(|s| numbered.contains(&(s.to_string(), level + 1)));
+        for item in list.elements().filter(|i| matches!(i.local(), "list-item" | "list-header")) {
+            for child in item.elements() {
+                match child.local() {
+                    "p" | "h" => {
+                        let text = text(child);
+                        if !text.is_empty() {
+                            out.push(Block::Item { level, ordered, text });
+                        }
+                    }
+                    "list" => self::list(child, level + 1, style, numbered, out),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// Escaped text of a `text:p` or `text:h`.
+    fn text(p: &Element) -> String {
+        escape_markdown(&raw_text(p))
+    }
+
+    fn raw_text(element: &Element) -> String {
+        let mut out = String::new();
+        push_text(element, &mut out);
+        out
+    }
+
+    /// Appends the text inside `element` to `out`, spacing elements included.
+    fn push_text(element: &Element, out: &mut String) {
+        for node in &element.children {
+            match node {
+                Node::Text(t) => out.push_str(t),
+                Node::Element(e) => match e.local() {
+                    "s" | "tab" | "line-break" => out.push(' '),
+                    // Footnotes and comments are not part of the line.
+                    "note" | "annotation" => {}
+                    _ => push_text(e, out),
+                },
+            }
+        }
+    }
+}
@@ -21,7 +21,7 @@ use axum::{
 };
 use axum_login::{AuthManagerLayerBuilder, AuthzBackend};
 use futures_lite::future::or;
-use futures_util::{SinkExt, StreamExt};
+use futures_util::{SinkExt, StreamExt, stream::{SplitSink, SplitStream}};
 use sqlx::SqlitePool;
 use sqlx::sqlite::SqliteConnectOptions;
 use std::str::FromStr;
@@ -33,25 +33,47 @@ use tower_http::services::ServeDir;
 use tower_sessions::{Expiry, SessionManagerLayer};
 use tower_sessions_sqlx_store::SqliteStore;
 
-use tokio::sync::broadcast::{self, Receiver, Sender};
+use tokio::sync::broadcast::{self, Receiver, Sender, error::{RecvError, TryRecvError}};
 
+use base64::{Engine, prelude::BASE64_STANDARD};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+use std::io::Write;
+use zip::{ZipWriter, write::SimpleFileOptions};
 
-use signal_hook::consts::signal::SIGUSR1;
+use signal_hook::consts::signal::{SIGINT, SIGTERM};
 use signal_hook_tokio::Signals;
 
-use pulldown_cmark::{Event, HeadingLevel, Options, Parser, Tag, html as cmark_html};
+use pulldown_cmark::{
+    CodeBlockKind, CowStr, Event, HeadingLevel, Options, Parser, Tag, TagEnd, html as cmark_html,
+};
 use std::{
-    collections::HashMap,
-    sync::{Arc, Mutex},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    ops::{Range, RangeInclusive},
+    sync::{
+        Arc, Mutex, Weak,
+        atomic::{AtomicU64, Ordering},
+    },
 };
 
+mod captions;
 mod db;
 use db::{
     check_access, AccessResult, AddUserForm, AuthSession, Backend, ChangePasswordForm, Group,
-    LoginForm, Presentation as DbPresentation, PresentationAccess, Recording, RecordingSlide,
-    RecordingSlideInput, User,
+    LoginForm, Poll, Presentation as DbPresentation, PresentationAccess, PresentationAsset, PresentationVariant,
+    PresentationRevision, Question, QuestionStatus, Recording, RecordingPoll, RecordingPollInput,
+    RecordingSlide, RecordingSlideInput, User, LiveState, RecordingCheckpoint, LIVE_LEASE_SECS,
 };
+mod import;
+mod pacing;
+use pacing::{ClockState, Overrun, Pacing, SessionClock};
+mod protocol;
+use protocol::{ErrorCode, Hello, ProtocolError};
+mod pubsub;
+use pubsub::{Payloads, PubSub, PubSubError};
+mod upload;
+use upload::{MediaKind, StagedFile, UploadLimit};
 
 /// Wraps Tera renderer so that we can force a special render process.
 #[derive(Clone)]
@@ -87,16 +109,106 @@ impl Tera {
 }
 
 /// A message indicating a _change_ in [`Presentation`] state.
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", content = "data")]
 #[serde(rename_all = "lowercase")]
 pub enum SlideMessage {
+    /// The protocol version, role and capabilities of the connection, sent to it first.
+    ///
+    /// Sent by the server only; never accepted from clients.
+    Hello(Hello),
+    /// Why a message from the connection was refused, sent to that connection only.
+    ///
+    /// Sent by the server only; never accepted from clients.
+    Error(ProtocolError),
     /// Change the `content` field of the presentation.
+    ///
+    /// Clients may send the whole deck; the server relays every edit as a [`Patch`](Self::Patch)
+    /// and only sends `Text` as part of a snapshot, followed by its [`Revision`](Self::Revision).
     Text(String),
-    /// Change the `slide` index.
+    /// Replace one range of the `content`, made against a known text revision.
+    Patch(TextPatch),
+    /// The text revision of the `Text` just sent; the next patch applies to it.
+    ///
+    /// Sent by the server only; never accepted from clients.
+    Revision(u64),
+    /// Where an editor's caret or selection is, relayed to the other editors.
+    Cursor(Cursor),
+    /// The editor on this connection id left; forget their cursor.
+    ///
+    /// Sent by the server only; never accepted from clients.
+    #[serde(rename = "cursor_gone")]
+    CursorGone(u64),
+    /// Where the connection is in the presentation's stream of broadcasts, sent once it is
+    /// up to date; reconnecting with it replays only what was missed.
+    ///
+    /// Sent by the server only; never accepted from clients.
+    Resume {
+        /// Identifies this run of the stream; sequence numbers restart with a new one.
+        stream: u64,
+        /// Sequence number of the last broadcast the connection is up to date with.
+        seq: u64,
+    },
+    /// Change the `slide` index, showing the slide before any of its fragment steps.
     Slide(u32),
+    /// Show a slide with some of its fragment steps revealed.
+    Fragment {
+        /// The slide index.
+        slide: u32,
+        /// How many steps are revealed; the server clamps it to the steps the slide has.
+        fragment: u32,
+    },
+    /// Point the audience at one block of the current slide, or at nothing.
+    ///
+    /// Presenters name the block by `target` (or by `block`); the server resolves it to its
+    /// own count and sends both, so clients find the element by `target` without
+    /// numbering blocks themselves.
+    Highlight {
+        /// The slide index; a pointer at any other slide than the current one is dropped.
+        slide: u32,
+        /// The block pointed at, counted as [`Deck::block`] counts them; `None` clears it.
+        block: Option<u32>,
+        /// What the block shows, when one is pointed at.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        target: Option<BlockTarget>,
+        /// The same block in each translation that has it, by language tag; set by the server.
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        variants: BTreeMap<String, BlockTarget>,
+    },
+    /// One line of live captions, from a captioner, shown to everyone.
+    Caption(String),
     /// Change the presentation name.
     Name(String),
+    /// The language tag of the `content`, or `None` if its presenters have not set one.
+    ///
+    /// Sent by the server only; never accepted from clients.
+    Language(Option<String>),
+    /// A translation of the deck, slide for slide; viewers may follow the presentation in it.
+    ///
+    /// Sent by the server only; never accepted from clients.
+    Variant {
+        /// Language tag of the translation.
+        lang: String,
+        /// The translated Markdown; `None` once the translation is removed.
+        text: Option<String>,
+    },
+    /// Speaker notes of the current slide.
+    ///
+    /// Sent by the server to presenter connections only; never accepted from clients.
+    Notes(String),
+    /// A newly asked or newly moderated audience question.
+    ///
+    /// Sent by the server to presenter connections only; never accepted from clients.
+    Question(Question),
+    /// The moderation queue of the presentation, sent to presenters on connect.
+    Questions(Vec<Question>),
+    /// The currently pinned questions, sent to everyone whenever they change.
+    #[serde(rename = "pinned_questions")]
+    PinnedQuestions(Vec<Question>),
+    /// The current poll and its tally, sent whenever it opens, receives a vote, or closes.
+    ///
+    /// Sent by the server only; never accepted from clients.
+    Poll(Poll),
     /// Start a new recording with elapsed time.
     #[serde(rename = "recording_start")]
     RecordingStart {
@@ -125,11 +237,199 @@ pub enum SlideMessage {
         /// Creation date in YYYY-MM-DD format.
         start: String,
     },
+    /// Who is connected to this process, sent to presenter connections only.
+    ///
+    /// Sent by the server, at most every [`PRESENCE_INTERVAL`]; never accepted from clients.
+    Presence(Presence),
+    /// How the session keeps to its planned timings, sent to presenter connections only
+    /// on each move to another slide and when something runs over.
+    ///
+    /// Sent by the server only; never accepted from clients.
+    Pacing(Pacing),
+}
+
+/// A replacement of one range of the deck's Markdown.
+///
+/// Offsets count UTF-16 code units, like JavaScript string indices, so browsers can
+/// compute and apply patches with plain string slicing.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct TextPatch {
+    /// The text revision the patch applies to; applying it yields revision `base + 1`.
+    base: u64,
+    start: usize,
+    end: usize,
+    text: String,
+    /// The same edit against the deck without speaker notes, for audience connections.
+    #[serde(skip)]
+    audience: Option<Box<TextPatch>>,
+    /// Connection the edit came from, if it came from an editor's patch.
+    #[serde(skip)]
+    author: Option<u64>,
+    /// Name of the editor who made the edit; filled in by the server.
+    #[serde(default, skip_deserializing, skip_serializing_if = "Option::is_none")]
+    by: Option<String>,
+    /// Set on the copy sent back to the author, which acknowledges their patch.
+    #[serde(default, skip_deserializing, skip_serializing_if = "std::ops::Not::not")]
+    mine: bool,
+}
+
+impl TextPatch {
+    /// The single-range patch that turns `old` into `new`.
+    fn between(old: &str, new: &str, base: u64) -> Self {
+        let prefix: usize = old
+            .chars()
+            .zip(new.chars())
+            .take_while(|(a, b)| a == b)
+            .map(|(c, _)| c.len_utf8())
+            .sum();
+        let (old_rest, new_rest) = (&old[prefix..], &new[prefix..]);
+        let suffix: usize = old_rest
+            .chars()
+            .rev()
+            .zip(new_rest.chars().rev())
+            .take_while(|(a, b)| a == b)
+            .map(|(c, _)| c.len_utf8())
+            .sum();
+        let start = utf16_len(&old[..prefix]);
+        TextPatch {
+            base,
+            start,
+            end: start + utf16_len(&old_rest[..old_rest.len() - suffix]),
+            text: new_rest[..new_rest.len() - suffix].to_string(),
+            ..TextPatch::default()
+        }
+    }
+
+    /// Rewrites the patch to apply after `other`, made against the same revision.
+    ///
+    /// Edits to separate ranges both survive. Where the ranges overlap, text deleted by
+    /// either stays deleted and `other`'s insertion is kept unless this patch replaces its
+    /// whole range; an edit wholly inside a range `other` replaced is dropped. Ties between
+    /// identical ranges or insertions at one point go to the `later` patch, so the server
+    /// (`later`) and a client with this patch in flight (not `later`) reach the same text.
+    fn transform(&self, other: &TextPatch, later: bool) -> TextPatch {
+        let inserted = utf16_len(&other.text);
+        // Moves a position at or after `other.end` past its edit. Saturating, so no range
+        // can make the arithmetic overflow; `apply` refuses a range that ends up not fitting.
+        let shift = |pos: usize| pos.saturating_add(inserted).saturating_sub(other.end.saturating_sub(other.start));
+        let text = self.text.clone();
+        let (start, end, text) = if self.start == self.end && other.start == other.end && self.start == other.start {
+            // Two insertions at one point: the later one goes after.
+            if later { (shift(self.start), shift(self.end), text) } else { (self.start, self.end, text) }
+        } else if self.end <= other.start {
+            (self.start, self.end, text)
+        } else if self.start >= other.end {
+            (shift(self.start), shift(self.end), text)
+        } else {
+            let covers = self.start <= other.start && self.end >= other.end;
+            let covered = other.start <= self.start && other.end >= self.end;
+            if covers && (!covered || later) {
+                (self.start, shift(self.end), text)
+            } else if covered {
+                (other.start, other.start, String::new())
+            } else if self.start > other.start {
+                // Starts inside `other`: edit what is left after its insertion.
+                (other.start.saturating_add(inserted), shift(self.end), text)
+            } else {
+                // Ends inside `other`: edit up to where it starts.
+                (self.start, other.start, text)
+            }
+        };
+        TextPatch { base: other.base + 1, start, end, text, ..TextPatch::default() }
+    }
+
+    /// Applies the patch to `content`, or `None` if its range does not fit it.
+    fn apply(&self, content: &str) -> Option<String> {
+        let start = utf16_to_byte(content, self.start)?;
+        let end = utf16_to_byte(content, self.end)?;
+        (start <= end).then(|| [&content[..start], self.text.as_str(), &content[end..]].concat())
+    }
+}
+
+/// An editor's caret or selection, as UTF-16 offsets into the deck.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Cursor {
+    /// Connection the cursor belongs to; filled in by the server.
+    #[serde(default, skip_deserializing)]
+    id: u64,
+    /// Name of the editor; filled in by the server.
+    #[serde(default, skip_deserializing)]
+    user: String,
+    start: usize,
+    end: usize,
+}
+
+/// A block of a slide as clients find it in what they rendered: the first element with
+/// this tag and text after `occurrence` others with the same.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct BlockTarget {
+    /// Tag of the element: `h1` to `h6`, `p`, `li`, `tr`, `th`, `td` or `pre`.
+    tag: String,
+    /// Its letters and digits, image alt text left out. Spacing and punctuation are dropped,
+    /// since the server and client Markdown renderers treat them differently.
+    text: String,
+    /// Blocks before it on the slide with the same tag and text.
+    occurrence: u32,
+}
+
+/// The connections to a live presentation, by role.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Presence {
+    /// Audience connections of either kind.
+    viewers: usize,
+    /// Connections of users given the audience role.
+    audience: usize,
+    /// Connections of anyone else to a public presentation.
+    public: usize,
+    /// Connections of owners, editors and controllers.
+    presenters: usize,
+    /// Connections of users given the captioner role.
+    captioners: usize,
+    /// Names of the signed-in viewers, sorted and without repeats.
+    users: Vec<String>,
+}
+
+/// Length of `s` in UTF-16 code units.
+fn utf16_len(s: &str) -> usize {
+    s.encode_utf16().count()
+}
+
+/// Byte offset of the UTF-16 offset `index` in `s`; `None` past the end or inside a character.
+fn utf16_to_byte(s: &str, index: usize) -> Option<usize> {
+    let mut units = 0;
+    for (byte, c) in s.char_indices() {
+        if units >= index {
+            return (units == index).then_some(byte);
+        }
+        units += c.len_utf16();
+    }
+    (units == index).then_some(s.len())
 }
 
 struct RecordingEvent {
     offset_ms: u64,
     slide: u32,
+    /// Fragment steps of the slide revealed.
+    fragment: u32,
+    /// Block of the slide the presenter pointed at.
+    highlight: Option<u32>,
+}
+
+/// A poll opening or closing during a recording.
+struct RecordingPollEvent {
+    offset_ms: u64,
+    poll_id: i64,
+    opened: bool,
+}
+
+impl From<RecordingPollEvent> for RecordingPollInput {
+    fn from(ev: RecordingPollEvent) -> Self {
+        RecordingPollInput {
+            poll_id: ev.poll_id,
+            start_seconds: std::time::Duration::from_millis(ev.offset_ms).as_secs_f64(),
+            event: if ev.opened { "open" } else { "close" },
+        }
+    }
 }
 
 struct RecordingState {
@@ -141,9 +441,59 @@ struct RecordingState {
     is_paused: bool,
     pause_started_at: Option<std::time::Instant>,
     slides: Vec<RecordingEvent>,
+    polls: Vec<RecordingPollEvent>,
+    /// `(offset_ms, text)` of each live caption.
+    captions: Vec<(u64, String)>,
 }
 
-#[derive(Deserialize)]
+impl RecordingState {
+    /// Time recorded so far, without pauses.
+    fn elapsed_ms(&self) -> u64 {
+        let running = match self.pause_started_at {
+            Some(paused) if self.is_paused => paused - self.started_at,
+            _ => self.started_at.elapsed(),
+        };
+        u64::try_from(running.as_millis()).unwrap_or(u64::MAX).saturating_add(self.active_ms)
+    }
+
+    /// The timeline so far, or `None` until the recording has its database row.
+    fn checkpoint(&self) -> Option<RecordingCheckpoint> {
+        (self.db_id >= 0).then(|| RecordingCheckpoint {
+            recording_id: self.db_id,
+            active_ms: self.elapsed_ms(),
+            slides: self.slides.iter().map(|ev| (ev.offset_ms, ev.slide, ev.fragment, ev.highlight)).collect(),
+            polls: self.polls.iter().map(|ev| (ev.offset_ms, ev.poll_id, ev.opened)).collect(),
+            captions: self.captions.clone(),
+        })
+    }
+
+    /// Picks a recording back up from its checkpoint after a restart, paused until the
+    /// presenter resumes it.
+    fn restore(checkpoint: RecordingCheckpoint, name: String) -> Self {
+        let now = std::time::Instant::now();
+        RecordingState {
+            db_id: checkpoint.recording_id,
+            name,
+            started_at: now,
+            active_ms: checkpoint.active_ms,
+            is_paused: true,
+            pause_started_at: Some(now),
+            slides: checkpoint
+                .slides
+                .into_iter()
+                .map(|(offset_ms, slide, fragment, highlight)| RecordingEvent { offset_ms, slide, fragment, highlight })
+                .collect(),
+            polls: checkpoint
+                .polls
+                .into_iter()
+                .map(|(offset_ms, poll_id, opened)| RecordingPollEvent { offset_ms, poll_id, opened })
+                .collect(),
+            captions: checkpoint.captions,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "snake_case")]
 enum RecordingMessage {
     RecordingStart,
@@ -152,6 +502,38 @@ enum RecordingMessage {
     RecordingStop,
 }
 
+/// A Q&A message sent by a client.
+#[derive(Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum QuestionMessage {
+    /// Submit a new question; anonymous questions are stored without their author.
+    QuestionAsk {
+        text: String,
+        #[serde(default)]
+        anonymous: bool,
+    },
+    /// Mark a question as answered, dismissed, pinned or open again (presenters only).
+    QuestionModerate { id: i64, status: QuestionStatus },
+}
+
+/// A poll message sent by a client.
+#[derive(Deserialize, JsonSchema)]
+#[serde(tag = "type")]
+enum PollMessage {
+    /// Open a poll defined on the stage (owners and editors only).
+    #[serde(rename = "poll_open")]
+    Open { question: String, options: Vec<String> },
+    /// Open the poll defined in the current slide's Markdown (owners and editors only).
+    #[serde(rename = "poll_open_inline")]
+    OpenInline,
+    /// Close the current poll (owners and editors only).
+    #[serde(rename = "poll_close")]
+    Close,
+    /// Vote for an option of the current poll (audience only).
+    #[serde(rename = "poll_vote")]
+    Vote { poll_id: i64, option: usize },
+}
+
 /// A specific presetation.
 ///
 /// There is no key here, as presentations are stored in a hashmap with its associated keys.
@@ -160,1857 +542,7680 @@ pub struct Presentation {
     content: String,
     /// The slide index that is currently active.
     slide: u32,
+    /// How many fragment steps of the active slide are revealed.
+    fragment: u32,
+    /// Block of the active slide the presenter points at.
+    highlight: Option<u32>,
+    /// Language tag of `content`, if its presenters set one.
+    lang: Option<String>,
+    /// Translations of `content` by language tag, sharing its slide index.
+    variants: BTreeMap<String, String>,
     /// A set of channels for reading and writing to the sockets.
+    ///
+    /// Connections do not read this channel directly but the sequenced copy in `replay`.
     channel: (Sender<SlideMessage>, Receiver<SlideMessage>),
+    /// Every broadcast in order with its sequence number, and the latest ones for replay.
+    replay: Arc<Mutex<Replay>>,
     recording: Option<RecordingState>,
+    /// Whether another process serving the presentation holds a recording of it.
+    recording_elsewhere: bool,
+    /// The most recent poll, kept after closing so late joiners see its results.
+    poll: Option<Poll>,
     presenter_count: usize,
+    /// Open WebSocket connections of any role, by connection id; pending edits are
+    /// saved when the last one closes.
+    connections: HashMap<u64, Arc<ConnectionStats>>,
+    /// Id handed to the next connection.
+    next_connection_id: u64,
+    /// Role and signed-in name of each open connection, by connection id.
+    attendees: HashMap<u64, (AccessResult, Option<String>)>,
+    /// The connections to each other process serving the presentation, by process,
+    /// with when that process last told.
+    remote_presence: HashMap<u64, (Presence, tokio::time::Instant)>,
+    presence_throttle: PresenceThrottle,
+    /// When the last connection closed; `None` while anyone is connected.
+    idle_since: Option<std::time::Instant>,
+    /// Bumped on every edit; a pending revision write only proceeds if it is unchanged.
+    revision_generation: u64,
+    /// When the first edit not yet saved in a revision arrived; `None` when all are saved.
+    revision_burst: Option<tokio::time::Instant>,
+    /// Revision of `content` as seen by clients; every edit increments it.
+    text_revision: u64,
+    /// The most recent patches broadcast, oldest first, for rebasing late patches.
+    text_history: VecDeque<TextPatch>,
+    /// Saves edits to the database; `None` when there is no presentation row to save to.
+    writer: Option<ContentWriter>,
+    /// The session clock, once the first slide move started it.
+    clock: Option<SessionClock>,
+    /// This presentation, for the timers it sets; dangling until it is shared.
+    this: Weak<Mutex<Presentation>>,
 }
 
-/// Extracts the client IP address for rate limiting.
-///
-/// Reads from `X-Forwarded-For` (set by Caddy in production) or `X-Real-IP`.
-/// Falls back to the loopback address in local development and test environments.
-/// Safe to use behind a trusted reverse proxy like Caddy that controls these headers.
-#[derive(Clone, Debug)]
-struct ClientIpExtractor;
-
-impl KeyExtractor for ClientIpExtractor {
-    type Key = IpAddr;
+/// Least time between two presence updates sent to presenters.
+const PRESENCE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Rate-limits the presence updates of a presentation.
+#[derive(Default)]
+struct PresenceThrottle {
+    /// The presence last broadcast.
+    sent: Option<Presence>,
+    /// When it was broadcast.
+    sent_at: Option<tokio::time::Instant>,
+    /// Set while an update waits for [`PRESENCE_INTERVAL`] to pass.
+    scheduled: bool,
+}
 
-    fn extract<T>(&self, req: &axum::http::Request<T>) -> Result<IpAddr, GovernorError> {
-        let headers = req.headers();
-        let ip = headers
-            .get("x-forwarded-for")
-            .and_then(|v| v.to_str().ok())
-            .and_then(|s| s.split(',').find_map(|part| part.trim().parse::<IpAddr>().ok()))
-            .or_else(|| {
-                headers
-                    .get("x-real-ip")
-                    .and_then(|v| v.to_str().ok())
-                    .and_then(|s| s.trim().parse::<IpAddr>().ok())
-            })
-            // Fall back to loopback for local dev and tests (no proxy headers present).
-            .unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST));
-        Ok(ip)
-    }
+/// Tells presenters who is connected, once [`PRESENCE_INTERVAL`] has passed since the
+/// last update; connections coming and going in between make a single update.
+fn announce_presence(pres: &Arc<Mutex<Presentation>>) {
+    let wait = {
+        let Ok(mut p) = pres.lock() else { return; };
+        if p.presence_throttle.scheduled {
+            return;
+        }
+        p.presence_throttle.scheduled = true;
+        p.presence_throttle
+            .sent_at
+            .map_or(std::time::Duration::ZERO, |at| PRESENCE_INTERVAL.saturating_sub(at.elapsed()))
+    };
+    let pres = Arc::downgrade(pres);
+    tokio::spawn(async move {
+        tokio::time::sleep(wait).await;
+        let Some(pres) = pres.upgrade() else { return; };
+        let Ok(mut p) = pres.lock() else { return; };
+        p.presence_throttle.scheduled = false;
+        let presence = p.presence();
+        if p.presence_throttle.sent.as_ref() != Some(&presence) {
+            p.presence_throttle.sent = Some(presence.clone());
+            p.presence_throttle.sent_at = Some(tokio::time::Instant::now());
+            // No receivers only means nobody is connected to be told.
+            let _ = p.channel.0.send(SlideMessage::Presence(presence));
+        }
+    });
 }
 
-/// The state of the entire application.
-#[derive(Clone)]
-pub struct AppState {
-    /// Used to render HTML templates.
-    tera: Tera,
-    /// Used to store all the ongoing presentation.
-    /// They Key here is a user-defined string, and the value is a [`Presentation`] struct.
-    slides: Arc<Mutex<HashMap<String, Arc<Mutex<Presentation>>>>>,
-    db_pool: SqlitePool,
+/// Calls [`Presentation::warn`] about `over` once `wait_ms` milliseconds have passed,
+/// if the presentation is still live.
+fn warn_after(pres: Weak<Mutex<Presentation>>, wait_ms: u64, over: Overrun, visit: u64) {
+    // Only shared presentations set timers.
+    if pres.strong_count() == 0 {
+        return;
+    }
+    tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_millis(wait_ms)).await;
+        let Some(pres) = pres.upgrade() else { return; };
+        let Ok(mut p) = pres.lock() else { return; };
+        p.warn(over, visit);
+    });
 }
 
-impl FromRef<AppState> for SqlitePool {
-    fn from_ref(state: &AppState) -> Self {
-        state.db_pool.clone()
-    }
+/// Diagnostics for one WebSocket connection.
+#[derive(Debug, Default)]
+struct ConnectionStats {
+    /// Times the connection fell behind the broadcast channel and was resynchronised.
+    lag_events: AtomicU64,
+    /// Broadcast messages the connection missed while behind.
+    skipped_messages: AtomicU64,
 }
-impl FromRef<AppState> for Tera {
-    fn from_ref(state: &AppState) -> Self {
-        state.tera.clone()
+
+impl ConnectionStats {
+    /// Records a lag event that skipped `skipped` messages; returns the lag events so far.
+    fn record_lag(&self, skipped: u64) -> u64 {
+        self.skipped_messages.fetch_add(skipped, Ordering::Relaxed);
+        self.lag_events.fetch_add(1, Ordering::Relaxed) + 1
     }
 }
 
-async fn broadcast_to_all(
-    ws: WebSocketUpgrade,
-    Path(pid): Path<String>,
-    State(state): State<AppState>,
-    auth_session: AuthSession,
-) -> Response {
-    // Resolve role at connect time. Password is not passed — the WebSocket
-    // endpoint does not handle password authentication; the HTTP layer (plan 3)
-    // gates who can reach the audience page in the first place.
-    let pid_i64 = pid.parse::<i64>().unwrap_or(-1);
-    let role = check_access(
-        &state.db_pool,
-        auth_session.user.as_ref(),
-        pid_i64,
-        None,
-    )
-    .await
-    .unwrap_or(AccessResult::Denied);
-    ws.on_upgrade(move |socket| ws_handle(socket, pid, state, role))
+/// Broadcasts kept for connections that resume after a drop.
+const REPLAY_LEN: usize = 256;
+
+/// The presentation's broadcasts, stamped with sequence numbers in the order sent.
+///
+/// The [`sequencer`] task moves every message from the presentation channel onto
+/// `channel` with the next number and keeps the latest [`REPLAY_LEN`] for replay.
+/// Connections subscribe while holding the lock, so what they replay and what they
+/// receive afterwards meet without gaps or repeats.
+struct Replay {
+    /// Identifies this run of the stream; sequence numbers restart with a new one.
+    stream: u64,
+    /// Sequence number of the latest broadcast; 0 before the first.
+    seq: u64,
+    messages: VecDeque<(u64, SlideMessage)>,
+    channel: Sender<(u64, SlideMessage)>,
 }
 
-fn update_slide(pid: &str, msg: SlideMessage, state: &mut AppState) {
-    let Ok(mut slides) = state.slides.lock() else { return; };
-    let Some(pres_arc) = slides.get_mut(pid) else { return; };
-    let Ok(mut pres) = pres_arc.lock() else { return; };
-    match msg {
-        SlideMessage::Slide(sn) => {
-            pres.slide = sn;
+impl Replay {
+    fn new() -> Self {
+        // Milliseconds, so the id survives the round trip through a JavaScript number.
+        let stream = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| u64::try_from(d.as_millis()).unwrap_or(u64::MAX));
+        Replay { stream, seq: 0, messages: VecDeque::new(), channel: broadcast::channel(1024).0 }
+    }
+
+    /// Stamps `msg` with the next sequence number and sends it to the connections.
+    fn push(&mut self, msg: SlideMessage) {
+        self.seq += 1;
+        if self.messages.len() == REPLAY_LEN {
+            self.messages.pop_front();
         }
-        SlideMessage::Text(text) => {
-            pres.content = text;
+        self.messages.push_back((self.seq, msg.clone()));
+        // No receivers only means nobody is connected to be told.
+        let _ = self.channel.send((self.seq, msg));
+    }
+
+    /// The broadcasts after `seq` in `stream`, or `None` if some are no longer kept.
+    fn since(&self, stream: u64, seq: u64) -> Option<Vec<(u64, SlideMessage)>> {
+        let oldest = self.messages.front().map_or(self.seq + 1, |(first, _)| *first);
+        if stream != self.stream || seq > self.seq || seq + 1 < oldest {
+            return None;
         }
-        SlideMessage::Name(_) => {}
-        _ => {}
+        Some(self.messages.iter().filter(|(n, _)| *n > seq).cloned().collect())
     }
 }
 
-async fn add_client_handler_channel(
+/// How a presentation's broadcasts reach the other server processes serving it.
+#[derive(Clone)]
+struct Link {
+    pubsub: Arc<dyn PubSub>,
+    /// This process, to recognise its own messages coming back.
+    node: u64,
     pid: String,
-    state: &mut AppState,
-) -> Result<Arc<Mutex<Presentation>>, StatusCode> {
-    // Check if already in memory without holding lock across await
-    {
-        let Ok(slides) = state.slides.lock() else {
-            eprintln!("K/V store mutex poisoned in add_client_handler_channel (read)");
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
-        };
-        if let Some(pres) = slides.get(&pid) {
-            return Ok(Arc::clone(pres));
+    db_pool: SqlitePool,
+}
+
+impl Link {
+    fn new(state: &AppState, pid: &str) -> Self {
+        Link { pubsub: Arc::clone(&state.pubsub), node: state.node, pid: pid.to_string(), db_pool: state.db_pool.clone() }
+    }
+
+    /// The topic the presentation's broadcasts are published on.
+    fn topic(&self) -> String {
+        format!("syncslide:presentation:{}", self.pid)
+    }
+
+    /// The count of presenters connected to any process.
+    fn presenters_key(&self) -> String {
+        format!("syncslide:presenters:{}", self.pid)
+    }
+
+    /// Tells the broker that `count` presenters are connected to this process; returns
+    /// the number connected to any process.
+    async fn count_presenters(&self, count: usize) -> Result<i64, PubSubError> {
+        let count = i64::try_from(count).unwrap_or(i64::MAX);
+        self.pubsub.set_share(&self.presenters_key(), self.node, count, PRESENTERS_TTL).await
+    }
+
+    /// Tells the other processes about `event`; a broker failure is logged and dropped.
+    async fn publish(&self, event: RemoteEvent) {
+        // Envelopes contain only strings, integers and slide messages; serialisation cannot fail.
+        let payload = serde_json::to_string(&Envelope { node: self.node, event }).expect("Envelope is always serializable");
+        if let Err(e) = self.pubsub.publish(&self.topic(), payload).await {
+            eprintln!("[pubsub] pid={} reason=publish_failed error={e}", self.pid);
         }
     }
-    // Not in memory — load content from DB so the initial WS message has real content
-    let db_content = if let Ok(pid_i64) = pid.parse::<i64>() {
-        DbPresentation::get_by_id(pid_i64, &state.db_pool)
-            .await
-            .ok()
-            .flatten()
-            .map(|p| p.content)
-            .unwrap_or_default()
-    } else {
-        String::new()
-    };
-    let Ok(mut slides) = state.slides.lock() else {
-        eprintln!("K/V store mutex poisoned in add_client_handler_channel (write)");
-        return Err(StatusCode::INTERNAL_SERVER_ERROR);
-    };
-    let pres = slides.entry(pid).or_insert_with(|| {
-        Arc::new(Mutex::new(Presentation {
-            content: db_content,
-            slide: 0,
-            channel: broadcast::channel(1024),
-            recording: None,
-            presenter_count: 0,
-        }))
-    });
-    Ok(Arc::clone(pres))
 }
 
-/// Returns `true` if every slide section in `content` is within the 100 KB limit.
-///
-/// Slides are delimited by `\n## ` (H2 headings), matching the frontend split logic.
-/// 100 KB per slide — keeps individual slides lean while allowing arbitrarily large decks.
-/// (OWASP Input Validation Cheat Sheet recommends explicit length caps on rich text inputs.)
-fn slides_within_size_limit(content: &str) -> bool {
-    // 100 KB per slide — lower end of the ±50% range allowed by the spec.
-    const MAX_SLIDE_BYTES: usize = 100 * 1024;
-    content.split("\n## ").all(|section| section.len() <= MAX_SLIDE_BYTES)
+/// A message between the processes serving a presentation.
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    /// The process that published it.
+    node: u64,
+    event: RemoteEvent,
 }
 
-/// Returns a short, grep-able type label for a [`SlideMessage`] variant.
-fn slide_msg_type_name(msg: &SlideMessage) -> &'static str {
-    match msg {
-        SlideMessage::Text(_) => "text",
-        SlideMessage::Slide(_) => "slide",
-        SlideMessage::Name(_) => "name",
-        SlideMessage::RecordingStart { .. } => "recording_start",
-        SlideMessage::RecordingPause { .. } => "recording_pause",
-        SlideMessage::RecordingResume { .. } => "recording_resume",
-        SlideMessage::RecordingStop { .. } => "recording_stop",
-    }
+/// What one process tells the others about a presentation.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum RemoteEvent {
+    /// A broadcast to apply and relay as it is.
+    Broadcast { message: SlideMessage },
+    /// An edit to the deck, numbered (process, edit counter) by the process that made it.
+    ///
+    /// The broker's delivery order is the order every process applies edits in. `base` is
+    /// the last edit the publisher had applied, so each process can rebase the patch over
+    /// the edits delivered since, as [`handle_patch`] rebases an editor's patch.
+    Edit { id: (u64, u64), base: Option<(u64, u64)>, patch: TextPatch },
+    /// An editor's cursor, by the editor's connection id in the publishing process.
+    Cursor { id: u64, user: String, start: usize, end: usize },
+    CursorGone { id: u64 },
+    /// A presenter's recording control, for the process holding the recording.
+    Recording { message: RecordingMessage },
+    /// The connections to the publishing process, whenever they change and on every
+    /// [`HEARTBEAT`].
+    Presence { presence: Presence },
+    /// A process started serving the presentation, or lost track of the deck, and asks
+    /// for the current state.
+    Joined,
+    /// The current state, in answer to [`Joined`](Self::Joined) from process `to`. The
+    /// deck is as of that `Joined`, before any edit delivered after it.
+    State {
+        #[serde(default)]
+        to: u64,
+        slide: u32,
+        #[serde(default)]
+        fragment: u32,
+        content: String,
+        poll: Option<Poll>,
+        /// Whether any process holds a recording.
+        #[serde(default)]
+        recording: bool,
+        #[serde(default)]
+        clock: Option<ClockState>,
+    },
+    /// The last presenter on any process left; whichever holds the recording stops it.
+    PresentersGone,
 }
 
-fn handle_socket(
-    msg: Result<Message, axum::Error>,
-    pid: &str,
-    tx: &mut Sender<SlideMessage>,
-    state: &mut AppState,
-    role: &AccessResult,
-) -> Result<bool, &'static str> {
-    let Ok(raw) = msg else {
-        cleanup(state);
-        return Err("Disconnected");
-    };
-    if let Message::Close(_) = raw {
-        cleanup(state);
-        return Err("Closed");
-    }
-    let slide_msg: SlideMessage = match raw.to_text().ok().and_then(|t| serde_json::from_str(t).ok()) {
-        Some(m) => m,
-        None => {
-            eprintln!("[ws] pid={pid} role={role:?} msg_type=unknown reason=malformed_or_unknown_type");
-            return Err("Invalid message!");
+/// How often a process serving a presentation refreshes its share of the presenters and
+/// tells the others who is connected to it.
+const HEARTBEAT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// How long a process's presenters and presence count after it was last heard from.
+const PRESENTERS_TTL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// How long a process waits for another to send it the state before it trusts its own.
+const SYNC_WAIT: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// What a presentation's sequencer keeps about the other processes.
+struct Remote {
+    /// This process.
+    node: u64,
+    /// The deck with every delivered edit applied, the same on every process.
+    shared: String,
+    /// The deck as this process last published or applied it.
+    local: String,
+    /// Edits this process published that the broker has not delivered back yet.
+    pending: VecDeque<TextPatch>,
+    /// Edits published by this process so far.
+    published: u64,
+    /// Edits delivered so far, and the id of the last.
+    delivered: usize,
+    last: Option<(u64, u64)>,
+    /// Recently delivered edits, as applied to the shared deck, by delivery position.
+    log: VecDeque<(usize, (u64, u64), TextPatch)>,
+    /// Per process, the delivered edits it had not applied when it last published, each
+    /// rewritten to apply after that process's own edits since.
+    unseen: HashMap<u64, VecDeque<(usize, TextPatch)>>,
+    /// Delivery position at which each process's last [`RemoteEvent::Joined`] arrived.
+    joined: HashMap<u64, usize>,
+    /// When this process last asked for the state, until it has it.
+    syncing: Option<std::time::Instant>,
+    /// The presence last published.
+    presence: Option<Presence>,
+    /// Presenters connected to any process at the last heartbeat.
+    presenters: i64,
+    /// Connection ids in this process standing in for other processes' editors,
+    /// by process and connection id there.
+    cursors: HashMap<(u64, u64), u64>,
+}
+
+impl Remote {
+    fn new(node: u64, content: String) -> Self {
+        Remote {
+            node,
+            shared: content.clone(),
+            local: content,
+            pending: VecDeque::new(),
+            published: 0,
+            delivered: 0,
+            last: None,
+            log: VecDeque::new(),
+            unseen: HashMap::new(),
+            joined: HashMap::new(),
+            syncing: Some(std::time::Instant::now()),
+            presence: None,
+            presenters: 0,
+            cursors: HashMap::new(),
         }
-    };
-    let permitted = match (role, &slide_msg) {
-        (AccessResult::Owner, _) => true,
-        (AccessResult::Editor, SlideMessage::Text(_) | SlideMessage::Slide(_)) => true,
-        (AccessResult::Controller, SlideMessage::Slide(_)) => true,
-        _ => false,
-    };
-    if !permitted {
-        eprintln!("[ws] pid={pid} role={role:?} msg_type={} reason=unauthorized", slide_msg_type_name(&slide_msg));
-        return Ok(true); // silently drop
     }
-    // Slide content: max 100 KB per slide section. Disconnects on excess — mirrors HTTP 400
-    // semantics for oversized input (OWASP Input Validation Cheat Sheet §Rich Text).
-    if let SlideMessage::Text(ref content) = slide_msg {
-        if !slides_within_size_limit(content) {
-            eprintln!("[ws] pid={pid} role={role:?} msg_type=text reason=content_too_large");
-            return Err("Slide content too large");
-        }
+
+    /// Whether this process has the state, or has waited long enough to trust its own.
+    fn synced(&self) -> bool {
+        self.syncing.is_none_or(|asked| asked.elapsed() >= SYNC_WAIT)
+    }
+
+    /// What to tell the other processes about a broadcast made in this one.
+    fn outgoing(&mut self, msg: &SlideMessage, p: &Presentation) -> Option<RemoteEvent> {
+        Some(match msg {
+            SlideMessage::Text(_) | SlideMessage::Patch(_) => return self.edited(p),
+            SlideMessage::Cursor(c) => RemoteEvent::Cursor { id: c.id, user: c.user.clone(), start: c.start, end: c.end },
+            SlideMessage::CursorGone(id) => RemoteEvent::CursorGone { id: *id },
+            SlideMessage::Presence(_) => return self.present(p, false),
+            // Sent to single connections, never broadcast; each process times the slide
+            // moves it sees for pacing, on a clock shared when it joins.
+            SlideMessage::Hello(_)
+            | SlideMessage::Error(_)
+            | SlideMessage::Revision(_)
+            | SlideMessage::Resume { .. }
+            | SlideMessage::Notes(_)
+            | SlideMessage::Pacing(_) => {
+                return None;
+            }
+            _ => RemoteEvent::Broadcast { message: msg.clone() },
+        })
     }
-    update_slide(pid, slide_msg.clone(), state);
-    if tx.send(slide_msg).is_err() {
-        cleanup(state);
-        return Err("Channel disconnected!");
+
+    /// The connections to this process, if they changed since last published or `always`.
+    fn present(&mut self, p: &Presentation, always: bool) -> Option<RemoteEvent> {
+        let presence = p.local_presence();
+        if !always && self.presence.as_ref() == Some(&presence) {
+            return None;
+        }
+        self.presence = Some(presence.clone());
+        Some(RemoteEvent::Presence { presence })
     }
-    Ok(true)
-}
 
-/// Handles a recording control message from a presenter.
-///
-/// Returns a [`SlideMessage`] to broadcast to all clients, or `None` if the
-/// message is a no-op (e.g. start when already recording, pause when already paused).
-async fn handle_recording_message(
-    msg: RecordingMessage,
-    pres: &Arc<Mutex<Presentation>>,
-    presentation_id: i64,
-    pool: &SqlitePool,
-) -> Option<SlideMessage> {
-    match msg {
-        RecordingMessage::RecordingStart => {
-            // Compute name before acquiring the lock
-            let name = {
-                let now = time::OffsetDateTime::now_utc();
-                format!("Recording \u{2013} {:04}-{:02}-{:02} {:02}:{:02}",
-                    now.year(), u8::from(now.month()), now.day(), now.hour(), now.minute())
-            };
-            // Check and initialise under lock (placeholder db_id = -1)
-            {
-                let Ok(mut p) = pres.lock() else { return None; };
-                if p.recording.is_some() {
-                    return None;
+    /// The edit this process made to the deck since it last published one, if any.
+    fn edited(&mut self, p: &Presentation) -> Option<RemoteEvent> {
+        let patch = TextPatch::between(&self.local, &p.content, 0);
+        if patch.start == patch.end && patch.text.is_empty() {
+            return None;
+        }
+        self.local.clone_from(&p.content);
+        self.pending.push_back(patch.clone());
+        self.published += 1;
+        Some(RemoteEvent::Edit { id: (self.node, self.published), base: self.last, patch })
+    }
+
+    /// Applies what process `from` published; returns the broadcasts for this process's
+    /// connections and the answers to publish.
+    fn incoming(&mut self, event: RemoteEvent, from: u64, p: &mut Presentation) -> (Vec<SlideMessage>, Vec<RemoteEvent>) {
+        match event {
+            RemoteEvent::Broadcast { message } => {
+                match &message {
+                    SlideMessage::Slide(n) => {
+                        p.show(*n, 0);
+                        p.record_step();
+                        p.checkpoint();
+                    }
+                    SlideMessage::Fragment { slide, fragment } => {
+                        p.show(*slide, *fragment);
+                        p.record_step();
+                        p.checkpoint();
+                    }
+                    SlideMessage::Highlight { slide, block, .. } if *slide == p.slide => {
+                        p.highlight = *block;
+                        p.record_step();
+                    }
+                    SlideMessage::Caption(text) => p.record_caption(text),
+                    SlideMessage::Language(lang) => p.lang.clone_from(lang),
+                    SlideMessage::Variant { lang, text: Some(text) } => {
+                        p.variants.insert(lang.clone(), text.clone());
+                    }
+                    SlideMessage::Variant { lang, text: None } => {
+                        p.variants.remove(lang);
+                    }
+                    SlideMessage::Poll(poll) => p.poll = Some(poll.clone()),
+                    SlideMessage::RecordingStart { .. } => p.recording_elsewhere = true,
+                    SlideMessage::RecordingStop { .. } => p.recording_elsewhere = false,
+                    _ => {}
                 }
-                let slide = p.slide;
-                p.recording = Some(RecordingState {
-                    db_id: -1,
-                    name: name.clone(),
-                    started_at: std::time::Instant::now(),
-                    active_ms: 0,
-                    is_paused: false,
-                    pause_started_at: None,
-                    slides: vec![RecordingEvent { offset_ms: 0, slide }],
+                (vec![message], Vec::new())
+            }
+            RemoteEvent::Edit { id, base, patch } => {
+                if let Ok(message) = self.deliver(id, base, patch, p) {
+                    return (message.into_iter().collect(), Vec::new());
+                }
+                eprintln!("[pubsub] node={} reason=edit_out_of_sync", self.node);
+                self.syncing = Some(std::time::Instant::now());
+                (Vec::new(), vec![RemoteEvent::Joined])
+            }
+            RemoteEvent::Cursor { id, user, start, end } => {
+                let id = *self.cursors.entry((from, id)).or_insert_with(|| {
+                    p.next_connection_id += 1;
+                    p.next_connection_id - 1
                 });
+                (vec![SlideMessage::Cursor(Cursor { id, user, start, end })], Vec::new())
             }
-            // Create DB row
-            let rec = Recording::create(presentation_id, name, None, String::new(), pool).await.ok()?;
-            // Store real db_id
-            {
-                let Ok(mut p) = pres.lock() else { return None; };
-                if let Some(rec_state) = p.recording.as_mut() {
-                    rec_state.db_id = rec.id;
-                } else {
-                    return None;
+            RemoteEvent::CursorGone { id } => {
+                (self.cursors.remove(&(from, id)).map(SlideMessage::CursorGone).into_iter().collect(), Vec::new())
+            }
+            RemoteEvent::Joined => {
+                self.joined.insert(from, self.delivered);
+                if from == self.node {
+                    return (Vec::new(), Vec::new());
+                }
+                let mut answers: Vec<RemoteEvent> = self.present(p, true).into_iter().collect();
+                // A process still waiting for the state has none to give.
+                if self.synced() {
+                    answers.push(RemoteEvent::State {
+                        to: from,
+                        slide: p.slide,
+                        fragment: p.fragment,
+                        content: self.shared.clone(),
+                        poll: p.poll.clone(),
+                        recording: p.recording.is_some() || p.recording_elsewhere,
+                        clock: p.clock.as_ref().map(SessionClock::state),
+                    });
                 }
+                (Vec::new(), answers)
+            }
+            RemoteEvent::State { to, slide, fragment, content, poll, recording, clock } => {
+                if to != self.node || self.synced() {
+                    return (Vec::new(), Vec::new());
+                }
+                self.syncing = None;
+                p.recording_elsewhere = recording && p.recording.is_none();
+                let mut messages: Vec<SlideMessage> = self.resync(content, p).into_iter().collect();
+                // Pacing carries on from the session's first move, wherever it was seen.
+                if p.clock.is_none()
+                    && let Some(clock) = clock
+                {
+                    p.clock = Some(SessionClock::resume(clock));
+                    p.pace();
+                }
+                if (slide, fragment) != (p.slide, p.fragment) {
+                    p.show(slide, fragment);
+                    p.checkpoint();
+                    messages.push(SlideMessage::Fragment { slide, fragment });
+                }
+                if p.poll.is_none()
+                    && let Some(poll) = poll
+                {
+                    p.poll = Some(poll.clone());
+                    messages.push(SlideMessage::Poll(poll));
+                }
+                (messages, Vec::new())
+            }
+            // Handled by the sequencer, which may need to wait on the database.
+            RemoteEvent::Recording { .. } | RemoteEvent::Presence { .. } | RemoteEvent::PresentersGone => {
+                (Vec::new(), Vec::new())
             }
-            Some(SlideMessage::RecordingStart { elapsed_ms: 0 })
         }
+    }
 
-        RecordingMessage::RecordingPause => {
-            let elapsed_ms = {
-                let Ok(mut p) = pres.lock() else { return None; };
-                let rec = p.recording.as_mut()?;
-                if rec.is_paused { return None; }
-                let elapsed_ms = (std::time::Instant::now() - rec.started_at).as_millis() as u64 + rec.active_ms;
-                rec.pause_started_at = Some(std::time::Instant::now());
-                rec.is_paused = true;
-                elapsed_ms
-            };
-            Some(SlideMessage::RecordingPause { elapsed_ms })
+    /// Applies the edit `id`, made after the edit `base`, in its place in the delivery
+    /// order; returns the patch to broadcast, or `Err` if the deck here is out of sync.
+    fn deliver(
+        &mut self,
+        id: (u64, u64),
+        base: Option<(u64, u64)>,
+        mut patch: TextPatch,
+        p: &mut Presentation,
+    ) -> Result<Option<SlideMessage>, ()> {
+        let origin = id.0;
+        // The edits the publisher had applied, by delivery position; one too old to be
+        // in the log counts as none.
+        let seen = match base {
+            Some(base) => self.log.iter().find(|(_, logged, _)| *logged == base).map_or(0, |(at, _, _)| *at),
+            None => self.joined.get(&origin).copied().unwrap_or(0),
+        };
+        let log = &self.log;
+        let unseen = self.unseen.entry(origin).or_insert_with(|| {
+            log.iter().filter(|(at, logged, _)| *at > seen && logged.0 != origin).map(|(at, _, e)| (*at, e.clone())).collect()
+        });
+        unseen.retain(|(at, _)| *at > seen);
+        for (_, earlier) in unseen.iter_mut() {
+            let rebased = patch.transform(earlier, true);
+            *earlier = earlier.transform(&patch, false);
+            patch = rebased;
+        }
+        self.delivered += 1;
+        self.last = Some(id);
+        // A process that stops publishing stops pruning its list; past the log's length
+        // it is dropped and rebuilt from the log if the process publishes again.
+        self.unseen.retain(|_, unseen| unseen.len() < TEXT_HISTORY_LEN);
+        for (node, unseen) in &mut self.unseen {
+            if *node != origin {
+                unseen.push_back((self.delivered, patch.clone()));
+            }
+        }
+        if self.log.len() == TEXT_HISTORY_LEN {
+            self.log.pop_front();
+        }
+        self.log.push_back((self.delivered, id, patch.clone()));
+        self.shared = patch.apply(&self.shared).ok_or(())?;
+        if origin == self.node {
+            self.pending.pop_front();
+            return Ok(None);
+        }
+        // This process's edits still in flight come after it in the delivery order.
+        for pending in &mut self.pending {
+            let rebased = patch.transform(pending, false);
+            *pending = pending.transform(&patch, true);
+            patch = rebased;
         }
+        let content = patch.apply(&p.content).ok_or(())?;
+        self.local.clone_from(&content);
+        Ok(Some(p.replace_content(content, None)))
+    }
+
+    /// Takes the deck another process sent in answer to this one's last
+    /// [`RemoteEvent::Joined`]; returns the patch to broadcast, if the deck changed.
+    fn resync(&mut self, content: String, p: &mut Presentation) -> Option<SlideMessage> {
+        let asked = self.joined.get(&self.node).copied().unwrap_or(0);
+        let mut shared = Some(content);
+        for (_, _, patch) in self.log.iter().filter(|(at, _, _)| *at > asked) {
+            shared = shared.and_then(|deck| patch.apply(&deck));
+        }
+        self.shared = shared?;
+        let local = self.pending.iter().try_fold(self.shared.clone(), |deck, patch| patch.apply(&deck));
+        let local = local.unwrap_or_else(|| self.shared.clone());
+        self.local.clone_from(&local);
+        (local != p.content).then(|| p.replace_content(local, None))
+    }
+}
 
-        RecordingMessage::RecordingResume => {
-            let elapsed_ms = {
-                let Ok(mut p) = pres.lock() else { return None; };
-                let rec = p.recording.as_mut()?;
-                if !rec.is_paused { return None; }
-                let pause_start = rec.pause_started_at?;
-                // Accumulate running time from last start to pause
-                rec.active_ms += (pause_start - rec.started_at).as_millis() as u64;
-                rec.started_at = std::time::Instant::now();
-                rec.pause_started_at = None;
-                rec.is_paused = false;
-                // elapsed ≈ active_ms since started_at was just reset
-                rec.active_ms
-            };
-            Some(SlideMessage::RecordingResume { elapsed_ms })
+/// Stamps one message from the presentation channel, returning what to tell the other
+/// processes about it; `None` once the channel is closed.
+fn sequence(
+    received: Result<SlideMessage, RecvError>,
+    p: &Presentation,
+    replay: &mut Replay,
+    remote: &mut Remote,
+) -> Option<Vec<RemoteEvent>> {
+    match received {
+        Ok(msg) => {
+            let event = remote.outgoing(&msg, p);
+            replay.push(msg);
+            Some(event.into_iter().collect())
+        }
+        // Skipping the numbers leaves a gap that makes every connection resynchronise;
+        // the other processes get the edits and slide again.
+        Err(RecvError::Lagged(skipped)) => {
+            replay.seq += skipped;
+            replay.messages.clear();
+            let message = SlideMessage::Fragment { slide: p.slide, fragment: p.fragment };
+            Some(remote.edited(p).into_iter().chain([RemoteEvent::Broadcast { message }]).collect())
         }
+        Err(RecvError::Closed) => None,
+    }
+}
 
-        RecordingMessage::RecordingStop => {
-            // Extract everything needed before async work
-            let (db_id, name, slides, content) = {
-                let Ok(mut p) = pres.lock() else { return None; };
-                let rec = p.recording.take()?;
-                (rec.db_id, rec.name, rec.slides, p.content.clone())
-            };
-            if db_id < 0 {
-                // DB row not yet created (start still in progress) — nothing to save
-                return Some(SlideMessage::RecordingStop { id: -1, name: String::new(), start: String::new() });
+/// The next payload of a subscription; never resolves without one.
+async fn next_payload(payloads: &mut Option<Payloads>) -> Option<String> {
+    match payloads {
+        Some(payloads) => payloads.next().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Acts on another process's recording control, presence or departure of the last
+/// presenter, none of which are broadcast as they are; returns any other event for
+/// [`Remote::incoming`] to apply.
+async fn act_remotely(event: RemoteEvent, from: u64, pres: &Arc<Mutex<Presentation>>, link: &Link) -> Option<RemoteEvent> {
+    let pid_i64 = link.pid.parse::<i64>().unwrap_or(-1);
+    match event {
+        RemoteEvent::PresentersGone => {
+            let stop = pres.lock().is_ok_and(|p| p.presenter_count == 0 && p.recording.is_some());
+            if stop {
+                handle_recording_message(RecordingMessage::RecordingStop, pres, pid_i64, &link.db_pool).await;
+            }
+        }
+        RemoteEvent::Recording { message } => {
+            if pres.lock().is_ok_and(|p| p.recording.is_some())
+                && let Some(msg) = handle_recording_message(message, pres, pid_i64, &link.db_pool).await
+                && let Ok(p) = pres.lock()
+            {
+                // No receivers only means nobody is connected here to be told.
+                let _ = p.channel.0.send(msg);
             }
-            // Resolve slide indices to title/content
-            let all_slides = render_all_slides(&content);
-            let inputs: Vec<RecordingSlideInput> = slides
-                .into_iter()
-                .filter_map(|ev| {
-                    let (title, html) = all_slides.get(ev.slide as usize)?.clone();
-                    Some(RecordingSlideInput {
-                        start_seconds: ev.offset_ms as f64 / 1000.0,
-                        title,
-                        content: html,
-                    })
-                })
-                .collect();
-            let _ = RecordingSlide::create_batch(db_id, inputs, pool).await;
-            let _ = Recording::touch(db_id, pool).await;
-            let start = {
-                let now = time::OffsetDateTime::now_utc();
-                format!("{:04}-{:02}-{:02}", now.year(), u8::from(now.month()), now.day())
-            };
-            Some(SlideMessage::RecordingStop { id: db_id, name, start })
         }
+        RemoteEvent::Presence { presence } => {
+            let changed = pres.lock().is_ok_and(|mut p| {
+                let heard = (presence.clone(), tokio::time::Instant::now());
+                p.remote_presence.insert(from, heard).map(|(before, _)| before).unwrap_or_default() != presence
+            });
+            if changed {
+                announce_presence(pres);
+            }
+        }
+        event => return Some(event),
     }
+    None
 }
 
-async fn ws_handle(mut socket: WebSocket, pid: String, mut state: AppState, role: AccessResult) {
-    let pres = match add_client_handler_channel(pid.clone(), &mut state).await {
-        Ok(p) => p,
-        Err(_) => {
-            // K/V store mutex is poisoned — close the connection rather than crash.
-            let _ = socket.close().await;
-            return;
+/// Refreshes this process's share of the presenters and its lease on the recording it
+/// holds, and tells the other processes who is connected to it; returns what to publish.
+///
+/// Processes not heard from for [`PRESENTERS_TTL`] stop counting, as if their connections
+/// had closed: once that leaves no presenter anywhere, the recording held here stops.
+async fn beat(pres: &Arc<Mutex<Presentation>>, remote: &mut Remote, link: &Link) -> Vec<RemoteEvent> {
+    let (count, presence, expired) = {
+        let Ok(mut p) = pres.lock() else { return Vec::new(); };
+        let heard = p.remote_presence.len();
+        p.remote_presence.retain(|_, (_, at)| at.elapsed() < PRESENTERS_TTL);
+        // Renews this process's lease on the recording it holds.
+        if p.recording.is_some() {
+            p.checkpoint();
         }
+        (p.presenter_count, remote.present(&p, true), p.remote_presence.len() != heard)
     };
-    let is_presenter = matches!(role, AccessResult::Owner | AccessResult::Editor | AccessResult::Controller);
-    // Audience and public viewers must not receive recording control messages.
-    let is_audience = matches!(role, AccessResult::Audience | AccessResult::PublicOk);
-
-    // Increment presenter_count for authorized roles
-    if is_presenter {
-        if let Ok(mut p) = pres.lock() {
-            p.presenter_count += 1;
+    if expired {
+        announce_presence(pres);
+    }
+    match link.count_presenters(count).await {
+        // Only a drop to none: a recording resumed after a restart waits for its presenters.
+        Ok(0) if remote.presenters > 0 => {
+            remote.presenters = 0;
+            if pres.lock().is_ok_and(|p| p.recording.is_some()) {
+                let pid_i64 = link.pid.parse::<i64>().unwrap_or(-1);
+                handle_recording_message(RecordingMessage::RecordingStop, pres, pid_i64, &link.db_pool).await;
+            }
         }
+        Ok(total) => remote.presenters = total,
+        Err(e) => eprintln!("[pubsub] pid={} reason=count_failed error={e}", link.pid),
     }
+    presence.into_iter().collect()
+}
 
-    let (mut tx, mut rx, text, slide, recording_msg) = {
-        let Ok(p) = pres.lock() else { return; };
-        // SlideMessage contains only strings and integers; serialisation cannot fail.
-        let text = serde_json::to_string(&SlideMessage::Text(p.content.clone()))
-            .expect("SlideMessage is always serializable");
-        let slide = serde_json::to_string(&SlideMessage::Slide(p.slide))
-            .expect("SlideMessage is always serializable");
-        let (tx, rx) = (p.channel.0.clone(), p.channel.0.subscribe());
-        // Build connect-time recording state message if recording is active
-        let recording_msg = p.recording.as_ref().map(|rec| {
-            let elapsed_ms = if rec.is_paused {
-                rec.active_ms
-            } else {
-                (std::time::Instant::now() - rec.started_at).as_millis() as u64 + rec.active_ms
-            };
-            if rec.is_paused {
-                serde_json::to_string(&SlideMessage::RecordingPause { elapsed_ms })
-                    .expect("SlideMessage is always serializable")
-            } else {
-                serde_json::to_string(&SlideMessage::RecordingStart { elapsed_ms })
-                    .expect("SlideMessage is always serializable")
-            }
-        });
-        (tx, rx, text, slide, recording_msg)
+/// Stamps each message sent on the presentation channel until the presentation is gone,
+/// and exchanges broadcasts with the other processes serving it through `link`.
+///
+/// Broadcasts from other processes are applied to the presentation and stamped here
+/// too, after whatever this process sent before taking the presentation lock, so
+/// patches keep their revision order. `content` is the deck when `raw` was subscribed.
+/// Every [`HEARTBEAT`] it keeps this process counted among those serving the
+/// presentation, with [`beat`].
+async fn sequencer(
+    mut raw: Receiver<SlideMessage>,
+    pres: Weak<Mutex<Presentation>>,
+    replay: Arc<Mutex<Replay>>,
+    link: Link,
+    content: String,
+) {
+    let mut payloads = match link.pubsub.subscribe(&link.topic()).await {
+        Ok(payloads) => Some(payloads),
+        Err(e) => {
+            eprintln!("[pubsub] pid={} reason=subscribe_failed error={e}", link.pid);
+            None
+        }
     };
-
-    if socket.send(Message::from(text)).await.is_err() { return; }
-    if socket.send(Message::from(slide)).await.is_err() { return; }
-    // Audience and public connections do not receive recording control state on connect.
-    if let Some(rec_msg) = recording_msg {
-        if !is_audience {
-            let _ = socket.send(Message::from(rec_msg)).await;
+    link.publish(RemoteEvent::Joined).await;
+    let mut remote = Remote::new(link.node, content);
+    let mut heartbeat = tokio::time::interval_at(tokio::time::Instant::now() + HEARTBEAT, HEARTBEAT);
+    loop {
+        let outgoing = tokio::select! {
+            received = raw.recv() => {
+                let Some(pres) = pres.upgrade() else { return; };
+                let Ok(p) = pres.lock() else { return; };
+                let Ok(mut buffer) = replay.lock() else { return; };
+                let Some(outgoing) = sequence(received, &p, &mut buffer, &mut remote) else { return; };
+                outgoing
+            }
+            payload = next_payload(&mut payloads) => {
+                let Some(payload) = payload else {
+                    eprintln!("[pubsub] pid={} reason=subscription_ended", link.pid);
+                    payloads = None;
+                    continue;
+                };
+                let Ok(envelope) = serde_json::from_str::<Envelope>(&payload) else {
+                    eprintln!("[pubsub] pid={} reason=malformed_envelope", link.pid);
+                    continue;
+                };
+                // Edits and joins come back to their own process to take their place in
+                // the delivery order; everything else it has already applied.
+                if envelope.node == link.node && !matches!(envelope.event, RemoteEvent::Edit { .. } | RemoteEvent::Joined) {
+                    continue;
+                }
+                let Some(pres) = pres.upgrade() else { return; };
+                let Some(event) = act_remotely(envelope.event, envelope.node, &pres, &link).await else { continue; };
+                let Ok(mut p) = pres.lock() else { return; };
+                let Ok(mut buffer) = replay.lock() else { return; };
+                let mut outgoing = Vec::new();
+                loop {
+                    let received = match raw.try_recv() {
+                        Ok(msg) => Ok(msg),
+                        Err(TryRecvError::Empty) => break,
+                        Err(TryRecvError::Lagged(skipped)) => Err(RecvError::Lagged(skipped)),
+                        Err(TryRecvError::Closed) => Err(RecvError::Closed),
+                    };
+                    let Some(events) = sequence(received, &p, &mut buffer, &mut remote) else { return; };
+                    outgoing.extend(events);
+                }
+                let (messages, answers) = remote.incoming(event, envelope.node, &mut p);
+                for msg in messages {
+                    buffer.push(msg);
+                }
+                outgoing.extend(answers);
+                outgoing
+            }
+            _ = heartbeat.tick() => {
+                let Some(pres) = pres.upgrade() else { return; };
+                beat(&pres, &mut remote, &link).await
+            }
+        };
+        for event in outgoing {
+            link.publish(event).await;
         }
     }
+}
 
-    let mut state1 = state.clone();
-    let pid_i64 = pid.parse::<i64>().unwrap_or(-1);
-    let pres1 = Arc::clone(&pres);
-    let (mut sock_send, mut sock_recv) = socket.split();
-
-    let socket_handler = async {
-        while let Some(msg) = sock_recv.next().await {
-            // Pre-extract text for recording dispatch and snapshot capture
-            let text_val: Option<String> = msg
-                .as_ref()
-                .ok()
-                .and_then(|m| m.to_text().ok())
-                .map(String::from);
-
-            let is_recording_msg = text_val
-                .as_deref()
-                .and_then(|t| serde_json::from_str::<serde_json::Value>(t).ok())
-                .and_then(|v| v["type"].as_str().map(|s| s.starts_with("recording_")))
-                .unwrap_or(false);
-
-            if is_recording_msg {
-                if is_presenter {
-                    if let Some(text) = &text_val {
-                        if let Ok(rec_msg) = serde_json::from_str::<RecordingMessage>(text) {
-                            if let Some(broadcast_msg) = handle_recording_message(
-                                rec_msg, &pres1, pid_i64, &state1.db_pool,
-                            ).await {
-                                let _ = tx.send(broadcast_msg);
-                            }
-                        } else {
-                            let raw_type = serde_json::from_str::<serde_json::Value>(text)
-                                .ok()
-                                .and_then(|v| v["type"].as_str().map(String::from))
-                                .unwrap_or_else(|| "unknown".to_string());
-                            eprintln!("[ws] pid={pid} role={role:?} msg_type={raw_type} reason=malformed_recording_message");
-                        }
-                    }
-                } else {
-                    let raw_type = text_val
-                        .as_deref()
-                        .and_then(|t| serde_json::from_str::<serde_json::Value>(t).ok())
-                        .and_then(|v| v["type"].as_str().map(String::from))
-                        .unwrap_or_else(|| "recording_unknown".to_string());
-                    eprintln!("[ws] pid={pid} role={role:?} msg_type={raw_type} reason=unauthorized_role_for_recording");
-                }
-                continue;
-            }
+/// Serialises a message for the WebSocket.
+fn json(msg: &SlideMessage) -> String {
+    // SlideMessage contains only strings and integers; serialisation cannot fail.
+    serde_json::to_string(msg).expect("SlideMessage is always serializable")
+}
 
-            // Pre-parse slide index for snapshot capture (before handle_socket consumes msg)
-            let slide_n: Option<u32> = text_val
-                .as_deref()
-                .and_then(|t| serde_json::from_str::<SlideMessage>(t).ok())
-                .and_then(|m| if let SlideMessage::Slide(n) = m { Some(n) } else { None });
+/// Serialises a broadcast with its sequence number, as `{"type", "data", "seq"}`.
+fn sequenced_json(msg: &SlideMessage, seq: u64) -> String {
+    // SlideMessage contains only strings and integers; serialisation cannot fail.
+    let mut value = serde_json::to_value(msg).expect("SlideMessage is always serializable");
+    if let Some(fields) = value.as_object_mut() {
+        fields.insert("seq".to_string(), seq.into());
+    }
+    value.to_string()
+}
 
-            if handle_socket(msg, &pid, &mut tx, &mut state1, &role).is_err() {
-                return;
-            }
+/// What one connection may see of the presentation's broadcasts.
+#[derive(Clone, Copy)]
+struct Viewer {
+    connection_id: u64,
+    is_presenter: bool,
+    is_audience: bool,
+    is_editor: bool,
+}
 
-            // Capture slide snapshot for active recording
-            if let Some(n) = slide_n {
-                if is_presenter {
-                    let Ok(mut slides_map) = state1.slides.lock() else { continue; };
-                    if let Some(p) = slides_map.get_mut(&pid) {
-                        let Ok(mut p) = p.lock() else { continue; };
-                        if let Some(ref mut rec) = p.recording {
-                            if !rec.is_paused {
-                                let offset_ms = (std::time::Instant::now() - rec.started_at)
-                                    .as_millis() as u64 + rec.active_ms;
-                                rec.slides.push(RecordingEvent { offset_ms, slide: n });
-                            }
-                        }
-                    }
-                }
-            }
+impl Viewer {
+    /// The form of a broadcast this connection receives, if it receives it at all.
+    fn view(self, msg: &SlideMessage) -> Option<SlideMessage> {
+        // Audience and public connections only receive their hello and errors, the deck and its translations,
+        // Slide, Fragment, Highlight, Caption, Name, polls and pinned questions. Recording control events (start/pause/resume/stop) are presenter-only.
+        if self.is_audience
+            && !matches!(
+                msg,
+                SlideMessage::Hello(_)
+                    | SlideMessage::Error(_)
+                    | SlideMessage::Text(_)
+                    | SlideMessage::Patch(_)
+                    | SlideMessage::Revision(_)
+                    | SlideMessage::Slide(_)
+                    | SlideMessage::Fragment { .. }
+                    | SlideMessage::Highlight { .. }
+                    | SlideMessage::Caption(_)
+                    | SlideMessage::Name(_)
+                    | SlideMessage::Language(_)
+                    | SlideMessage::Variant { .. }
+                    | SlideMessage::PinnedQuestions(_)
+                    | SlideMessage::Poll(_)
+            )
+        {
+            return None;
         }
-    };
-
-    let channel_handler = async {
-        while let Ok(msg) = rx.recv().await {
-            // Audience and public connections only receive Slide, Text, and Name messages.
-            // Recording control events (start/pause/resume/stop) are presenter-only.
-            if is_audience
-                && !matches!(
-                    msg,
-                    SlideMessage::Text(_) | SlideMessage::Slide(_) | SlideMessage::Name(_)
-                )
+        match msg {
+            // The question queue may name askers; only presenters see it.
+            SlideMessage::Question(_) | SlideMessage::Questions(_) | SlideMessage::Presence(_) | SlideMessage::Pacing(_)
+                if !self.is_presenter =>
             {
-                continue;
+                None
             }
-            // SlideMessage contains only strings and integers; serialisation cannot fail.
-            let text = serde_json::to_string(&msg)
-                .expect("SlideMessage is always serializable");
-            if sock_send.send(Message::from(text)).await.is_err() {
-                return;
+            // Cursors go to the other editors only.
+            SlideMessage::Cursor(Cursor { id, .. }) | SlideMessage::CursorGone(id)
+                if !self.is_editor || *id == self.connection_id =>
+            {
+                None
+            }
+            SlideMessage::Text(content) if self.is_audience => Some(SlideMessage::Text(strip_speaker_notes(content))),
+            SlideMessage::Variant { lang, text: Some(text) } if self.is_audience => {
+                Some(SlideMessage::Variant { lang: lang.clone(), text: Some(strip_speaker_notes(text)) })
+            }
+            // The audience copy of a patch applies to their notes-free deck.
+            SlideMessage::Patch(TextPatch { audience: Some(patch), .. }) if self.is_audience => {
+                Some(SlideMessage::Patch((**patch).clone()))
             }
-            // pid is a URL path segment parsed as i64 in the ws route; persist without panicking.
-            if let (Ok(id), SlideMessage::Text(content)) = (pid.parse::<i64>(), msg) {
-                let _ = DbPresentation::update_content(id, content, &state.db_pool).await;
+            SlideMessage::Patch(patch) if patch.author == Some(self.connection_id) => {
+                Some(SlideMessage::Patch(TextPatch { mine: true, ..patch.clone() }))
             }
+            _ => Some(msg.clone()),
         }
-    };
+    }
+}
 
-    let () = or(socket_handler, channel_handler).await;
+impl Presentation {
+    /// Replaces the content and broadcasts the patch that brings clients up to date.
+    ///
+    /// `editor` is the connection id and name of the editor who made the change, if any.
+    /// Broadcasting under the presentation lock keeps patches in revision order.
+    fn set_content(&mut self, content: String, editor: Option<(u64, &str)>) {
+        let patch = self.replace_content(content, editor);
+        // No receivers only means nobody is connected to be told.
+        let _ = self.channel.0.send(patch);
+    }
+
+    /// Replaces the content like [`set_content`](Self::set_content), returning the patch
+    /// for the caller to broadcast instead.
+    fn replace_content(&mut self, content: String, editor: Option<(u64, &str)>) -> SlideMessage {
+        let mut patch = TextPatch::between(&self.content, &content, self.text_revision);
+        if self.text_history.len() == TEXT_HISTORY_LEN {
+            self.text_history.pop_front();
+        }
+        self.text_history.push_back(patch.clone());
+        patch.audience = Some(Box::new(TextPatch::between(
+            &strip_speaker_notes(&self.content),
+            &strip_speaker_notes(&content),
+            self.text_revision,
+        )));
+        if let Some((connection, name)) = editor {
+            patch.author = Some(connection);
+            patch.by = Some(name.to_string());
+        }
+        self.text_revision += 1;
+        if let Some(writer) = &self.writer {
+            writer.edit(content.clone());
+        }
+        self.content = content;
+        SlideMessage::Patch(patch)
+    }
 
-    // Auto-stop recording if this was the last presenter
-    if is_presenter {
-        let should_stop = match pres.lock() {
-            Ok(mut p) => {
-                p.presenter_count = p.presenter_count.saturating_sub(1);
-                p.presenter_count == 0 && p.recording.is_some()
-            }
-            Err(_) => false, // mutex poisoned; skip recording cleanup
+    /// Shows the first `fragment` steps of `slide`; moving to another slide drops the highlight.
+    ///
+    /// The first move starts the session clock.
+    fn show(&mut self, slide: u32, fragment: u32) {
+        if slide != self.slide {
+            self.highlight = None;
+        }
+        (self.slide, self.fragment) = (slide, fragment);
+        let clock = self.clock.get_or_insert_with(SessionClock::start);
+        let visit = clock.visit;
+        clock.step(slide);
+        if clock.visit != visit {
+            self.pace();
+        }
+    }
+
+    /// The highlight now shown, with what clients find its block by in the deck and in
+    /// each translation.
+    fn highlight_message(&self) -> SlideMessage {
+        let idx = usize::try_from(self.slide).unwrap_or(usize::MAX);
+        let target_in = |markdown: &str| {
+            let block = usize::try_from(self.highlight?).ok()?;
+            parse_deck(markdown).targets(idx).into_iter().nth(block)
         };
-        if should_stop {
-            handle_recording_message(
-                RecordingMessage::RecordingStop, &pres, pid_i64, &state.db_pool,
-            ).await;
-            // No broadcast: no clients remain
+        SlideMessage::Highlight {
+            slide: self.slide,
+            block: self.highlight,
+            target: target_in(&self.content),
+            variants: self.variants.iter().filter_map(|(lang, text)| Some((lang.clone(), target_in(text)?))).collect(),
         }
     }
 
-    drop(pres);
-}
+    /// Tells presenters how the session keeps to its plan, and sets timers to warn them
+    /// when the slide shown, or the whole session, runs over.
+    fn pace(&mut self) {
+        let Some(clock) = self.clock.as_mut() else { return; };
+        let plan = planned_times(&self.content);
+        let (slide_left, session_left) = clock.time_left(self.slide, &plan);
+        // A slide already over its plan was warned about on an earlier visit.
+        if let Some(left) = slide_left.filter(|&left| left > 0)
+            && clock.slide_warning != Some(clock.visit)
+        {
+            clock.slide_warning = Some(clock.visit);
+            warn_after(self.this.clone(), left, Overrun::Slide, clock.visit);
+        }
+        if let Some(left) = session_left
+            && !clock.session_check
+            && !clock.session_warned
+        {
+            clock.session_check = true;
+            warn_after(self.this.clone(), left, Overrun::Session, clock.visit);
+        }
+        let pacing = clock.pacing(self.slide, &plan);
+        // No receivers only means nobody is connected to be told.
+        let _ = self.channel.0.send(SlideMessage::Pacing(pacing));
+    }
+
+    /// Warns presenters that `over` ran over its plan, unless the slide moved on since
+    /// `visit` or the plan changed so that it no longer has.
+    fn warn(&mut self, over: Overrun, visit: u64) {
+        let Some(clock) = self.clock.as_mut() else { return; };
+        let plan = planned_times(&self.content);
+        let (slide_left, session_left) = clock.time_left(self.slide, &plan);
+        match over {
+            Overrun::Slide if clock.visit != visit || slide_left != Some(0) => return,
+            Overrun::Slide => {}
+            Overrun::Session => {
+                clock.session_check = false;
+                if clock.session_warned || session_left != Some(0) {
+                    return;
+                }
+                clock.session_warned = true;
+            }
+        }
+        let pacing = Pacing { over: Some(over), ..clock.pacing(self.slide, &plan) };
+        let _ = self.channel.0.send(SlideMessage::Pacing(pacing));
+    }
 
-async fn join(
-    State(tera): State<Tera>,
-    State(db): State<SqlitePool>,
-    auth_session: AuthSession,
-) -> impl IntoResponse {
-    tera.render("join.html", Context::new(), auth_session, db)
-        .await
-}
-/// Returns the HTML for a single slide from rendered markdown.
-/// Splits at `<h2>` boundaries, mirroring the JS `addSiblings` function.
-#[must_use]
-fn render_slide(markdown: &str, slide_index: u32, pres_name: &str) -> String {
-    // Strip raw HTML events to prevent XSS — pulldown-cmark passes them through
-    // unchanged, which would let editors embed <script> or other dangerous tags.
-    let events: Vec<Event<'_>> = Parser::new_ext(markdown, Options::all())
-        .filter(|e| !matches!(e, Event::Html(_) | Event::InlineHtml(_)))
-        .collect();
-    let slide_starts: Vec<usize> = events
-        .iter()
-        .enumerate()
-        .filter_map(|(i, e)| match e {
-            Event::Start(Tag::Heading { level: HeadingLevel::H2, .. }) => Some(i),
-            _ => None,
+    /// Notes the slide, fragment step and highlight now shown in the active recording,
+    /// unless it is paused.
+    fn record_step(&mut self) {
+        let (slide, fragment, highlight) = (self.slide, self.fragment, self.highlight);
+        if let Some(ref mut rec) = self.recording
+            && !rec.is_paused
+        {
+            let offset_ms = rec.elapsed_ms();
+            rec.slides.push(RecordingEvent { offset_ms, slide, fragment, highlight });
+            self.checkpoint();
+        }
+    }
+
+    /// Times a live caption against the active recording, unless it is paused.
+    fn record_caption(&mut self, text: &str) {
+        if let Some(ref mut rec) = self.recording
+            && !rec.is_paused
+        {
+            rec.captions.push((rec.elapsed_ms(), text.to_string()));
+            self.checkpoint();
+        }
+    }
+
+    /// Queues the slide shown, the recording so far and the session clock to be saved,
+    /// so that they survive a restart and the pacing report outlives the session.
+    fn checkpoint(&self) {
+        if let Some(writer) = &self.writer {
+            writer.checkpoint(LiveState {
+                slide: self.slide,
+                fragment: self.fragment,
+                recording: self.recording.as_ref().and_then(RecordingState::checkpoint),
+                clock: self.clock.as_ref().map(|clock| {
+                    let ClockState { elapsed_ms, steps } = clock.state();
+                    (elapsed_ms, steps)
+                }),
+            });
+        }
+    }
+
+    /// The patches made since text revision `base`, oldest first.
+    ///
+    /// `None` if the revision is in the future or too old to be in the history.
+    fn edits_since(&self, base: u64) -> Option<std::collections::vec_deque::Iter<'_, TextPatch>> {
+        let missed = usize::try_from(self.text_revision.checked_sub(base)?).ok()?;
+        let skip = self.text_history.len().checked_sub(missed)?;
+        Some(self.text_history.range(skip..))
+    }
+
+    /// Rewrites a patch made against an earlier revision to apply to the current one.
+    ///
+    /// `None` if the revision is in the future or too old to be in the history.
+    fn rebase(&self, patch: &TextPatch) -> Option<TextPatch> {
+        Some(self.edits_since(patch.base)?.fold(patch.clone(), |rebased, earlier| rebased.transform(earlier, true)))
+    }
+
+    /// Length of the deck at text revision `base` in UTF-16 code units, if it is still known.
+    fn length_at(&self, base: u64) -> Option<usize> {
+        self.edits_since(base)?.rev().try_fold(utf16_len(&self.content), |len, later| {
+            (len + (later.end - later.start)).checked_sub(utf16_len(&later.text))
         })
-        .collect();
-    if slide_starts.is_empty() {
-        return String::new();
     }
-    let idx = usize::try_from(slide_index)
-        .unwrap_or(usize::MAX)
-        .min(slide_starts.len() - 1);
-    let start = slide_starts[idx];
-    let end = slide_starts.get(idx + 1).copied().unwrap_or(events.len());
-    let mut output = String::new();
-    if !pres_name.is_empty() {
-        output.push_str("<h1>");
-        output.push_str(&html_escape(pres_name));
-        output.push_str("</h1>");
+
+    /// Counts the open connections by role, to this process and every other serving
+    /// the presentation.
+    fn presence(&self) -> Presence {
+        let mut presence = self.local_presence();
+        let mut users: BTreeSet<String> = presence.users.drain(..).collect();
+        for (other, _) in self.remote_presence.values() {
+            presence.audience += other.audience;
+            presence.public += other.public;
+            presence.presenters += other.presenters;
+            presence.captioners += other.captioners;
+            users.extend(other.users.iter().cloned());
+        }
+        presence.viewers = presence.audience + presence.public;
+        presence.users = users.into_iter().collect();
+        presence
+    }
+
+    /// Counts the open connections to this process by role.
+    fn local_presence(&self) -> Presence {
+        let mut presence = Presence::default();
+        let mut users = BTreeSet::new();
+        for (role, name) in self.attendees.values() {
+            match role {
+                AccessResult::Owner | AccessResult::Editor | AccessResult::Controller => {
+                    presence.presenters += 1;
+                    continue;
+                }
+                AccessResult::Captioner => {
+                    presence.captioners += 1;
+                    continue;
+                }
+                AccessResult::Audience => presence.audience += 1,
+                AccessResult::PublicOk => presence.public += 1,
+                AccessResult::Denied => continue,
+            }
+            users.extend(name.clone());
+        }
+        presence.viewers = presence.audience + presence.public;
+        presence.users = users.into_iter().collect();
+        presence
+    }
+
+    /// Creates the live state for a presentation, starting on the first slide.
+    fn new(content: String) -> Self {
+        Presentation {
+            content,
+            slide: 0,
+            fragment: 0,
+            highlight: None,
+            lang: None,
+            variants: BTreeMap::new(),
+            channel: broadcast::channel(1024),
+            replay: Arc::new(Mutex::new(Replay::new())),
+            recording: None,
+            recording_elsewhere: false,
+            poll: None,
+            presenter_count: 0,
+            connections: HashMap::new(),
+            next_connection_id: 0,
+            attendees: HashMap::new(),
+            remote_presence: HashMap::new(),
+            presence_throttle: PresenceThrottle::default(),
+            idle_since: Some(std::time::Instant::now()),
+            revision_generation: 0,
+            revision_burst: None,
+            text_revision: 0,
+            text_history: VecDeque::new(),
+            writer: None,
+            clock: None,
+            this: Weak::new(),
+        }
     }
-    cmark_html::push_html(&mut output, events[start..end].iter().cloned());
-    output
 }
 
-/// Returns `(title, html_content)` for every `## ` slide in the markdown.
-/// Uses the same pulldown-cmark parser as `render_slide`.
-#[must_use]
-fn render_all_slides(markdown: &str) -> Vec<(String, String)> {
-    // Strip raw HTML events to prevent XSS — pulldown-cmark passes them through
-    // unchanged, which would let editors embed <script> or other dangerous tags.
-    let events: Vec<Event<'_>> = Parser::new_ext(markdown, Options::all())
-        .filter(|e| !matches!(e, Event::Html(_) | Event::InlineHtml(_)))
-        .collect();
-    let slide_starts: Vec<usize> = events
-        .iter()
-        .enumerate()
-        .filter_map(|(i, e)| match e {
-            Event::Start(Tag::Heading { level: HeadingLevel::H2, .. }) => Some(i),
-            _ => None,
-        })
-        .collect();
-    slide_starts
-        .iter()
-        .enumerate()
-        .map(|(i, &start)| {
-            let end = slide_starts.get(i + 1).copied().unwrap_or(events.len());
-            // Extract heading text
-            let title = events[start..end]
-                .iter()
-                .filter_map(|e| if let Event::Text(t) = e { Some(t.as_ref()) } else { None })
-                .next()
-                .unwrap_or("")
-                .to_string();
-            // Render full slide HTML
-            let mut html = String::new();
-            cmark_html::push_html(&mut html, events[start..end].iter().cloned());
-            (title, html)
-        })
-        .collect()
+/// Extracts the client IP address for rate limiting.
+///
+/// Reads from `X-Forwarded-For` (set by Caddy in production) or `X-Real-IP`.
+/// Falls back to the loopback address in local development and test environments.
+/// Safe to use behind a trusted reverse proxy like Caddy that controls these headers.
+#[derive(Clone, Debug)]
+struct ClientIpExtractor;
+
+impl KeyExtractor for ClientIpExtractor {
+    type Key = IpAddr;
+
+    fn extract<T>(&self, req: &axum::http::Request<T>) -> Result<IpAddr, GovernorError> {
+        let headers = req.headers();
+        let ip = headers
+            .get("x-forwarded-for")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|s| s.split(',').find_map(|part| part.trim().parse::<IpAddr>().ok()))
+            .or_else(|| {
+                headers
+                    .get("x-real-ip")
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|s| s.trim().parse::<IpAddr>().ok())
+            })
+            // Fall back to loopback for local dev and tests (no proxy headers present).
+            .unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST));
+        Ok(ip)
+    }
 }
 
-/// Gets the current slide index from in-memory state, defaulting to 0.
-#[must_use]
-fn current_slide_index(app_state: &AppState, pid: i64) -> u32 {
-    let Ok(map) = app_state.slides.lock() else { return 0; };
-    map.get(&pid.to_string())
-        .and_then(|p| p.lock().ok().map(|p| p.slide))
-        .unwrap_or(0)
+/// The state of the entire application.
+#[derive(Clone)]
+pub struct AppState {
+    /// Used to render HTML templates.
+    tera: Tera,
+    /// Used to store all the ongoing presentation.
+    /// They Key here is a user-defined string, and the value is a [`Presentation`] struct.
+    slides: Arc<Mutex<HashMap<String, Arc<Mutex<Presentation>>>>>,
+    db_pool: SqlitePool,
+    /// Largest accepted upload, from `APP_UPLOAD_LIMIT_MB`.
+    upload_limit: UploadLimit,
+    /// Shares live presentations with other server processes, from `APP_PUBSUB_URL`.
+    pubsub: Arc<dyn PubSub>,
+    /// Identifies this process to the others sharing `pubsub`.
+    node: u64,
+    /// Counts of idle presentations evicted from `slides`.
+    evictions: Arc<EvictionStats>,
 }
 
-async fn audience(tera: Tera, auth_session: AuthSession, db: SqlitePool) -> impl IntoResponse {
-    tera.render("audience.html", Context::new(), auth_session, db)
-        .await
+impl FromRef<AppState> for SqlitePool {
+    fn from_ref(state: &AppState) -> Self {
+        state.db_pool.clone()
+    }
 }
-async fn start(
-    State(tera): State<Tera>,
-    auth_session: AuthSession,
-    State(db): State<SqlitePool>,
-) -> impl IntoResponse {
-    if auth_session.user.is_none() {
-        return Redirect::to("/auth/login").into_response();
+impl FromRef<AppState> for Tera {
+    fn from_ref(state: &AppState) -> Self {
+        state.tera.clone()
     }
-    tera.render("create.html", Context::new(), auth_session, db)
-        .await
 }
-
-#[derive(Clone, Debug, Serialize, Deserialize)]
-struct NameForm {
-    name: String,
+impl FromRef<AppState> for UploadLimit {
+    fn from_ref(state: &AppState) -> Self {
+        state.upload_limit
+    }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-struct PwdQuery {
-    error: Option<String>,
+/// Where a reconnecting client left off, from the last [`SlideMessage::Resume`] and `seq` it saw.
+#[derive(Clone, Copy, Deserialize)]
+struct ResumeQuery {
+    stream: Option<u64>,
+    seq: Option<u64>,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-struct AddAccessForm {
-    username: String,
-    role: String,
+async fn broadcast_to_all(
+    ws: WebSocketUpgrade,
+    Path(pid): Path<String>,
+    Query(resume): Query<ResumeQuery>,
+    State(state): State<AppState>,
+    auth_session: AuthSession,
+) -> Response {
+    // Resolve role at connect time. Password is not passed — the WebSocket
+    // endpoint does not handle password authentication; the HTTP layer (plan 3)
+    // gates who can reach the audience page in the first place.
+    let pid_i64 = pid.parse::<i64>().unwrap_or(-1);
+    let role = check_access(
+        &state.db_pool,
+        auth_session.user.as_ref(),
+        pid_i64,
+        None,
+    )
+    .await
+    .unwrap_or(AccessResult::Denied);
+    let user_id = auth_session.user.as_ref().map(|u| u.id);
+    ws.on_upgrade(move |socket| ws_handle(socket, pid, state, role, user_id, resume))
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-struct RemoveAccessForm {
-    user_id: i64,
+/// Applies a client's change to the live presentation and returns what to broadcast.
+///
+/// A whole-deck `Text` is broadcast as the [`Patch`](SlideMessage::Patch) it amounts to
+/// by [`Presentation::set_content`], so nothing is returned for it.
+fn update_slide(pid: &str, msg: SlideMessage, state: &mut AppState) -> Option<SlideMessage> {
+    let Ok(mut slides) = state.slides.lock() else { return Some(msg); };
+    let Some(pres_arc) = slides.get_mut(pid) else { return Some(msg); };
+    let Ok(mut pres) = pres_arc.lock() else { return Some(msg); };
+    match msg {
+        SlideMessage::Slide(sn) => {
+            pres.show(sn, 0);
+            pres.record_step();
+            pres.checkpoint();
+            Some(msg)
+        }
+        SlideMessage::Fragment { slide, fragment } => {
+            // Steps past the last reveal nothing more.
+            let fragment = fragment.min(slide_steps(&pres.content, slide));
+            pres.show(slide, fragment);
+            pres.record_step();
+            pres.checkpoint();
+            Some(SlideMessage::Fragment { slide, fragment })
+        }
+        SlideMessage::Highlight { slide, block, target, .. } => {
+            // A pointer sent before the slide moved on, or at a block the slide lacks, is stale.
+            if slide != pres.slide {
+                return None;
+            }
+            let idx = usize::try_from(slide).unwrap_or(usize::MAX);
+            pres.highlight = match (target, block) {
+                (Some(target), _) => Some(parse_deck(&pres.content).find_block(idx, &target)?),
+                (None, Some(b)) if slide_has_block(&pres.content, slide, b) => Some(b),
+                (None, Some(_)) => return None,
+                (None, None) => None,
+            };
+            pres.record_step();
+            Some(pres.highlight_message())
+        }
+        SlideMessage::Caption(text) => {
+            let text = captions::clean(&text)?;
+            pres.record_caption(&text);
+            Some(SlideMessage::Caption(text))
+        }
+        SlideMessage::Text(text) => {
+            pres.set_content(text, None);
+            None
+        }
+        _ => Some(msg),
+    }
 }
 
-#[derive(Deserialize)]
-struct SetAccessModeForm {
-    mode: String,
+async fn add_client_handler_channel(
+    pid: String,
+    state: &mut AppState,
+) -> Result<Arc<Mutex<Presentation>>, StatusCode> {
+    // Check if already in memory without holding lock across await
+    {
+        let Ok(slides) = state.slides.lock() else {
+            eprintln!("K/V store mutex poisoned in add_client_handler_channel (read)");
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        };
+        if let Some(pres) = slides.get(&pid) {
+            return Ok(Arc::clone(pres));
+        }
+    }
+    // Not in memory — load content from DB so the initial WS message has real content
+    let db_pres = match pid.parse::<i64>() {
+        Ok(pid_i64) => DbPresentation::get_by_id(pid_i64, &state.db_pool).await.ok().flatten(),
+        Err(_) => None,
+    };
+    // ...and where it was left before a restart.
+    let live = match &db_pres {
+        Some(db_pres) => LiveState::get(db_pres.id, &state.db_pool).await.ok().flatten().unwrap_or_default(),
+        None => LiveState::default(),
+    };
+    // ...and the languages it is given in.
+    let (lang, variants) = match &db_pres {
+        Some(db_pres) => (
+            DbPresentation::get_language(db_pres.id, &state.db_pool).await.ok().flatten(),
+            PresentationVariant::get_for_presentation(&state.db_pool, db_pres.id).await.unwrap_or_default(),
+        ),
+        None => (None, Vec::new()),
+    };
+    let recording = match live.recording {
+        Some(checkpoint) => {
+            let name = Recording::get_by_id(checkpoint.recording_id, &state.db_pool).await.ok().flatten().map(|r| r.name);
+            name.map(|name| RecordingState::restore(checkpoint, name))
+        }
+        None => None,
+    };
+    let Ok(mut slides) = state.slides.lock() else {
+        eprintln!("K/V store mutex poisoned in add_client_handler_channel (write)");
+        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+    };
+    let link = Link::new(state, &pid);
+    let pres = slides.entry(pid).or_insert_with(|| {
+        let mut pres = Presentation::new(db_pres.as_ref().map(|p| p.content.clone()).unwrap_or_default());
+        (pres.slide, pres.fragment) = (live.slide, live.fragment);
+        pres.lang = lang;
+        pres.variants = variants.into_iter().map(|v| (v.lang, v.content)).collect();
+        pres.recording = recording;
+        if let Some(db_pres) = db_pres {
+            pres.writer = Some(ContentWriter::spawn(db_pres.id, state.node, state.db_pool.clone()));
+        }
+        let (raw, replay, content) = (pres.channel.0.subscribe(), Arc::clone(&pres.replay), pres.content.clone());
+        let pres = Arc::new_cyclic(|this| {
+            pres.this = this.clone();
+            Mutex::new(pres)
+        });
+        tokio::spawn(sequencer(raw, Arc::downgrade(&pres), replay, link, content));
+        pres
+    });
+    Ok(Arc::clone(pres))
 }
 
-#[derive(Deserialize)]
-struct SetRecordingAccessModeForm {
-    action: String,
-    mode: Option<String>,
+/// Patches broadcast before the current revision that late patches can still be rebased over.
+const TEXT_HISTORY_LEN: usize = 256;
+
+/// Applies an editor's patch to the live deck, which broadcasts it.
+///
+/// A patch made against an earlier revision is first rebased over the edits made since,
+/// so concurrent editors keep each other's changes. One too old to rebase, or whose range
+/// does not fit the deck, is answered with a snapshot sent to `direct` (the sender only)
+/// to start over from; a refused patch, or one whose range is reversed or runs past the
+/// deck it was made against, with an error. `editor` is the sender's connection
+/// id and name.
+/// Returns `Err` if the connection must be closed.
+fn handle_patch(
+    pres: &Arc<Mutex<Presentation>>,
+    patch: &TextPatch,
+    role: &AccessResult,
+    pid: &str,
+    editor: (u64, &str),
+    direct: &tokio::sync::mpsc::UnboundedSender<SlideMessage>,
+) -> Result<(), &'static str> {
+    if !protocol::may_send(role, "patch") {
+        eprintln!("[ws] pid={pid} role={role:?} msg_type=patch reason=unauthorized");
+        let _ = direct.send(SlideMessage::Error(ProtocolError::unauthorized("patch")));
+        return Ok(());
+    }
+    let Ok(mut p) = pres.lock() else { return Err("Presentation unavailable"); };
+    // A range that is reversed or runs past the deck it was made against edits nothing.
+    if patch.start > patch.end || p.length_at(patch.base).is_some_and(|len| patch.end > len) {
+        eprintln!("[ws] pid={pid} role={role:?} msg_type=patch reason=invalid_range");
+        let error = ProtocolError::new(ErrorCode::Malformed, Some("patch"), "The edit does not fit the slides it was made to.");
+        let _ = direct.send(SlideMessage::Error(error));
+        return Ok(());
+    }
+    let applied = p.rebase(patch).and_then(|rebased| rebased.apply(&p.content));
+    let Some(content) = applied else {
+        eprintln!(
+            "[ws] pid={pid} role={role:?} msg_type=patch reason=stale base={} revision={}",
+            patch.base, p.text_revision
+        );
+        // Sent under the lock, so no later patch can overtake the snapshot.
+        let _ = direct.send(SlideMessage::Text(p.content.clone()));
+        let _ = direct.send(SlideMessage::Revision(p.text_revision));
+        return Ok(());
+    };
+    // Same limit and consequence as a whole-deck Text message.
+    if !slides_within_size_limit(&content) {
+        eprintln!("[ws] pid={pid} role={role:?} msg_type=patch reason=content_too_large");
+        let _ = direct.send(SlideMessage::Error(slide_too_large("patch")));
+        return Err("Slide content too large");
+    }
+    p.set_content(content, Some(editor));
+    Ok(())
 }
 
-#[derive(Deserialize)]
-struct UserExistsQuery {
-    username: Option<String>,
+/// Returns `true` if every slide section in `content` is within the 100 KB limit.
+///
+/// Slides are delimited by `\n## ` (H2 headings), matching the frontend split logic.
+/// 100 KB per slide — keeps individual slides lean while allowing arbitrarily large decks.
+/// (OWASP Input Validation Cheat Sheet recommends explicit length caps on rich text inputs.)
+fn slides_within_size_limit(content: &str) -> bool {
+    // 100 KB per slide — lower end of the ±50% range allowed by the spec.
+    const MAX_SLIDE_BYTES: usize = 100 * 1024;
+    content.split("\n## ").all(|section| section.len() <= MAX_SLIDE_BYTES)
 }
 
-async fn user_exists(
-    State(db): State<SqlitePool>,
-    auth_session: AuthSession,
-    Query(query): Query<UserExistsQuery>,
-) -> impl IntoResponse {
-    let Some(_) = auth_session.user else {
-        return StatusCode::UNAUTHORIZED.into_response();
-    };
-    let Some(username) = query.username else {
-        return StatusCode::BAD_REQUEST.into_response();
-    };
-    match User::get_by_name(username, &db).await {
-        Ok(Some(_)) => StatusCode::OK.into_response(),
-        Ok(None) => StatusCode::NOT_FOUND.into_response(),
-        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
-    }
+/// Returns `true` if an audience question is within the 1 KB limit.
+///
+/// Questions are short free text shown to presenters and, once pinned, to everyone.
+fn question_within_size_limit(text: &str) -> bool {
+    const MAX_QUESTION_BYTES: usize = 1024;
+    text.len() <= MAX_QUESTION_BYTES
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-struct ChangeRoleForm {
-    user_id: i64,
-    role: String,
+/// Returns a short, grep-able type label for a [`SlideMessage`] variant.
+fn slide_msg_type_name(msg: &SlideMessage) -> &'static str {
+    match msg {
+        SlideMessage::Hello(_) => "hello",
+        SlideMessage::Error(_) => "error",
+        SlideMessage::Text(_) => "text",
+        SlideMessage::Patch(_) => "patch",
+        SlideMessage::Revision(_) => "revision",
+        SlideMessage::Cursor(_) => "cursor",
+        SlideMessage::CursorGone(_) => "cursor_gone",
+        SlideMessage::Resume { .. } => "resume",
+        SlideMessage::Slide(_) => "slide",
+        SlideMessage::Fragment { .. } => "fragment",
+        SlideMessage::Highlight { .. } => "highlight",
+        SlideMessage::Caption(_) => "caption",
+        SlideMessage::Name(_) => "name",
+        SlideMessage::Language(_) => "language",
+        SlideMessage::Variant { .. } => "variant",
+        SlideMessage::Notes(_) => "notes",
+        SlideMessage::Question(_) => "question",
+        SlideMessage::Questions(_) => "questions",
+        SlideMessage::PinnedQuestions(_) => "pinned_questions",
+        SlideMessage::Poll(_) => "poll",
+        SlideMessage::RecordingStart { .. } => "recording_start",
+        SlideMessage::RecordingPause { .. } => "recording_pause",
+        SlideMessage::RecordingResume { .. } => "recording_resume",
+        SlideMessage::RecordingStop { .. } => "recording_stop",
+        SlideMessage::Presence(_) => "presence",
+        SlideMessage::Pacing(_) => "pacing",
+    }
 }
 
-async fn start_pres(
-    State(db): State<SqlitePool>,
-    auth_session: AuthSession,
-    Form(name_form): Form<NameForm>,
-) -> impl IntoResponse {
-    let Some(user) = auth_session.user else {
-        return Redirect::to("/auth/login").into_response();
+/// Handles a deck or slide message from any connection.
+///
+/// A message the connection's role may not send, or one that cannot be read, is answered
+/// with an error sent to `direct` (the sender only). Returns `Err` if the connection must
+/// be closed.
+fn handle_socket(
+    msg: Result<Message, axum::Error>,
+    pid: &str,
+    tx: &mut Sender<SlideMessage>,
+    state: &mut AppState,
+    role: &AccessResult,
+    direct: &tokio::sync::mpsc::UnboundedSender<SlideMessage>,
+) -> Result<bool, &'static str> {
+    let Ok(raw) = msg else {
+        return Err("Disconnected");
     };
-    if name_form.name.is_empty() {
-        return Redirect::to("/start").into_response();
+    if let Message::Close(_) = raw {
+        return Err("Closed");
     }
-    // Presentation name: 200-char limit — aligns with Google Slides and common tools.
-    // Prevents DB text column overflow and oversized page titles.
-    if name_form.name.len() > 200 {
-        return StatusCode::BAD_REQUEST.into_response();
+    let text = raw.to_text().ok();
+    let Some(slide_msg) = text.and_then(|t| serde_json::from_str::<SlideMessage>(t).ok()) else {
+        eprintln!("[ws] pid={pid} role={role:?} msg_type=unknown reason=malformed_or_unknown_type");
+        let raw_type = text.and_then(message_type);
+        let _ = direct.send(SlideMessage::Error(ProtocolError::new(
+            ErrorCode::Malformed,
+            raw_type.as_deref(),
+            "The server could not read this message.",
+        )));
+        return Ok(true);
+    };
+    // Notes, questions, presence, pacing, revisions and translations are produced server-side;
+    // they are in no role's capabilities, so no client may inject them.
+    let msg_type = slide_msg_type_name(&slide_msg);
+    if !protocol::may_send(role, msg_type) {
+        eprintln!("[ws] pid={pid} role={role:?} msg_type={msg_type} reason=unauthorized");
+        let _ = direct.send(SlideMessage::Error(ProtocolError::unauthorized(msg_type)));
+        return Ok(true);
     }
-    let pres = DbPresentation::new(&user, name_form.name, &db).await;
-    if let Err(ref e) = pres {
-        println!("{e:?}");
+    // Slide content: max 100 KB per slide section. Disconnects on excess — mirrors HTTP 400
+    // semantics for oversized input (OWASP Input Validation Cheat Sheet §Rich Text).
+    if let SlideMessage::Text(ref content) = slide_msg {
+        if !slides_within_size_limit(content) {
+            eprintln!("[ws] pid={pid} role={role:?} msg_type=text reason=content_too_large");
+            let _ = direct.send(SlideMessage::Error(slide_too_large("text")));
+            return Err("Slide content too large");
+        }
     }
-    let Ok(pres) = pres else {
-        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
-    };
-    Redirect::to(&format!("/{}/{}/edit", user.name, pres.id)).into_response()
+    if let SlideMessage::Caption(ref text) = slide_msg
+        && text.len() > captions::MAX_CAPTION_BYTES
+    {
+        eprintln!("[ws] pid={pid} role={role:?} msg_type=caption reason=caption_too_large");
+        let _ = direct.send(SlideMessage::Error(ProtocolError::new(
+            ErrorCode::TooLarge,
+            Some("caption"),
+            "A caption line may be at most 1 KB.",
+        )));
+        return Ok(true);
+    }
+    if let Some(broadcast) = update_slide(pid, slide_msg, state)
+        && tx.send(broadcast).is_err()
+    {
+        return Err("Channel disconnected!");
+    }
+    Ok(true)
 }
 
-async fn present(
-    State(tera): State<Tera>,
-    State(db): State<SqlitePool>,
-    State(app_state): State<AppState>,
-    auth_session: AuthSession,
-    Path((uname, pid)): Path<(String, i64)>,
-) -> impl IntoResponse {
-    let pres_user = User::get_by_name(uname.clone(), &db).await;
-    let pres_user = match pres_user {
-        Ok(Some(u)) => u,
-        Ok(None) => return audience(tera, auth_session, db).await.into_response(),
-        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
-    };
-    let pres = DbPresentation::get_by_id(pid, &db).await;
-    let pres = match pres {
-        Ok(Some(p)) => p,
-        Ok(None) => return audience(tera, auth_session, db).await.into_response(),
-        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
-    };
-    if pres.user_id != pres_user.id {
-        let Ok(Some(owner)) = User::get_by_id(pres.user_id, &db).await else {
-            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
-        };
-        let redirect = format!("/{}/{pid}", owner.name);
-        return Redirect::permanent(&redirect).into_response();
-    }
-    let access = match check_access(&db, auth_session.user.as_ref(), pid, None).await {
-        Ok(a) => a,
-        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
-    };
+/// The `type` of a JSON message, if it is an object with a string `type`.
+fn message_type(text: &str) -> Option<String> {
+    let value = serde_json::from_str::<serde_json::Value>(text).ok()?;
+    value["type"].as_str().map(String::from)
+}
 
-    match access {
-        AccessResult::Owner | AccessResult::Editor | AccessResult::Controller => {
-            stage(tera, db, auth_session, pid, app_state, pres_user).await.into_response()
-        }
-        AccessResult::Audience | AccessResult::PublicOk => {
-            let slide_index = current_slide_index(&app_state, pid);
-            let initial_slide = render_slide(&pres.content, slide_index, &pres.name);
-            let mut ctx = Context::new();
-            ctx.insert("pres", &pres);
-            ctx.insert("pres_user", &pres_user);
-            ctx.insert("initial_slide", &initial_slide);
-            tera.render("audience.html", ctx, auth_session, db).await.into_response()
+/// The error that closes a connection for sending a slide over the size limit.
+fn slide_too_large(message_type: &str) -> ProtocolError {
+    ProtocolError::new(ErrorCode::TooLarge, Some(message_type), "A slide may be at most 100 KB.").fatal()
+}
+
+/// Minimum time between two questions asked over the same connection.
+const QUESTION_COOLDOWN: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Handles a Q&A message from any connection.
+///
+/// Returns the messages to broadcast (empty if the message was dropped), or `Err`
+/// if the connection must be closed. `last_asked` is the per-connection rate limit state;
+/// refusals are sent to `direct` (the sender only).
+async fn handle_question_message(
+    msg: QuestionMessage,
+    presentation_id: i64,
+    role: &AccessResult,
+    user_id: Option<i64>,
+    last_asked: &mut Option<std::time::Instant>,
+    pool: &SqlitePool,
+    direct: &tokio::sync::mpsc::UnboundedSender<SlideMessage>,
+) -> Result<Vec<SlideMessage>, &'static str> {
+    let pid = presentation_id;
+    match msg {
+        QuestionMessage::QuestionAsk { text, anonymous } => {
+            if !protocol::may_send(role, "question_ask") {
+                eprintln!("[ws] pid={pid} role={role:?} msg_type=question_ask reason=unauthorized");
+                let _ = direct.send(SlideMessage::Error(ProtocolError::unauthorized("question_ask")));
+                return Ok(vec![]);
+            }
+            // Oversized questions disconnect, like oversized slide content.
+            if !question_within_size_limit(&text) {
+                eprintln!("[ws] pid={pid} role={role:?} msg_type=question_ask reason=question_too_large");
+                let error = ProtocolError::new(ErrorCode::TooLarge, Some("question_ask"), "A question may be at most 1 KB.");
+                let _ = direct.send(SlideMessage::Error(error.fatal()));
+                return Err("Question too large");
+            }
+            let text = text.trim();
+            if text.is_empty() {
+                return Ok(vec![]);
+            }
+            if last_asked.is_some_and(|at| at.elapsed() < QUESTION_COOLDOWN) {
+                eprintln!("[ws] pid={pid} role={role:?} msg_type=question_ask reason=rate_limited");
+                let _ = direct.send(SlideMessage::Error(ProtocolError::new(
+                    ErrorCode::RateLimited,
+                    Some("question_ask"),
+                    "Wait a few seconds before asking another question.",
+                )));
+                return Ok(vec![]);
+            }
+            let author = if anonymous { None } else { user_id };
+            match Question::create(pool, presentation_id, author, text).await {
+                Ok(question) => {
+                    *last_asked = Some(std::time::Instant::now());
+                    Ok(vec![SlideMessage::Question(question)])
+                }
+                Err(e) => {
+                    eprintln!("handle_question_message: pid={pid} create failed: {e:?}");
+                    Ok(vec![])
+                }
+            }
         }
-        AccessResult::Denied => {
-            StatusCode::FORBIDDEN.into_response()
+        QuestionMessage::QuestionModerate { id, status } => {
+            if !protocol::may_send(role, "question_moderate") {
+                eprintln!("[ws] pid={pid} role={role:?} msg_type=question_moderate reason=unauthorized");
+                let _ = direct.send(SlideMessage::Error(ProtocolError::unauthorized("question_moderate")));
+                return Ok(vec![]);
+            }
+            let Ok(old) = Question::get_by_id(pool, id).await else { return Ok(vec![]); };
+            let Some(old) = old.filter(|old| old.presentation_id == presentation_id) else {
+                eprintln!("[ws] pid={pid} role={role:?} msg_type=question_moderate reason=foreign_question");
+                let error = ProtocolError::new(ErrorCode::Malformed, Some("question_moderate"), "There is no such question.");
+                let _ = direct.send(SlideMessage::Error(error));
+                return Ok(vec![]);
+            };
+            if let Err(e) = Question::set_status(pool, presentation_id, id, status).await {
+                eprintln!("handle_question_message: pid={pid} set_status failed: {e:?}");
+                return Ok(vec![]);
+            }
+            let Ok(Some(question)) = Question::get_by_id(pool, id).await else { return Ok(vec![]); };
+            let mut out = vec![SlideMessage::Question(question)];
+            if (old.status == QuestionStatus::Pinned || status == QuestionStatus::Pinned)
+                && let Ok(pinned) = Question::get_pinned(pool, presentation_id).await
+            {
+                out.push(SlideMessage::PinnedQuestions(pinned));
+            }
+            Ok(out)
         }
     }
 }
 
-async fn stage(
-    tera: Tera,
-    db: SqlitePool,
-    auth_session: AuthSession,
-    pid: i64,
-    app_state: AppState,
-    pres_user: User,
-) -> impl IntoResponse {
-    if auth_session.user.is_none() {
-        return Redirect::to("/auth/login").into_response();
+/// Handles a recording control message from a presenter connected to this process,
+/// passing it on to the process holding the recording if that is another.
+async fn control_recording(
+    msg: RecordingMessage,
+    pres: &Arc<Mutex<Presentation>>,
+    presentation_id: i64,
+    link: &Link,
+) -> Option<SlideMessage> {
+    if pres.lock().is_ok_and(|p| p.recording.is_none() && p.recording_elsewhere) {
+        link.publish(RemoteEvent::Recording { message: msg }).await;
+        return None;
     }
-    let pres = match DbPresentation::get_by_id(pid, &db).await {
-        Ok(Some(p)) => p,
-        Ok(None) => return StatusCode::NOT_FOUND.into_response(),
-        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
-    };
-    let slide_index = current_slide_index(&app_state, pid);
-    let initial_slide = render_slide(&pres.content, slide_index, &pres.name);
-    let mut ctx = Context::new();
-    ctx.insert("pres", &pres);
-    ctx.insert("pres_user", &pres_user);
-    ctx.insert("initial_slide", &initial_slide);
-    tera.render("stage.html", ctx, auth_session, db).await
+    handle_recording_message(msg, pres, presentation_id, &link.db_pool).await
 }
-async fn edit_pres(
-    State(tera): State<Tera>,
-    State(db): State<SqlitePool>,
-    auth_session: AuthSession,
-    Path((uname, pid)): Path<(String, i64)>,
-) -> impl IntoResponse {
-    if auth_session.user.is_none() {
-        return Redirect::to("/auth/login").into_response();
+
+/// Handles a recording control message from a presenter.
+///
+/// Returns a [`SlideMessage`] to broadcast to all clients, or `None` if the
+/// message is a no-op (e.g. start when already recording, pause when already paused).
+async fn handle_recording_message(
+    msg: RecordingMessage,
+    pres: &Arc<Mutex<Presentation>>,
+    presentation_id: i64,
+    pool: &SqlitePool,
+) -> Option<SlideMessage> {
+    match msg {
+        RecordingMessage::RecordingStart => {
+            // Compute name before acquiring the lock
+            let name = {
+                let now = time::OffsetDateTime::now_utc();
+                format!("Recording \u{2013} {:04}-{:02}-{:02} {:02}:{:02}",
+                    now.year(), u8::from(now.month()), now.day(), now.hour(), now.minute())
+            };
+            // Check and initialise under lock (placeholder db_id = -1)
+            {
+                let Ok(mut p) = pres.lock() else { return None; };
+                if p.recording.is_some() {
+                    return None;
+                }
+                let (slide, fragment, highlight) = (p.slide, p.fragment, p.highlight);
+                p.recording = Some(RecordingState {
+                    db_id: -1,
+                    name: name.clone(),
+                    started_at: std::time::Instant::now(),
+                    active_ms: 0,
+                    is_paused: false,
+                    pause_started_at: None,
+                    slides: vec![RecordingEvent { offset_ms: 0, slide, fragment, highlight }],
+                    polls: vec![],
+                    captions: vec![],
+                });
+            }
+            // Create DB row
+            let rec = Recording::create(presentation_id, name, None, String::new(), pool).await.ok()?;
+            // Store real db_id
+            {
+                let Ok(mut p) = pres.lock() else { return None; };
+                if let Some(rec_state) = p.recording.as_mut() {
+                    rec_state.db_id = rec.id;
+                } else {
+                    return None;
+                }
+                p.checkpoint();
+            }
+            Some(SlideMessage::RecordingStart { elapsed_ms: 0 })
+        }
+
+        RecordingMessage::RecordingPause => {
+            let elapsed_ms = {
+                let Ok(mut p) = pres.lock() else { return None; };
+                let rec = p.recording.as_mut()?;
+                if rec.is_paused { return None; }
+                let elapsed_ms = (std::time::Instant::now() - rec.started_at).as_millis() as u64 + rec.active_ms;
+                rec.pause_started_at = Some(std::time::Instant::now());
+                rec.is_paused = true;
+                p.checkpoint();
+                elapsed_ms
+            };
+            Some(SlideMessage::RecordingPause { elapsed_ms })
+        }
+
+        RecordingMessage::RecordingResume => {
+            let elapsed_ms = {
+                let Ok(mut p) = pres.lock() else { return None; };
+                let rec = p.recording.as_mut()?;
+                if !rec.is_paused { return None; }
+                let pause_start = rec.pause_started_at?;
+                // Accumulate running time from last start to pause
+                rec.active_ms += (pause_start - rec.started_at).as_millis() as u64;
+                rec.started_at = std::time::Instant::now();
+                rec.pause_started_at = None;
+                rec.is_paused = false;
+                // elapsed ≈ active_ms since started_at was just reset
+                let elapsed_ms = rec.active_ms;
+                p.checkpoint();
+                elapsed_ms
+            };
+            Some(SlideMessage::RecordingResume { elapsed_ms })
+        }
+
+        RecordingMessage::RecordingStop => {
+            // Extract everything needed before async work
+            let (rec, content) = {
+                let Ok(mut p) = pres.lock() else { return None; };
+                let rec = p.recording.take()?;
+                p.checkpoint();
+                (rec, p.content.clone())
+            };
+            if rec.db_id < 0 {
+                // DB row not yet created (start still in progress) — nothing to save
+                return Some(SlideMessage::RecordingStop { id: -1, name: String::new(), start: String::new() });
+            }
+            let (db_id, name) = (rec.db_id, rec.name.clone());
+            save_recording(rec, &content, pool).await;
+            let start = {
+                let now = time::OffsetDateTime::now_utc();
+                format!("{:04}-{:02}-{:02}", now.year(), u8::from(now.month()), now.day())
+            };
+            Some(SlideMessage::RecordingStop { id: db_id, name, start })
+        }
     }
-    let pres_user = match User::get_by_name(uname.clone(), &db).await {
-        Ok(Some(u)) => u,
-        Ok(None) => return StatusCode::NOT_FOUND.into_response(),
-        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
-    };
-    let pres = match DbPresentation::get_by_id(pid, &db).await {
-        Ok(Some(p)) => p,
-        Ok(None) => return StatusCode::NOT_FOUND.into_response(),
-        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+}
+
+/// The `(title, html)` each recorded step shows of `content`, or `None` for a step on a
+/// slide that `content` lacks.
+fn recorded_slides(content: &str, steps: &[RecordingEvent]) -> Vec<Option<(String, String)>> {
+    let all_slides = render_all_slides(content);
+    let deck = parse_deck(content);
+    steps
+        .iter()
+        .map(|ev| {
+            let (title, mut html) = all_slides.get(ev.slide as usize)?.clone();
+            // Each fragment step and highlight is a cue of its own, showing what was revealed
+            // by then and marking what the presenter pointed at.
+            let (revealed, steps) = match ev.highlight {
+                Some(block) => deck.highlighted(ev.slide as usize, block).reveal(ev.slide as usize, ev.fragment),
+                None => deck.reveal(ev.slide as usize, ev.fragment),
+            };
+            if ev.fragment < steps || ev.highlight.is_some() {
+                html.clear();
+                cmark_html::push_html(&mut html, revealed.into_iter());
+            }
+            Some((title, html))
+        })
+        .collect()
+}
+
+/// Saves the timeline of a stopped recording, resolving slide indices, fragment steps and
+/// highlights against `content` and each translation of the presentation, and writes its
+/// live captions as its `captions.vtt`.
+async fn save_recording(rec: RecordingState, content: &str, pool: &SqlitePool) {
+    let db_id = rec.db_id;
+    let length_ms = rec.elapsed_ms();
+    let RecordingState { slides, polls, captions, .. } = rec;
+    let recorded = recorded_slides(content, &slides);
+    let inputs: Vec<RecordingSlideInput> = slides
+        .iter()
+        .zip(&recorded)
+        .filter_map(|(ev, shown)| {
+            let (title, html) = shown.clone()?;
+            Some(RecordingSlideInput { start_seconds: ev.offset_ms as f64 / 1000.0, title, content: html })
+        })
+        .collect();
+    let _ = RecordingSlide::create_batch(db_id, inputs, pool).await;
+    // Translated steps take the positions of the steps they translate.
+    let variants = match Recording::get_by_id(db_id, pool).await {
+        Ok(Some(recording)) => {
+            PresentationVariant::get_for_presentation(pool, recording.presentation_id).await.unwrap_or_default()
+        }
+        _ => Vec::new(),
     };
-    if pres.user_id != pres_user.id {
-        let Ok(Some(owner)) = User::get_by_id(pres.user_id, &db).await else {
-            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    for variant in variants {
+        let translated: Vec<(i64, String, String)> = recorded_slides(&variant.content, &slides)
+            .into_iter()
+            .zip(&recorded)
+            // Only the steps saved above have a position.
+            .filter(|(_, main)| main.is_some())
+            .zip(0i64..)
+            .filter_map(|((shown, _), position)| shown.map(|(title, html)| (position, title, html)))
+            .collect();
+        let _ = RecordingSlide::create_variants(db_id, &variant.lang, translated, pool).await;
+    }
+    let polls = polls.into_iter().map(RecordingPollInput::from).collect();
+    let _ = RecordingPoll::create_batch(db_id, polls, pool).await;
+    // Captions uploaded later replace these, like any other.
+    if !captions.is_empty() {
+        let dir = format!("assets/{db_id}");
+        let written = match tokio::fs::create_dir_all(&dir).await {
+            Ok(()) => tokio::fs::write(format!("{dir}/captions.vtt"), captions::webvtt(&captions, length_ms)).await,
+            Err(e) => Err(e),
         };
-        let redirect = format!("/{}/{pid}/edit", owner.name);
-        return Redirect::permanent(&redirect).into_response();
-    }
-    let access = match check_access(&db, auth_session.user.as_ref(), pid, None).await {
-        Ok(a) => a,
-        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
-    };
-    match access {
-        AccessResult::Owner | AccessResult::Editor => {
-            let mut ctx = Context::new();
-            ctx.insert("pres", &pres);
-            ctx.insert("pres_user", &pres_user);
-            tera.render("edit.html", ctx, auth_session, db).await.into_response()
-        }
-        AccessResult::Controller | AccessResult::Audience | AccessResult::PublicOk => {
-            Redirect::to(&format!("/{uname}/{pid}")).into_response()
+        match written {
+            Ok(()) => {
+                let _ = Recording::set_captions_path(db_id, "captions.vtt", pool).await;
+            }
+            Err(e) => eprintln!("save_recording: write captions of {db_id} failed: {e:?}"),
         }
-        AccessResult::Denied => Redirect::to("/auth/login").into_response(),
     }
+    let _ = Recording::touch(db_id, pool).await;
+    let _ = LiveState::clear_recording(db_id, pool).await;
 }
 
-/// Returns an SVG QR code linking to the presentation at `/{uname}/{pid}`.
-async fn qr_code(Path((uname, pid)): Path<(String, String)>, headers: HeaderMap) -> impl IntoResponse {
-    let proto = headers
-        .get("x-forwarded-proto")
-        .and_then(|v| v.to_str().ok())
-        .unwrap_or("https");
-    let host = headers
-        .get("host")
-        .and_then(|v| v.to_str().ok())
-        .unwrap_or("localhost");
-    let url = format!("{proto}://{host}/{uname}/{pid}");
-    let Ok(code) = QrCode::new(url.as_bytes()) else {
-        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
-    };
-    let image = code
-        .render::<svg::Color<'_>>()
-        .min_dimensions(200, 200)
-        .quiet_zone(false)
-        .build();
-    ([(axum::http::header::CONTENT_TYPE, "image/svg+xml")], image).into_response()
+/// Appends a poll open/close event to the active recording, unless it is paused.
+fn record_poll_event(pres: &mut Presentation, poll_id: i64, opened: bool) {
+    let Some(rec) = pres.recording.as_mut() else { return; };
+    if rec.is_paused {
+        return;
+    }
+    let running_ms = u64::try_from(rec.started_at.elapsed().as_millis()).unwrap_or(u64::MAX);
+    let offset_ms = running_ms.saturating_add(rec.active_ms);
+    rec.polls.push(RecordingPollEvent { offset_ms, poll_id, opened });
+    pres.checkpoint();
 }
 
-async fn presentations(
-    State(tera): State<Tera>,
-    State(db): State<SqlitePool>,
-    auth_session: AuthSession,
-) -> impl IntoResponse {
-    let Some(ref user) = auth_session.user else {
-        return Redirect::to("/auth/login").into_response();
+/// Returns `true` if a poll has a question and 2–10 distinct options, all within size limits.
+fn poll_is_valid(question: &str, options: &[String]) -> bool {
+    const MAX_QUESTION_BYTES: usize = 500;
+    const MAX_OPTION_BYTES: usize = 200;
+    const MAX_OPTIONS: usize = 10;
+    let distinct: HashSet<&String> = options.iter().collect();
+    !question.is_empty()
+        && question.len() <= MAX_QUESTION_BYTES
+        && (2..=MAX_OPTIONS).contains(&options.len())
+        && distinct.len() == options.len()
+        && options.iter().all(|o| !o.is_empty() && o.len() <= MAX_OPTION_BYTES)
+}
+
+/// Handles a poll message from any connection.
+///
+/// Returns the updated poll to broadcast, `None` if the server could not act on it, or the
+/// error to send back if the connection's role may not send it or the poll or vote is
+/// not one the server accepts.
+/// `voted` holds the polls this connection has voted in, so every connection votes once.
+async fn handle_poll_message(
+    msg: PollMessage,
+    pres: &Arc<Mutex<Presentation>>,
+    presentation_id: i64,
+    role: &AccessResult,
+    user_id: Option<i64>,
+    voted: &mut HashSet<i64>,
+    db: &SqlitePool,
+) -> Result<Option<SlideMessage>, ProtocolError> {
+    let pid = presentation_id;
+    let msg_type = match msg {
+        PollMessage::Open { .. } => "poll_open",
+        PollMessage::OpenInline => "poll_open_inline",
+        PollMessage::Close => "poll_close",
+        PollMessage::Vote { .. } => "poll_vote",
     };
-    let Ok(owned) = DbPresentation::get_for_user(user, &db).await else {
-        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    if !protocol::may_send(role, msg_type) {
+        eprintln!("[ws] pid={pid} role={role:?} msg_type={msg_type} reason=unauthorized");
+        return Err(ProtocolError::unauthorized(msg_type));
+    }
+    let (question, options) = match msg {
+        PollMessage::Open { question, options } => {
+            (question.trim().to_string(), options.iter().map(|o| o.trim().to_string()).collect())
+        }
+        PollMessage::OpenInline => {
+            let Ok((content, slide)) = pres.lock().map(|p| (p.content.clone(), p.slide)) else { return Ok(None); };
+            let Some(poll) = slide_poll(&content, slide) else {
+                eprintln!("[ws] pid={pid} role={role:?} msg_type=poll_open_inline reason=no_poll_on_slide");
+                return Err(ProtocolError::new(ErrorCode::Malformed, Some(msg_type), "This slide has no poll."));
+            };
+            poll
+        }
+        PollMessage::Close => return Ok(close_poll(pres, db).await.map(SlideMessage::Poll)),
+        PollMessage::Vote { poll_id, option } => {
+            return vote_poll(pres, (poll_id, option), user_id, voted, db).await.map_err(|reason| {
+                eprintln!("[ws] pid={pid} role={role:?} msg_type=poll_vote reason={reason}");
+                let message = if reason == "repeat_vote" {
+                    "You have already voted in this poll."
+                } else {
+                    "That poll is not open or has no such option."
+                };
+                ProtocolError::new(ErrorCode::Malformed, Some(msg_type), message)
+            });
+        }
     };
-    let Ok(shared) = DbPresentation::get_shared_with_user(user, &db).await else {
-        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    if !poll_is_valid(&question, &options) {
+        eprintln!("[ws] pid={pid} role={role:?} msg_type={msg_type} reason=invalid_poll");
+        return Err(ProtocolError::new(
+            ErrorCode::Malformed,
+            Some(msg_type),
+            "A poll needs a question and 2 to 10 different options, none of them too long.",
+        ));
+    }
+    Ok(open_poll(pres, presentation_id, &question, &options, db).await)
+}
+
+/// Opens a new poll, closing the current one first. The caller has checked it with
+/// [`poll_is_valid`].
+async fn open_poll(
+    pres: &Arc<Mutex<Presentation>>,
+    presentation_id: i64,
+    question: &str,
+    options: &[String],
+    db: &SqlitePool,
+) -> Option<SlideMessage> {
+    close_poll(pres, db).await;
+    let poll = match Poll::create(db, presentation_id, question, options).await {
+        Ok(poll) => poll,
+        Err(e) => {
+            eprintln!("open_poll: pid={presentation_id} create failed: {e:?}");
+            return None;
+        }
     };
-    let mut press_with_recordings = vec![];
-    for pres in owned {
-        let Ok(mut pwr) = Recording::get_by_presentation(pres, user.name.clone(), &db).await else {
-            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
-        };
-        pwr.role = "owner".to_string();
-        press_with_recordings.push(pwr);
-    }
-    for (pres, role) in shared {
-        let owner_name = match User::get_by_id(pres.user_id, &db).await {
-            Ok(Some(owner)) => owner.name,
-            _ => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
-        };
-        let Ok(mut pwr) = Recording::get_by_presentation(pres, owner_name, &db).await else {
-            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
-        };
-        pwr.role = role;
-        press_with_recordings.push(pwr);
-    }
-    let mut ctx = Context::new();
-    ctx.insert("press", &press_with_recordings);
-    tera.render("presentations.html", ctx, auth_session, db)
-        .await
+    let mut p = pres.lock().ok()?;
+    record_poll_event(&mut p, poll.id, true);
+    p.poll = Some(poll.clone());
+    Some(SlideMessage::Poll(poll))
 }
-async fn login(
-    State(tera): State<Tera>,
-    State(db): State<SqlitePool>,
-    auth_session: AuthSession,
-) -> impl IntoResponse {
-    tera.render("login.html", Context::new(), auth_session, db)
-        .await
+
+/// Closes the open poll of a presentation, if any, and returns it.
+async fn close_poll(pres: &Arc<Mutex<Presentation>>, db: &SqlitePool) -> Option<Poll> {
+    let poll_id = {
+        let p = pres.lock().ok()?;
+        p.poll.as_ref().filter(|poll| poll.open)?.id
+    };
+    if let Err(e) = Poll::close(db, poll_id).await {
+        eprintln!("close_poll: poll={poll_id} close failed: {e:?}");
+        return None;
+    }
+    let mut p = pres.lock().ok()?;
+    record_poll_event(&mut p, poll_id, false);
+    let poll = p.poll.as_mut().filter(|poll| poll.id == poll_id)?;
+    poll.open = false;
+    Some(poll.clone())
 }
-async fn new_user_form(
-    State(db): State<SqlitePool>,
-    State(_tera): State<Tera>,
-    auth_session: AuthSession,
-    Form(new_user): Form<AddUserForm>,
-) -> impl IntoResponse {
-    let Some(ref user) = auth_session.user else {
-        return Redirect::to("/auth/login").into_response();
+
+/// Records one vote for `option` of the current poll and returns the new tally, or
+/// why the vote was refused: `repeat_vote` or `closed_or_unknown_option`.
+///
+/// A vote only counts against `voted` once it is recorded, so one sent before the poll
+/// opens or naming no option leaves the real vote to come. Signed-in users vote once
+/// per poll across connections; anonymous viewers are only tracked per connection, so
+/// one who reconnects can vote again.
+async fn vote_poll(
+    pres: &Arc<Mutex<Presentation>>,
+    (poll_id, option): (i64, usize),
+    user_id: Option<i64>,
+    voted: &mut HashSet<i64>,
+    db: &SqlitePool,
+) -> Result<Option<SlideMessage>, &'static str> {
+    if voted.contains(&poll_id) {
+        return Err("repeat_vote");
+    }
+    let Ok(accepts_vote) = pres.lock().map(|p| {
+        p.poll.as_ref().is_some_and(|poll| poll.id == poll_id && poll.open && option < poll.options.len())
+    }) else {
+        return Ok(None);
     };
-    if let Ok(is_admin) = auth_session.backend.has_perm(user, Group::Admin).await
-        && !is_admin
-    {
-        return StatusCode::NOT_FOUND.into_response();
-    }
-    // User display name: 100-char limit — prevents oversized names in DB and UI rendering.
-    // Chosen at the lower end of the ±50% range; aligns with GOV.UK Design System name field guidance.
-    if new_user.name.len() > 100 {
-        return StatusCode::BAD_REQUEST.into_response();
+    if !accepts_vote {
+        return Err("closed_or_unknown_option");
     }
-    if User::new(&db, new_user).await.is_err() {
-        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    match Poll::vote(db, poll_id, option, user_id).await {
+        Ok(true) => {
+            voted.insert(poll_id);
+        }
+        // This user already voted from another connection.
+        Ok(false) => return Err("repeat_vote"),
+        Err(e) => {
+            eprintln!("vote_poll: poll={poll_id} vote failed: {e:?}");
+            return Ok(None);
+        }
     }
-    Redirect::to("/user/presentations").into_response()
+    let Ok(mut p) = pres.lock() else { return Ok(None); };
+    let Some(poll) = p.poll.as_mut().filter(|poll| poll.id == poll_id) else { return Ok(None); };
+    poll.counts[option] += 1;
+    Ok(Some(SlideMessage::Poll(poll.clone())))
 }
-async fn new_user(
-    State(db): State<SqlitePool>,
-    State(tera): State<Tera>,
-    auth_session: AuthSession,
-) -> impl IntoResponse {
-    let Some(ref _user) = auth_session.user else {
-        return Redirect::to("/auth/login").into_response();
+
+/// Quiet period after the last edit before a revision of the deck is saved.
+const REVISION_DEBOUNCE: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Longest edits may keep coming before a revision of the deck is saved regardless.
+const REVISION_MAX_DELAY: std::time::Duration = std::time::Duration::from_mins(1);
+
+/// Saves revisions around an edit about to be applied to the live content.
+///
+/// The first edit after load or after a saved revision first saves the content as it
+/// stands, so the deck from before the edit can always be restored. Every call bumps
+/// `revision_generation` and saves the content after [`REVISION_DEBOUNCE`] if no later
+/// edit has bumped it again, so a burst of keystrokes produces one revision, or if the
+/// burst has gone on for [`REVISION_MAX_DELAY`].
+fn schedule_revision(
+    pres: &Arc<Mutex<Presentation>>,
+    presentation_id: i64,
+    user_id: Option<i64>,
+    role: &AccessResult,
+    pool: &SqlitePool,
+) {
+    let (generation, before) = {
+        let Ok(mut p) = pres.lock() else { return; };
+        p.revision_generation += 1;
+        let before = p.revision_burst.is_none().then(|| p.content.clone());
+        p.revision_burst.get_or_insert_with(tokio::time::Instant::now);
+        (p.revision_generation, before)
     };
-    tera.render("user/add_user.html", Context::new(), auth_session, db)
-        .await
+    let pres = Arc::clone(pres);
+    let pool = pool.clone();
+    let role = role.as_str();
+    tokio::spawn(async move {
+        if let Some(before) = before
+            && let Err(e) = PresentationRevision::create(&pool, presentation_id, user_id, role, before).await
+        {
+            eprintln!("schedule_revision: pid={presentation_id} create failed: {e:?}");
+        }
+        tokio::time::sleep(REVISION_DEBOUNCE).await;
+        let content = {
+            let Ok(mut p) = pres.lock() else { return; };
+            let overdue = p.revision_burst.is_some_and(|start| start.elapsed() >= REVISION_MAX_DELAY);
+            if p.revision_generation != generation && !overdue {
+                return;
+            }
+            p.revision_burst = None;
+            p.content.clone()
+        };
+        if let Err(e) = PresentationRevision::create(&pool, presentation_id, user_id, role, content).await {
+            eprintln!("schedule_revision: pid={presentation_id} create failed: {e:?}");
+        }
+    });
 }
-async fn change_pwd(
-    State(db): State<SqlitePool>,
-    State(tera): State<Tera>,
-    auth_session: AuthSession,
-    Query(params): Query<PwdQuery>,
-) -> impl IntoResponse {
-    let Some(ref _user) = auth_session.user else {
-        return Redirect::to("/auth/login").into_response();
-    };
-    let mut ctx = Context::new();
-    if let Some(ref err) = params.error {
-        ctx.insert("error", err);
-    }
-    tera.render("user/change_pwd.html", ctx, auth_session, db)
-        .await
+
+/// Whether `msg` may change the speaker notes presenters see: an edit or a move to
+/// another slide.
+fn moves_notes(msg: &SlideMessage) -> bool {
+    matches!(
+        msg,
+        SlideMessage::Text(_) | SlideMessage::Patch(_) | SlideMessage::Slide(_) | SlideMessage::Fragment { .. }
+    )
 }
-async fn change_pwd_form(
-    State(db): State<SqlitePool>,
-    auth_session: AuthSession,
-    Form(pwd_form): Form<ChangePasswordForm>,
-) -> impl IntoResponse {
-    let Some(user) = auth_session.user else {
-        return Redirect::to("/auth/login").into_response();
-    };
-    if pwd_form.new != pwd_form.confirm {
-        return Redirect::to("/user/change_pwd?error=Passwords+do+not+match").into_response();
+
+/// The live state a connection needs to show the presentation from scratch.
+///
+/// Sent on connect, and again when a connection fell too far behind the broadcast
+/// channel to replay what it missed. Audience connections get the deck without
+/// speaker notes and no recording state.
+fn state_messages(p: &Presentation, is_presenter: bool, is_audience: bool) -> Vec<SlideMessage> {
+    // Audience connections render the deck client-side, so notes are stripped before sending.
+    let content = if is_audience { strip_speaker_notes(&p.content) } else { p.content.clone() };
+    let mut messages = vec![SlideMessage::Text(content), SlideMessage::Revision(p.text_revision)];
+    if p.lang.is_some() {
+        messages.push(SlideMessage::Language(p.lang.clone()));
+    }
+    for (lang, text) in &p.variants {
+        let text = if is_audience { strip_speaker_notes(text) } else { text.clone() };
+        messages.push(SlideMessage::Variant { lang: lang.clone(), text: Some(text) });
+    }
+    messages.push(SlideMessage::Slide(p.slide));
+    if p.fragment > 0 {
+        messages.push(SlideMessage::Fragment { slide: p.slide, fragment: p.fragment });
+    }
+    if p.highlight.is_some() {
+        messages.push(p.highlight_message());
     }
-    let phash = match PasswordHash::new(&user.password) {
-        Ok(h) => h,
-        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
-    };
-    if Argon2::default()
-        .verify_password(pwd_form.old.as_bytes(), &phash)
-        .is_err()
+    if is_presenter {
+        messages.push(SlideMessage::Notes(slide_notes(&p.content, p.slide)));
+        messages.push(SlideMessage::Presence(p.presence()));
+        if let Some(clock) = &p.clock {
+            messages.push(SlideMessage::Pacing(clock.pacing(p.slide, &planned_times(&p.content))));
+        }
+    }
+    if (is_presenter || is_audience)
+        && let Some(poll) = &p.poll
     {
-        return Redirect::to("/user/change_pwd?error=Current+password+is+incorrect").into_response();
+        messages.push(SlideMessage::Poll(poll.clone()));
     }
-    if user.change_password(pwd_form.new, &db).await.is_err() {
-        return Redirect::to("/user/change_pwd?error=Failed+to+update+password").into_response();
+    // Audience and public connections do not receive recording control state.
+    if !is_audience
+        && let Some(rec) = &p.recording
+    {
+        let elapsed_ms = if rec.is_paused { rec.active_ms } else { rec.elapsed_ms() };
+        messages.push(if rec.is_paused {
+            SlideMessage::RecordingPause { elapsed_ms }
+        } else {
+            SlideMessage::RecordingStart { elapsed_ms }
+        });
     }
-    return Redirect::to("/").into_response();
+    messages
 }
 
-async fn login_process(
-    State(tera): State<Tera>,
-    State(db): State<SqlitePool>,
-    mut auth_session: AuthSession,
-    Form(login): Form<LoginForm>,
-) -> impl IntoResponse {
-    let user = match auth_session.authenticate(login).await {
-        Ok(Some(u)) => u,
-        Ok(None) => {
-            let mut ctx = Context::new();
-            ctx.insert("error", "Invalid username or password.");
-            return tera.render("login.html", ctx, auth_session, db).await;
-        }
-        Err(_) => {
-            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+/// Quiet period after the last edit before the live content is written to the database.
+const PERSIST_DEBOUNCE: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Longest an edit waits for its write while typing continues without a pause.
+const PERSIST_MAX_LATENCY: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// A request to a presentation's [`persistence_writer`].
+enum PersistCommand {
+    /// The live content changed; saved once edits pause.
+    Edit(String),
+    /// The slide shown or the recording timeline changed; saved like an edit.
+    Checkpoint(LiveState),
+    /// Save any pending content now, then acknowledge.
+    Flush(tokio::sync::oneshot::Sender<()>),
+}
+
+/// Handle to the one task that saves a live presentation's content.
+///
+/// Edits from every connection go through the same writer, so the database sees one
+/// write per pause in typing however many clients are watching.
+#[derive(Clone)]
+struct ContentWriter(tokio::sync::mpsc::UnboundedSender<PersistCommand>);
+
+impl ContentWriter {
+    /// Spawns the writer for presentation `id`, served by process `node`. It saves what
+    /// is pending and exits once the last handle is dropped.
+    fn spawn(id: i64, node: u64, pool: SqlitePool) -> Self {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(persistence_writer(id, node, rx, pool));
+        ContentWriter(tx)
+    }
+
+    /// Queues `content` to be saved.
+    fn edit(&self, content: String) {
+        let _ = self.0.send(PersistCommand::Edit(content));
+    }
+
+    /// Queues the live slide and recording timeline to be saved, to restore after a restart.
+    fn checkpoint(&self, state: LiveState) {
+        let _ = self.0.send(PersistCommand::Checkpoint(state));
+    }
+
+    /// Saves pending content now and waits until it is written.
+    async fn flush(&self) {
+        let (ack, done) = tokio::sync::oneshot::channel();
+        if self.0.send(PersistCommand::Flush(ack)).is_ok() {
+            let _ = done.await;
         }
-    };
-    if auth_session.login(&user).await.is_err() {
-        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
     }
-    Redirect::to("/").into_response()
 }
-async fn logout(mut auth_session: AuthSession) -> impl IntoResponse {
-    match auth_session.logout().await {
-        Ok(_) => Redirect::to("/").into_response(),
-        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+
+/// Coalesces edits and checkpoints of presentation `id`, writing after
+/// [`PERSIST_DEBOUNCE`] of quiet or at most [`PERSIST_MAX_LATENCY`] after the first
+/// unsaved change.
+async fn persistence_writer(
+    id: i64,
+    node: u64,
+    mut commands: tokio::sync::mpsc::UnboundedReceiver<PersistCommand>,
+    pool: SqlitePool,
+) {
+    let mut pending = Pending::default();
+    let mut deadline = tokio::time::Instant::now();
+    loop {
+        let command = if pending.is_empty() {
+            commands.recv().await
+        } else {
+            let wait = PERSIST_DEBOUNCE.min(deadline.saturating_duration_since(tokio::time::Instant::now()));
+            let Ok(command) = tokio::time::timeout(wait, commands.recv()).await else {
+                pending.write(id, node, &pool).await;
+                continue;
+            };
+            command
+        };
+        if pending.is_empty() {
+            deadline = tokio::time::Instant::now() + PERSIST_MAX_LATENCY;
+        }
+        match command {
+            Some(PersistCommand::Edit(content)) => pending.content = Some(content),
+            Some(PersistCommand::Checkpoint(state)) => pending.live = Some(state),
+            Some(PersistCommand::Flush(ack)) => {
+                pending.write(id, node, &pool).await;
+                let _ = ack.send(());
+            }
+            None => {
+                pending.write(id, node, &pool).await;
+                return;
+            }
+        }
     }
 }
 
-async fn recording(
-    State(tera): State<Tera>,
-    State(db): State<SqlitePool>,
-    auth_session: AuthSession,
-    Path((uname, pid, rid)): Path<(String, i64, i64)>,
-) -> impl IntoResponse {
-    let Ok(Some(pres_user)) = User::get_by_name(uname, &db).await else {
-        return StatusCode::NOT_FOUND.into_response();
-    };
-    let Ok(Some(pres)) = DbPresentation::get_by_id(pid, &db).await else {
-        return StatusCode::NOT_FOUND.into_response();
-    };
-    if pres.user_id != pres_user.id {
-        return StatusCode::NOT_FOUND.into_response();
-    }
-    let Ok(Some(rec)) = Recording::get_by_id(rid, &db).await else {
-        return StatusCode::NOT_FOUND.into_response();
-    };
-    if rec.presentation_id != pid {
-        return StatusCode::NOT_FOUND.into_response();
+/// What a [`persistence_writer`] has yet to save; only the latest of each is kept.
+#[derive(Default)]
+struct Pending {
+    content: Option<String>,
+    live: Option<LiveState>,
+}
+
+impl Pending {
+    fn is_empty(&self) -> bool {
+        self.content.is_none() && self.live.is_none()
     }
-    let access = match check_access(&db, auth_session.user.as_ref(), pid, Some(rid)).await {
-        Ok(a) => a,
-        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
-    };
-    if matches!(access, AccessResult::Denied) {
-        return StatusCode::FORBIDDEN.into_response();
+
+    /// Saves what is pending for presentation `id`, served by process `node`.
+    async fn write(&mut self, id: i64, node: u64, pool: &SqlitePool) {
+        if let Some(content) = self.content.take()
+            && let Err(e) = DbPresentation::update_content(id, content, pool).await
+        {
+            eprintln!("persistence_writer: pid={id} update_content failed: {e:?}");
+        }
+        if let Some(live) = self.live.take()
+            && let Err(e) = live.save(id, node, pool).await
+        {
+            eprintln!("persistence_writer: pid={id} live state failed: {e:?}");
+        }
     }
-    let mut ctx = Context::new();
-    ctx.insert("recording", &rec);
-    ctx.insert("pres", &pres);
-    ctx.insert("pres_user", &pres_user);
-    tera.render("recording.html", ctx, auth_session, db)
-        .await
-        .into_response()
 }
 
-async fn edit_recording(
-    State(tera): State<Tera>,
-    State(db): State<SqlitePool>,
-    auth_session: AuthSession,
-    Path((uname, pid, rid)): Path<(String, i64, i64)>,
-) -> impl IntoResponse {
-    if auth_session.user.is_none() {
-        return Redirect::to("/auth/login").into_response();
+/// Marks as interrupted, every [`LIVE_LEASE_SECS`], the recordings whose process stopped
+/// renewing its lease on them without a restart to notice.
+async fn sweep_interrupted(db_pool: SqlitePool) {
+    let period = std::time::Duration::from_secs(LIVE_LEASE_SECS.unsigned_abs());
+    let mut sweep = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+    loop {
+        sweep.tick().await;
+        match Recording::mark_interrupted(None, &db_pool).await {
+            Ok(0) => {}
+            Ok(n) => eprintln!("{n} recording(s) were interrupted by another process stopping"),
+            Err(e) => eprintln!("mark_interrupted failed: {e:?}"),
+        }
     }
-    let Ok(Some(pres_user)) = User::get_by_name(uname, &db).await else {
-        return StatusCode::NOT_FOUND.into_response();
-    };
-    let Ok(Some(pres)) = DbPresentation::get_by_id(pid, &db).await else {
-        return StatusCode::NOT_FOUND.into_response();
+}
+
+/// Saves the pending edits of every live presentation, for shutdown.
+async fn flush_all(state: &AppState) {
+    let writers: Vec<ContentWriter> = match state.slides.lock() {
+        Ok(slides) => slides
+            .values()
+            .filter_map(|pres| pres.lock().ok().and_then(|p| p.writer.clone()))
+            .collect(),
+        Err(_) => return,
     };
-    if pres.user_id != pres_user.id {
-        return StatusCode::NOT_FOUND.into_response();
+    for writer in writers {
+        writer.flush().await;
     }
-    let Ok(Some(rec)) = Recording::get_by_id(rid, &db).await else {
-        return StatusCode::NOT_FOUND.into_response();
-    };
-    if rec.presentation_id != pid {
-        return StatusCode::NOT_FOUND.into_response();
-    }
-    let access = match check_access(&db, auth_session.user.as_ref(), pid, Some(rid)).await {
-        Ok(a) => a,
-        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
-    };
-    if !matches!(access, AccessResult::Owner | AccessResult::Editor | AccessResult::Controller) {
-        return StatusCode::FORBIDDEN.into_response();
-    }
-    let mut ctx = Context::new();
-    ctx.insert("recording", &rec);
-    ctx.insert("pres", &pres);
-    ctx.insert("pres_user", &pres_user);
-    tera.render("edit_recording.html", ctx, auth_session, db)
-        .await
-        .into_response()
 }
 
-async fn demo(State(db): State<SqlitePool>) -> impl IntoResponse {
-    let Ok(Some(user)) = User::get_by_name("admin".to_string(), &db).await else {
-        return Redirect::to("/").into_response();
-    };
-    let Ok(presses) = DbPresentation::get_for_user(&user, &db).await else {
-        return Redirect::to("/").into_response();
-    };
-    match presses.into_iter().next() {
-        Some(pres) => Redirect::to(&format!("/{}/{}", user.name, pres.id)).into_response(),
-        None => Redirect::to("/").into_response(),
-    }
+/// One WebSocket connection to a live presentation: who is on the other end, and what
+/// they may see and do.
+struct Connection {
+    pid: String,
+    pid_i64: i64,
+    role: AccessResult,
+    user_id: Option<i64>,
+    viewer: Viewer,
+    /// Name shown on the editor's cursor; empty for anyone who may not edit.
+    editor_name: String,
+    pres: Arc<Mutex<Presentation>>,
+    diagnostics: Arc<ConnectionStats>,
 }
 
-async fn index(
-    State(tera): State<Tera>,
-    auth_session: AuthSession,
-    State(db): State<SqlitePool>,
-) -> impl IntoResponse {
-    tera.render("index.html", Context::new(), auth_session, db)
-        .await
-}
+/// Handles what the client sends until it disconnects, or until a message it sent means
+/// it must be disconnected.
+async fn receive_from_client(
+    mut sock_recv: SplitStream<WebSocket>,
+    conn: &Connection,
+    mut tx: Sender<SlideMessage>,
+    mut state: AppState,
+    link: &Link,
+    direct: &tokio::sync::mpsc::UnboundedSender<SlideMessage>,
+) {
+    let (pid, role, pres, editor_name) = (conn.pid.as_str(), &conn.role, &conn.pres, conn.editor_name.as_str());
+    let (pid_i64, user_id, connection_id) = (conn.pid_i64, conn.user_id, conn.viewer.connection_id);
+    let (is_presenter, is_editor) = (conn.viewer.is_presenter, conn.viewer.is_editor);
+    let mut last_asked: Option<std::time::Instant> = None;
+    let mut voted: HashSet<i64> = HashSet::new();
+    while let Some(msg) = sock_recv.next().await {
+        // Pre-extract text for recording dispatch and snapshot capture
+        let text_val: Option<String> = msg
+            .as_ref()
+            .ok()
+            .and_then(|m| m.to_text().ok())
+            .map(String::from);
+
+        let is_recording_msg = text_val
+            .as_deref()
+            .and_then(|t| serde_json::from_str::<serde_json::Value>(t).ok())
+            .and_then(|v| v["type"].as_str().map(|s| s.starts_with("recording_")))
+            .unwrap_or(false);
+
+        if is_recording_msg {
+            let raw_type = text_val.as_deref().and_then(message_type).unwrap_or_default();
+            match text_val.as_deref().and_then(|t| serde_json::from_str::<RecordingMessage>(t).ok()) {
+                _ if !is_presenter => {
+                    eprintln!("[ws] pid={pid} role={role:?} msg_type={raw_type} reason=unauthorized_role_for_recording");
+                    let _ = direct.send(SlideMessage::Error(ProtocolError::unauthorized(&raw_type)));
+                }
+                Some(rec_msg) => {
+                    if let Some(broadcast_msg) = control_recording(rec_msg, pres, pid_i64, link).await {
+                        let _ = tx.send(broadcast_msg);
+                    }
+                }
+                None => {
+                    eprintln!("[ws] pid={pid} role={role:?} msg_type={raw_type} reason=malformed_recording_message");
+                    let error = ProtocolError::new(ErrorCode::Malformed, Some(&raw_type), "There is no such recording control.");
+                    let _ = direct.send(SlideMessage::Error(error));
+                }
+            }
+            continue;
+        }
 
-async fn help(
-    State(tera): State<Tera>,
-    auth_session: AuthSession,
-    State(db): State<SqlitePool>,
-) -> impl IntoResponse {
-    tera.render("help.html", Context::new(), auth_session, db)
-        .await
-}
+        if let Some(question_msg) = text_val
+            .as_deref()
+            .and_then(|t| serde_json::from_str::<QuestionMessage>(t).ok())
+        {
+            match handle_question_message(
+                question_msg, pid_i64, role, user_id, &mut last_asked, &state.db_pool, direct,
+            ).await {
+                Ok(msgs) => {
+                    for m in msgs {
+                        let _ = tx.send(m);
+                    }
+                }
+                Err(_) => return,
+            }
+            continue;
+        }
 
-fn html_escape(s: &str) -> String {
-    s.replace('&', "&amp;")
-        .replace('<', "&lt;")
-        .replace('>', "&gt;")
-        .replace('"', "&quot;")
-}
+        if let Some(poll_msg) = text_val
+            .as_deref()
+            .and_then(|t| serde_json::from_str::<PollMessage>(t).ok())
+        {
+            match handle_poll_message(
+                poll_msg, pres, pid_i64, role, user_id, &mut voted, &state.db_pool,
+            ).await {
+                Ok(m) => m.into_iter().for_each(|m| { let _ = tx.send(m); }),
+                Err(error) => { let _ = direct.send(SlideMessage::Error(error)); }
+            }
+            continue;
+        }
 
-fn strip_leading_h1(content: &str) -> &str {
-    let t = content.trim_start();
-    if t.to_ascii_lowercase().starts_with("<h1") {
-        if let Some(end) = t.to_ascii_lowercase().find("</h1>") {
-            return t[end + 5..].trim_start();
+        // Pre-parse slide index for snapshot capture (before handle_socket consumes msg)
+        let parsed: Option<SlideMessage> = text_val
+            .as_deref()
+            .and_then(|t| serde_json::from_str::<SlideMessage>(t).ok());
+        if let Some(SlideMessage::Patch(patch)) = &parsed {
+            if is_editor {
+                schedule_revision(pres, pid_i64, user_id, role, &state.db_pool);
+            }
+            if handle_patch(pres, patch, role, pid, (connection_id, editor_name), direct).is_err() {
+                return;
+            }
+            continue;
+        }
+        if let Some(SlideMessage::Cursor(cursor)) = parsed {
+            if is_editor {
+                let _ = tx.send(SlideMessage::Cursor(Cursor { id: connection_id, user: editor_name.to_string(), ..cursor }));
+            } else {
+                eprintln!("[ws] pid={pid} role={role:?} msg_type=cursor reason=unauthorized");
+                let _ = direct.send(SlideMessage::Error(ProtocolError::unauthorized("cursor")));
+            }
+            continue;
+        }
+        // Only owners and editors may change the text; handle_socket drops anyone else's.
+        if is_editor && matches!(parsed, Some(SlideMessage::Text(_))) {
+            schedule_revision(pres, pid_i64, user_id, role, &state.db_pool);
+        }
+
+        if handle_socket(msg, pid, &mut tx, &mut state, role, direct).is_err() {
+            return;
         }
     }
-    content
 }
 
-fn format_vtt_time(seconds: f64) -> String {
-    let ms = ((seconds % 1.0) * 1000.0).round() as u64;
-    let total_s = seconds as u64;
-    let s = total_s % 60;
-    let m = (total_s / 60) % 60;
-    let h = total_s / 3600;
-    format!("{h:02}:{m:02}:{s:02}.{ms:03}")
+/// Sends the client its direct replies and the broadcasts it may see, newer than
+/// `last_seq`, until it goes away.
+async fn send_to_client(
+    sock_send: &mut SplitSink<WebSocket, Message>,
+    conn: &Connection,
+    mut rx: Receiver<(u64, SlideMessage)>,
+    direct_rx: &mut tokio::sync::mpsc::UnboundedReceiver<SlideMessage>,
+    mut last_seq: u64,
+    db: &SqlitePool,
+) {
+    let (pid, role, pres, viewer) = (conn.pid.as_str(), &conn.role, &conn.pres, conn.viewer);
+    let (pid_i64, connection_id) = (conn.pid_i64, viewer.connection_id);
+    loop {
+        // Direct replies first: a stale-patch snapshot must reach the client before
+        // the patches that build on it. Direct replies carry no sequence number.
+        let received = tokio::select! {
+            biased;
+            Some(msg) = direct_rx.recv() => Ok((None, msg)),
+            received = rx.recv() => received.map(|(seq, msg)| (Some(seq), msg)),
+        };
+        let (seq, msg, missed) = match received {
+            Ok((Some(seq), msg)) => (Some(seq), Some(msg), seq.saturating_sub(last_seq + 1)),
+            Ok((None, msg)) => (None, Some(msg), 0),
+            Err(RecvError::Lagged(skipped)) => (None, None, skipped),
+            Err(RecvError::Closed) => return,
+        };
+        if missed > 0 {
+            // Too slow to keep up, or the sequencer was: the missed messages are gone,
+            // so resend the current state in full instead of dropping the connection.
+            let lag_events = conn.diagnostics.record_lag(missed);
+            eprintln!(
+                "[ws] pid={pid} role={role:?} conn={connection_id} reason=lagged skipped={missed} lag_events={lag_events}"
+            );
+            let name = DbPresentation::get_by_id(pid_i64, db)
+                .await
+                .ok()
+                .flatten()
+                .map(|p| p.name);
+            let Ok(mut snapshot) = pres.lock().map(|p| {
+                let mut snapshot = state_messages(&p, viewer.is_presenter, viewer.is_audience);
+                if let Ok(replay) = p.replay.lock() {
+                    // The state already reflects every broadcast stamped so far.
+                    last_seq = replay.seq;
+                    snapshot.push(SlideMessage::Resume { stream: replay.stream, seq: replay.seq });
+                }
+                snapshot
+            }) else {
+                return;
+            };
+            snapshot.extend(name.map(SlideMessage::Name));
+            for msg in snapshot {
+                if sock_send.send(Message::from(json(&msg))).await.is_err() {
+                    return;
+                }
+            }
+            continue;
+        }
+        let Some(msg) = msg else { continue; };
+        if let Some(seq) = seq {
+            // Already part of the snapshot this connection was last sent.
+            if seq <= last_seq {
+                continue;
+            }
+            last_seq = seq;
+        }
+        let Some(view) = viewer.view(&msg) else { continue; };
+        let text = match seq {
+            Some(seq) => sequenced_json(&view, seq),
+            None => json(&view),
+        };
+        if sock_send.send(Message::from(text)).await.is_err() {
+            return;
+        }
+        // Presenters get the current slide's notes whenever the deck or slide changes.
+        if viewer.is_presenter && moves_notes(&msg) {
+            let notes = pres
+                .lock()
+                .map(|p| slide_notes(&p.content, p.slide))
+                .unwrap_or_default();
+            if sock_send.send(Message::from(json(&SlideMessage::Notes(notes)))).await.is_err() {
+                return;
+            }
+        }
+    }
 }
 
-async fn slides_vtt(
-    State(db): State<SqlitePool>,
-    auth_session: AuthSession,
-    Path((uname, pid, rid)): Path<(String, i64, i64)>,
-) -> impl IntoResponse {
-    let Ok(Some(pres_user)) = User::get_by_name(uname, &db).await else {
-        return StatusCode::NOT_FOUND.into_response();
-    };
-    let Ok(Some(pres)) = DbPresentation::get_by_id(pid, &db).await else {
-        return StatusCode::NOT_FOUND.into_response();
+async fn ws_handle(
+    mut socket: WebSocket,
+    pid: String,
+    mut state: AppState,
+    role: AccessResult,
+    user_id: Option<i64>,
+    resume: ResumeQuery,
+) {
+    let pres = match add_client_handler_channel(pid.clone(), &mut state).await {
+        Ok(p) => p,
+        Err(_) => {
+            // K/V store mutex is poisoned — close the connection rather than crash.
+            let _ = socket.close().await;
+            return;
+        }
     };
-    if pres.user_id != pres_user.id {
-        return StatusCode::NOT_FOUND.into_response();
+    let is_presenter = matches!(role, AccessResult::Owner | AccessResult::Editor | AccessResult::Controller);
+    // Only those who may change the text share cursors and have their edits named.
+    let is_editor = matches!(role, AccessResult::Owner | AccessResult::Editor);
+    // Audience and public viewers must not receive recording control messages; captioners
+    // follow along as the audience does.
+    let is_audience = matches!(role, AccessResult::Audience | AccessResult::PublicOk | AccessResult::Captioner);
+
+    // Increment presenter_count for authorized roles; the broker counts them across processes.
+    let link = Link::new(&state, &pid);
+    if is_presenter {
+        let count = pres.lock().map_or(0, |mut p| { p.presenter_count += 1; p.presenter_count });
+        if let Err(e) = link.count_presenters(count).await {
+            eprintln!("[pubsub] pid={pid} reason=count_failed error={e}");
+        }
     }
-    let Ok(Some(rec)) = Recording::get_by_id(rid, &db).await else {
-        return StatusCode::NOT_FOUND.into_response();
+
+    // A reconnecting client that missed little gets just the broadcasts it missed;
+    // anyone else gets the current state in full.
+    let (tx, rx, greeting, last_seq, resumed, viewer) = {
+        let Ok(mut p) = pres.lock() else { return; };
+        let viewer = Viewer { connection_id: p.next_connection_id, is_presenter, is_audience, is_editor };
+        p.next_connection_id += 1;
+        let Ok(replay) = p.replay.lock() else { return; };
+        let missed = resume.stream.zip(resume.seq).and_then(|(stream, seq)| replay.since(stream, seq));
+        let resumed = missed.is_some();
+        let mut greeting: Vec<String> = match missed {
+            Some(missed) => {
+                let mut greeting: Vec<String> = missed
+                    .iter()
+                    .filter_map(|(seq, msg)| viewer.view(msg).map(|m| sequenced_json(&m, *seq)))
+                    .collect();
+                let changed = missed
+                    .iter()
+                    .any(|(_, m)| moves_notes(m));
+                if is_presenter && changed {
+                    greeting.push(json(&SlideMessage::Notes(slide_notes(&p.content, p.slide))));
+                }
+                greeting
+            }
+            None => state_messages(&p, is_presenter, is_audience).iter().map(json).collect(),
+        };
+        greeting.push(json(&SlideMessage::Resume { stream: replay.stream, seq: replay.seq }));
+        // The hello comes before anything else the connection is sent.
+        greeting.insert(0, json(&SlideMessage::Hello(Hello::new(&role))));
+        (p.channel.0.clone(), replay.channel.subscribe(), greeting, replay.seq, resumed, viewer)
     };
-    if rec.presentation_id != pid {
-        return StatusCode::NOT_FOUND.into_response();
+    let connection_id = viewer.connection_id;
+
+    for text in greeting {
+        if socket.send(Message::from(text)).await.is_err() { return; }
     }
-    let access = match check_access(&db, auth_session.user.as_ref(), pid, Some(rid)).await {
-        Ok(a) => a,
-        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
-    };
-    if matches!(access, AccessResult::Denied) {
-        return StatusCode::FORBIDDEN.into_response();
+    let pid_i64 = pid.parse::<i64>().unwrap_or(-1);
+    // Presenters get the moderation queue; presenters and audience get the pinned questions.
+    // A resumed connection already has them, kept current by the broadcasts it replayed.
+    if is_presenter && !resumed {
+        let queue = Question::get_for_presentation(&state.db_pool, pid_i64).await.unwrap_or_default();
+        let queue = serde_json::to_string(&SlideMessage::Questions(queue))
+            .expect("SlideMessage is always serializable");
+        if socket.send(Message::from(queue)).await.is_err() { return; }
     }
-    let Ok(slides) = RecordingSlide::get_by_recording(rid, &db).await else {
-        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
-    };
-    let mut vtt = String::from("WEBVTT\n\n");
-    for (i, slide) in slides.iter().enumerate() {
-        let start = format_vtt_time(slide.start_seconds);
-        let end = slides
-            .get(i + 1)
-            .map(|s| format_vtt_time(s.start_seconds))
-            .unwrap_or_else(|| "99:59:59.999".to_string());
-        let json = serde_json::json!({
-            "id": slide.id,
-            "title": slide.title,
-            "content": slide.content,
-        });
-        vtt.push_str(&format!("{start} --> {end}\n{json}\n\n"));
+    if (is_presenter || is_audience) && !resumed {
+        let pinned = Question::get_pinned(&state.db_pool, pid_i64).await.unwrap_or_default();
+        let pinned = serde_json::to_string(&SlideMessage::PinnedQuestions(pinned))
+            .expect("SlideMessage is always serializable");
+        if socket.send(Message::from(pinned)).await.is_err() { return; }
     }
-    (
-        [(axum::http::header::CONTENT_TYPE, "text/vtt; charset=utf-8")],
-        vtt,
-    )
-        .into_response()
-}
 
-async fn slides_html(
-    State(db): State<SqlitePool>,
-    auth_session: AuthSession,
-    Path((uname, pid, rid)): Path<(String, i64, i64)>,
-) -> impl IntoResponse {
-    let Ok(Some(pres_user)) = User::get_by_name(uname, &db).await else {
-        return StatusCode::NOT_FOUND.into_response();
+    // Editors are named on their edits; signed-in viewers are named to the presenters.
+    let user_name = match user_id {
+        Some(uid) if is_editor || is_audience => {
+            User::get_by_id(uid, &state.db_pool).await.ok().flatten().map(|u| u.name)
+        }
+        _ => None,
     };
-    let Ok(Some(pres)) = DbPresentation::get_by_id(pid, &db).await else {
-        return StatusCode::NOT_FOUND.into_response();
+    let editor_name = if is_editor { user_name.clone().unwrap_or_default() } else { String::new() };
+
+    let diagnostics = {
+        let Ok(mut p) = pres.lock() else { return; };
+        let diagnostics = Arc::new(ConnectionStats::default());
+        p.connections.insert(connection_id, Arc::clone(&diagnostics));
+        p.attendees.insert(connection_id, (role.clone(), user_name.filter(|_| is_audience)));
+        p.idle_since = None;
+        diagnostics
     };
-    if pres.user_id != pres_user.id {
-        return StatusCode::NOT_FOUND.into_response();
-    }
-    let Ok(Some(rec)) = Recording::get_by_id(rid, &db).await else {
-        return StatusCode::NOT_FOUND.into_response();
+    announce_presence(&pres);
+
+    let conn = Connection {
+        pid: pid.clone(),
+        pid_i64,
+        role: role.clone(),
+        user_id,
+        viewer,
+        editor_name,
+        pres: Arc::clone(&pres),
+        diagnostics: Arc::clone(&diagnostics),
     };
-    if rec.presentation_id != pid {
-        return StatusCode::NOT_FOUND.into_response();
+    let (mut sock_send, sock_recv) = socket.split();
+    // Replies meant for this connection alone, such as the snapshot for a stale patch.
+    let (direct_tx, mut direct_rx) = tokio::sync::mpsc::unbounded_channel();
+    let socket_handler = receive_from_client(sock_recv, &conn, tx, state.clone(), &link, &direct_tx);
+    let channel_handler = send_to_client(&mut sock_send, &conn, rx, &mut direct_rx, last_seq, &state.db_pool);
+
+    let () = or(socket_handler, channel_handler).await;
+    // Replies not sent yet, such as the error a connection is closed for, go out before it closes.
+    while let Ok(msg) = direct_rx.try_recv() {
+        let _ = sock_send.send(Message::from(json(&msg))).await;
     }
-    let access = match check_access(&db, auth_session.user.as_ref(), pid, Some(rid)).await {
-        Ok(a) => a,
-        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
-    };
-    if matches!(access, AccessResult::Denied) {
-        return StatusCode::FORBIDDEN.into_response();
+
+    // Auto-stop recording if this was the last presenter on any process
+    if is_presenter {
+        let local = match pres.lock() {
+            Ok(mut p) => {
+                p.presenter_count = p.presenter_count.saturating_sub(1);
+                Some(p.presenter_count)
+            }
+            Err(_) => None, // mutex poisoned; skip recording cleanup
+        };
+        let remaining = match (local, link.count_presenters(local.unwrap_or(0)).await) {
+            (Some(_), Ok(remaining)) => remaining,
+            (Some(local), Err(e)) => {
+                eprintln!("[pubsub] pid={pid} reason=count_failed error={e}");
+                i64::try_from(local).unwrap_or(i64::MAX)
+            }
+            (None, _) => 1,
+        };
+        if remaining <= 0 {
+            if pres.lock().is_ok_and(|p| p.recording.is_some()) {
+                handle_recording_message(
+                    RecordingMessage::RecordingStop, &pres, pid_i64, &state.db_pool,
+                ).await;
+                // No broadcast: no presenters remain
+            }
+            // The recording may be held by another process.
+            link.publish(RemoteEvent::PresentersGone).await;
+        }
     }
-    let Ok(slides) = RecordingSlide::get_by_recording(rid, &db).await else {
-        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+
+    let lag_events = diagnostics.lag_events.load(Ordering::Relaxed);
+    if lag_events > 0 {
+        let skipped = diagnostics.skipped_messages.load(Ordering::Relaxed);
+        eprintln!("[ws] pid={pid} role={role:?} conn={connection_id} closed lag_events={lag_events} skipped={skipped}");
+    }
+    // The last connection out saves any edits still waiting for the debounce.
+    let writer = match pres.lock() {
+        Ok(mut p) => {
+            p.connections.remove(&connection_id);
+            p.attendees.remove(&connection_id);
+            if is_editor {
+                let _ = p.channel.0.send(SlideMessage::CursorGone(connection_id));
+            }
+            if p.connections.is_empty() {
+                p.idle_since = Some(std::time::Instant::now());
+                // The session's time runs until now, not until the presentation is evicted.
+                p.checkpoint();
+                p.writer.clone()
+            } else {
+                None
+            }
+        }
+        Err(_) => None,
     };
-    let rec_name = html_escape(&rec.name);
-    let pres_name = html_escape(&pres.name);
-    let mut html = format!(
-        "<!DOCTYPE html><html lang=\"en\"><head><meta charset=\"UTF-8\"><title>{rec_name} - Slides</title></head><body>\n<h1>{pres_name}</h1>\n"
-    );
-    for slide in &slides {
-        let content = strip_leading_h1(&slide.content);
-        html.push_str(&format!("<section>\n{content}\n</section>\n"));
+    if let Some(writer) = writer {
+        writer.flush().await;
     }
-    html.push_str("</body></html>");
-    (
-        [(axum::http::header::CONTENT_TYPE, "text/html; charset=utf-8")],
-        html,
-    )
-        .into_response()
+
+    drop(pres);
 }
 
-async fn update_slide_time(
+async fn join(
+    State(tera): State<Tera>,
     State(db): State<SqlitePool>,
     auth_session: AuthSession,
-    Path((rid, sid)): Path<(i64, i64)>,
-    body: String,
 ) -> impl IntoResponse {
-    let Some(user) = auth_session.user else {
-        return StatusCode::UNAUTHORIZED.into_response();
-    };
-    let owner_count = sqlx::query_scalar::<_, i64>(
-        "SELECT COUNT(*) FROM recording
-         JOIN presentation ON presentation.id = recording.presentation_id
-         WHERE recording.id = ? AND presentation.user_id = ?;",
-    )
-    .bind(rid)
-    .bind(user.id)
-    .fetch_one(&db)
-    .await;
-    if !matches!(owner_count, Ok(1)) {
-        return StatusCode::FORBIDDEN.into_response();
+    tera.render("join.html", Context::new(), auth_session, db)
+        .await
+}
+/// Paragraph that starts a slide's speaker notes (remark.js convention).
+///
+/// Everything after it up to the next `## ` heading is presenter-only and is
+/// never rendered into audience output.
+const NOTES_MARKER: &str = "???";
+
+/// A deck parsed into audience-visible events and per-slide speaker notes.
+#[derive(Clone)]
+struct Deck<'a> {
+    /// Events with raw HTML and speaker notes removed.
+    events: Vec<Event<'a>>,
+    /// Index into `events` of each slide's `<h2>` start event.
+    slide_starts: Vec<usize>,
+    /// Speaker notes of each slide as raw Markdown (empty when the slide has none).
+    notes: Vec<String>,
+    /// Byte ranges of the source holding speaker notes, marker included.
+    notes_ranges: Vec<Range<usize>>,
+    /// Fragment markers of each slide, with the index into `events` they stood before.
+    fragments: Vec<Vec<(usize, FragmentMarker)>>,
+}
+
+impl<'a> Deck<'a> {
+    /// The events of slide `idx`, if there is one.
+    fn slide(&self, idx: usize) -> Option<&[Event<'a>]> {
+        let start = *self.slide_starts.get(idx)?;
+        let end = self.slide_starts.get(idx + 1).copied().unwrap_or(self.events.len());
+        Some(&self.events[start..end])
     }
-    let Ok(start_seconds) = body.trim().parse::<f64>() else {
-        return StatusCode::BAD_REQUEST.into_response();
-    };
-    let slide_count = sqlx::query_scalar::<_, i64>(
-        "SELECT COUNT(*) FROM recording_slide WHERE id = ? AND recording_id = ?;",
-    )
-    .bind(sid)
-    .bind(rid)
-    .fetch_one(&db)
-    .await;
-    if !matches!(slide_count, Ok(1)) {
-        return StatusCode::NOT_FOUND.into_response();
+
+    /// The events of slide `idx` shown once its first `fragment` steps are revealed, and
+    /// the number of steps it has.
+    ///
+    /// Each `--` marker and each item of a list after `++` starts a step; markers with
+    /// nothing after them are ignored.
+    fn reveal(&self, idx: usize, fragment: u32) -> (Vec<Event<'a>>, u32) {
+        let Some(events) = self.slide(idx) else { return (Vec::new(), 0); };
+        let start = self.slide_starts[idx];
+        let markers = &self.fragments[idx];
+        let mut shown = Vec::with_capacity(events.len());
+        let (mut step, mut depth) = (0, 0usize);
+        // Step of the block, or list item, the current event belongs to.
+        let mut block_step = 0;
+        let (mut items_next, mut stepped_list) = (false, None);
+        for (i, event) in events.iter().enumerate() {
+            for (_, marker) in markers.iter().filter(|(at, _)| *at == start + i) {
+                match marker {
+                    FragmentMarker::Rest => step += 1,
+                    FragmentMarker::Items => items_next = true,
+                }
+            }
+            if depth == 0 {
+                // A list shown item by item appears with its first item.
+                stepped_list = (items_next && matches!(event, Event::Start(Tag::List(_)))).then_some(step + 1);
+                block_step = stepped_list.unwrap_or(step);
+                items_next = false;
+            } else if let Some(list_step) = stepped_list
+                && depth == 1
+            {
+                match event {
+                    Event::Start(Tag::Item) => {
+                        step += 1;
+                        block_step = step;
+                    }
+                    Event::End(TagEnd::List(_)) => block_step = list_step,
+                    _ => {}
+                }
+            }
+            if block_step <= fragment {
+                shown.push(event.clone());
+            }
+            match event {
+                Event::Start(_) => depth += 1,
+                Event::End(_) => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+        (shown, step)
     }
-    match RecordingSlide::update_start_seconds(sid, start_seconds, &db).await {
-        Ok(()) => StatusCode::OK.into_response(),
-        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+
+    /// The range of `events` holding block `block` of slide `idx`, start and end included.
+    ///
+    /// Blocks are the headings, paragraphs, list items, table rows, table cells and code
+    /// blocks of the slide, counted in the order they start. Clients do not count them; they
+    /// find a block by its [`BlockTarget`].
+    fn block(&self, idx: usize, block: u32) -> Option<RangeInclusive<usize>> {
+        self.blocks(idx).into_iter().nth(usize::try_from(block).ok()?)
+    }
+
+    /// The ranges of `events` holding each block of slide `idx`, in order; see [`Deck::block`].
+    fn blocks(&self, idx: usize) -> Vec<RangeInclusive<usize>> {
+        let Some(events) = self.slide(idx) else { return Vec::new(); };
+        let start = self.slide_starts[idx];
+        let mut open = Vec::new();
+        let mut blocks = Vec::new();
+        let mut depth = 0usize;
+        for (i, event) in events.iter().enumerate() {
+            match event {
+                Event::Start(tag) => {
+                    if matches!(
+                        tag,
+                        Tag::Heading { .. }
+                            | Tag::Paragraph
+                            | Tag::Item
+                            | Tag::TableHead
+                            | Tag::TableRow
+                            | Tag::TableCell
+                            | Tag::CodeBlock(_)
+                    ) {
+                        open.push((depth, blocks.len()));
+                        blocks.push(start + i..=start + i);
+                    }
+                    depth += 1;
+                }
+                Event::End(_) => {
+                    depth = depth.saturating_sub(1);
+                    if let Some(&(d, b)) = open.last()
+                        && d == depth
+                    {
+                        open.pop();
+                        blocks[b] = *blocks[b].start()..=start + i;
+                    }
+                }
+                _ => {}
+            }
+        }
+        blocks
+    }
+
+    /// How clients find each block of slide `idx` in what they rendered, in order.
+    fn targets(&self, idx: usize) -> Vec<BlockTarget> {
+        let mut targets: Vec<BlockTarget> = Vec::new();
+        for range in self.blocks(idx) {
+            let tag = match &self.events[*range.start()] {
+                Event::Start(Tag::Heading { level, .. }) => level.to_string(),
+                Event::Start(Tag::Paragraph) => "p".to_string(),
+                Event::Start(Tag::Item) => "li".to_string(),
+                // The head of a table renders to a row of its own.
+                Event::Start(Tag::TableHead | Tag::TableRow) => "tr".to_string(),
+                Event::Start(Tag::TableCell) => {
+                    let in_head = self.events[..*range.start()].iter().rev().find_map(|e| match e {
+                        Event::Start(Tag::TableHead) => Some(true),
+                        Event::Start(Tag::TableRow | Tag::Table(_)) => Some(false),
+                        _ => None,
+                    });
+                    if in_head == Some(true) { "th" } else { "td" }.to_string()
+                }
+                _ => "pre".to_string(),
+            };
+            let mut text = String::new();
+            let mut in_image = 0usize;
+            for event in &self.events[range] {
+                match event {
+                    Event::Start(Tag::Image { .. }) => in_image += 1,
+                    Event::End(TagEnd::Image) => in_image -= 1,
+                    Event::Text(t) | Event::Code(t) | Event::InlineMath(t) | Event::DisplayMath(t)
+                        if in_image == 0 =>
+                    {
+                        text.extend(t.chars().filter(|c| c.is_alphanumeric()));
+                    }
+                    _ => {}
+                }
+            }
+            let occurrence = targets.iter().filter(|t| t.tag == tag && t.text == text).count();
+            targets.push(BlockTarget { tag, text, occurrence: u32::try_from(occurrence).unwrap_or(u32::MAX) });
+        }
+        targets
+    }
+
+    /// The number of the block of slide `idx` that `target` names, as [`Deck::block`] counts.
+    fn find_block(&self, idx: usize, target: &BlockTarget) -> Option<u32> {
+        let block = self.targets(idx).iter().position(|t| t == target)?;
+        u32::try_from(block).ok()
+    }
+
+    /// This deck with the text of block `block` of slide `idx` marked as highlighted.
+    fn highlighted(&self, idx: usize, block: u32) -> Deck<'a> {
+        let mut deck = self.clone();
+        if let Some(range) = self.block(idx, block) {
+            // Same number of events, so the fragment markers still stand where they did.
+            // Image alt text is left as it is; markup there would show up as text.
+            let mut in_image = 0usize;
+            for event in &mut deck.events[range] {
+                match event {
+                    Event::Start(Tag::Image { .. }) => in_image += 1,
+                    Event::End(TagEnd::Image) => in_image -= 1,
+                    Event::Text(t) if in_image == 0 => {
+                        *event = Event::Html(format!("<mark>{}</mark>", html_escape(t)).into());
+                    }
+                    Event::Code(c) if in_image == 0 => {
+                        *event = Event::Html(format!("<mark><code>{}</code></mark>", html_escape(c)).into());
+                    }
+                    _ => {}
+                }
+            }
+        }
+        deck
     }
 }
 
-async fn update_presentation_name(
-    State(db): State<SqlitePool>,
-    auth_session: AuthSession,
-    Path(pid): Path<i64>,
-    body: String,
-) -> impl IntoResponse {
-    let Some(user) = auth_session.user else {
-        return StatusCode::UNAUTHORIZED.into_response();
-    };
-    let owner_count = sqlx::query_scalar::<_, i64>(
-        "SELECT COUNT(*) FROM presentation WHERE id = ? AND user_id = ?;",
-    )
-    .bind(pid)
-    .bind(user.id)
-    .fetch_one(&db)
-    .await;
-    if !matches!(owner_count, Ok(1)) {
-        return StatusCode::FORBIDDEN.into_response();
-    }
-    // Presentation name: 200-char limit — consistent with the /create form validation.
-    if body.len() > 200 {
-        return StatusCode::BAD_REQUEST.into_response();
-    }
-    match DbPresentation::update_name(pid, body, &db).await {
-        Ok(()) => StatusCode::OK.into_response(),
-        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
-    }
+/// A paragraph of its own splitting a slide into steps that are revealed one at a time.
+#[derive(Clone, Copy)]
+enum FragmentMarker {
+    /// `--`: the rest of the slide comes one step later.
+    Rest,
+    /// `++`: each item of the list that follows comes one step after the one before.
+    Items,
 }
 
-async fn update_recording_name(
-    State(db): State<SqlitePool>,
-    auth_session: AuthSession,
-    Path(rid): Path<i64>,
-    body: String,
-) -> impl IntoResponse {
-    let Some(user) = auth_session.user else {
-        return StatusCode::UNAUTHORIZED.into_response();
-    };
-    let owner_count = sqlx::query_scalar::<_, i64>(
-        "SELECT COUNT(*) FROM recording
-         JOIN presentation ON presentation.id = recording.presentation_id
-         WHERE recording.id = ? AND presentation.user_id = ?;",
-    )
-    .bind(rid)
-    .bind(user.id)
-    .fetch_one(&db)
-    .await;
-    if !matches!(owner_count, Ok(1)) {
-        return StatusCode::FORBIDDEN.into_response();
-    }
-    match Recording::update_name(rid, body, &db).await {
-        Ok(()) => StatusCode::OK.into_response(),
-        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+impl FragmentMarker {
+    fn parse(paragraph: &str) -> Option<Self> {
+        match paragraph.trim() {
+            "--" => Some(FragmentMarker::Rest),
+            "++" => Some(FragmentMarker::Items),
+            _ => None,
+        }
     }
 }
 
-async fn delete_recording(
-    State(db): State<SqlitePool>,
-    auth_session: AuthSession,
-    Path(rid): Path<i64>,
-) -> impl IntoResponse {
-    let Some(user) = auth_session.user else {
-        return StatusCode::UNAUTHORIZED.into_response();
-    };
-    let owner_count = sqlx::query_scalar::<_, i64>(
-        "SELECT COUNT(*) FROM recording
-         JOIN presentation ON presentation.id = recording.presentation_id
-         WHERE recording.id = ? AND presentation.user_id = ?;",
-    )
-    .bind(rid)
-    .bind(user.id)
-    .fetch_one(&db)
-    .await;
-    if !matches!(owner_count, Ok(1)) {
-        return StatusCode::FORBIDDEN.into_response();
-    }
-    match Recording::delete(rid, &db).await {
-        Ok(()) => Redirect::to("/user/presentations").into_response(),
-        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
-    }
+/// Where a [`NOTES_MARKER`] line was found in the event stream.
+enum NotesMarker {
+    /// The marker is the first line of a paragraph.
+    ParagraphStart,
+    /// The marker follows a line break inside a paragraph, which must be closed first.
+    MidParagraph,
 }
 
-async fn delete_presentation(
-    State(db): State<SqlitePool>,
-    auth_session: AuthSession,
-    Path(pid): Path<i64>,
-) -> impl IntoResponse {
-    let Some(user) = auth_session.user else {
-        return StatusCode::UNAUTHORIZED.into_response();
-    };
-    match DbPresentation::delete(pid, user.id, &db).await {
-        Ok(()) => Redirect::to("/user/presentations").into_response(),
-        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+/// Detects a [`NOTES_MARKER`] line at the start of `events`.
+fn notes_marker(events: &[(Event<'_>, Range<usize>)]) -> Option<NotesMarker> {
+    match events {
+        [
+            (Event::Start(Tag::Paragraph), _),
+            (Event::Text(t), _),
+            (Event::End(TagEnd::Paragraph) | Event::SoftBreak | Event::HardBreak, _),
+            ..
+        ] if t.as_ref() == NOTES_MARKER => Some(NotesMarker::ParagraphStart),
+        [
+            (Event::SoftBreak | Event::HardBreak, _),
+            (Event::Text(t), _),
+            (Event::End(TagEnd::Paragraph) | Event::SoftBreak | Event::HardBreak, _),
+            ..
+        ] if t.as_ref() == NOTES_MARKER => Some(NotesMarker::MidParagraph),
+        _ => None,
     }
 }
 
-async fn add_access(
-    State(db): State<SqlitePool>,
-    auth_session: AuthSession,
-    Path(pid): Path<i64>,
-    Form(form): Form<AddAccessForm>,
-) -> impl IntoResponse {
-    let Some(user) = auth_session.user else {
-        return StatusCode::UNAUTHORIZED.into_response();
-    };
-    let Ok(Some(pres)) = DbPresentation::get_by_id(pid, &db).await else {
-        return StatusCode::NOT_FOUND.into_response();
+/// Splits `markdown` into slides at `## ` headings and separates out speaker notes.
+fn parse_deck(markdown: &str) -> Deck<'_> {
+    // Strip raw HTML events to prevent XSS — pulldown-cmark passes them through
+    // unchanged, which would let editors embed <script> or other dangerous tags.
+    let parsed: Vec<(Event<'_>, Range<usize>)> = Parser::new_ext(markdown, Options::all())
+        .into_offset_iter()
+        .filter(|(e, _)| !matches!(e, Event::Html(_) | Event::InlineHtml(_)))
+        .collect();
+    let mut deck = Deck {
+        events: Vec::with_capacity(parsed.len()),
+        slide_starts: vec![],
+        notes: vec![],
+        notes_ranges: vec![],
+        fragments: vec![],
     };
-    if pres.user_id != user.id {
-        return StatusCode::NOT_FOUND.into_response();
-    }
-    if !["editor", "controller", "audience"].contains(&form.role.as_str()) {
-        return StatusCode::BAD_REQUEST.into_response();
-    }
-    let Ok(Some(target)) = User::get_by_name(form.username, &db).await else {
-        return StatusCode::NOT_FOUND.into_response();
+    let mut notes_start: Option<usize> = None;
+    let mut depth = 0usize;
+    // End of the fragment marker being left out of the events.
+    let mut marker_end = 0;
+    let close_notes = |deck: &mut Deck<'_>, range: Range<usize>| {
+        let text = markdown[range.clone()].trim_start();
+        let text = text.strip_prefix(NOTES_MARKER).unwrap_or(text).trim();
+        if let Some(notes) = deck.notes.last_mut() {
+            *notes = text.to_string();
+        }
+        deck.notes_ranges.push(range);
     };
-    // Don't allow adding the owner as a co-presenter
-    if target.id == user.id {
-        return StatusCode::BAD_REQUEST.into_response();
+    for i in 0..parsed.len() {
+        let (event, range) = &parsed[i];
+        let top_level = depth == 0;
+        match event {
+            Event::Start(_) => depth += 1,
+            Event::End(_) => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        if range.start < marker_end {
+            continue;
+        }
+        if let Event::Start(Tag::Heading { level: HeadingLevel::H2, .. }) = event {
+            if let Some(start) = notes_start.take() {
+                close_notes(&mut deck, start..range.start);
+            }
+            deck.slide_starts.push(deck.events.len());
+            deck.notes.push(String::new());
+            deck.fragments.push(vec![]);
+        } else if notes_start.is_none()
+            && top_level
+            && matches!(event, Event::Start(Tag::Paragraph))
+            && let Some(marker) = FragmentMarker::parse(&markdown[range.clone()])
+            && let Some(markers) = deck.fragments.last_mut()
+        {
+            markers.push((deck.events.len(), marker));
+            marker_end = range.end;
+            continue;
+        } else if notes_start.is_none() && !deck.slide_starts.is_empty() {
+            match notes_marker(&parsed[i..]) {
+                Some(NotesMarker::ParagraphStart) => notes_start = Some(range.start),
+                Some(NotesMarker::MidParagraph) => {
+                    // Close the paragraph the marker interrupted; the line break is dropped.
+                    deck.events.push(Event::End(TagEnd::Paragraph));
+                    notes_start = Some(parsed[i + 1].1.start);
+                }
+                None => {}
+            }
+        }
+        if notes_start.is_none() {
+            deck.events.push(event.clone());
+        }
     }
-    match PresentationAccess::add(&db, pid, target.id, &form.role).await {
-        Ok(()) => Redirect::to("/user/presentations").into_response(),
-        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    if let Some(start) = notes_start {
+        close_notes(&mut deck, start..markdown.len());
     }
+    deck
 }
 
-async fn remove_access(
-    State(db): State<SqlitePool>,
-    auth_session: AuthSession,
-    Path(pid): Path<i64>,
-    Form(form): Form<RemoveAccessForm>,
-) -> impl IntoResponse {
-    let Some(user) = auth_session.user else {
-        return StatusCode::UNAUTHORIZED.into_response();
-    };
-    let Ok(Some(pres)) = DbPresentation::get_by_id(pid, &db).await else {
-        return StatusCode::NOT_FOUND.into_response();
-    };
-    if pres.user_id != user.id {
-        return StatusCode::NOT_FOUND.into_response();
-    }
-    // Don't allow removing the owner from their own presentation
-    if form.user_id == user.id {
-        return StatusCode::BAD_REQUEST.into_response();
-    }
-    match PresentationAccess::remove(&db, pid, form.user_id).await {
-        Ok(()) => Redirect::to("/user/presentations").into_response(),
-        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+/// Returns `markdown` with every slide's speaker notes removed.
+///
+/// Used for the `Text` message sent to audience connections, which render the
+/// deck client-side and must never receive presenter-only notes.
+fn strip_speaker_notes(markdown: &str) -> String {
+    let deck = parse_deck(markdown);
+    let mut output = String::with_capacity(markdown.len());
+    let mut last = 0;
+    for range in deck.notes_ranges {
+        output.push_str(&markdown[last..range.start]);
+        last = range.end;
+    }
+    output.push_str(&markdown[last..]);
+    output
+}
+
+/// Returns the speaker notes of one slide, or an empty string if it has none.
+#[must_use]
+fn slide_notes(markdown: &str, slide_index: u32) -> String {
+    let idx = usize::try_from(slide_index).unwrap_or(usize::MAX);
+    parse_deck(markdown).notes.into_iter().nth(idx).unwrap_or_default()
+}
+
+/// Seconds planned for each slide by a `Time:` line in its speaker notes.
+fn planned_times(markdown: &str) -> Vec<Option<u64>> {
+    parse_deck(markdown).notes.iter().map(|notes| pacing::planned_time(notes)).collect()
+}
+
+/// Whether one slide has a block numbered `block`, as counted by [`Deck::block`].
+fn slide_has_block(markdown: &str, slide_index: u32, block: u32) -> bool {
+    let idx = usize::try_from(slide_index).unwrap_or(usize::MAX);
+    parse_deck(markdown).block(idx, block).is_some()
+}
+
+/// Returns the number of fragment steps of one slide, 0 if it has none.
+fn slide_steps(markdown: &str, slide_index: u32) -> u32 {
+    let idx = usize::try_from(slide_index).unwrap_or(usize::MAX);
+    parse_deck(markdown).reveal(idx, u32::MAX).1
+}
+
+/// Returns the poll defined on one slide by a fenced code block with the `poll` info string.
+///
+/// The first line of the block is the question; every following `- ` line is an option.
+fn slide_poll(markdown: &str, slide_index: u32) -> Option<(String, Vec<String>)> {
+    let Deck { events, slide_starts, .. } = parse_deck(markdown);
+    let idx = usize::try_from(slide_index).ok()?;
+    let start = *slide_starts.get(idx)?;
+    let end = slide_starts.get(idx + 1).copied().unwrap_or(events.len());
+    let mut block: Option<String> = None;
+    for event in &events[start..end] {
+        match (event, block.as_mut()) {
+            (Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))), None) if info.trim() == "poll" => {
+                block = Some(String::new());
+            }
+            (Event::Text(t), Some(b)) => b.push_str(t),
+            (Event::End(TagEnd::CodeBlock), Some(_)) => break,
+            _ => {}
+        }
     }
+    let block = block?;
+    let mut lines = block.lines().map(str::trim).filter(|l| !l.is_empty());
+    let question = lines.next()?.to_string();
+    let options = lines
+        .filter_map(|l| l.strip_prefix("- ").or_else(|| l.strip_prefix("* ")))
+        .map(|o| o.trim().to_string())
+        .collect();
+    Some((question, options))
 }
 
-async fn change_access_role(
-    State(db): State<SqlitePool>,
-    auth_session: AuthSession,
-    Path(pid): Path<i64>,
-    Form(form): Form<ChangeRoleForm>,
-) -> impl IntoResponse {
-    let Some(user) = auth_session.user else {
-        return StatusCode::UNAUTHORIZED.into_response();
-    };
-    let Ok(Some(pres)) = DbPresentation::get_by_id(pid, &db).await else {
-        return StatusCode::NOT_FOUND.into_response();
-    };
-    if pres.user_id != user.id {
-        return StatusCode::NOT_FOUND.into_response();
+/// Returns the HTML for a single slide from rendered markdown, with its first
+/// `fragment` steps revealed, marked as written in `lang` if given.
+/// Splits at `<h2>` boundaries, mirroring the JS `addSiblings` function.
+#[must_use]
+fn render_slide(markdown: &str, slide_index: u32, fragment: u32, pres_name: &str, lang: Option<&str>) -> String {
+    let deck = parse_deck(markdown);
+    if deck.slide_starts.is_empty() {
+        return String::new();
     }
-    if !["editor", "controller", "audience"].contains(&form.role.as_str()) {
-        return StatusCode::BAD_REQUEST.into_response();
+    let idx = usize::try_from(slide_index)
+        .unwrap_or(usize::MAX)
+        .min(deck.slide_starts.len() - 1);
+    let mut output = String::new();
+    if !pres_name.is_empty() {
+        output.push_str("<h1>");
+        output.push_str(&html_escape(pres_name));
+        output.push_str("</h1>");
     }
-    match PresentationAccess::change_role(&db, pid, form.user_id, &form.role).await {
-        Ok(()) => Redirect::to("/user/presentations").into_response(),
-        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    cmark_html::push_html(&mut output, deck.reveal(idx, fragment).0.into_iter());
+    match lang {
+        Some(lang) => format!("<div lang=\"{}\">{output}</div>", html_escape(lang)),
+        None => output,
     }
 }
 
-async fn set_presentation_access_mode(
-    State(db): State<SqlitePool>,
-    auth_session: AuthSession,
-    Path(pid): Path<i64>,
-    Form(form): Form<SetAccessModeForm>,
-) -> impl IntoResponse {
-    let Some(user) = auth_session.user else {
-        return StatusCode::UNAUTHORIZED.into_response();
-    };
-    let Ok(Some(pres)) = DbPresentation::get_by_id(pid, &db).await else {
-        return StatusCode::NOT_FOUND.into_response();
-    };
-    if pres.user_id != user.id {
-        return StatusCode::NOT_FOUND.into_response();
-    }
-    if !["public", "audience", "private"].contains(&form.mode.as_str()) {
-        return StatusCode::BAD_REQUEST.into_response();
-    }
-    match DbPresentation::set_access_mode(pid, &form.mode, &db).await {
-        Ok(()) => Redirect::to("/user/presentations").into_response(),
-        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+/// Returns `(title, html_content)` for every `## ` slide in the markdown.
+/// Uses the same pulldown-cmark parser as `render_slide`; speaker notes are excluded.
+#[must_use]
+fn render_all_slides(markdown: &str) -> Vec<(String, String)> {
+    render_all_slides_with(markdown, |_| None)
+}
+
+/// Like [`render_all_slides`], but `map_url` may replace the URL of each image.
+fn render_all_slides_with(
+    markdown: &str,
+    mut map_url: impl FnMut(&str) -> Option<String>,
+) -> Vec<(String, String)> {
+    let Deck { events, slide_starts, .. } = parse_deck(markdown);
+    slide_starts
+        .iter()
+        .enumerate()
+        .map(|(i, &start)| {
+            let end = slide_starts.get(i + 1).copied().unwrap_or(events.len());
+            // Extract heading text
+            let title = events[start..end]
+                .iter()
+                .filter_map(|e| if let Event::Text(t) = e { Some(t.as_ref()) } else { None })
+                .next()
+                .unwrap_or("")
+                .to_string();
+            // Render full slide HTML
+            let mut html = String::new();
+            let slide_events = events[start..end].iter().cloned().map(|e| match e {
+                Event::Start(Tag::Image { link_type, dest_url, title, id }) => {
+                    let dest_url = map_url(&dest_url).map_or(dest_url, CowStr::from);
+                    Event::Start(Tag::Image { link_type, dest_url, title, id })
+                }
+                e => e,
+            });
+            cmark_html::push_html(&mut html, slide_events);
+            (title, html)
+        })
+        .collect()
+}
+
+/// Gets the current slide index and fragment step from in-memory state, defaulting to 0.
+#[must_use]
+fn current_slide_step(app_state: &AppState, pid: i64) -> (u32, u32) {
+    let Ok(map) = app_state.slides.lock() else { return (0, 0); };
+    map.get(&pid.to_string())
+        .and_then(|p| p.lock().ok().map(|p| (p.slide, p.fragment)))
+        .unwrap_or((0, 0))
+}
+
+async fn audience(tera: Tera, auth_session: AuthSession, db: SqlitePool) -> impl IntoResponse {
+    tera.render("audience.html", Context::new(), auth_session, db)
+        .await
+}
+async fn start(
+    State(tera): State<Tera>,
+    auth_session: AuthSession,
+    State(db): State<SqlitePool>,
+) -> impl IntoResponse {
+    if auth_session.user.is_none() {
+        return Redirect::to("/auth/login").into_response();
     }
+    tera.render("create.html", Context::new(), auth_session, db)
+        .await
 }
 
-async fn set_recording_access_mode(
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct NameForm {
+    name: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct PwdQuery {
+    error: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct AddAccessForm {
+    username: String,
+    role: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct RemoveAccessForm {
+    user_id: i64,
+}
+
+#[derive(Deserialize)]
+struct SetAccessModeForm {
+    mode: String,
+}
+
+#[derive(Deserialize)]
+struct SetRecordingAccessModeForm {
+    action: String,
+    mode: Option<String>,
+}
+
+/// The main language of a presentation; empty to forget it.
+#[derive(Deserialize)]
+struct LanguageForm {
+    lang: String,
+}
+
+/// A translation of a presentation; empty `content` starts it as a copy of the deck.
+#[derive(Deserialize)]
+struct VariantForm {
+    lang: String,
+    content: String,
+}
+
+/// `?lang=` of the pages and downloads that can show a presentation in a translation.
+#[derive(Deserialize)]
+struct LangQuery {
+    lang: Option<String>,
+}
+
+/// `tag` if it is shaped like a BCP 47 language tag, such as `en`, `fr-CA` or `zh-Hant-TW`.
+fn language_tag(tag: &str) -> Option<&str> {
+    // 35 characters is the longest tag RFC 5646 requires implementations to support.
+    const MAX_TAG_LEN: usize = 35;
+    let mut subtags = tag.split('-');
+    let primary = subtags.next()?;
+    let well_formed = tag.len() <= MAX_TAG_LEN
+        && (2..=3).contains(&primary.len())
+        && primary.chars().all(|c| c.is_ascii_alphabetic())
+        && subtags.all(|s| (1..=8).contains(&s.len()) && s.chars().all(|c| c.is_ascii_alphanumeric()));
+    well_formed.then_some(tag)
+}
+
+/// The Markdown of `pres` to show for `?lang=`, and the tag of the language it is in: its
+/// translation into `lang` if it has one, otherwise its own Markdown.
+///
+/// A live presentation is ahead of the database until its writer catches up, so its
+/// content and translations are used while it is loaded.
+async fn deck_in(pres: &DbPresentation, lang: Option<&str>, db: &SqlitePool, app_state: &AppState) -> (String, Option<String>) {
+    if let Some(live) = live_presentation(app_state, pres.id)
+        && let Ok(p) = live.lock()
+    {
+        if let Some(lang) = lang.and_then(language_tag)
+            && let Some(text) = p.variants.get(lang)
+        {
+            return (text.clone(), Some(lang.to_string()));
+        }
+        return (p.content.clone(), p.lang.clone());
+    }
+    if let Some(lang) = lang.and_then(language_tag)
+        && let Ok(Some(variant)) = PresentationVariant::get(db, pres.id, lang).await
+    {
+        return (variant.content, Some(variant.lang));
+    }
+    (pres.content.clone(), DbPresentation::get_language(pres.id, db).await.ok().flatten())
+}
+
+#[derive(Deserialize)]
+struct UserExistsQuery {
+    username: Option<String>,
+}
+
+async fn user_exists(
     State(db): State<SqlitePool>,
     auth_session: AuthSession,
-    Path(rid): Path<i64>,
-    Form(form): Form<SetRecordingAccessModeForm>,
+    Query(query): Query<UserExistsQuery>,
 ) -> impl IntoResponse {
-    let Some(user) = auth_session.user else {
+    let Some(_) = auth_session.user else {
         return StatusCode::UNAUTHORIZED.into_response();
     };
-    let owner_count = sqlx::query_scalar::<_, i64>(
-        "SELECT COUNT(*) FROM recording
-         JOIN presentation ON presentation.id = recording.presentation_id
-         WHERE recording.id = ? AND presentation.user_id = ?",
-    )
-    .bind(rid)
-    .bind(user.id)
-    .fetch_one(&db)
-    .await;
-    if !matches!(owner_count, Ok(c) if c > 0) {
-        return StatusCode::NOT_FOUND.into_response();
-    }
-    let mode = if form.action == "inherit" {
-        None
-    } else {
-        match form.mode.as_deref() {
-            Some(m) if ["public", "audience", "private"].contains(&m) => Some(m.to_string()),
-            _ => return StatusCode::BAD_REQUEST.into_response(),
-        }
+    let Some(username) = query.username else {
+        return StatusCode::BAD_REQUEST.into_response();
     };
-    match Recording::set_access_mode(rid, mode.as_deref(), &db).await {
-        Ok(()) => Redirect::to("/user/presentations").into_response(),
+    match User::get_by_name(username, &db).await {
+        Ok(Some(_)) => StatusCode::OK.into_response(),
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
         Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
     }
 }
 
-async fn update_recording_files(
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct ChangeRoleForm {
+    user_id: i64,
+    role: String,
+}
+
+async fn start_pres(
     State(db): State<SqlitePool>,
     auth_session: AuthSession,
-    Path(rid): Path<i64>,
-    mut multipart: Multipart,
+    Form(name_form): Form<NameForm>,
 ) -> impl IntoResponse {
     let Some(user) = auth_session.user else {
-        return StatusCode::UNAUTHORIZED.into_response();
+        return Redirect::to("/auth/login").into_response();
     };
-    let owner_count = sqlx::query_scalar::<_, i64>(
-        "SELECT COUNT(*) FROM recording
-         JOIN presentation ON presentation.id = recording.presentation_id
-         WHERE recording.id = ? AND presentation.user_id = ?;",
-    )
-    .bind(rid)
-    .bind(user.id)
-    .fetch_one(&db)
-    .await;
-    if !matches!(owner_count, Ok(1)) {
-        return StatusCode::NOT_FOUND.into_response();
+    if name_form.name.is_empty() {
+        return Redirect::to("/start").into_response();
+    }
+    // Presentation name: 200-char limit — aligns with Google Slides and common tools.
+    // Prevents DB text column overflow and oversized page titles.
+    if name_form.name.len() > 200 {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+    let pres = DbPresentation::new(&user, name_form.name, &db).await;
+    if let Err(ref e) = pres {
+        println!("{e:?}");
     }
+    let Ok(pres) = pres else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    Redirect::to(&format!("/{}/{}/edit", user.name, pres.id)).into_response()
+}
 
-    let mut video_bytes: Option<(Vec<u8>, String)> = None;
-    let mut captions_bytes: Option<Vec<u8>> = None;
+/// Re-renders the create page with an import error and a 400 status.
+async fn import_failed(tera: Tera, auth_session: AuthSession, db: SqlitePool, error: &str) -> Response {
+    let mut ctx = Context::new();
+    ctx.insert("import_error", error);
+    let mut response = tera.render("create.html", ctx, auth_session, db).await;
+    *response.status_mut() = StatusCode::BAD_REQUEST;
+    response
+}
 
+/// Creates a presentation from an uploaded `.pptx` or `.odp` file.
+///
+/// Extracted images are stored as uploads of the new presentation. When parts of the
+/// deck could not be converted, the user sees the list before opening the editor.
+async fn import_pres(
+    State(tera): State<Tera>,
+    State(db): State<SqlitePool>,
+    auth_session: AuthSession,
+    mut multipart: Multipart,
+) -> impl IntoResponse {
+    let Some(user) = auth_session.user.clone() else {
+        return Redirect::to("/auth/login").into_response();
+    };
+    let mut name = String::new();
+    let mut upload: Option<(String, Vec<u8>)> = None;
     while let Ok(Some(field)) = multipart.next_field().await {
         match field.name().unwrap_or("") {
-            "video" => {
-                let ext = field
+            "name" => {
+                name = field.text().await.unwrap_or_default();
+            }
+            "file" => {
+                let stem = field
                     .file_name()
-                    .and_then(|f| std::path::Path::new(f).extension())
+                    .and_then(|f| std::path::Path::new(f).file_stem())
                     .and_then(std::ffi::OsStr::to_str)
-                    .unwrap_or("bin")
+                    .unwrap_or_default()
                     .to_string();
-                if let Ok(b) = field.bytes().await {
-                    if !b.is_empty() {
-                        video_bytes = Some((b.to_vec(), ext));
-                    }
-                }
-            }
-            "captions" => {
-                if let Ok(b) = field.bytes().await {
-                    if !b.is_empty() {
-                        captions_bytes = Some(b.to_vec());
-                    }
+                if let Ok(b) = field.bytes().await
+                    && !b.is_empty()
+                {
+                    upload = Some((stem, b.to_vec()));
                 }
             }
             _ => {}
         }
     }
+    let Some((stem, bytes)) = upload else {
+        return import_failed(tera, auth_session, db, "Choose a .pptx or .odp file to import.").await;
+    };
+    // An empty name falls back to the file name, like opening the file would show.
+    let name = match name.trim() {
+        "" if stem.trim().is_empty() => "Imported presentation".to_string(),
+        "" => stem.trim().to_string(),
+        name => name.to_string(),
+    };
+    // Same 200-char limit as start_pres.
+    if name.len() > 200 {
+        return import_failed(tera, auth_session, db, "Presentation names can be at most 200 characters.").await;
+    }
+    // Unpacking and parsing can take a while; keep it off the thread serving every socket.
+    let deck = match tokio::task::spawn_blocking(move || import::import_deck(&bytes)).await {
+        Ok(Ok(deck)) => deck,
+        Ok(Err(e)) => return import_failed(tera, auth_session, db, &e.to_string()).await,
+        Err(e) => {
+            eprintln!("import_pres: import task failed: {e:?}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let pres = match DbPresentation::new(&user, name, &db).await {
+        Ok(pres) => pres,
+        Err(e) => {
+            eprintln!("import_pres: DbPresentation::new failed: {e:?}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    let markdown = deck.markdown(&format!("/assets/presentations/{}/", pres.id));
+    if !slides_within_size_limit(&markdown) {
+        if let Err(e) = DbPresentation::delete(pres.id, user.id, &db).await {
+            eprintln!("import_pres: delete oversized presentation failed: {e:?}");
+        }
+        return import_failed(tera, auth_session, db, "A slide is larger than 100 KB of text.").await;
+    }
+    let dir = format!("{PRESENTATION_ASSET_DIR}/{}", pres.id);
+    if let Err(e) = store_import(&dir, pres.id, &deck, markdown, &db).await {
+        eprintln!("import_pres: {e}");
+        // A failed import leaves neither a presentation nor any of its images behind.
+        if let Err(e) = tokio::fs::remove_dir_all(&dir).await
+            && e.kind() != std::io::ErrorKind::NotFound
+        {
+            eprintln!("import_pres: remove_dir_all {dir} failed: {e:?}");
+        }
+        if let Err(e) = DbPresentation::delete(pres.id, user.id, &db).await {
+            eprintln!("import_pres: delete failed import failed: {e:?}");
+        }
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    }
+
+    if deck.warnings.is_empty() {
+        return Redirect::to(&format!("/{}/{}/edit", user.name, pres.id)).into_response();
+    }
+    let mut ctx = Context::new();
+    ctx.insert("pres", &pres);
+    ctx.insert("warnings", &deck.warnings);
+    tera.render("imported.html", ctx, auth_session, db).await
+}
+
+/// Writes the images of an imported deck to `dir`, lists them among the assets of
+/// presentation `id` and saves its `markdown` as the presentation's content.
+///
+/// An image without alt text is listed with an empty one; the import warnings already
+/// ask for a description.
+async fn store_import(dir: &str, id: i64, deck: &import::ImportedDeck, markdown: String, db: &SqlitePool) -> Result<(), String> {
+    tokio::fs::create_dir_all(dir).await.map_err(|e| format!("create_dir_all {dir} failed: {e:?}"))?;
+    for (file, data) in &deck.images {
+        tokio::fs::write(format!("{dir}/{file}"), data).await.map_err(|e| format!("write {file} failed: {e:?}"))?;
+        PresentationAsset::create(db, id, file, deck.alt_text(file))
+            .await
+            .map_err(|e| format!("PresentationAsset::create {file} failed: {e:?}"))?;
+    }
+    DbPresentation::update_content(id, markdown, db).await.map_err(|e| format!("update_content failed: {e:?}"))
+}
+
+async fn present(
+    State(tera): State<Tera>,
+    State(db): State<SqlitePool>,
+    State(app_state): State<AppState>,
+    auth_session: AuthSession,
+    Path((uname, pid)): Path<(String, i64)>,
+    Query(query): Query<LangQuery>,
+) -> impl IntoResponse {
+    let pres_user = User::get_by_name(uname.clone(), &db).await;
+    let pres_user = match pres_user {
+        Ok(Some(u)) => u,
+        Ok(None) => return audience(tera, auth_session, db).await.into_response(),
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+    let pres = DbPresentation::get_by_id(pid, &db).await;
+    let pres = match pres {
+        Ok(Some(p)) => p,
+        Ok(None) => return audience(tera, auth_session, db).await.into_response(),
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+    if pres.user_id != pres_user.id {
+        let Ok(Some(owner)) = User::get_by_id(pres.user_id, &db).await else {
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        };
+        let redirect = format!("/{}/{pid}", owner.name);
+        return Redirect::permanent(&redirect).into_response();
+    }
+    let access = match check_access(&db, auth_session.user.as_ref(), pid, None).await {
+        Ok(a) => a,
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+
+    match access {
+        AccessResult::Owner | AccessResult::Editor | AccessResult::Controller => {
+            stage(tera, db, auth_session, pid, app_state, pres_user).await.into_response()
+        }
+        AccessResult::Captioner | AccessResult::Audience | AccessResult::PublicOk => {
+            let (slide_index, fragment) = current_slide_step(&app_state, pid);
+            let (mut content, mut lang) = deck_in(&pres, query.lang.as_deref(), &db, &app_state).await;
+            // A translation short of the slide shown falls back to the deck, as it does live.
+            if parse_deck(&content).slide_starts.len() <= slide_index as usize {
+                (content, lang) = deck_in(&pres, None, &db, &app_state).await;
+            }
+            let initial_slide = render_slide(&content, slide_index, fragment, &pres.name, lang.as_deref());
+            let mut ctx = Context::new();
+            ctx.insert("pres", &pres);
+            ctx.insert("pres_user", &pres_user);
+            ctx.insert("initial_slide", &initial_slide);
+            ctx.insert("captioner", &(access == AccessResult::Captioner));
+            tera.render("audience.html", ctx, auth_session, db).await.into_response()
+        }
+        AccessResult::Denied => {
+            StatusCode::FORBIDDEN.into_response()
+        }
+    }
+}
+
+async fn stage(
+    tera: Tera,
+    db: SqlitePool,
+    auth_session: AuthSession,
+    pid: i64,
+    app_state: AppState,
+    pres_user: User,
+) -> impl IntoResponse {
+    if auth_session.user.is_none() {
+        return Redirect::to("/auth/login").into_response();
+    }
+    let pres = match DbPresentation::get_by_id(pid, &db).await {
+        Ok(Some(p)) => p,
+        Ok(None) => return StatusCode::NOT_FOUND.into_response(),
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+    let (slide_index, fragment) = current_slide_step(&app_state, pid);
+    let lang = DbPresentation::get_language(pid, &db).await.ok().flatten();
+    let initial_slide = render_slide(&pres.content, slide_index, fragment, &pres.name, lang.as_deref());
+    let initial_notes = slide_notes(&pres.content, slide_index);
+    let mut ctx = Context::new();
+    ctx.insert("pres", &pres);
+    ctx.insert("pres_user", &pres_user);
+    ctx.insert("initial_slide", &initial_slide);
+    ctx.insert("initial_notes", &initial_notes);
+    tera.render("stage.html", ctx, auth_session, db).await
+}
+async fn edit_pres(
+    State(tera): State<Tera>,
+    State(db): State<SqlitePool>,
+    auth_session: AuthSession,
+    Path((uname, pid)): Path<(String, i64)>,
+) -> impl IntoResponse {
+    if auth_session.user.is_none() {
+        return Redirect::to("/auth/login").into_response();
+    }
+    let pres_user = match User::get_by_name(uname.clone(), &db).await {
+        Ok(Some(u)) => u,
+        Ok(None) => return StatusCode::NOT_FOUND.into_response(),
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+    let pres = match DbPresentation::get_by_id(pid, &db).await {
+        Ok(Some(p)) => p,
+        Ok(None) => return StatusCode::NOT_FOUND.into_response(),
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+    if pres.user_id != pres_user.id {
+        let Ok(Some(owner)) = User::get_by_id(pres.user_id, &db).await else {
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        };
+        let redirect = format!("/{}/{pid}/edit", owner.name);
+        return Redirect::permanent(&redirect).into_response();
+    }
+    let access = match check_access(&db, auth_session.user.as_ref(), pid, None).await {
+        Ok(a) => a,
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+    match access {
+        AccessResult::Owner | AccessResult::Editor => {
+            let assets: Vec<AssetLink> = match PresentationAsset::get_for_presentation(&db, pid).await {
+                Ok(assets) => assets.into_iter().map(AssetLink::from).collect(),
+                Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+            };
+            let variants: Vec<VariantListing> = match PresentationVariant::get_for_presentation(&db, pid).await {
+                Ok(variants) => variants.into_iter().map(VariantListing::from).collect(),
+                Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+            };
+            let language = DbPresentation::get_language(pid, &db).await.ok().flatten();
+            let mut ctx = Context::new();
+            ctx.insert("pres", &pres);
+            ctx.insert("pres_user", &pres_user);
+            ctx.insert("assets", &assets);
+            ctx.insert("variants", &variants);
+            ctx.insert("language", &language.unwrap_or_default());
+            ctx.insert("slide_count", &parse_deck(&pres.content).slide_starts.len());
+            tera.render("edit.html", ctx, auth_session, db).await.into_response()
+        }
+        AccessResult::Controller | AccessResult::Captioner | AccessResult::Audience | AccessResult::PublicOk => {
+            Redirect::to(&format!("/{uname}/{pid}")).into_response()
+        }
+        AccessResult::Denied => Redirect::to("/auth/login").into_response(),
+    }
+}
+
+/// One line of a revision diff, as rendered by `revision.html`.
+#[derive(Serialize)]
+struct DiffLine {
+    /// `"insert"`, `"delete"`, or `"equal"`.
+    kind: &'static str,
+    text: String,
+}
+
+/// Line diff from `old` to `new`, grouped into hunks with three lines of context.
+fn diff_hunks(old: &str, new: &str) -> Vec<Vec<DiffLine>> {
+    let diff = TextDiff::from_lines(old, new);
+    diff.grouped_ops(3)
+        .iter()
+        .map(|group| {
+            group
+                .iter()
+                .flat_map(|op| diff.iter_changes(op))
+                .map(|change| DiffLine {
+                    kind: match change.tag() {
+                        ChangeTag::Insert => "insert",
+                        ChangeTag::Delete => "delete",
+                        ChangeTag::Equal => "equal",
+                    },
+                    text: change.value().trim_end_matches('\n').to_string(),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Resolves `/{uname}/{pid}` for the revision pages, requiring owner or editor access.
+///
+/// Returns the presentation and its owner, or the response to send instead.
+async fn revision_page_access(
+    db: &SqlitePool,
+    auth_session: &AuthSession,
+    uname: String,
+    pid: i64,
+) -> Result<(DbPresentation, User), Response> {
+    if auth_session.user.is_none() {
+        return Err(Redirect::to("/auth/login").into_response());
+    }
+    let Ok(Some(pres_user)) = User::get_by_name(uname, db).await else {
+        return Err(StatusCode::NOT_FOUND.into_response());
+    };
+    let Ok(Some(pres)) = DbPresentation::get_by_id(pid, db).await else {
+        return Err(StatusCode::NOT_FOUND.into_response());
+    };
+    if pres.user_id != pres_user.id {
+        return Err(StatusCode::NOT_FOUND.into_response());
+    }
+    let Ok(access) = check_access(db, auth_session.user.as_ref(), pid, None).await else {
+        return Err(StatusCode::INTERNAL_SERVER_ERROR.into_response());
+    };
+    if !matches!(access, AccessResult::Owner | AccessResult::Editor) {
+        return Err(StatusCode::FORBIDDEN.into_response());
+    }
+    Ok((pres, pres_user))
+}
+
+/// Those who run a live presentation: its owner, editors and controllers.
+const PRESENTERS: [AccessResult; 3] = [AccessResult::Owner, AccessResult::Editor, AccessResult::Controller];
+
+/// Resolves `/{uname}/{pid}` for the tools of a live presentation, requiring a signed-in
+/// user whose access to it is one of `allowed`.
+///
+/// Returns the presentation and that access, or the response to send instead.
+async fn live_tool_access(
+    db: &SqlitePool,
+    auth_session: &AuthSession,
+    uname: String,
+    pid: i64,
+    allowed: &[AccessResult],
+) -> Result<(DbPresentation, AccessResult), Response> {
+    if auth_session.user.is_none() {
+        return Err(StatusCode::UNAUTHORIZED.into_response());
+    }
+    let Ok(Some(pres_user)) = User::get_by_name(uname, db).await else {
+        return Err(StatusCode::NOT_FOUND.into_response());
+    };
+    let Ok(Some(pres)) = DbPresentation::get_by_id(pid, db).await else {
+        return Err(StatusCode::NOT_FOUND.into_response());
+    };
+    if pres.user_id != pres_user.id {
+        return Err(StatusCode::NOT_FOUND.into_response());
+    }
+    let Ok(access) = check_access(db, auth_session.user.as_ref(), pid, None).await else {
+        return Err(StatusCode::INTERNAL_SERVER_ERROR.into_response());
+    };
+    if !allowed.contains(&access) {
+        return Err(StatusCode::FORBIDDEN.into_response());
+    }
+    Ok((pres, access))
+}
+
+/// Reports who is connected to a live presentation, as JSON, to its presenters.
+///
+/// The same [`Presence`] that presenters connected to the stage receive, counting the
+/// connections to every process; all zero when the presentation is not live here.
+async fn presence(
+    State(state): State<AppState>,
+    auth_session: AuthSession,
+    Path((uname, pid)): Path<(String, i64)>,
+) -> impl IntoResponse {
+    let db = &state.db_pool;
+    if let Err(resp) = live_tool_access(db, &auth_session, uname, pid, &PRESENTERS).await {
+        return resp;
+    }
+    let live = state.slides.lock().ok().and_then(|slides| slides.get(&pid.to_string()).cloned());
+    let presence = live.and_then(|pres| pres.lock().ok().map(|p| p.presence())).unwrap_or_default();
+    // Counts and names always serialise.
+    let body = serde_json::to_string(&presence).expect("Presence is always serializable");
+    ([(axum::http::header::CONTENT_TYPE, "application/json")], body).into_response()
+}
+
+/// Reports planned against actual time per slide of a presentation, as JSON, to its
+/// presenters.
+///
+/// Actual times come from the session clock, which starts at the first slide move and
+/// lasts as long as the presentation stays live. Until a new session's clock starts, the
+/// report is of the last session, as saved when everyone left; all zero before any.
+async fn pacing_report(
+    State(state): State<AppState>,
+    auth_session: AuthSession,
+    Path((uname, pid)): Path<(String, i64)>,
+) -> impl IntoResponse {
+    let db = &state.db_pool;
+    let (pres, _) = match live_tool_access(db, &auth_session, uname, pid, &PRESENTERS).await {
+        Ok(found) => found,
+        Err(resp) => return resp,
+    };
+    let live = state.slides.lock().ok().and_then(|slides| slides.get(&pid.to_string()).cloned());
+    let (content, clock) = match live.as_ref().and_then(|pres| pres.lock().ok()) {
+        Some(p) => (p.content.clone(), p.clock.as_ref().map(SessionClock::state)),
+        None => (pres.content, None),
+    };
+    let clock = match clock {
+        Some(clock) => Some(clock),
+        None => match LiveState::get(pid, db).await {
+            Ok(saved) => saved.and_then(|s| s.clock).map(|(elapsed_ms, steps)| ClockState { elapsed_ms, steps }),
+            Err(e) => {
+                eprintln!("pacing_report: pid={pid} LiveState::get failed: {e:?}");
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        },
+    };
+    let titles = render_all_slides(&content).into_iter().map(|(title, _)| title).collect();
+    let report = pacing::Report::new(clock.as_ref(), titles, &planned_times(&content));
+    // Numbers and titles always serialise.
+    let body = serde_json::to_string(&report).expect("Report is always serializable");
+    ([(axum::http::header::CONTENT_TYPE, "application/json")], body).into_response()
+}
+
+/// Takes live captions as plain text, one caption per line, from a captioner or anyone
+/// who may edit the presentation, and shows each to everyone as soon as its line ends.
+///
+/// CART software can stream a long chunked request, or post a line at a time. Answers
+/// `413` for a line longer than [`captions::MAX_CAPTION_BYTES`], and `400` for text that
+/// is not UTF-8; the lines before it were shown.
+async fn ingest_captions(
+    State(mut state): State<AppState>,
+    auth_session: AuthSession,
+    Path((uname, pid)): Path<(String, i64)>,
+    body: Body,
+) -> impl IntoResponse {
+    let db = state.db_pool.clone();
+    let allowed = [AccessResult::Owner, AccessResult::Editor, AccessResult::Captioner];
+    if let Err(resp) = live_tool_access(&db, &auth_session, uname, pid, &allowed).await {
+        return resp;
+    }
+    let key = pid.to_string();
+    let Ok(live) = add_client_handler_channel(key.clone(), &mut state).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    let mut show = |line: &[u8]| -> Result<(), StatusCode> {
+        let text = std::str::from_utf8(line).map_err(|_| StatusCode::BAD_REQUEST)?;
+        if let Some(msg) = update_slide(&key, SlideMessage::Caption(text.to_string()), &mut state)
+            && let Ok(p) = live.lock()
+        {
+            // No receivers only means nobody is watching.
+            let _ = p.channel.0.send(msg);
+        }
+        Ok(())
+    };
+    let mut chunks = body.into_data_stream();
+    let mut pending = Vec::new();
+    while let Some(chunk) = chunks.next().await {
+        let Ok(chunk) = chunk else { return StatusCode::BAD_REQUEST.into_response(); };
+        pending.extend_from_slice(&chunk);
+        while let Some(end) = pending.iter().position(|&b| b == b'\n') {
+            let caption: Vec<u8> = pending.drain(..=end).collect();
+            if caption.len() > captions::MAX_CAPTION_BYTES {
+                return StatusCode::PAYLOAD_TOO_LARGE.into_response();
+            }
+            if let Err(status) = show(&caption) {
+                return status.into_response();
+            }
+        }
+        if pending.len() > captions::MAX_CAPTION_BYTES {
+            return StatusCode::PAYLOAD_TOO_LARGE.into_response();
+        }
+    }
+    // The last line needs no line break.
+    match show(&pending) {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(status) => status.into_response(),
+    }
+}
+
+/// Lists saved revisions of a presentation's Markdown.
+async fn history(
+    State(tera): State<Tera>,
+    State(db): State<SqlitePool>,
+    auth_session: AuthSession,
+    Path((uname, pid)): Path<(String, i64)>,
+) -> impl IntoResponse {
+    let (pres, pres_user) = match revision_page_access(&db, &auth_session, uname, pid).await {
+        Ok(found) => found,
+        Err(resp) => return resp,
+    };
+    let Ok(revisions) = PresentationRevision::get_for_presentation(&db, pid).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    let mut ctx = Context::new();
+    ctx.insert("pres", &pres);
+    ctx.insert("pres_user", &pres_user);
+    ctx.insert("revisions", &revisions);
+    tera.render("history.html", ctx, auth_session, db)
+        .await
+        .into_response()
+}
+
+/// Shows what changed between a saved revision and the current content.
+async fn revision_diff(
+    State(tera): State<Tera>,
+    State(db): State<SqlitePool>,
+    State(app_state): State<AppState>,
+    auth_session: AuthSession,
+    Path((uname, pid, rev_id)): Path<(String, i64, i64)>,
+) -> impl IntoResponse {
+    let (pres, pres_user) = match revision_page_access(&db, &auth_session, uname, pid).await {
+        Ok(found) => found,
+        Err(resp) => return resp,
+    };
+    let Ok(Some(revision)) = PresentationRevision::get_by_id(&db, rev_id).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if revision.presentation_id != pid {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    // A live presentation is ahead of the database until its writer catches up.
+    let current = live_presentation(&app_state, pid)
+        .and_then(|p| p.lock().ok().map(|p| p.content.clone()))
+        .unwrap_or_else(|| pres.content.clone());
+    let hunks = diff_hunks(&revision.content, &current);
+    let mut ctx = Context::new();
+    ctx.insert("pres", &pres);
+    ctx.insert("pres_user", &pres_user);
+    ctx.insert("revision", &revision);
+    ctx.insert("hunks", &hunks);
+    tera.render("revision.html", ctx, auth_session, db)
+        .await
+        .into_response()
+}
+
+/// Replaces the presentation content with a saved revision and pushes it to live clients.
+///
+/// The content being replaced is saved as a revision first, so a restore can be undone.
+async fn restore_revision(
+    State(db): State<SqlitePool>,
+    State(app_state): State<AppState>,
+    auth_session: AuthSession,
+    Path((pid, rev_id)): Path<(i64, i64)>,
+) -> impl IntoResponse {
+    let Some(user) = auth_session.user else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    let Ok(access) = check_access(&db, Some(&user), pid, None).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    if !matches!(access, AccessResult::Owner | AccessResult::Editor) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    let Ok(Some(revision)) = PresentationRevision::get_by_id(&db, rev_id).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if revision.presentation_id != pid {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    let Ok(Some(pres)) = DbPresentation::get_by_id(pid, &db).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let live = app_state
+        .slides
+        .lock()
+        .ok()
+        .and_then(|map| map.get(&pid.to_string()).map(Arc::clone));
+    let current = live
+        .as_ref()
+        .and_then(|p| p.lock().ok().map(|p| p.content.clone()))
+        .unwrap_or(pres.content);
+    let role = access.as_str();
+    if PresentationRevision::create(&db, pid, Some(user.id), role, current).await.is_err()
+        || DbPresentation::update_content(pid, revision.content.clone(), &db).await.is_err()
+        || PresentationRevision::create(&db, pid, Some(user.id), role, revision.content.clone()).await.is_err()
+    {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    }
+    if let Some(Ok(mut p)) = live.as_ref().map(|p| p.lock()) {
+        // Also supersedes any edit still waiting in the writer, which would undo the restore.
+        p.set_content(revision.content, None);
+    }
+    let Ok(Some(owner)) = User::get_by_id(pres.user_id, &db).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    Redirect::to(&format!("/{}/{pid}/history", owner.name)).into_response()
+}
+
+/// A single slide in the context consumed by `transcription.html`.
+#[derive(Serialize)]
+struct TranscriptSlide {
+    title: String,
+    content_html: String,
+    notes: String,
+}
+
+/// The `presentation` context consumed by `transcription.html`.
+#[derive(Serialize)]
+struct Transcript {
+    title: String,
+    author: String,
+    created_at: String,
+    created_at_formatted: String,
+    slides: Vec<TranscriptSlide>,
+}
+
+/// Builds the transcript slide list from the presentation Markdown.
+///
+/// The leading `<h2>` of each slide is removed because the template renders the
+/// slide title as its own `<h3>`. Images stay inline in `content_html` with their alt text.
+/// Speaker notes are included only when `with_notes` is set (presenter roles).
+fn transcript_slides(markdown: &str, with_notes: bool) -> Vec<TranscriptSlide> {
+    let notes = if with_notes { parse_deck(markdown).notes } else { vec![] };
+    render_all_slides(markdown)
+        .into_iter()
+        .enumerate()
+        .map(|(i, (title, html))| TranscriptSlide {
+            title,
+            content_html: strip_leading_tag(&html, "h2").to_string(),
+            notes: notes.get(i).cloned().unwrap_or_default(),
+        })
+        .collect()
+}
+
+/// Renders every slide of a presentation as a single accessible transcript page, in the
+/// translation `?lang=` names if it has one.
+async fn transcript(
+    State(tera): State<Tera>,
+    State(db): State<SqlitePool>,
+    State(app_state): State<AppState>,
+    auth_session: AuthSession,
+    Path((uname, pid)): Path<(String, i64)>,
+    Query(query): Query<LangQuery>,
+) -> impl IntoResponse {
+    let pres_user = match User::get_by_name(uname, &db).await {
+        Ok(Some(u)) => u,
+        Ok(None) => return StatusCode::NOT_FOUND.into_response(),
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+    let pres = match DbPresentation::get_by_id(pid, &db).await {
+        Ok(Some(p)) => p,
+        Ok(None) => return StatusCode::NOT_FOUND.into_response(),
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+    if pres.user_id != pres_user.id {
+        let Ok(Some(owner)) = User::get_by_id(pres.user_id, &db).await else {
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        };
+        let redirect = format!("/{}/{pid}/transcript", owner.name);
+        return Redirect::permanent(&redirect).into_response();
+    }
+    let Ok(access) = check_access(&db, auth_session.user.as_ref(), pid, None).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    if matches!(access, AccessResult::Denied) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    let (content, lang) = deck_in(&pres, query.lang.as_deref(), &db, &app_state).await;
+    let now = time::OffsetDateTime::now_utc();
+    let presentation = Transcript {
+        title: pres.name.clone(),
+        author: pres_user.name.clone(),
+        created_at: format!("{:04}-{:02}-{:02}", now.year(), u8::from(now.month()), now.day()),
+        created_at_formatted: format!("{} {}, {}", now.month(), now.day(), now.year()),
+        slides: transcript_slides(
+            &content,
+            matches!(access, AccessResult::Owner | AccessResult::Editor | AccessResult::Controller),
+        ),
+    };
+    let mut ctx = Context::new();
+    ctx.insert("presentation", &presentation);
+    if let Some(lang) = &lang {
+        ctx.insert("lang", lang);
+    }
+    ctx.insert("pres", &pres);
+    ctx.insert("pres_user", &pres_user);
+    tera.render("transcription.html", ctx, auth_session, db)
+        .await
+        .into_response()
+}
+
+/// How an export makes locally served images available offline.
+#[derive(Clone, Copy)]
+enum ExportAssets {
+    /// Inline images as `data:` URIs so the page is a single file.
+    Inline,
+    /// Point images at `assets/…` next to the page; the files ship alongside it.
+    Bundled,
+}
+
+/// A slide of an exported presentation.
+#[derive(Serialize)]
+struct ExportSlide {
+    title: String,
+    html: String,
+}
+
+/// Maps the URL of one of presentation `pid`'s uploads to its path on disk.
+///
+/// Remote URLs, other presentations' uploads and anything escaping the directory
+/// are left alone.
+fn local_asset_path(pid: i64, url: &str) -> Option<std::path::PathBuf> {
+    let rest = url.strip_prefix(&format!("/assets/presentations/{pid}/"))?;
+    let path = std::path::Path::new(rest);
+    if path.components().all(|c| matches!(c, std::path::Component::Normal(_))) {
+        Some(std::path::Path::new(PRESENTATION_ASSET_DIR).join(pid.to_string()).join(path))
+    } else {
+        None
+    }
+}
+
+/// Guesses the MIME type of an image from its file extension.
+fn image_mime_type(path: &std::path::Path) -> &'static str {
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or_default();
+    match ext.to_ascii_lowercase().as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Directory holding each presentation's uploads in a `{pid}/` subdirectory.
+///
+/// Kept outside `assets/` so the open file server there can never reach it; the
+/// files are served by [`presentation_asset`] at `/assets/presentations/{pid}/{file}`.
+const PRESENTATION_ASSET_DIR: &str = "presentation_assets";
+
+/// Guesses the MIME type of an uploadable image, audio or video file.
+///
+/// Returns `None` for any other file type, which uploads refuse.
+fn asset_mime_type(path: &std::path::Path) -> Option<&'static str> {
+    match image_mime_type(path) {
+        "application/octet-stream" => {}
+        image => return Some(image),
+    }
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or_default();
+    match ext.to_ascii_lowercase().as_str() {
+        "mp3" => Some("audio/mpeg"),
+        "ogg" | "oga" => Some("audio/ogg"),
+        "wav" => Some("audio/wav"),
+        "mp4" | "m4v" => Some("video/mp4"),
+        "webm" => Some("video/webm"),
+        "ogv" => Some("video/ogg"),
+        _ => None,
+    }
+}
+
+/// Turns an uploaded file name into one that is safe on disk and in URLs.
+fn asset_file_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') { c } else { '_' })
+        .collect();
+    // A leading dot would hide the file or, as `..`, leave its directory.
+    match name.strip_prefix('.') {
+        Some(rest) => format!("_{rest}"),
+        None => name,
+    }
+}
+
+/// A translation as listed on the edit page, with how many slides it has.
+#[derive(Serialize)]
+struct VariantListing {
+    lang: String,
+    content: String,
+    slides: usize,
+}
+
+impl From<PresentationVariant> for VariantListing {
+    fn from(variant: PresentationVariant) -> Self {
+        let slides = parse_deck(&variant.content).slide_starts.len();
+        VariantListing { lang: variant.lang, content: variant.content, slides }
+    }
+}
+
+/// An upload as listed on the edit page, with the Markdown that shows it on a slide.
+#[derive(Serialize)]
+struct AssetLink {
+    file_name: String,
+    alt_text: String,
+    url: String,
+    markdown: String,
+}
+
+impl From<PresentationAsset> for AssetLink {
+    fn from(asset: PresentationAsset) -> Self {
+        let url = format!("/assets/presentations/{}/{}", asset.presentation_id, asset.file_name);
+        let alt = asset.alt_text.replace('\\', "\\\\").replace('[', "\\[").replace(']', "\\]");
+        // Audio and video cannot be embedded with Markdown, so they become links.
+        let markdown = if image_mime_type(std::path::Path::new(&asset.file_name)) == "application/octet-stream" {
+            format!("[{alt}]({url})")
+        } else {
+            format!("![{alt}]({url})")
+        };
+        AssetLink { file_name: asset.file_name, alt_text: asset.alt_text, url, markdown }
+    }
+}
+
+/// Returns `katex_css` with its font URLs replaced by inline WOFF2 `data:` URIs.
+///
+/// The WOFF and TrueType fallbacks are dropped; every browser that runs `KaTeX` reads WOFF2.
+fn inline_katex_fonts(katex_css: &str, woff2_fonts: &HashMap<String, Vec<u8>>) -> String {
+    const URL_PREFIX: &str = "url(fonts/";
+    const FORMAT_PREFIX: &str = " format(";
+    let mut output = String::with_capacity(katex_css.len());
+    let mut rest = katex_css;
+    while let Some(start) = rest.find(URL_PREFIX) {
+        let Some(len) = rest[start..].find(')') else { break };
+        let name = &rest[start + URL_PREFIX.len()..start + len];
+        let after = &rest[start + len + 1..];
+        // Length of the ` format("…")` hint that follows the URL, if any.
+        let hint_len = after
+            .strip_prefix(FORMAT_PREFIX)
+            .and_then(|h| h.find(')'))
+            .map_or(0, |end| FORMAT_PREFIX.len() + end + 1);
+        if let Some(font) = woff2_fonts.get(name) {
+            output.push_str(&rest[..start]);
+            output.push_str("url(data:font/woff2;base64,");
+            output.push_str(&BASE64_STANDARD.encode(font));
+            output.push(')');
+            output.push_str(&after[..hint_len]);
+        } else {
+            // Drop the fallback together with the comma separating it from the previous source.
+            output.push_str(rest[..start].strip_suffix(',').unwrap_or(&rest[..start]));
+        }
+        rest = &after[hint_len..];
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Reads every WOFF2 font served under `/css/fonts`, keyed by file name.
+async fn read_katex_fonts() -> std::io::Result<HashMap<String, Vec<u8>>> {
+    let mut fonts = HashMap::new();
+    let mut dir = tokio::fs::read_dir("css/fonts").await?;
+    while let Some(entry) = dir.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if std::path::Path::new(&name).extension().is_some_and(|e| e.eq_ignore_ascii_case("woff2")) {
+            fonts.insert(name, tokio::fs::read(entry.path()).await?);
+        }
+    }
+    Ok(fonts)
+}
+
+/// Renders a presentation as a standalone HTML page that works without the server.
+///
+/// Styles, `KaTeX` and the slide navigation are inlined. Returns the page together with
+/// the image files it references as `(path in bundle, bytes)`; with
+/// [`ExportAssets::Inline`] the images are already inside the page and the list is empty.
+/// The slides are marked as written in `lang`, if given.
+async fn export_presentation(
+    tera: &Tera,
+    pres: &DbPresentation,
+    assets: ExportAssets,
+    lang: Option<&str>,
+) -> std::io::Result<(String, Vec<(String, Vec<u8>)>)> {
+    // Load every local image the deck references before rendering, which is synchronous.
+    let mut images: HashMap<String, Vec<u8>> = HashMap::new();
+    for (event, _) in Parser::new_ext(&pres.content, Options::all()).into_offset_iter() {
+        if let Event::Start(Tag::Image { dest_url, .. }) = event
+            && let Some(path) = local_asset_path(pres.id, &dest_url)
+            && let Ok(bytes) = tokio::fs::read(&path).await
+        {
+            images.insert(dest_url.into_string(), bytes);
+        }
+    }
+    let slides: Vec<ExportSlide> = render_all_slides_with(&pres.content, |url| {
+        let bytes = images.get(url)?;
+        match assets {
+            ExportAssets::Inline => {
+                let mime = local_asset_path(pres.id, url).map_or("application/octet-stream", |p| image_mime_type(&p));
+                Some(format!("data:{mime};base64,{}", BASE64_STANDARD.encode(bytes)))
+            }
+            ExportAssets::Bundled => url.strip_prefix('/').map(String::from),
+        }
+    })
+    .into_iter()
+    .map(|(title, html)| ExportSlide { title, html })
+    .collect();
+    let katex_css = tokio::fs::read_to_string("css/katex.css").await?;
+    let mut ctx = Context::new();
+    ctx.insert("pres", pres);
+    ctx.insert("slides", &slides);
+    if let Some(lang) = lang {
+        ctx.insert("lang", lang);
+    }
+    ctx.insert("style_css", &tokio::fs::read_to_string("css/style.css").await?);
+    ctx.insert("katex_css", &inline_katex_fonts(&katex_css, &read_katex_fonts().await?));
+    ctx.insert("katex_js", &tokio::fs::read_to_string("js/katex.js").await?);
+    ctx.insert("theme_init_js", &tokio::fs::read_to_string("js/theme-init.js").await?);
+    let html = tera.tera.render("export.html", &ctx).map_err(std::io::Error::other)?;
+    let files = match assets {
+        ExportAssets::Inline => vec![],
+        ExportAssets::Bundled => images
+            .into_iter()
+            .filter_map(|(url, bytes)| Some((url.strip_prefix('/')?.to_string(), bytes)))
+            .collect(),
+    };
+    Ok((html, files))
+}
+
+/// Returns a download file name for a presentation, e.g. `My_Talk.zip`.
+fn export_file_name(pres_name: &str, extension: &str) -> String {
+    let stem: String = pres_name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+        .collect();
+    let stem = stem.trim_matches('_');
+    let stem = if stem.is_empty() { "presentation" } else { stem };
+    format!("{stem}.{extension}")
+}
+
+/// Looks up a presentation for export, enforcing [`check_access`].
+///
+/// Redirects to the owner's canonical URL, ending in `suffix`, when `uname` is not the owner.
+async fn export_access(
+    db: &SqlitePool,
+    auth_session: &AuthSession,
+    uname: String,
+    pid: i64,
+    suffix: &str,
+) -> Result<DbPresentation, Response> {
+    let pres_user = match User::get_by_name(uname, db).await {
+        Ok(Some(u)) => u,
+        Ok(None) => return Err(StatusCode::NOT_FOUND.into_response()),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR.into_response()),
+    };
+    let pres = match DbPresentation::get_by_id(pid, db).await {
+        Ok(Some(p)) => p,
+        Ok(None) => return Err(StatusCode::NOT_FOUND.into_response()),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR.into_response()),
+    };
+    if pres.user_id != pres_user.id {
+        let Ok(Some(owner)) = User::get_by_id(pres.user_id, db).await else {
+            return Err(StatusCode::INTERNAL_SERVER_ERROR.into_response());
+        };
+        let redirect = format!("/{}/{pid}/{suffix}", owner.name);
+        return Err(Redirect::permanent(&redirect).into_response());
+    }
+    let Ok(access) = check_access(db, auth_session.user.as_ref(), pid, None).await else {
+        return Err(StatusCode::INTERNAL_SERVER_ERROR.into_response());
+    };
+    if matches!(access, AccessResult::Denied) {
+        return Err(StatusCode::FORBIDDEN.into_response());
+    }
+    Ok(pres)
+}
+
+/// Downloads the presentation, in the translation `?lang=` names if it has one, as a single
+/// self-contained HTML file.
+async fn export_html(
+    State(tera): State<Tera>,
+    State(db): State<SqlitePool>,
+    State(app_state): State<AppState>,
+    auth_session: AuthSession,
+    Path((uname, pid)): Path<(String, i64)>,
+    Query(query): Query<LangQuery>,
+) -> impl IntoResponse {
+    let pres = match export_access(&db, &auth_session, uname, pid, "export.html").await {
+        Ok(p) => p,
+        Err(response) => return response,
+    };
+    let (content, lang) = deck_in(&pres, query.lang.as_deref(), &db, &app_state).await;
+    let pres = DbPresentation { content, ..pres };
+    let html = match export_presentation(&tera, &pres, ExportAssets::Inline, lang.as_deref()).await {
+        Ok((html, _)) => html,
+        Err(e) => {
+            eprintln!("export_html: pid={pid} render failed: {e:?}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    let disposition = format!("attachment; filename=\"{}\"", export_file_name(&pres.name, "html"));
+    (
+        [
+            (axum::http::header::CONTENT_TYPE, "text/html; charset=utf-8".to_string()),
+            (axum::http::header::CONTENT_DISPOSITION, disposition),
+        ],
+        html,
+    )
+        .into_response()
+}
+
+/// Downloads the presentation, in the translation `?lang=` names if it has one, as a zip of
+/// `index.html` and the images it references.
+async fn export_zip(
+    State(tera): State<Tera>,
+    State(db): State<SqlitePool>,
+    State(app_state): State<AppState>,
+    auth_session: AuthSession,
+    Path((uname, pid)): Path<(String, i64)>,
+    Query(query): Query<LangQuery>,
+) -> impl IntoResponse {
+    let pres = match export_access(&db, &auth_session, uname, pid, "export.zip").await {
+        Ok(p) => p,
+        Err(response) => return response,
+    };
+    let (content, lang) = deck_in(&pres, query.lang.as_deref(), &db, &app_state).await;
+    let pres = DbPresentation { content, ..pres };
+    let (html, files) = match export_presentation(&tera, &pres, ExportAssets::Bundled, lang.as_deref()).await {
+        Ok(export) => export,
+        Err(e) => {
+            eprintln!("export_zip: pid={pid} render failed: {e:?}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    // Compressing a deck with media can take a while; keep it off the thread serving every socket.
+    let bytes = match tokio::task::spawn_blocking(move || zip_export(html, files)).await {
+        Ok(Ok(bytes)) => bytes,
+        Ok(Err(e)) => {
+            eprintln!("export_zip: pid={pid} zip failed: {e:?}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+        Err(e) => {
+            eprintln!("export_zip: pid={pid} zip task failed: {e:?}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    let disposition = format!("attachment; filename=\"{}\"", export_file_name(&pres.name, "zip"));
+    (
+        [
+            (axum::http::header::CONTENT_TYPE, "application/zip".to_string()),
+            (axum::http::header::CONTENT_DISPOSITION, disposition),
+        ],
+        bytes,
+    )
+        .into_response()
+}
+
+/// Packs an exported `html` page and its `files` into a zip archive.
+fn zip_export(html: String, files: Vec<(String, Vec<u8>)>) -> zip::result::ZipResult<Vec<u8>> {
+    let mut zip = ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let entries = std::iter::once(("index.html".to_string(), html.into_bytes())).chain(files);
+    for (name, bytes) in entries {
+        zip.start_file(name, SimpleFileOptions::default())?;
+        zip.write_all(&bytes)?;
+    }
+    Ok(zip.finish()?.into_inner())
+}
+
+/// Returns an SVG QR code linking to the presentation at `/{uname}/{pid}`.
+async fn qr_code(Path((uname, pid)): Path<(String, String)>, headers: HeaderMap) -> impl IntoResponse {
+    let proto = headers
+        .get("x-forwarded-proto")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("https");
+    let host = headers
+        .get("host")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("localhost");
+    let url = format!("{proto}://{host}/{uname}/{pid}");
+    let Ok(code) = QrCode::new(url.as_bytes()) else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    let image = code
+        .render::<svg::Color<'_>>()
+        .min_dimensions(200, 200)
+        .quiet_zone(false)
+        .build();
+    ([(axum::http::header::CONTENT_TYPE, "image/svg+xml")], image).into_response()
+}
+
+async fn presentations(
+    State(tera): State<Tera>,
+    State(db): State<SqlitePool>,
+    auth_session: AuthSession,
+) -> impl IntoResponse {
+    let Some(ref user) = auth_session.user else {
+        return Redirect::to("/auth/login").into_response();
+    };
+    let Ok(owned) = DbPresentation::get_for_user(user, &db).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    let Ok(shared) = DbPresentation::get_shared_with_user(user, &db).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    let mut press_with_recordings = vec![];
+    for pres in owned {
+        let Ok(mut pwr) = Recording::get_by_presentation(pres, user.name.clone(), &db).await else {
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        };
+        pwr.role = "owner".to_string();
+        press_with_recordings.push(pwr);
+    }
+    for (pres, role) in shared {
+        let owner_name = match User::get_by_id(pres.user_id, &db).await {
+            Ok(Some(owner)) => owner.name,
+            _ => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        };
+        let Ok(mut pwr) = Recording::get_by_presentation(pres, owner_name, &db).await else {
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        };
+        pwr.role = role;
+        press_with_recordings.push(pwr);
+    }
+    let mut ctx = Context::new();
+    ctx.insert("press", &press_with_recordings);
+    tera.render("presentations.html", ctx, auth_session, db)
+        .await
+}
+async fn login(
+    State(tera): State<Tera>,
+    State(db): State<SqlitePool>,
+    auth_session: AuthSession,
+) -> impl IntoResponse {
+    tera.render("login.html", Context::new(), auth_session, db)
+        .await
+}
+async fn new_user_form(
+    State(db): State<SqlitePool>,
+    State(_tera): State<Tera>,
+    auth_session: AuthSession,
+    Form(new_user): Form<AddUserForm>,
+) -> impl IntoResponse {
+    let Some(ref user) = auth_session.user else {
+        return Redirect::to("/auth/login").into_response();
+    };
+    if let Ok(is_admin) = auth_session.backend.has_perm(user, Group::Admin).await
+        && !is_admin
+    {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    // User display name: 100-char limit — prevents oversized names in DB and UI rendering.
+    // Chosen at the lower end of the ±50% range; aligns with GOV.UK Design System name field guidance.
+    if new_user.name.len() > 100 {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+    if User::new(&db, new_user).await.is_err() {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    }
+    Redirect::to("/user/presentations").into_response()
+}
+async fn new_user(
+    State(db): State<SqlitePool>,
+    State(tera): State<Tera>,
+    auth_session: AuthSession,
+) -> impl IntoResponse {
+    let Some(ref _user) = auth_session.user else {
+        return Redirect::to("/auth/login").into_response();
+    };
+    tera.render("user/add_user.html", Context::new(), auth_session, db)
+        .await
+}
+/// Live presentations in memory, how far each open connection fell behind the broadcasts,
+/// and what idle eviction has removed, as JSON (admins only).
+async fn live_stats(State(state): State<AppState>, auth_session: AuthSession) -> impl IntoResponse {
+    let Some(ref user) = auth_session.user else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    if !matches!(auth_session.backend.has_perm(user, Group::Admin).await, Ok(true)) {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    let Ok(live) = state.slides.lock().map(|slides| slides.clone()) else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    let mut connections = Vec::new();
+    for (pid, pres) in &live {
+        let Ok(p) = pres.lock() else { continue; };
+        for (&id, stats) in &p.connections {
+            let lag_events = stats.lag_events.load(Ordering::Relaxed);
+            let skipped = stats.skipped_messages.load(Ordering::Relaxed);
+            connections.push((pid.clone(), id, lag_events, skipped));
+        }
+    }
+    connections.sort();
+    let connections: Vec<serde_json::Value> = connections
+        .into_iter()
+        .map(|(pid, id, lag_events, skipped)| {
+            serde_json::json!({"presentation": pid, "connection": id, "lag_events": lag_events, "skipped_messages": skipped})
+        })
+        .collect();
+    let body = serde_json::json!({
+        "live": live.len(),
+        "evicted": state.evictions.presentations.load(Ordering::Relaxed),
+        "recordings_stopped": state.evictions.recordings_stopped.load(Ordering::Relaxed),
+        "connections": connections
+    });
+    ([(axum::http::header::CONTENT_TYPE, "application/json")], body.to_string()).into_response()
+}
+async fn change_pwd(
+    State(db): State<SqlitePool>,
+    State(tera): State<Tera>,
+    auth_session: AuthSession,
+    Query(params): Query<PwdQuery>,
+) -> impl IntoResponse {
+    let Some(ref _user) = auth_session.user else {
+        return Redirect::to("/auth/login").into_response();
+    };
+    let mut ctx = Context::new();
+    if let Some(ref err) = params.error {
+        ctx.insert("error", err);
+    }
+    tera.render("user/change_pwd.html", ctx, auth_session, db)
+        .await
+}
+async fn change_pwd_form(
+    State(db): State<SqlitePool>,
+    auth_session: AuthSession,
+    Form(pwd_form): Form<ChangePasswordForm>,
+) -> impl IntoResponse {
+    let Some(user) = auth_session.user else {
+        return Redirect::to("/auth/login").into_response();
+    };
+    if pwd_form.new != pwd_form.confirm {
+        return Redirect::to("/user/change_pwd?error=Passwords+do+not+match").into_response();
+    }
+    let phash = match PasswordHash::new(&user.password) {
+        Ok(h) => h,
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+    if Argon2::default()
+        .verify_password(pwd_form.old.as_bytes(), &phash)
+        .is_err()
+    {
+        return Redirect::to("/user/change_pwd?error=Current+password+is+incorrect").into_response();
+    }
+    if user.change_password(pwd_form.new, &db).await.is_err() {
+        return Redirect::to("/user/change_pwd?error=Failed+to+update+password").into_response();
+    }
+    return Redirect::to("/").into_response();
+}
+
+async fn login_process(
+    State(tera): State<Tera>,
+    State(db): State<SqlitePool>,
+    mut auth_session: AuthSession,
+    Form(login): Form<LoginForm>,
+) -> impl IntoResponse {
+    let user = match auth_session.authenticate(login).await {
+        Ok(Some(u)) => u,
+        Ok(None) => {
+            let mut ctx = Context::new();
+            ctx.insert("error", "Invalid username or password.");
+            return tera.render("login.html", ctx, auth_session, db).await;
+        }
+        Err(_) => {
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    if auth_session.login(&user).await.is_err() {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    }
+    Redirect::to("/").into_response()
+}
+async fn logout(mut auth_session: AuthSession) -> impl IntoResponse {
+    match auth_session.logout().await {
+        Ok(_) => Redirect::to("/").into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+async fn recording(
+    State(tera): State<Tera>,
+    State(db): State<SqlitePool>,
+    auth_session: AuthSession,
+    Path((uname, pid, rid)): Path<(String, i64, i64)>,
+    Query(query): Query<LangQuery>,
+) -> impl IntoResponse {
+    let Ok(Some(pres_user)) = User::get_by_name(uname, &db).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let Ok(Some(pres)) = DbPresentation::get_by_id(pid, &db).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if pres.user_id != pres_user.id {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    let Ok(Some(rec)) = Recording::get_by_id(rid, &db).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if rec.presentation_id != pid {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    let access = match check_access(&db, auth_session.user.as_ref(), pid, Some(rid)).await {
+        Ok(a) => a,
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+    if matches!(access, AccessResult::Denied) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    // The slides were recorded in the deck's language and in each translation it had then.
+    let Ok(languages) = RecordingSlide::languages(rid, &db).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    let main_lang = DbPresentation::get_language(pid, &db).await.ok().flatten();
+    let lang = query.lang.filter(|lang| languages.contains(lang));
+    let mut ctx = Context::new();
+    ctx.insert("recording", &rec);
+    ctx.insert("pres", &pres);
+    ctx.insert("pres_user", &pres_user);
+    ctx.insert("languages", &languages);
+    if let Some(main_lang) = &main_lang {
+        ctx.insert("main_lang", main_lang);
+    }
+    if let Some(lang) = lang.as_ref().or(main_lang.as_ref()) {
+        ctx.insert("lang", lang);
+    }
+    ctx.insert("translated", &lang.is_some());
+    tera.render("recording.html", ctx, auth_session, db)
+        .await
+        .into_response()
+}
+
+async fn edit_recording(
+    State(tera): State<Tera>,
+    State(db): State<SqlitePool>,
+    auth_session: AuthSession,
+    Path((uname, pid, rid)): Path<(String, i64, i64)>,
+) -> impl IntoResponse {
+    if auth_session.user.is_none() {
+        return Redirect::to("/auth/login").into_response();
+    }
+    let Ok(Some(pres_user)) = User::get_by_name(uname, &db).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let Ok(Some(pres)) = DbPresentation::get_by_id(pid, &db).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if pres.user_id != pres_user.id {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    let Ok(Some(rec)) = Recording::get_by_id(rid, &db).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if rec.presentation_id != pid {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    let access = match check_access(&db, auth_session.user.as_ref(), pid, Some(rid)).await {
+        Ok(a) => a,
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+    if !matches!(access, AccessResult::Owner | AccessResult::Editor | AccessResult::Controller) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    // A timeline left unsaved by a restart can be finalised by the owner.
+    let unsaved_timeline = matches!(access, AccessResult::Owner)
+        && matches!(LiveState::get_for_recording(rid, &db).await, Ok(Some(_)));
+    let mut ctx = Context::new();
+    ctx.insert("recording", &rec);
+    ctx.insert("pres", &pres);
+    ctx.insert("pres_user", &pres_user);
+    ctx.insert("unsaved_timeline", &unsaved_timeline);
+    tera.render("edit_recording.html", ctx, auth_session, db)
+        .await
+        .into_response()
+}
+
+async fn demo(State(db): State<SqlitePool>) -> impl IntoResponse {
+    let Ok(Some(user)) = User::get_by_name("admin".to_string(), &db).await else {
+        return Redirect::to("/").into_response();
+    };
+    let Ok(presses) = DbPresentation::get_for_user(&user, &db).await else {
+        return Redirect::to("/").into_response();
+    };
+    match presses.into_iter().next() {
+        Some(pres) => Redirect::to(&format!("/{}/{}", user.name, pres.id)).into_response(),
+        None => Redirect::to("/").into_response(),
+    }
+}
+
+async fn index(
+    State(tera): State<Tera>,
+    auth_session: AuthSession,
+    State(db): State<SqlitePool>,
+) -> impl IntoResponse {
+    tera.render("index.html", Context::new(), auth_session, db)
+        .await
+}
+
+async fn help(
+    State(tera): State<Tera>,
+    auth_session: AuthSession,
+    State(db): State<SqlitePool>,
+) -> impl IntoResponse {
+    tera.render("help.html", Context::new(), auth_session, db)
+        .await
+}
+
+/// The JSON Schema of every WebSocket message, for checking other clients against.
+async fn protocol_schema() -> impl IntoResponse {
+    ([(axum::http::header::CONTENT_TYPE, "application/schema+json")], protocol::schema().to_string())
+}
+
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn strip_leading_h1(content: &str) -> &str {
+    strip_leading_tag(content, "h1")
+}
+
+/// Removes a leading `<tag>…</tag>` element (e.g. the slide heading) from rendered HTML.
+fn strip_leading_tag<'a>(content: &'a str, tag: &str) -> &'a str {
+    let t = content.trim_start();
+    let lower = t.to_ascii_lowercase();
+    if lower.starts_with(&format!("<{tag}")) {
+        let close = format!("</{tag}>");
+        if let Some(end) = lower.find(&close) {
+            return t[end + close.len()..].trim_start();
+        }
+    }
+    content
+}
+
+fn format_vtt_time(seconds: f64) -> String {
+    let ms = ((seconds % 1.0) * 1000.0).round() as u64;
+    let total_s = seconds as u64;
+    let s = total_s % 60;
+    let m = (total_s / 60) % 60;
+    let h = total_s / 3600;
+    format!("{h:02}:{m:02}:{s:02}.{ms:03}")
+}
+
+async fn slides_vtt(
+    State(db): State<SqlitePool>,
+    auth_session: AuthSession,
+    Path((uname, pid, rid)): Path<(String, i64, i64)>,
+    Query(query): Query<LangQuery>,
+) -> impl IntoResponse {
+    let Ok(Some(pres_user)) = User::get_by_name(uname, &db).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let Ok(Some(pres)) = DbPresentation::get_by_id(pid, &db).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if pres.user_id != pres_user.id {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    let Ok(Some(rec)) = Recording::get_by_id(rid, &db).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if rec.presentation_id != pid {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    let access = match check_access(&db, auth_session.user.as_ref(), pid, Some(rid)).await {
+        Ok(a) => a,
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+    if matches!(access, AccessResult::Denied) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    let slides = match query.lang.as_deref().and_then(language_tag) {
+        Some(lang) => RecordingSlide::get_by_recording_in(rid, lang, &db).await,
+        None => RecordingSlide::get_by_recording(rid, &db).await,
+    };
+    let Ok(slides) = slides else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    let mut vtt = String::from("WEBVTT\n\n");
+    for (i, slide) in slides.iter().enumerate() {
+        let start = format_vtt_time(slide.start_seconds);
+        let end = slides
+            .get(i + 1)
+            .map(|s| format_vtt_time(s.start_seconds))
+            .unwrap_or_else(|| "99:59:59.999".to_string());
+        let json = serde_json::json!({
+            "id": slide.id,
+            "title": slide.title,
+            "content": slide.content,
+        });
+        vtt.push_str(&format!("{start} --> {end}\n{json}\n\n"));
+    }
+    (
+        [(axum::http::header::CONTENT_TYPE, "text/vtt; charset=utf-8")],
+        vtt,
+    )
+        .into_response()
+}
+
+async fn slides_html(
+    State(db): State<SqlitePool>,
+    auth_session: AuthSession,
+    Path((uname, pid, rid)): Path<(String, i64, i64)>,
+    Query(query): Query<LangQuery>,
+) -> impl IntoResponse {
+    let Ok(Some(pres_user)) = User::get_by_name(uname, &db).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let Ok(Some(pres)) = DbPresentation::get_by_id(pid, &db).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if pres.user_id != pres_user.id {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    let Ok(Some(rec)) = Recording::get_by_id(rid, &db).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if rec.presentation_id != pid {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    let access = match check_access(&db, auth_session.user.as_ref(), pid, Some(rid)).await {
+        Ok(a) => a,
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+    if matches!(access, AccessResult::Denied) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    let lang = match query.lang.as_deref().and_then(language_tag) {
+        Some(lang) if RecordingSlide::languages(rid, &db).await.is_ok_and(|l| l.iter().any(|l| l == lang)) => {
+            Some(lang.to_string())
+        }
+        _ => None,
+    };
+    let slides = match &lang {
+        Some(lang) => RecordingSlide::get_by_recording_in(rid, lang, &db).await,
+        None => RecordingSlide::get_by_recording(rid, &db).await,
+    };
+    let Ok(slides) = slides else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    let lang = match lang {
+        Some(lang) => lang,
+        None => DbPresentation::get_language(pid, &db).await.ok().flatten().unwrap_or_else(|| "en".to_string()),
+    };
+    let lang = html_escape(&lang);
+    let rec_name = html_escape(&rec.name);
+    let pres_name = html_escape(&pres.name);
+    let mut html = format!(
+        "<!DOCTYPE html><html lang=\"{lang}\"><head><meta charset=\"UTF-8\"><title>{rec_name} - Slides</title></head><body>\n<h1>{pres_name}</h1>\n"
+    );
+    for slide in &slides {
+        let content = strip_leading_h1(&slide.content);
+        html.push_str(&format!("<section>\n{content}\n</section>\n"));
+    }
+    html.push_str("</body></html>");
+    (
+        [(axum::http::header::CONTENT_TYPE, "text/html; charset=utf-8")],
+        html,
+    )
+        .into_response()
+}
+
+/// Serves a recording's video or captions to anyone who may watch the recording.
+///
+/// Only the two files the recording names are served, never anything else in
+/// its `assets/{rid}/` directory.
+async fn recording_file(
+    State(db): State<SqlitePool>,
+    auth_session: AuthSession,
+    headers: HeaderMap,
+    Path((uname, pid, rid, file)): Path<(String, i64, i64, String)>,
+) -> impl IntoResponse {
+    let Ok(Some(pres_user)) = User::get_by_name(uname, &db).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let Ok(Some(pres)) = DbPresentation::get_by_id(pid, &db).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if pres.user_id != pres_user.id {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    let Ok(Some(rec)) = Recording::get_by_id(rid, &db).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if rec.presentation_id != pid {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    let Ok(access) = check_access(&db, auth_session.user.as_ref(), pid, Some(rid)).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    if matches!(access, AccessResult::Denied) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    let mime = if file == rec.captions_path {
+        "text/vtt; charset=utf-8"
+    } else if rec.video_path.as_deref() == Some(file.as_str()) {
+        asset_mime_type(std::path::Path::new(&file)).unwrap_or("application/octet-stream")
+    } else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    serve_file(&format!("assets/{rid}/{file}"), mime, &headers).await
+}
+
+async fn update_slide_time(
+    State(db): State<SqlitePool>,
+    auth_session: AuthSession,
+    Path((rid, sid)): Path<(i64, i64)>,
+    body: String,
+) -> impl IntoResponse {
+    let Some(user) = auth_session.user else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    let owner_count = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM recording
+         JOIN presentation ON presentation.id = recording.presentation_id
+         WHERE recording.id = ? AND presentation.user_id = ?;",
+    )
+    .bind(rid)
+    .bind(user.id)
+    .fetch_one(&db)
+    .await;
+    if !matches!(owner_count, Ok(1)) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    let Ok(start_seconds) = body.trim().parse::<f64>() else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    let slide_count = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM recording_slide WHERE id = ? AND recording_id = ?;",
+    )
+    .bind(sid)
+    .bind(rid)
+    .fetch_one(&db)
+    .await;
+    if !matches!(slide_count, Ok(1)) {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    match RecordingSlide::update_start_seconds(sid, start_seconds, &db).await {
+        Ok(()) => StatusCode::OK.into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+async fn update_presentation_name(
+    State(db): State<SqlitePool>,
+    auth_session: AuthSession,
+    Path(pid): Path<i64>,
+    body: String,
+) -> impl IntoResponse {
+    let Some(user) = auth_session.user else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    let owner_count = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM presentation WHERE id = ? AND user_id = ?;",
+    )
+    .bind(pid)
+    .bind(user.id)
+    .fetch_one(&db)
+    .await;
+    if !matches!(owner_count, Ok(1)) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    // Presentation name: 200-char limit — consistent with the /create form validation.
+    if body.len() > 200 {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+    match DbPresentation::update_name(pid, body, &db).await {
+        Ok(()) => StatusCode::OK.into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+async fn update_recording_name(
+    State(db): State<SqlitePool>,
+    auth_session: AuthSession,
+    Path(rid): Path<i64>,
+    body: String,
+) -> impl IntoResponse {
+    let Some(user) = auth_session.user else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    let owner_count = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM recording
+         JOIN presentation ON presentation.id = recording.presentation_id
+         WHERE recording.id = ? AND presentation.user_id = ?;",
+    )
+    .bind(rid)
+    .bind(user.id)
+    .fetch_one(&db)
+    .await;
+    if !matches!(owner_count, Ok(1)) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    match Recording::update_name(rid, body, &db).await {
+        Ok(()) => StatusCode::OK.into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+async fn delete_recording(
+    State(db): State<SqlitePool>,
+    auth_session: AuthSession,
+    Path(rid): Path<i64>,
+) -> impl IntoResponse {
+    let Some(user) = auth_session.user else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    let owner_count = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM recording
+         JOIN presentation ON presentation.id = recording.presentation_id
+         WHERE recording.id = ? AND presentation.user_id = ?;",
+    )
+    .bind(rid)
+    .bind(user.id)
+    .fetch_one(&db)
+    .await;
+    if !matches!(owner_count, Ok(1)) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    match Recording::delete(rid, &db).await {
+        Ok(()) => Redirect::to("/user/presentations").into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+/// Ends a recording that a restart left unfinished, saving its timeline up to the last
+/// checkpoint.
+async fn finalise_recording(
+    State(state): State<AppState>,
+    auth_session: AuthSession,
+    Path(rid): Path<i64>,
+) -> impl IntoResponse {
+    let Some(user) = auth_session.user else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    let db = &state.db_pool;
+    let owner_count = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM recording
+         JOIN presentation ON presentation.id = recording.presentation_id
+         WHERE recording.id = ? AND presentation.user_id = ?;",
+    )
+    .bind(rid)
+    .bind(user.id)
+    .fetch_one(db)
+    .await;
+    if !matches!(owner_count, Ok(1)) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    let Ok(Some((pid, checkpoint))) = LiveState::get_for_recording(rid, db).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    // Once the presentation is opened again the recording is live, paused; stop it as
+    // the presenter would so that connected clients hear of it.
+    let live = state
+        .slides
+        .lock()
+        .ok()
+        .and_then(|slides| slides.get(&pid.to_string()).cloned())
+        .filter(|pres| pres.lock().is_ok_and(|p| p.recording.as_ref().is_some_and(|r| r.db_id == rid)));
+    if let Some(pres) = live {
+        if let Some(msg) = handle_recording_message(RecordingMessage::RecordingStop, &pres, pid, db).await
+            && let Ok(p) = pres.lock()
+        {
+            let _ = p.channel.0.send(msg);
+        }
+    } else {
+        let Ok(Some(db_pres)) = DbPresentation::get_by_id(pid, db).await else {
+            return StatusCode::NOT_FOUND.into_response();
+        };
+        save_recording(RecordingState::restore(checkpoint, String::new()), &db_pres.content, db).await;
+    }
+    Redirect::to(&format!("/{}/{pid}/{rid}/edit", user.name)).into_response()
+}
+
+async fn delete_presentation(
+    State(db): State<SqlitePool>,
+    auth_session: AuthSession,
+    Path(pid): Path<i64>,
+) -> impl IntoResponse {
+    let Some(user) = auth_session.user else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    let owned = matches!(DbPresentation::get_by_id(pid, &db).await, Ok(Some(p)) if p.user_id == user.id);
+    match DbPresentation::delete(pid, user.id, &db).await {
+        Ok(()) => {
+            if owned {
+                let dir = format!("{PRESENTATION_ASSET_DIR}/{pid}");
+                if let Err(e) = tokio::fs::remove_dir_all(&dir).await
+                    && e.kind() != std::io::ErrorKind::NotFound
+                {
+                    eprintln!("delete_presentation: remove {dir} failed: {e:?}");
+                }
+            }
+            Redirect::to("/user/presentations").into_response()
+        }
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+async fn add_access(
+    State(db): State<SqlitePool>,
+    auth_session: AuthSession,
+    Path(pid): Path<i64>,
+    Form(form): Form<AddAccessForm>,
+) -> impl IntoResponse {
+    let Some(user) = auth_session.user else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    let Ok(Some(pres)) = DbPresentation::get_by_id(pid, &db).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if pres.user_id != user.id {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    if !["editor", "controller", "captioner", "audience"].contains(&form.role.as_str()) {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+    let Ok(Some(target)) = User::get_by_name(form.username, &db).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    // Don't allow adding the owner as a co-presenter
+    if target.id == user.id {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+    match PresentationAccess::add(&db, pid, target.id, &form.role).await {
+        Ok(()) => Redirect::to("/user/presentations").into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+async fn remove_access(
+    State(db): State<SqlitePool>,
+    auth_session: AuthSession,
+    Path(pid): Path<i64>,
+    Form(form): Form<RemoveAccessForm>,
+) -> impl IntoResponse {
+    let Some(user) = auth_session.user else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    let Ok(Some(pres)) = DbPresentation::get_by_id(pid, &db).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if pres.user_id != user.id {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    // Don't allow removing the owner from their own presentation
+    if form.user_id == user.id {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+    match PresentationAccess::remove(&db, pid, form.user_id).await {
+        Ok(()) => Redirect::to("/user/presentations").into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+async fn change_access_role(
+    State(db): State<SqlitePool>,
+    auth_session: AuthSession,
+    Path(pid): Path<i64>,
+    Form(form): Form<ChangeRoleForm>,
+) -> impl IntoResponse {
+    let Some(user) = auth_session.user else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    let Ok(Some(pres)) = DbPresentation::get_by_id(pid, &db).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if pres.user_id != user.id {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    if !["editor", "controller", "captioner", "audience"].contains(&form.role.as_str()) {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+    match PresentationAccess::change_role(&db, pid, form.user_id, &form.role).await {
+        Ok(()) => Redirect::to("/user/presentations").into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+async fn set_presentation_access_mode(
+    State(db): State<SqlitePool>,
+    auth_session: AuthSession,
+    Path(pid): Path<i64>,
+    Form(form): Form<SetAccessModeForm>,
+) -> impl IntoResponse {
+    let Some(user) = auth_session.user else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    let Ok(Some(pres)) = DbPresentation::get_by_id(pid, &db).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if pres.user_id != user.id {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    if !["public", "audience", "private"].contains(&form.mode.as_str()) {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+    match DbPresentation::set_access_mode(pid, &form.mode, &db).await {
+        Ok(()) => Redirect::to("/user/presentations").into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+async fn set_recording_access_mode(
+    State(db): State<SqlitePool>,
+    auth_session: AuthSession,
+    Path(rid): Path<i64>,
+    Form(form): Form<SetRecordingAccessModeForm>,
+) -> impl IntoResponse {
+    let Some(user) = auth_session.user else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    let owner_count = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM recording
+         JOIN presentation ON presentation.id = recording.presentation_id
+         WHERE recording.id = ? AND presentation.user_id = ?",
+    )
+    .bind(rid)
+    .bind(user.id)
+    .fetch_one(&db)
+    .await;
+    if !matches!(owner_count, Ok(c) if c > 0) {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    let mode = if form.action == "inherit" {
+        None
+    } else {
+        match form.mode.as_deref() {
+            Some(m) if ["public", "audience", "private"].contains(&m) => Some(m.to_string()),
+            _ => return StatusCode::BAD_REQUEST.into_response(),
+        }
+    };
+    match Recording::set_access_mode(rid, mode.as_deref(), &db).await {
+        Ok(()) => Redirect::to("/user/presentations").into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+/// Streams the `video` and `captions` fields of a recording upload to the staging area.
+///
+/// Text fields are returned by name; any other file field is skipped unread.
+async fn stage_recording_upload(
+    multipart: &mut Multipart,
+    limit: UploadLimit,
+) -> Result<(HashMap<String, String>, Option<StagedFile>, Option<StagedFile>), StatusCode> {
+    let mut text = HashMap::new();
+    let mut video = None;
+    let mut captions = None;
+    loop {
+        let field = match multipart.next_field().await {
+            Ok(Some(field)) => field,
+            Ok(None) => break,
+            Err(e) => return Err(e.status()),
+        };
+        let name = field.name().unwrap_or_default().to_string();
+        let (slot, accept) = match name.as_str() {
+            "video" => (&mut video, MediaKind::VIDEO),
+            "captions" => (&mut captions, MediaKind::CAPTIONS),
+            _ => {
+                if field.file_name().is_none() {
+                    text.insert(name, field.text().await.map_err(|e| e.status())?);
+                }
+                continue;
+            }
+        };
+        match upload::stage(field, limit, accept).await {
+            Ok(Some(file)) => *slot = Some(file),
+            Ok(None) => {}
+            Err(e) => {
+                eprintln!("recording upload: {name}: {e}");
+                return Err(e.status());
+            }
+        }
+    }
+    Ok((text, video, captions))
+}
+
+/// Moves staged recording files into `dir`, returning the video's file name.
+///
+/// The video is named after its sniffed container, never the client's file name.
+async fn persist_recording_files(
+    dir: &str,
+    video: Option<StagedFile>,
+    captions: Option<StagedFile>,
+) -> std::io::Result<Option<String>> {
+    tokio::fs::create_dir_all(dir).await?;
+    let video_path = match video {
+        Some(file) => {
+            let name = format!("video.{}", file.kind().extension());
+            file.persist(&std::path::Path::new(dir).join(&name)).await?;
+            Some(name)
+        }
+        None => None,
+    };
+    if let Some(file) = captions {
+        file.persist(&std::path::Path::new(dir).join("captions.vtt")).await?;
+    }
+    Ok(video_path)
+}
+
+/// Removes a recording whose upload failed part way, files and all.
+async fn discard_recording(rid: i64, db: &SqlitePool) {
+    let _ = tokio::fs::remove_dir_all(format!("assets/{rid}")).await;
+    if let Err(e) = Recording::delete(rid, db).await {
+        eprintln!("discard_recording: delete {rid} failed: {e:?}");
+    }
+}
+
+async fn update_recording_files(
+    State(db): State<SqlitePool>,
+    State(limit): State<UploadLimit>,
+    auth_session: AuthSession,
+    Path(rid): Path<i64>,
+    mut multipart: Multipart,
+) -> impl IntoResponse {
+    let Some(user) = auth_session.user else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    let owner_count = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM recording
+         JOIN presentation ON presentation.id = recording.presentation_id
+         WHERE recording.id = ? AND presentation.user_id = ?;",
+    )
+    .bind(rid)
+    .bind(user.id)
+    .fetch_one(&db)
+    .await;
+    if !matches!(owner_count, Ok(1)) {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    let Ok(Some(rec)) = Recording::get_by_id(rid, &db).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    let (_, video, captions) = match stage_recording_upload(&mut multipart, limit).await {
+        Ok(staged) => staged,
+        Err(status) => return status.into_response(),
+    };
+    if video.is_none() && captions.is_none() {
+        return StatusCode::OK.into_response();
+    }
+    let has_captions = captions.is_some();
+
+    let dir = format!("assets/{rid}");
+    let video_path = match persist_recording_files(&dir, video, captions).await {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("update_recording_files: store in {dir} failed: {e:?}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    let video_path = video_path.or(rec.video_path.clone());
+    let captions_path = if has_captions { "captions.vtt" } else { rec.captions_path.as_str() };
+    if let Err(e) = sqlx::query(
+        "UPDATE recording SET video_path = ?, captions_path = ?, last_edited = strftime('%s', 'now') WHERE id = ?;",
+    )
+    .bind(&video_path)
+    .bind(captions_path)
+    .bind(rid)
+    .execute(&db)
+    .await
+    {
+        eprintln!("update_recording_files: update {rid} failed: {e:?}");
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    }
+    // A video in a different container leaves the old file behind; drop it.
+    if let Some(old) = rec.video_path
+        && video_path.as_deref() != Some(old.as_str())
+    {
+        let _ = tokio::fs::remove_file(format!("{dir}/{old}")).await;
+    }
+
+    StatusCode::OK.into_response()
+}
+
+async fn add_recording(
+    State(db): State<SqlitePool>,
+    State(limit): State<UploadLimit>,
+    auth_session: AuthSession,
+    Path(pid): Path<i64>,
+    mut multipart: Multipart,
+) -> impl IntoResponse {
+    let Some(user) = auth_session.user else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    let has_access = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM (
+            SELECT 1 FROM presentation WHERE id = ? AND user_id = ?
+            UNION ALL
+            SELECT 1 FROM presentation_access WHERE presentation_id = ? AND user_id = ? AND role IN ('controller', 'editor')
+        )",
+    )
+    .bind(pid)
+    .bind(user.id)
+    .bind(pid)
+    .bind(user.id)
+    .fetch_one(&db)
+    .await;
+    if !matches!(has_access, Ok(n) if n > 0) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    // Staged files are removed again by any early return below.
+    let (mut text, video, captions) = match stage_recording_upload(&mut multipart, limit).await {
+        Ok(staged) => staged,
+        Err(status) => return status.into_response(),
+    };
+    let name = text.remove("name").unwrap_or_default();
+    let Some(slides_str) = text.remove("slides").filter(|s| !s.is_empty()) else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    if name.is_empty() {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+    let Ok(slides) = serde_json::from_str::<Vec<RecordingSlideInput>>(&slides_str) else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+
+    let video_path = video.as_ref().map(|f| format!("video.{}", f.kind().extension()));
+    let has_captions = captions.is_some();
+    let rec = match Recording::create(pid, name, video_path, "captions.vtt".to_string(), &db).await {
+        Ok(r) => r,
+        Err(e) => { eprintln!("add_recording: Recording::create failed: {e:?}"); return StatusCode::INTERNAL_SERVER_ERROR.into_response(); }
+    };
+
+    let dir = format!("assets/{}", rec.id);
+    if let Err(e) = persist_recording_files(&dir, video, captions).await {
+        eprintln!("add_recording: store in {dir} failed: {e:?}");
+        discard_recording(rec.id, &db).await;
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    }
+    if !has_captions
+        && let Err(e) = tokio::fs::write(format!("{dir}/captions.vtt"), b"WEBVTT\n").await
+    {
+        eprintln!("add_recording: write captions failed: {e:?}");
+        discard_recording(rec.id, &db).await;
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    }
+    if let Err(e) = RecordingSlide::create_batch(rec.id, slides, &db).await {
+        eprintln!("add_recording: create_batch failed: {e:?}");
+        discard_recording(rec.id, &db).await;
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    }
+
+    Redirect::to("/user/presentations").into_response()
+}
+
+/// Writes an upload to the presentation's asset directory and returns its file name.
+///
+/// A name already taken gets a `2-`, `3-`, … prefix instead of being overwritten.
+async fn store_asset(pid: i64, name: &str, data: &[u8]) -> std::io::Result<String> {
+    let dir = format!("{PRESENTATION_ASSET_DIR}/{pid}");
+    tokio::fs::create_dir_all(&dir).await?;
+    let mut file_name = name.to_string();
+    let mut n = 1;
+    while tokio::fs::try_exists(format!("{dir}/{file_name}")).await? {
+        n += 1;
+        file_name = format!("{n}-{name}");
+    }
+    tokio::fs::write(format!("{dir}/{file_name}"), data).await?;
+    Ok(file_name)
+}
+
+/// The live presentation `pid`, if anyone has it open on this process.
+fn live_presentation(app_state: &AppState, pid: i64) -> Option<Arc<Mutex<Presentation>>> {
+    app_state.slides.lock().ok().and_then(|map| map.get(&pid.to_string()).map(Arc::clone))
+}
+
+/// Where the language forms send an editor back to.
+async fn languages_section(pid: i64, db: &SqlitePool) -> Response {
+    let Ok(Some(pres)) = DbPresentation::get_by_id(pid, db).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let Ok(Some(owner)) = User::get_by_id(pres.user_id, db).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    Redirect::to(&format!("/{}/{pid}/edit#languages-heading", owner.name)).into_response()
+}
+
+/// Sets the language the presentation's own Markdown is written in; an empty tag forgets it.
+async fn set_presentation_language(
+    State(db): State<SqlitePool>,
+    State(app_state): State<AppState>,
+    auth_session: AuthSession,
+    Path(pid): Path<i64>,
+    Form(form): Form<LanguageForm>,
+) -> impl IntoResponse {
+    let Some(user) = auth_session.user else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    let Ok(access) = check_access(&db, Some(&user), pid, None).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    if !matches!(access, AccessResult::Owner | AccessResult::Editor) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    let lang = match form.lang.trim() {
+        "" => None,
+        tag => match language_tag(tag) {
+            Some(tag) => Some(tag),
+            None => return (StatusCode::BAD_REQUEST, "Give a language tag such as en, fr or pt-BR.").into_response(),
+        },
+    };
+    if let Some(lang) = lang
+        && matches!(PresentationVariant::get(&db, pid, lang).await, Ok(Some(_)))
+    {
+        return (StatusCode::BAD_REQUEST, "The presentation already has a translation into this language.")
+            .into_response();
+    }
+    if DbPresentation::set_language(pid, lang, &db).await.is_err() {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    }
+    if let Some(Ok(mut p)) = live_presentation(&app_state, pid).as_ref().map(|p| p.lock()) {
+        p.lang = lang.map(String::from);
+        // No receivers only means nobody is connected to be told.
+        let _ = p.channel.0.send(SlideMessage::Language(p.lang.clone()));
+    }
+    languages_section(pid, &db).await
+}
+
+/// Adds or replaces a translation of the presentation, which must have the same slides.
+///
+/// Empty content starts the translation as a copy of the deck, to translate slide by slide.
+async fn save_presentation_variant(
+    State(db): State<SqlitePool>,
+    State(app_state): State<AppState>,
+    auth_session: AuthSession,
+    Path(pid): Path<i64>,
+    Form(form): Form<VariantForm>,
+) -> impl IntoResponse {
+    let Some(user) = auth_session.user else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    let Ok(access) = check_access(&db, Some(&user), pid, None).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    if !matches!(access, AccessResult::Owner | AccessResult::Editor) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    let Ok(Some(pres)) = DbPresentation::get_by_id(pid, &db).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let Some(lang) = language_tag(form.lang.trim()) else {
+        return (StatusCode::BAD_REQUEST, "Give a language tag such as en, fr or pt-BR.").into_response();
+    };
+    if DbPresentation::get_language(pid, &db).await.ok().flatten().as_deref() == Some(lang) {
+        return (StatusCode::BAD_REQUEST, "The presentation is already written in this language.").into_response();
+    }
+    // The live deck may have edits the database does not have yet.
+    let live = live_presentation(&app_state, pid);
+    let deck = live.as_ref().and_then(|p| p.lock().ok().map(|p| p.content.clone())).unwrap_or(pres.content);
+    let content = if form.content.trim().is_empty() { deck.clone() } else { form.content };
+    let (slides, expected) = (parse_deck(&content).slide_starts.len(), parse_deck(&deck).slide_starts.len());
+    if slides != expected {
+        let message = format!("The translation has {slides} slides but the presentation has {expected}; give it one for each.");
+        return (StatusCode::BAD_REQUEST, message).into_response();
+    }
+    if !slides_within_size_limit(&content) {
+        return (StatusCode::BAD_REQUEST, "Each slide can be at most 100 KB.").into_response();
+    }
+    if PresentationVariant::save(&db, pid, lang, &content).await.is_err() {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    }
+    if let Some(Ok(mut p)) = live.as_ref().map(|p| p.lock()) {
+        p.variants.insert(lang.to_string(), content.clone());
+        let _ = p.channel.0.send(SlideMessage::Variant { lang: lang.to_string(), text: Some(content) });
+    }
+    languages_section(pid, &db).await
+}
+
+/// Removes the presentation's translation into `lang`.
+async fn delete_presentation_variant(
+    State(db): State<SqlitePool>,
+    State(app_state): State<AppState>,
+    auth_session: AuthSession,
+    Path((pid, lang)): Path<(i64, String)>,
+) -> impl IntoResponse {
+    let Some(user) = auth_session.user else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    let Ok(access) = check_access(&db, Some(&user), pid, None).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    if !matches!(access, AccessResult::Owner | AccessResult::Editor) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    if PresentationVariant::delete(&db, pid, &lang).await.is_err() {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    }
+    if let Some(Ok(mut p)) = live_presentation(&app_state, pid).as_ref().map(|p| p.lock())
+        && p.variants.remove(&lang).is_some()
+    {
+        let _ = p.channel.0.send(SlideMessage::Variant { lang, text: None });
+    }
+    languages_section(pid, &db).await
+}
+
+/// Uploads an image, audio or video file for use on a presentation's slides.
+///
+/// Alt text is required. Files never overwrite earlier uploads, so slides keep
+/// pointing at what they showed before.
+async fn add_asset(
+    State(db): State<SqlitePool>,
+    auth_session: AuthSession,
+    Path(pid): Path<i64>,
+    mut multipart: Multipart,
+) -> impl IntoResponse {
+    let Some(user) = auth_session.user else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    let Ok(access) = check_access(&db, Some(&user), pid, None).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    if !matches!(access, AccessResult::Owner | AccessResult::Editor) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    let mut alt_text = String::new();
+    let mut upload: Option<(String, Vec<u8>)> = None;
+    while let Ok(Some(field)) = multipart.next_field().await {
+        match field.name().unwrap_or("") {
+            "alt" => {
+                alt_text = field.text().await.unwrap_or_default();
+            }
+            "file" => {
+                let name = asset_file_name(field.file_name().unwrap_or_default());
+                if let Ok(b) = field.bytes().await
+                    && !b.is_empty()
+                {
+                    upload = Some((name, b.to_vec()));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let Some((name, data)) = upload else {
+        return (StatusCode::BAD_REQUEST, "Choose a file to upload.").into_response();
+    };
+    let alt_text = alt_text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if alt_text.is_empty() {
+        return (StatusCode::BAD_REQUEST, "Describe the file in its alt text.").into_response();
+    }
+    // Alt text: 1000-char limit — long descriptions belong in the slide itself.
+    if alt_text.len() > 1000 {
+        return (StatusCode::BAD_REQUEST, "Alt text can be at most 1000 characters.").into_response();
+    }
+    if asset_mime_type(std::path::Path::new(&name)).is_none() {
+        return (
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "Upload an image (PNG, JPEG, GIF, SVG, WebP), audio (MP3, Ogg, WAV) or video (MP4, WebM) file.",
+        )
+            .into_response();
+    }
+
+    let file_name = match store_asset(pid, &name, &data).await {
+        Ok(file_name) => file_name,
+        Err(e) => {
+            eprintln!("add_asset: storing {name} failed: {e:?}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    if let Err(e) = PresentationAsset::create(&db, pid, &file_name, &alt_text).await {
+        eprintln!("add_asset: PresentationAsset::create failed: {e:?}");
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    }
+
+    let Ok(Some(pres)) = DbPresentation::get_by_id(pid, &db).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    let Ok(Some(owner)) = User::get_by_id(pres.user_id, &db).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    Redirect::to(&format!("/{}/{pid}/edit#assets-heading", owner.name)).into_response()
+}
+
+/// Serves a presentation's uploaded file to anyone who may view the presentation.
+async fn presentation_asset(
+    State(db): State<SqlitePool>,
+    auth_session: AuthSession,
+    headers: HeaderMap,
+    Path((pid, file)): Path<(i64, String)>,
+) -> impl IntoResponse {
+    let Ok(access) = check_access(&db, auth_session.user.as_ref(), pid, None).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    if matches!(access, AccessResult::Denied) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    // Only names an upload could have been given; this also rules out `..` and `/`.
+    if asset_file_name(&file) != file {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    let Some(mime) = asset_mime_type(std::path::Path::new(&file)) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let mut response = serve_file(&format!("{PRESENTATION_ASSET_DIR}/{pid}/{file}"), mime, &headers).await;
+    // SVG can carry scripts; opened directly, it must not run them on this origin.
+    response.headers_mut().insert(
+        axum::http::header::CONTENT_SECURITY_POLICY,
+        axum::http::HeaderValue::from_static("sandbox"),
+    );
+    response
+}
+
+/// The part of a file a `Range` request header asks for.
+#[derive(Debug, PartialEq)]
+enum ByteRange {
+    /// No usable range: send the whole file.
+    Full,
+    /// Bytes `start..=end` of the file.
+    Partial { start: u64, end: u64 },
+    /// The range lies entirely past the end of the file.
+    Unsatisfiable,
+}
+
+/// Parses a `Range` header for a file of `len` bytes.
+///
+/// Only a single `bytes=` range is honoured. Multiple ranges and malformed headers
+/// fall back to the whole file, which RFC 9110 allows.
+fn byte_range(header: Option<&str>, len: u64) -> ByteRange {
+    let Some(spec) = header.and_then(|h| h.trim().strip_prefix("bytes=")) else {
+        return ByteRange::Full;
+    };
+    let Some((first, last)) = spec.trim().split_once('-') else {
+        return ByteRange::Full;
+    };
+    if first.is_empty() {
+        // `bytes=-n`: the final n bytes.
+        return match last.parse::<u64>() {
+            Ok(0) => ByteRange::Unsatisfiable,
+            Ok(_) if len == 0 => ByteRange::Unsatisfiable,
+            Ok(n) => ByteRange::Partial { start: len.saturating_sub(n), end: len - 1 },
+            Err(_) => ByteRange::Full,
+        };
+    }
+    let Ok(start) = first.parse::<u64>() else {
+        return ByteRange::Full;
+    };
+    let end = match last {
+        "" => u64::MAX,
+        last => match last.parse::<u64>() {
+            Ok(end) if end >= start => end,
+            _ => return ByteRange::Full,
+        },
+    };
+    if start >= len {
+        return ByteRange::Unsatisfiable;
+    }
+    ByteRange::Partial { start, end: end.min(len - 1) }
+}
+
+/// Streams the file at `path`, honouring a single-range `Range` request.
+///
+/// Callers check access first; responses are marked private so shared caches
+/// never keep them.
+async fn serve_file(path: &str, mime: &'static str, headers: &HeaderMap) -> Response {
+    use axum::http::header;
+    use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+    let Ok(mut file) = tokio::fs::File::open(path).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let len = match file.metadata().await {
+        Ok(meta) if meta.is_file() => meta.len(),
+        Ok(_) => return StatusCode::NOT_FOUND.into_response(),
+        Err(e) => {
+            eprintln!("serve_file: metadata {path} failed: {e:?}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    let range = byte_range(headers.get(header::RANGE).and_then(|v| v.to_str().ok()), len);
+    let (status, start, count) = match range {
+        ByteRange::Full => (StatusCode::OK, 0, len),
+        ByteRange::Partial { start, end } => (StatusCode::PARTIAL_CONTENT, start, end - start + 1),
+        ByteRange::Unsatisfiable => {
+            return (
+                StatusCode::RANGE_NOT_SATISFIABLE,
+                [(header::CONTENT_RANGE, format!("bytes */{len}"))],
+            )
+                .into_response();
+        }
+    };
+    if let Err(e) = file.seek(std::io::SeekFrom::Start(start)).await {
+        eprintln!("serve_file: seek {path} failed: {e:?}");
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    }
+    let body = Body::from_stream(tokio_util::io::ReaderStream::new(file.take(count)));
+    let mut response = (
+        status,
+        [
+            (header::CONTENT_TYPE, mime.to_string()),
+            (header::CONTENT_LENGTH, count.to_string()),
+            (header::ACCEPT_RANGES, "bytes".to_string()),
+            // Access-checked content must not be kept by shared caches.
+            (header::CACHE_CONTROL, "private".to_string()),
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+        ],
+        body,
+    )
+        .into_response();
+    if status == StatusCode::PARTIAL_CONTENT
+        && let Ok(value) = format!("bytes {start}-{}/{len}", start + count - 1).parse()
+    {
+        response.headers_mut().insert(header::CONTENT_RANGE, value);
+    }
+    response
+}
+
+/// Largest accepted import or asset upload; both are read into memory whole.
+const IN_MEMORY_UPLOAD_LIMIT: usize = 50 * 1024 * 1024;
+
+/// Routes that accept file uploads: recordings with a body limit sized for video, and
+/// imports and assets with a fixed one.
+fn upload_routes(limit: UploadLimit) -> Router<AppState> {
+    let recordings = Router::new()
+        .route(
+            "/user/presentations/{pid}/recordings",
+            post(add_recording),
+        )
+        .route(
+            "/user/recordings/{rid}/files",
+            post(update_recording_files),
+        )
+        // Defaults to 50 MB (common video-hosting constraints such as Mux or Vimeo Basic);
+        // raise `APP_UPLOAD_LIMIT_MB` for long lectures. Recordings stream to disk, so the
+        // limit bounds disk use, not memory.
+        .layer(DefaultBodyLimit::max(limit.body_limit()));
+    Router::new()
+        .route("/create/import", post(import_pres))
+        .route("/user/presentations/{pid}/assets", post(add_asset))
+        // These are held in memory while they are read, so raising the recording limit
+        // must not raise theirs.
+        .layer(DefaultBodyLimit::max(IN_MEMORY_UPLOAD_LIMIT))
+        .merge(recordings)
+}
+
+/// Builds the application router and state from an already-migrated database pool.
+///
+/// Accepts any `SqlitePool` (file-based or in-memory). The caller is responsible
+/// for running migrations before passing the pool in. Returns both the router (for
+/// serving) and the app state (so the caller can retain it for signal handling).
+/// `upload_limit` caps recording uploads, see [`UploadLimit`].
+pub async fn build_app(
+    db_pool: SqlitePool,
+    upload_limit: UploadLimit,
+    pubsub: Arc<dyn PubSub>,
+) -> (Router, AppState) {
+    let session_store = SqliteStore::new(db_pool.clone());
+    session_store.migrate().await.unwrap();
+    let session_layer = SessionManagerLayer::new(session_store)
+        // with_secure(false): Caddy terminates TLS; this binary binds to localhost:5002 only.
+        // Session cookies are never sent over plain HTTP in production.
+        .with_secure(false)
+        .with_expiry(Expiry::OnInactivity(Duration::days(1)));
+    let tera = Tera::new();
+    let backend = Backend::new(db_pool.clone());
+    let auth_layer = AuthManagerLayerBuilder::new(backend, session_layer).build();
+    let node = pubsub.node();
+    let state = AppState {
+        tera,
+        slides: Arc::new(Mutex::new(HashMap::new())),
+        db_pool,
+        upload_limit,
+        pubsub,
+        node,
+        evictions: Arc::new(EvictionStats::default()),
+    };
+    // Rate-limit POST /auth/login: 5 attempts per IP per minute (OWASP brute-force prevention).
+    // Burst of 5 with one token replenished every 12 s gives a sustained rate of 5/minute.
+    // ClientIpExtractor reads X-Forwarded-For (set by Caddy) and falls back to 127.0.0.1
+    // in dev/test environments where no proxy header is present.
+    let login_rate_limit = {
+        let mut builder = GovernorConfigBuilder::default();
+        builder.per_second(12);
+        builder.burst_size(5);
+        let conf = builder.key_extractor(ClientIpExtractor).finish().unwrap();
+        GovernorLayer::new(Arc::new(conf))
+    };
+    let router = Router::new()
+        .route("/", get(index))
+        .route("/auth/login", get(login))
+        .route("/auth/login", post(login_process).layer(login_rate_limit))
+        .route("/auth/logout", get(logout))
+        .route("/user/presentations", get(presentations))
+        .route("/user/recordings/{rid}/delete", post(delete_recording))
+        .route("/user/recordings/{rid}/finalise", post(finalise_recording))
+        .route("/user/presentations/{pid}/delete", post(delete_presentation))
+        .route("/user/presentations/{pid}/access/add", post(add_access))
+        .route("/user/presentations/{pid}/access/remove", post(remove_access))
+        .route("/user/presentations/{pid}/language", post(set_presentation_language))
+        .route("/user/presentations/{pid}/variants", post(save_presentation_variant))
+        .route("/user/presentations/{pid}/variants/{lang}/delete", post(delete_presentation_variant))
+        .route(
+            "/user/presentations/{pid}/revisions/{rev_id}/restore",
+            post(restore_revision),
+        )
+        .route("/users/exists", get(user_exists))
+        .route(
+            "/user/presentations/{pid}/access/change-role",
+            post(change_access_role),
+        )
+        .route(
+            "/user/presentations/{pid}/access/mode",
+            post(set_presentation_access_mode),
+        )
+        .route("/user/recordings/{rid}/access/mode", post(set_recording_access_mode))
+        .route(
+            "/user/recordings/{rid}/slides/{sid}/time",
+            post(update_slide_time),
+        )
+        .route("/user/recordings/{rid}/name", post(update_recording_name))
+        .route(
+            "/user/presentations/{pid}/name",
+            post(update_presentation_name),
+        )
+        .route("/user/change_pwd", get(change_pwd))
+        .route("/user/change_pwd", post(change_pwd_form))
+        .route("/user/new", get(new_user))
+        .route("/user/new", post(new_user_form))
+        .route("/admin/live", get(live_stats))
+        .route("/join", get(join))
+        .route("/create", get(start))
+        .route("/create", post(start_pres))
+        .route("/{uname}/{pid}", get(present))
+        .route("/qr/{uname}/{pid}", get(qr_code))
+        .route("/ws/{pid}", get(broadcast_to_all))
+        .route("/protocol/schema.json", get(protocol_schema))
+        .route("/demo", get(demo))
+        .route("/help", get(help))
+        .route("/{uname}/{pid}/edit", get(edit_pres))
+        .route("/{uname}/{pid}/transcript", get(transcript))
+        .route("/{uname}/{pid}/export.html", get(export_html))
+        .route("/{uname}/{pid}/export.zip", get(export_zip))
+        .route("/{uname}/{pid}/presence", get(presence))
+        .route("/{uname}/{pid}/pacing", get(pacing_report))
+        .route("/{uname}/{pid}/captions", post(ingest_captions))
+        .route("/{uname}/{pid}/history", get(history))
+        .route("/{uname}/{pid}/history/{rev_id}", get(revision_diff))
+        .route("/{uname}/{pid}/{rid}", get(recording))
+        .route("/{uname}/{pid}/{rid}/edit", get(edit_recording))
+        .route("/{uname}/{pid}/{rid}/slides.vtt", get(slides_vtt))
+        .route("/{uname}/{pid}/{rid}/slides.html", get(slides_html))
+        .route("/{uname}/{pid}/{rid}/files/{file}", get(recording_file))
+        .route("/assets/presentations/{pid}/{file}", get(presentation_asset))
+        .nest_service("/css", ServeDir::new("css/"))
+        .nest_service("/js", ServeDir::new("js/"))
+        .merge(upload_routes(state.upload_limit))
+        .with_state(state.clone())
+        .layer(auth_layer);
+    (router, state)
+}
+
+/// Idle period after which live presentations are evicted, when `APP_IDLE_EVICT_MINUTES`
+/// is unset or invalid.
+const DEFAULT_IDLE_EVICT_MINUTES: u64 = 15;
+
+/// Longest time between two eviction sweeps.
+const EVICTION_SWEEP: std::time::Duration = std::time::Duration::from_mins(1);
+
+/// How long a live presentation may go without connections, from `APP_IDLE_EVICT_MINUTES`.
+fn idle_eviction_after(minutes: Option<&str>) -> std::time::Duration {
+    let minutes = minutes
+        .and_then(|m| m.trim().parse::<u64>().ok())
+        .filter(|&m| m > 0)
+        .unwrap_or(DEFAULT_IDLE_EVICT_MINUTES);
+    std::time::Duration::from_secs(minutes.saturating_mul(60))
+}
+
+/// What idle eviction has removed since the server started.
+#[derive(Debug, Default)]
+struct EvictionStats {
+    /// Live presentations dropped from memory.
+    presentations: AtomicU64,
+    /// Recordings stopped and saved because nobody was left to stop them.
+    recordings_stopped: AtomicU64,
+}
+
+/// Drops the live presentations nobody has been connected to for `idle_after`, after
+/// saving their pending edits and stopping any recording left running.
+///
+/// Returns how many were evicted. A presentation someone connects to while it is being
+/// saved stays.
+async fn evict_idle(state: &AppState, idle_after: std::time::Duration) -> usize {
+    let idle = |pres: &Arc<Mutex<Presentation>>| {
+        pres.lock().is_ok_and(|p| p.idle_since.is_some_and(|since| since.elapsed() >= idle_after))
+    };
+    let candidates: Vec<(String, Arc<Mutex<Presentation>>)> = {
+        let Ok(slides) = state.slides.lock() else {
+            eprintln!("K/V store mutex poisoned in evict_idle");
+            return 0;
+        };
+        slides
+            .iter()
+            .filter(|(_, pres)| Arc::strong_count(pres) == 1 && idle(pres))
+            .map(|(pid, pres)| (pid.clone(), Arc::clone(pres)))
+            .collect()
+    };
+    let mut evicted = 0;
+    for (pid, pres) in candidates {
+        let (writer, recording) = match pres.lock() {
+            Ok(p) => (p.writer.clone(), p.recording.is_some()),
+            Err(_) => (None, false),
+        };
+        if recording {
+            let pid_i64 = pid.parse::<i64>().unwrap_or(-1);
+            handle_recording_message(RecordingMessage::RecordingStop, &pres, pid_i64, &state.db_pool).await;
+            state.evictions.recordings_stopped.fetch_add(1, Ordering::Relaxed);
+        }
+        if let Some(writer) = writer {
+            writer.flush().await;
+        }
+        let Ok(mut slides) = state.slides.lock() else { break; };
+        // Held by the map and by `pres` only: nobody connected in the meantime.
+        if Arc::strong_count(&pres) == 2 && idle(&pres) {
+            slides.remove(&pid);
+            evicted += 1;
+        }
+    }
+    state.evictions.presentations.fetch_add(evicted as u64, Ordering::Relaxed);
+    evicted
+}
+
+/// Evicts idle presentations for as long as the server runs.
+async fn evictor(state: AppState, idle_after: std::time::Duration) {
+    let mut sweep = tokio::time::interval(EVICTION_SWEEP.min(idle_after));
+    loop {
+        sweep.tick().await;
+        let evicted = evict_idle(&state, idle_after).await;
+        if evicted > 0 {
+            let total = state.evictions.presentations.load(Ordering::Relaxed);
+            eprintln!("[evict] presentations={evicted} total={total}");
+        }
+    }
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let port = std::env::var("APP_PORT").unwrap_or_else(|_| "5002".to_string());
+    let db_url = std::env::var("APP_DB").unwrap_or_else(|_| "sqlite://db.sqlite3".to_string());
+    let mut signals = Signals::new([SIGTERM, SIGINT]).unwrap();
+    let migrate_pool = SqlitePool::connect_with(
+        SqliteConnectOptions::from_str(&db_url)
+            .unwrap()
+            .create_if_missing(true)
+            .foreign_keys(false),
+    )
+    .await
+    .unwrap();
+    sqlx::migrate!("./migrations").run(&migrate_pool).await.unwrap();
+    migrate_pool.close().await;
+    let db_pool = SqlitePool::connect_with(
+        SqliteConnectOptions::from_str(&db_url)
+            .unwrap()
+            .create_if_missing(true)
+            .foreign_keys(true),
+    )
+    .await
+    .unwrap();
+    // Unset keeps live presentations within this process.
+    let pubsub_url = std::env::var("APP_PUBSUB_URL").ok();
+    let pubsub = pubsub::connect(pubsub_url.as_deref()).await.unwrap();
+    // Whatever this process was recording when it last stopped was cut short, and so was
+    // whatever a process that stopped renewing its lease was.
+    match Recording::mark_interrupted(Some(pubsub.node()), &db_pool).await {
+        Ok(0) => {}
+        Ok(n) => eprintln!("{n} recording(s) were interrupted by the last shutdown"),
+        Err(e) => eprintln!("mark_interrupted failed: {e:?}"),
+    }
+    tokio::spawn(sweep_interrupted(db_pool.clone()));
+    let (app, state) = build_app(db_pool, UploadLimit::from_env(), pubsub).await;
+    let listener = tokio::net::TcpListener::bind(format!("0.0.0.0:{port}"))
+        .await
+        .unwrap();
+    let idle_after = idle_eviction_after(std::env::var("APP_IDLE_EVICT_MINUTES").ok().as_deref());
+    tokio::spawn(evictor(state.clone(), idle_after));
+    // SIGTERM and SIGINT stop the server.
+    let shutdown = async move {
+        signals.next().await;
+    };
+    let server = async {
+        axum::serve(listener, app).await.unwrap();
+    };
+    or(server, shutdown).await;
+    // Open connections go down with the server; save the edits they left pending.
+    flush_all(&state).await;
+}
+
+#[cfg(test)]
+#[allow(clippy::pedantic, missing_docs)]
+mod tests {
+    use super::*;
+    use axum_test::TestServer;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    /// Creates a `TestServer` backed by a fresh isolated in-memory database.
+    ///
+    /// Uses `max_connections(1)` so that all queries share one SQLite connection
+    /// (and therefore one in-memory database). Migrations run with FK enforcement
+    /// off (some migrations DROP TABLE), then FK enforcement is enabled before
+    /// handing the pool to `build_app`.
+    ///
+    /// Returns both the server and the app state. The test has access to
+    /// `state.db_pool` for seeding data before making requests.
+    async fn test_server() -> (TestServer, AppState) {
+        test_server_with_limit(UploadLimit(50 * 1024 * 1024)).await
+    }
+
+    /// Like [`test_server`], with a custom upload limit.
+    async fn test_server_with_limit(upload_limit: UploadLimit) -> (TestServer, AppState) {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(
+                SqliteConnectOptions::from_str("sqlite::memory:")
+                    .unwrap()
+                    .foreign_keys(false),
+            )
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        // Enable FK enforcement on the single connection now that migrations are done.
+        sqlx::query("PRAGMA foreign_keys = ON")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let (router, state) = build_app(pool, upload_limit, Arc::new(pubsub::InMemory::default())).await;
+        // save_cookies() makes the TestServer persist Set-Cookie headers between
+        // requests, which is how session auth is maintained across test steps.
+        //
+        // API note: if this call does not compile for the installed version of
+        // axum-test, check the crate docs for the equivalent cookie persistence
+        // configuration (look for TestServerConfig, save_cookies, or similar).
+        let server = TestServer::builder()
+            .save_cookies()
+            .build(router)
+            .unwrap();
+        (server, state)
+    }
+
+    /// Seeds one user into the database using the same `User::new` path the app uses.
+    ///
+    /// The groups table row for id=1 ("admin") is created by migrations.
+    /// Do not re-insert it. This user is not added to any group; group membership
+    /// is not needed for basic login and session tests.
+    async fn seed_user(pool: &SqlitePool) {
+        User::new(
+            pool,
+            AddUserForm {
+                name: "testuser".to_string(),
+                email: "test@example.com".to_string(),
+                password: "testpass".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+    }
+
+    async fn seed_admin_user(pool: &SqlitePool) {
+        User::new(
+            pool,
+            AddUserForm {
+                name: "adminuser".to_string(),
+                email: "admin2@example.com".to_string(),
+                password: "adminpass".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO group_users (user_id, group_id) VALUES ((SELECT id FROM users WHERE name = 'adminuser'), 1)")
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    async fn login_as(server: &axum_test::TestServer, username: &str, password: &str) {
+        server
+            .post("/auth/login")
+            .form(&serde_json::json!({ "username": username, "password": password }))
+            .await;
+    }
+
+    async fn seed_presentation(user_id: i64, name: &str, pool: &SqlitePool) -> i64 {
+        sqlx::query_scalar::<_, i64>(
+            "INSERT INTO presentation (name, user_id, content) VALUES (?, ?, '') RETURNING id",
+        )
+        .bind(name)
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    async fn get_user_id(name: &str, pool: &SqlitePool) -> i64 {
+        sqlx::query_scalar::<_, i64>("SELECT id FROM users WHERE name = ?")
+            .bind(name)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    /// POST /create with a valid name must redirect to /{username}/{pid}/edit.
+    #[tokio::test]
+    async fn create_presentation_redirects_to_edit() {
+        let (server, state) = test_server().await;
+        seed_user(&state.db_pool).await;
+        login_as(&server, "testuser", "testpass").await;
+
+        let response = server
+            .post("/create")
+            .form(&serde_json::json!({ "name": "Test Pres" }))
+            .await;
+
+        assert_eq!(response.status_code(), 303);
+        let location = response.headers()["location"].to_str().unwrap();
+        assert!(
+            location.starts_with("/testuser/") && location.ends_with("/edit"),
+            "create must redirect to /{{username}}/{{pid}}/edit, got: {location}"
+        );
+    }
+
+    /// Deleting your own presentation must redirect to /user/presentations
+    /// and the row must be gone from the database.
+    #[tokio::test]
+    async fn delete_own_presentation_removes_it() {
+        let (server, state) = test_server().await;
+        seed_user(&state.db_pool).await;
+        let uid = get_user_id("testuser", &state.db_pool).await;
+        let pid = seed_presentation(uid, "To Delete", &state.db_pool).await;
+        login_as(&server, "testuser", "testpass").await;
+
+        let response = server
+            .post(&format!("/user/presentations/{pid}/delete"))
+            .await;
+
+        assert_eq!(response.status_code(), 303);
+        assert_eq!(response.headers()["location"], "/user/presentations");
+
+        let count: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM presentation WHERE id = ?")
+                .bind(pid)
+                .fetch_one(&state.db_pool)
+                .await
+                .unwrap();
+        assert_eq!(count, 0, "deleted presentation must not exist in the database");
+    }
+
+    /// Attempting to delete another user's presentation must leave it intact.
+    /// The handler redirects (303) but the ownership check in the SQL means
+    /// no row is deleted when user_id does not match.
+    #[tokio::test]
+    async fn delete_other_users_presentation_is_noop() {
+        let (server, state) = test_server().await;
+        seed_user(&state.db_pool).await;
+        seed_admin_user(&state.db_pool).await;
+        let owner_id = get_user_id("adminuser", &state.db_pool).await;
+        let pid = seed_presentation(owner_id, "Owner's Pres", &state.db_pool).await;
+        // Log in as a different user (testuser) and try to delete adminuser's presentation.
+        login_as(&server, "testuser", "testpass").await;
+
+        server
+            .post(&format!("/user/presentations/{pid}/delete"))
+            .await;
+
+        let count: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM presentation WHERE id = ?")
+                .bind(pid)
+                .fetch_one(&state.db_pool)
+                .await
+                .unwrap();
+        assert_eq!(count, 1, "another user's presentation must not be deleted");
+    }
+
+    /// POST /user/presentations/{pid}/name with a plain-text body must return 200.
+    #[tokio::test]
+    async fn rename_presentation_returns_200() {
+        let (server, state) = test_server().await;
+        seed_user(&state.db_pool).await;
+        let uid = get_user_id("testuser", &state.db_pool).await;
+        let pid = seed_presentation(uid, "Old Name", &state.db_pool).await;
+        login_as(&server, "testuser", "testpass").await;
+
+        let response = server
+            .post(&format!("/user/presentations/{pid}/name"))
+            .text("New Name")
+            .await;
+
+        assert_eq!(response.status_code(), 200);
+    }
+
+    /// Successful login must redirect to `/` (HTTP 303 See Other).
+    #[tokio::test]
+    async fn login_correct_credentials_redirects_to_home() {
+        let (server, state) = test_server().await;
+        seed_user(&state.db_pool).await;
+
+        let response = server
+            .post("/auth/login")
+            .form(&serde_json::json!({
+                "username": "testuser",
+                "password": "testpass"
+            }))
+            .await;
+
+        assert_eq!(response.status_code(), 303);
+        assert_eq!(
+            response.headers()["location"],
+            "/",
+            "successful login must redirect to /"
+        );
+    }
+
+    /// Wrong password must re-render the login page (HTTP 200), not redirect.
+    #[tokio::test]
+    async fn login_wrong_password_returns_login_page() {
+        let (server, state) = test_server().await;
+        seed_user(&state.db_pool).await;
+
+        let response = server
+            .post("/auth/login")
+            .form(&serde_json::json!({
+                "username": "testuser",
+                "password": "wrongpass"
+            }))
+            .await;
+
+        assert_eq!(
+            response.status_code(),
+            200,
+            "wrong password should return 200 (re-render login page), not redirect"
+        );
+        assert!(
+            response.text().contains("Invalid username or password."),
+            "wrong password must show an error message (WCAG 3.3.1 Error Identification)"
+        );
+    }
+
+    /// Accessing a protected route without a session must redirect to `/auth/login`.
+    #[tokio::test]
+    async fn presentations_without_session_redirects_to_login() {
+        let (server, _state) = test_server().await;
+
+        let response = server.get("/user/presentations").await;
+
+        assert_eq!(response.status_code(), 303);
+        assert_eq!(
+            response.headers()["location"],
+            "/auth/login",
+            "unauthenticated request must redirect to /auth/login"
+        );
+    }
+
+    /// Inserts a recording row for the given presentation and returns its id.
+    async fn seed_recording(presentation_id: i64, pool: &SqlitePool) -> i64 {
+        sqlx::query_scalar::<_, i64>(
+            "INSERT INTO recording (presentation_id, name, captions_path, start) VALUES (?, 'Test Recording', 'captions.vtt', '2026-01-01T00:00:00+00:00') RETURNING id",
+        )
+        .bind(presentation_id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    /// Inserts a recording_slide row for the given recording and returns its id.
+    async fn seed_recording_slide(recording_id: i64, pool: &SqlitePool) -> i64 {
+        sqlx::query_scalar::<_, i64>(
+            "INSERT INTO recording_slide (recording_id, start_seconds, position, title, content) VALUES (?, 0.0, 0, 'Slide 1', 'content') RETURNING id",
+        )
+        .bind(recording_id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    /// Deleting a presentation must remove all its recording_slide rows.
+    /// recording_slide has no ON DELETE CASCADE — DbPresentation::delete
+    /// performs the cleanup manually. If this ever breaks, recording_slide
+    /// rows become orphaned and FK enforcement will block future deletions.
+    #[tokio::test]
+    async fn delete_presentation_removes_recording_slides() {
+        let (_server, state) = test_server().await;
+        // test_server seeds admin/admin from migrations; use that directly.
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(uid, "With Recordings", &state.db_pool).await;
+        let rid = seed_recording(pid, &state.db_pool).await;
+        let sid = seed_recording_slide(rid, &state.db_pool).await;
+
+        DbPresentation::delete(pid, uid, &state.db_pool).await.unwrap();
+
+        let pres_count: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM presentation WHERE id = ?")
+                .bind(pid)
+                .fetch_one(&state.db_pool)
+                .await
+                .unwrap();
+        let rec_count: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM recording WHERE id = ?")
+                .bind(rid)
+                .fetch_one(&state.db_pool)
+                .await
+                .unwrap();
+        let slide_count: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM recording_slide WHERE id = ?")
+                .bind(sid)
+                .fetch_one(&state.db_pool)
+                .await
+                .unwrap();
+
+        assert_eq!(pres_count, 0, "presentation row must be deleted");
+        assert_eq!(rec_count, 0, "recording row must be deleted");
+        assert_eq!(slide_count, 0, "recording_slide row must be deleted (manual cascade)");
+    }
+
+    /// POST /user/new by a non-admin authenticated user must return 404.
+    /// The handler explicitly returns NOT_FOUND (not 403) to avoid leaking
+    /// the existence of the admin-only endpoint to non-admin users.
+    #[tokio::test]
+    async fn create_user_by_non_admin_returns_404() {
+        let (server, state) = test_server().await;
+        seed_user(&state.db_pool).await;
+        // testuser has no group membership — not in the admin group.
+        login_as(&server, "testuser", "testpass").await;
+
+        let response = server
+            .post("/user/new")
+            .form(&serde_json::json!({
+                "name": "newuser",
+                "email": "new@example.com",
+                "password": "password123"
+            }))
+            .await;
+
+        assert_eq!(
+            response.status_code(),
+            404,
+            "non-admin must not be able to create users"
+        );
+        // The user must not have been created.
+        let count: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM users WHERE name = 'newuser'")
+                .fetch_one(&state.db_pool)
+                .await
+                .unwrap();
+        assert_eq!(count, 0, "new user must not exist after rejected request");
+    }
+
+    /// After a successful login, the session cookie must grant access to protected routes.
+    #[tokio::test]
+    async fn presentations_with_valid_session_returns_200() {
+        let (server, state) = test_server().await;
+        seed_user(&state.db_pool).await;
+
+        // Establish a session. The TestServer saves the Set-Cookie from this
+        // response and sends it on subsequent requests.
+        server
+            .post("/auth/login")
+            .form(&serde_json::json!({
+                "username": "testuser",
+                "password": "testpass"
+            }))
+            .await;
+
+        let response = server.get("/user/presentations").await;
+        assert_eq!(
+            response.status_code(),
+            200,
+            "authenticated request should return 200"
+        );
+    }
+
+    // --- FAQ merge tests ---
+
+    #[tokio::test]
+    async fn test_home_faq_presenter_section_removed() {
+        let (server, _state) = test_server().await;
+        let response = server.get("/").await;
+        response.assert_status_ok();
+        let body = response.text();
+        assert!(
+            !body.contains("For Presenters"),
+            "home page should not contain the For Presenters heading after merge"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_home_faq_audience_section_intact() {
+        let (server, _state) = test_server().await;
+        let response = server.get("/").await;
+        response.assert_status_ok();
+        let body = response.text();
+        assert!(
+            body.contains("How does an audience member follow along"),
+            "home page should still contain audience FAQ questions"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_help_editing_slides_expanded() {
+        let (server, _state) = test_server().await;
+        let response = server.get("/help").await;
+        response.assert_status_ok();
+        let body = response.text();
+        assert!(
+            body.contains("KaTeX"),
+            "help page should mention KaTeX math support"
+        );
+        assert!(
+            body.contains("saved as you type"),
+            "help page should mention autosave"
+        );
+        assert!(
+            body.contains("pushed to all connected audience members instantly"),
+            "help page should mention live sync pushing edits instantly"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_help_recording_expanded() {
+        let (server, _state) = test_server().await;
+        let response = server.get("/help").await;
+        response.assert_status_ok();
+        let body = response.text();
+        assert!(
+            body.contains("WebVTT"),
+            "help page should mention WebVTT export in the Recording section"
+        );
+        assert!(
+            body.contains("12.5s"),
+            "help page should show dropdown label format example including timestamp"
+        );
+    }
+
+    /// The presentation_access table must exist after migrations and accept
+    /// a valid (presentation_id, user_id, role) row.
+    #[tokio::test]
+    async fn presentation_access_table_exists_and_accepts_rows() {
+        let (_server, state) = test_server().await;
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(uid, "Access Test", &state.db_pool).await;
+
+        // Insert a second user to be the co-presenter
+        User::new(
+            &state.db_pool,
+            AddUserForm {
+                name: "copresenter".to_string(),
+                email: "co@example.com".to_string(),
+                password: "copass".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+        let co_uid = get_user_id("copresenter", &state.db_pool).await;
+
+        sqlx::query(
+            "INSERT INTO presentation_access (presentation_id, user_id, role) VALUES (?, ?, 'editor')",
+        )
+        .bind(pid)
+        .bind(co_uid)
+        .execute(&state.db_pool)
+        .await
+        .expect("presentation_access table must accept a valid row");
+
+        let role: String = sqlx::query_scalar(
+            "SELECT role FROM presentation_access WHERE presentation_id = ? AND user_id = ?",
+        )
+        .bind(pid)
+        .bind(co_uid)
+        .fetch_one(&state.db_pool)
+        .await
+        .unwrap();
+        assert_eq!(role, "editor");
+    }
+
+    /// A Controller role must not be permitted to send a Name message.
+    /// handle_socket must return Ok(true) (keep connection open, message dropped).
+    #[tokio::test]
+    async fn ws_controller_cannot_send_name_message() {
+        let (_server, state) = test_server().await;
+        let (tx, _rx) = tokio::sync::broadcast::channel::<SlideMessage>(8);
+        let mut tx = tx;
+        let msg = axum::extract::ws::Message::text(
+            serde_json::to_string(&SlideMessage::Name("hacked".to_string())).unwrap(),
+        );
+        let mut state_clone = state.clone();
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(uid, "WS Test", &state.db_pool).await;
+        let (direct, mut replies) = tokio::sync::mpsc::unbounded_channel();
+        let result = handle_socket(Ok(msg), &pid.to_string(), &mut tx, &mut state_clone, &AccessResult::Controller, &direct);
+        assert!(
+            matches!(result, Ok(true)),
+            "Controller sending Name must be dropped (Ok(true)), not close the connection"
+        );
+        assert!(matches!(
+            replies.try_recv(),
+            Ok(SlideMessage::Error(ProtocolError { code: ErrorCode::Unauthorized, fatal: false, message_type: Some(t), .. })) if t == "name"
+        ));
+    }
+
+    /// An Editor role must be permitted to send a Slide message.
+    #[tokio::test]
+    async fn ws_editor_can_send_slide_message() {
+        let (_server, state) = test_server().await;
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(uid, "WS Test 2", &state.db_pool).await;
+        let mut state_clone = state.clone();
+
+        {
+            state_clone.slides.lock().unwrap().insert(
+                pid.to_string(),
+                Arc::new(Mutex::new(Presentation::new(String::new()))),
+            );
+        }
+
+        let (tx, mut rx) = tokio::sync::broadcast::channel::<SlideMessage>(8);
+        let mut tx_clone = tx;
+        let msg = axum::extract::ws::Message::text(
+            serde_json::to_string(&SlideMessage::Slide(2)).unwrap(),
+        );
+        let (direct, _replies) = tokio::sync::mpsc::unbounded_channel();
+        let result = handle_socket(Ok(msg), &pid.to_string(), &mut tx_clone, &mut state_clone, &AccessResult::Editor, &direct);
+        assert!(matches!(result, Ok(true)), "Editor must be able to send Slide");
+        let received = rx.try_recv();
+        assert!(
+            matches!(received, Ok(SlideMessage::Slide(2))),
+            "Slide message must be broadcast when sent by Editor"
+        );
+    }
+
+    /// Audience connections must not receive recording control messages from the broadcast channel.
+    ///
+    /// This mirrors the server-side filtering introduced to prevent data exposure: recording
+    /// start/pause/resume/stop events are only meaningful to presenter connections (Owner /
+    /// Editor / Controller) and must be silently dropped before they reach audience or public
+    /// subscribers.  `Text`, `Slide`, and `Name` messages must still pass through so that the
+    /// audience view continues to update.
+    ///
+    /// Analogous real-world precedent: Google Docs broadcasts cursor and selection events only to
+    /// participants with edit access and never to public-link viewers.
+    #[tokio::test]
+    async fn audience_connection_does_not_receive_recording_messages() {
+        use tokio::sync::broadcast;
+
+        let (tx, _rx_main) = broadcast::channel::<SlideMessage>(16);
+
+        // Simulate the audience subscriber's receiver.
+        let mut rx_audience = tx.subscribe();
+
+        // Publish each recording control variant as if a presenter had triggered them.
+        let recording_msgs: Vec<SlideMessage> = vec![
+            SlideMessage::RecordingStart { elapsed_ms: 0 },
+            SlideMessage::RecordingPause { elapsed_ms: 500 },
+            SlideMessage::RecordingResume { elapsed_ms: 500 },
+            SlideMessage::RecordingStop {
+                id: 1,
+                name: "test".to_string(),
+                start: "2026-01-01".to_string(),
+            },
+        ];
+        for m in &recording_msgs {
+            assert!(tx.send(m.clone()).is_ok(), "broadcast send must succeed");
+        }
+
+        // Also send allowed message types that the audience view needs.
+        assert!(tx.send(SlideMessage::Slide(3)).is_ok());
+        assert!(tx.send(SlideMessage::Text("# Hello".to_string())).is_ok());
+        assert!(tx.send(SlideMessage::Name("My Talk".to_string())).is_ok());
+
+        // Apply the filter the server-side channel_handler uses for audience connections.
+        let viewer = Viewer { connection_id: 0, is_presenter: false, is_audience: true, is_editor: false };
+        let mut allowed = vec![];
+        while let Ok(msg) = rx_audience.try_recv() {
+            allowed.extend(viewer.view(&msg));
+        }
+
+        assert_eq!(allowed.len(), 3, "audience must receive exactly 3 non-recording messages");
+        assert!(
+            matches!(allowed[0], SlideMessage::Slide(3)),
+            "audience must receive Slide"
+        );
+        assert!(
+            matches!(allowed[1], SlideMessage::Text(_)),
+            "audience must receive Text"
+        );
+        assert!(
+            matches!(allowed[2], SlideMessage::Name(_)),
+            "audience must receive Name"
+        );
+    }
+
+    /// POST /user/presentations/{pid}/access/add by the owner must insert the row
+    /// and redirect to /user/presentations.
+    #[tokio::test]
+    async fn add_access_as_owner_inserts_row() {
+        let (server, state) = test_server().await;
+        seed_user(&state.db_pool).await;
+        let uid = get_user_id("testuser", &state.db_pool).await;
+        let pid = seed_presentation(uid, "Shared Pres", &state.db_pool).await;
+        // Create a second user to add as co-presenter
+        User::new(
+            &state.db_pool,
+            AddUserForm {
+                name: "couser".to_string(),
+                email: "co@example.com".to_string(),
+                password: "copass".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+        login_as(&server, "testuser", "testpass").await;
+
+        let response = server
+            .post(&format!("/user/presentations/{pid}/access/add"))
+            .form(&serde_json::json!({ "username": "couser", "role": "editor" }))
+            .await;
+
+        assert_eq!(response.status_code(), 303);
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM presentation_access WHERE presentation_id = ?",
+        )
+        .bind(pid)
+        .fetch_one(&state.db_pool)
+        .await
+        .unwrap();
+        assert_eq!(count, 1, "access row must be inserted");
+    }
+
+    /// POST .../access/add by a non-owner must return 404.
+    #[tokio::test]
+    async fn add_access_by_non_owner_returns_404() {
+        let (server, state) = test_server().await;
+        seed_user(&state.db_pool).await;
+        seed_admin_user(&state.db_pool).await;
+        let owner_id = get_user_id("adminuser", &state.db_pool).await;
+        let pid = seed_presentation(owner_id, "Owner Pres", &state.db_pool).await;
+        login_as(&server, "testuser", "testpass").await;
+
+        let response = server
+            .post(&format!("/user/presentations/{pid}/access/add"))
+            .form(&serde_json::json!({ "username": "adminuser", "role": "editor" }))
+            .await;
+
+        assert_eq!(response.status_code(), 404);
+    }
+
+    /// POST .../access/remove by the owner must delete the row.
+    #[tokio::test]
+    async fn remove_access_as_owner_deletes_row() {
+        let (server, state) = test_server().await;
+        seed_user(&state.db_pool).await;
+        let uid = get_user_id("testuser", &state.db_pool).await;
+        let pid = seed_presentation(uid, "Rm Pres", &state.db_pool).await;
+        User::new(
+            &state.db_pool,
+            AddUserForm {
+                name: "couser2".to_string(),
+                email: "co2@example.com".to_string(),
+                password: "copass2".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+        let co_uid = get_user_id("couser2", &state.db_pool).await;
+        PresentationAccess::add(&state.db_pool, pid, co_uid, "editor").await.unwrap();
+        login_as(&server, "testuser", "testpass").await;
+
+        let response = server
+            .post(&format!("/user/presentations/{pid}/access/remove"))
+            .form(&serde_json::json!({ "user_id": co_uid }))
+            .await;
+
+        assert_eq!(response.status_code(), 303);
+        let count: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM presentation_access WHERE presentation_id = ?")
+                .bind(pid)
+                .fetch_one(&state.db_pool)
+                .await
+                .unwrap();
+        assert_eq!(count, 0, "access row must be deleted");
+    }
+
+    /// POST .../access/change-role by the owner must update the role in the DB.
+    #[tokio::test]
+    async fn change_access_role_as_owner_updates_role() {
+        let (server, state) = test_server().await;
+        seed_user(&state.db_pool).await;
+        let uid = get_user_id("testuser", &state.db_pool).await;
+        let pid = seed_presentation(uid, "Change Role Pres", &state.db_pool).await;
+        User::new(
+            &state.db_pool,
+            AddUserForm {
+                name: "couser3".to_string(),
+                email: "co3@example.com".to_string(),
+                password: "copass3".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+        let co_uid = get_user_id("couser3", &state.db_pool).await;
+        PresentationAccess::add(&state.db_pool, pid, co_uid, "editor").await.unwrap();
+        login_as(&server, "testuser", "testpass").await;
+
+        let response = server
+            .post(&format!("/user/presentations/{pid}/access/change-role"))
+            .form(&serde_json::json!({ "user_id": co_uid, "role": "controller" }))
+            .await;
+
+        assert_eq!(response.status_code(), 303);
+        let role: String = sqlx::query_scalar(
+            "SELECT role FROM presentation_access WHERE presentation_id = ? AND user_id = ?",
+        )
+        .bind(pid)
+        .bind(co_uid)
+        .fetch_one(&state.db_pool)
+        .await
+        .unwrap();
+        assert_eq!(role, "controller", "role must be updated to controller");
+    }
+
+    /// GET /{uname}/{pid} by an editor must redirect to the stage (same as owner).
+    /// The response is 200 (stage.html is rendered, not a redirect — stage() renders directly).
+    /// stage.html does not contain the markdown editor; that is in edit.html.
+    #[tokio::test]
+    async fn editor_gets_stage_access() {
+        let (server, state) = test_server().await;
+        seed_user(&state.db_pool).await;
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(uid, "Editor Stage Test", &state.db_pool).await;
+        User::new(
+            &state.db_pool,
+            AddUserForm {
+                name: "editoruser".to_string(),
+                email: "ed@example.com".to_string(),
+                password: "edpass".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+        let ed_uid = get_user_id("editoruser", &state.db_pool).await;
+        PresentationAccess::add(&state.db_pool, pid, ed_uid, "editor").await.unwrap();
+        login_as(&server, "editoruser", "edpass").await;
+
+        let response = server.get(&format!("/admin/{pid}")).await;
+
+        assert_eq!(response.status_code(), 200);
+        assert!(
+            response.text().contains(r#"id="recordPause""#),
+            "editor must see the record button on stage"
+        );
+        assert!(
+            !response.text().contains(r#"id="markdown-input""#),
+            "editor must not see the markdown editor on stage"
+        );
+    }
+
+    /// GET /user/presentations must include presentations shared with the user.
+    #[tokio::test]
+    async fn presentations_list_includes_shared() {
+        let (server, state) = test_server().await;
+        seed_user(&state.db_pool).await;
+        let admin_id = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(admin_id, "Shared With Testuser", &state.db_pool).await;
+        let testuser_id = get_user_id("testuser", &state.db_pool).await;
+        PresentationAccess::add(&state.db_pool, pid, testuser_id, "editor").await.unwrap();
+        login_as(&server, "testuser", "testpass").await;
+
+        let response = server.get("/user/presentations").await;
+
+        assert_eq!(response.status_code(), 200);
+        assert!(
+            response.text().contains("Shared With Testuser"),
+            "shared presentation must appear in testuser's list"
+        );
+    }
+
+    /// GET /user/presentations must link shared presentations using the owner's username, not the viewer's.
+    #[tokio::test]
+    async fn presentations_list_shared_link_uses_owner_name() {
+        let (server, state) = test_server().await;
+        seed_user(&state.db_pool).await;
+        let admin_id = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(admin_id, "Owner Link Test", &state.db_pool).await;
+        let testuser_id = get_user_id("testuser", &state.db_pool).await;
+        PresentationAccess::add(&state.db_pool, pid, testuser_id, "editor").await.unwrap();
+        login_as(&server, "testuser", "testpass").await;
+
+        let response = server.get("/user/presentations").await;
+        let body = response.text();
+
+        assert!(
+            body.contains(&format!("/admin/{pid}")),
+            "shared presentation link must use owner's username (admin), not viewer's"
+        );
+        assert!(
+            !body.contains(&format!("/testuser/{pid}")),
+            "shared presentation link must not use viewer's username"
+        );
+    }
+
+    /// GET /{uname}/{pid} by a controller must get stage access.
+    #[tokio::test]
+    async fn controller_gets_stage_access() {
+        let (server, state) = test_server().await;
+        seed_user(&state.db_pool).await;
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(uid, "Controller Stage Test", &state.db_pool).await;
+        User::new(
+            &state.db_pool,
+            AddUserForm {
+                name: "ctrluser".to_string(),
+                email: "ctrl@example.com".to_string(),
+                password: "ctrlpass".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+        let ctrl_uid = get_user_id("ctrluser", &state.db_pool).await;
+        PresentationAccess::add(&state.db_pool, pid, ctrl_uid, "controller").await.unwrap();
+        login_as(&server, "ctrluser", "ctrlpass").await;
+
+        let response = server.get(&format!("/admin/{pid}")).await;
+        assert_eq!(response.status_code(), 200);
+        assert!(
+            response.text().contains(r#"id="recordPause""#),
+            "controller must see the record button on stage"
+        );
+        assert!(
+            !response.text().contains(r#"id="markdown-input""#),
+            "controller must not see the markdown editor"
+        );
+    }
+
+    /// GET /{editor_name}/{pid} must redirect 308 (permanent) to /{owner_name}/{pid}.
+    #[tokio::test]
+    async fn non_owner_uname_redirects_to_canonical_url() {
+        let (server, state) = test_server().await;
+        seed_user(&state.db_pool).await;
+        let owner_uid = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(owner_uid, "Canon Test", &state.db_pool).await;
+        let editor_uid = get_user_id("testuser", &state.db_pool).await;
+        PresentationAccess::add(&state.db_pool, pid, editor_uid, "editor").await.unwrap();
+        login_as(&server, "testuser", "testpass").await;
+
+        let response = server.get(&format!("/testuser/{pid}")).await;
+
+        assert_eq!(response.status_code(), 308);
+        let location = response.headers()["location"].to_str().unwrap();
+        assert_eq!(location, &format!("/admin/{pid}"));
+    }
+
+    /// GET /{nonexistent_name}/{pid} must still return generic audience (no change).
+    #[tokio::test]
+    async fn nonexistent_uname_returns_audience() {
+        let (server, state) = test_server().await;
+        let owner_uid = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(owner_uid, "Uname Test", &state.db_pool).await;
+
+        let response = server.get(&format!("/nobody/{pid}")).await;
+
+        assert_eq!(response.status_code(), 200);
+    }
+
+    /// GET /users/exists?username=testuser returns 200 when user exists and caller is authenticated.
+    #[tokio::test]
+    async fn user_exists_returns_200_for_known_user() {
+        let (server, state) = test_server().await;
+        seed_user(&state.db_pool).await;
+        login_as(&server, "testuser", "testpass").await;
+        let response = server
+            .get("/users/exists")
+            .add_query_param("username", "testuser")
+            .await;
+        assert_eq!(response.status_code(), 200);
+    }
+
+    /// GET /users/exists?username=nobody returns 404 when user does not exist.
+    #[tokio::test]
+    async fn user_exists_returns_404_for_unknown_user() {
+        let (server, state) = test_server().await;
+        seed_user(&state.db_pool).await;
+        login_as(&server, "testuser", "testpass").await;
+        let response = server
+            .get("/users/exists")
+            .add_query_param("username", "nobody")
+            .await;
+        assert_eq!(response.status_code(), 404);
+    }
+
+    /// GET /users/exists without the username query param must return 400 Bad Request.
+    #[tokio::test]
+    async fn user_exists_returns_400_when_username_missing() {
+        let (server, state) = test_server().await;
+        seed_user(&state.db_pool).await;
+        login_as(&server, "testuser", "testpass").await;
+        let response = server.get("/users/exists").await;
+        assert_eq!(response.status_code(), 400);
+    }
+
+    /// GET /users/exists without a session must return 401 Unauthorized.
+    #[tokio::test]
+    async fn user_exists_requires_auth() {
+        let (server, _state) = test_server().await;
+        // axum-test does not follow redirects — the raw response status is returned.
+        let response = server
+            .get("/users/exists")
+            .add_query_param("username", "admin")
+            .await;
+        assert_eq!(response.status_code(), 401u16);
+    }
+
+    /// GET /users/exists without session AND without username param must return 401, not 400.
+    #[tokio::test]
+    async fn user_exists_unauthenticated_missing_param_returns_401() {
+        let (server, _state) = test_server().await;
+        let response = server.get("/users/exists").await;
+        assert_eq!(response.status_code(), 401u16);
+    }
+
+    /// POST /user/presentations/{pid}/access/mode must save the mode and redirect.
+    #[tokio::test]
+    async fn set_presentation_access_mode_as_owner() {
+        let (server, state) = test_server().await;
+        seed_user(&state.db_pool).await;
+        let uid = get_user_id("testuser", &state.db_pool).await;
+        let pid = seed_presentation(uid, "Mode Test", &state.db_pool).await;
+        login_as(&server, "testuser", "testpass").await;
+
+        let resp = server
+            .post(&format!("/user/presentations/{pid}/access/mode"))
+            .form(&serde_json::json!({ "mode": "private" }))
+            .await;
+        assert_eq!(resp.status_code(), 303);
+
+        let mode: String = sqlx::query_scalar("SELECT access_mode FROM presentation WHERE id = ?")
+            .bind(pid)
+            .fetch_one(&state.db_pool)
+            .await
+            .unwrap();
+        assert_eq!(mode, "private");
+    }
+
+    /// POST /user/presentations/{pid}/access/mode by a non-owner must return 404.
+    #[tokio::test]
+    async fn set_presentation_access_mode_non_owner_returns_404() {
+        let (server, state) = test_server().await;
+        seed_user(&state.db_pool).await;
+        let admin_uid = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(admin_uid, "Admin Pres", &state.db_pool).await;
+        login_as(&server, "testuser", "testpass").await;
+
+        let resp = server
+            .post(&format!("/user/presentations/{pid}/access/mode"))
+            .form(&serde_json::json!({ "mode": "private" }))
+            .await;
+        assert_eq!(resp.status_code(), 404);
+    }
+
+    /// POST /user/recordings/{rid}/access/mode must save the mode.
+    #[tokio::test]
+    async fn set_recording_access_mode_as_owner() {
+        let (server, state) = test_server().await;
+        seed_user(&state.db_pool).await;
+        let uid = get_user_id("testuser", &state.db_pool).await;
+        let pid = seed_presentation(uid, "Rec Mode Test", &state.db_pool).await;
+        login_as(&server, "testuser", "testpass").await;
+
+        let rid: i64 = sqlx::query_scalar(
+            "INSERT INTO recording (presentation_id, name, captions_path)
+             VALUES (?, 'Rec', 'rec.vtt') RETURNING id"
+        )
+        .bind(pid)
+        .fetch_one(&state.db_pool)
+        .await
+        .unwrap();
+
+        let resp = server
+            .post(&format!("/user/recordings/{rid}/access/mode"))
+            .form(&serde_json::json!({ "action": "set", "mode": "private" }))
+            .await;
+        assert_eq!(resp.status_code(), 303);
+
+        let mode: Option<String> = sqlx::query_scalar("SELECT access_mode FROM recording WHERE id = ?")
+            .bind(rid)
+            .fetch_one(&state.db_pool)
+            .await
+            .unwrap();
+        assert_eq!(mode, Some("private".to_string()));
+    }
+
+    /// POST /user/recordings/{rid}/access/mode with action=inherit must set NULL.
+    #[tokio::test]
+    async fn set_recording_access_mode_inherit() {
+        let (server, state) = test_server().await;
+        seed_user(&state.db_pool).await;
+        let uid = get_user_id("testuser", &state.db_pool).await;
+        let pid = seed_presentation(uid, "Rec Inherit Test", &state.db_pool).await;
+        login_as(&server, "testuser", "testpass").await;
+
+        let rid: i64 = sqlx::query_scalar(
+            "INSERT INTO recording (presentation_id, name, captions_path, access_mode)
+             VALUES (?, 'Rec', 'rec.vtt', 'private') RETURNING id"
+        )
+        .bind(pid)
+        .fetch_one(&state.db_pool)
+        .await
+        .unwrap();
+
+        let resp = server
+            .post(&format!("/user/recordings/{rid}/access/mode"))
+            .form(&serde_json::json!({ "action": "inherit" }))
+            .await;
+        assert_eq!(resp.status_code(), 303);
+
+        let mode: Option<String> = sqlx::query_scalar("SELECT access_mode FROM recording WHERE id = ?")
+            .bind(rid)
+            .fetch_one(&state.db_pool)
+            .await
+            .unwrap();
+        assert!(mode.is_none(), "inherit action must set access_mode to NULL");
+    }
+
+    /// POST /user/presentations/{pid}/access/add with role=audience must insert the row.
+    #[tokio::test]
+    async fn add_audience_member_as_owner() {
+        let (server, state) = test_server().await;
+        seed_user(&state.db_pool).await;
+        let uid = get_user_id("testuser", &state.db_pool).await;
+        let pid = seed_presentation(uid, "Audience Test", &state.db_pool).await;
+        User::new(
+            &state.db_pool,
+            AddUserForm {
+                name: "vieweruser".to_string(),
+                email: "viewer@example.com".to_string(),
+                password: "viewerpass".to_string(),
+            },
+        ).await.unwrap();
+        login_as(&server, "testuser", "testpass").await;
+
+        let resp = server
+            .post(&format!("/user/presentations/{pid}/access/add"))
+            .form(&serde_json::json!({ "username": "vieweruser", "role": "audience" }))
+            .await;
+        assert_eq!(resp.status_code(), 303);
+
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM presentation_access WHERE presentation_id = ? AND role = 'audience'"
+        )
+        .bind(pid)
+        .fetch_one(&state.db_pool)
+        .await
+        .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    /// GET /{uname}/{pid}/{rid} must return 403 for unauthenticated access on a private presentation.
+    #[tokio::test]
+    async fn recording_handler_denies_access_in_private_mode() {
+        let (server, state) = test_server().await;
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(uid, "Private Rec Test", &state.db_pool).await;
+
+        sqlx::query("UPDATE presentation SET access_mode = 'private' WHERE id = ?")
+            .bind(pid)
+            .execute(&state.db_pool)
+            .await
+            .unwrap();
+
+        let rid: i64 = sqlx::query_scalar(
+            "INSERT INTO recording (presentation_id, name, captions_path)
+             VALUES (?, 'Rec', 'rec.vtt') RETURNING id"
+        )
+        .bind(pid)
+        .fetch_one(&state.db_pool)
+        .await
+        .unwrap();
+
+        let resp = server
+            .get(&format!("/admin/{pid}/{rid}"))
+            .await;
+        assert_eq!(resp.status_code(), 403);
+    }
+
+    /// GET /{uname}/{pid}/{rid}/edit by the owner must return 200 with the edit-rec-heading.
+    #[tokio::test]
+    async fn owner_gets_edit_recording_page() {
+        let (server, state) = test_server().await;
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(uid, "Edit Rec Test", &state.db_pool).await;
+        let rid = seed_recording(pid, &state.db_pool).await;
+        login_as(&server, "admin", "admin").await;
+
+        let resp = server.get(&format!("/admin/{pid}/{rid}/edit")).await;
+        assert_eq!(resp.status_code(), 200);
+        assert!(
+            resp.text().contains(r#"id="edit-rec-heading""#),
+            "edit recording page must have edit-rec-heading"
+        );
+    }
+
+    /// GET /{uname}/{pid}/{rid}/edit without login must redirect to /auth/login.
+    #[tokio::test]
+    async fn unauthenticated_edit_recording_redirects() {
+        let (server, state) = test_server().await;
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(uid, "Edit Rec Auth Test", &state.db_pool).await;
+        let rid = seed_recording(pid, &state.db_pool).await;
+
+        let resp = server.get(&format!("/admin/{pid}/{rid}/edit")).await;
+        assert_eq!(resp.status_code(), 303);
+    }
+
+    /// GET /{uname}/{pid}/edit by the owner must serve the edit page.
+    #[tokio::test]
+    async fn owner_gets_edit_page() {
+        let (server, state) = test_server().await;
+        seed_user(&state.db_pool).await;
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(uid, "Edit Page Test", &state.db_pool).await;
+        login_as(&server, "admin", "admin").await;
+
+        let response = server.get(&format!("/admin/{pid}/edit")).await;
+        assert_eq!(response.status_code(), 200);
+        assert!(
+            response.text().contains(r#"id="edit-heading""#),
+            "edit page must have the edit-heading H1"
+        );
+        assert!(
+            response.text().contains(r#"id="markdown-input""#),
+            "edit page must have the markdown textarea"
+        );
+        assert!(
+            !response.text().contains(r#"id="recordPause""#),
+            "edit page must not have the record button"
+        );
+    }
+
+    /// GET /{uname}/{pid}/edit by a controller must redirect to the stage page.
+    #[tokio::test]
+    async fn controller_edit_redirects_to_stage() {
+        let (server, state) = test_server().await;
+        seed_user(&state.db_pool).await;
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(uid, "Edit Redirect Test", &state.db_pool).await;
+        User::new(
+            &state.db_pool,
+            AddUserForm {
+                name: "ctrluser2".to_string(),
+                email: "ctrl2@example.com".to_string(),
+                password: "ctrlpass2".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+        let ctrl_uid = get_user_id("ctrluser2", &state.db_pool).await;
+        PresentationAccess::add(&state.db_pool, pid, ctrl_uid, "controller").await.unwrap();
+        login_as(&server, "ctrluser2", "ctrlpass2").await;
+
+        // axum-test does not follow redirects; verify the redirect then follow manually.
+        let redirect_resp = server.get(&format!("/admin/{pid}/edit")).await;
+        assert!(
+            redirect_resp.status_code().is_redirection(),
+            "edit route must redirect a controller"
+        );
+        let location = redirect_resp
+            .headers()
+            .get("location")
+            .expect("redirect must have a Location header")
+            .to_str()
+            .unwrap()
+            .to_string();
+        let stage_resp = server.get(&location).await;
+        assert_eq!(stage_resp.status_code(), 200);
+        assert!(
+            stage_resp.text().contains(r#"id="recordPause""#),
+            "controller redirected to stage should see recordPause"
+        );
+    }
+
+    /// A controller must be able to POST to add_recording.
+    #[tokio::test]
+    async fn controller_can_add_recording() {
+        let (server, state) = test_server().await;
+        seed_user(&state.db_pool).await;
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(uid, "Recording Perm Test", &state.db_pool).await;
+        User::new(
+            &state.db_pool,
+            AddUserForm {
+                name: "ctrlrec".to_string(),
+                email: "ctrlrec@example.com".to_string(),
+                password: "ctrlrecpass".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+        let ctrl_uid = get_user_id("ctrlrec", &state.db_pool).await;
+        PresentationAccess::add(&state.db_pool, pid, ctrl_uid, "controller").await.unwrap();
+        login_as(&server, "ctrlrec", "ctrlrecpass").await;
+
+        // Multipart form with required fields: name + slides JSON
+        let form = axum_test::multipart::MultipartForm::new()
+            .add_text("name", "Test Recording")
+            .add_text("slides", "[]");
+        let response = server
+            .post(&format!("/user/presentations/{pid}/recordings"))
+            .multipart(form)
+            .await;
+        // Must not be 403
+        assert_ne!(
+            response.status_code(),
+            axum::http::StatusCode::FORBIDDEN,
+            "controller must not be forbidden from adding a recording"
+        );
+    }
+
+    /// render_all_slides must return one (title, html) pair per ## heading.
+    #[test]
+    fn render_all_slides_splits_on_h2() {
+        let md = "## Intro\nHello world\n\n## Second\nContent here";
+        let slides = render_all_slides(md);
+        assert_eq!(slides.len(), 2);
+        assert_eq!(slides[0].0, "Intro");
+        assert!(slides[0].1.contains("Hello world"), "got: {}", slides[0].1);
+        assert_eq!(slides[1].0, "Second");
+        assert!(slides[1].1.contains("Content here"), "got: {}", slides[1].1);
+    }
+
+    /// render_all_slides must return empty vec for content with no ## headings.
+    #[test]
+    fn render_all_slides_empty_for_no_headings() {
+        let slides = render_all_slides("just some text");
+        assert_eq!(slides.len(), 0);
+    }
+
+    /// Raw HTML in Markdown content must be stripped by render_slide and render_all_slides
+    /// to prevent stored XSS (OWASP A03).
+    #[test]
+    fn render_slide_strips_raw_html() {
+        let md = "## Slide 1\n<script>alert('xss')</script>\n\nSafe text";
+        // render_slide must not include the <script> tag
+        let html = render_slide(md, 0, 0, "", None);
+        assert!(
+            !html.contains("<script"),
+            "render_slide must strip <script> tags, got: {html}"
+        );
+        assert!(html.contains("Safe text"), "safe content must still render");
+
+        // render_all_slides must also strip raw HTML
+        let slides = render_all_slides(md);
+        assert_eq!(slides.len(), 1);
+        assert!(
+            !slides[0].1.contains("<script"),
+            "render_all_slides must strip <script> tags, got: {}",
+            slides[0].1
+        );
+    }
 
-    let dir = format!("assets/{rid}");
+    /// Speaker notes after a `???` line must never reach audience-facing renders.
+    #[test]
+    fn speaker_notes_are_stripped_from_rendered_slides() {
+        let md = "## Intro\nHello\n\n???\nRemember the joke\n\n## Second\nWorld";
+        let html = render_slide(md, 0, 0, "", None);
+        assert!(html.contains("Hello"), "slide content must still render, got: {html}");
+        assert!(!html.contains("Remember the joke"), "notes leaked into render_slide: {html}");
+        assert!(!html.contains("???"), "notes marker leaked into render_slide: {html}");
 
-    if let Some((video_data, video_ext)) = video_bytes {
-        let video_filename = format!("video.{video_ext}");
-        if tokio::fs::write(format!("{dir}/{video_filename}"), &video_data).await.is_err() {
-            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        let slides = render_all_slides(md);
+        assert_eq!(slides.len(), 2);
+        assert!(!slides[0].1.contains("Remember the joke"));
+        assert!(slides[1].1.contains("World"));
+
+        let stripped = strip_speaker_notes(md);
+        assert!(!stripped.contains("Remember the joke"), "got: {stripped}");
+        assert!(stripped.contains("## Second\nWorld"), "got: {stripped}");
+    }
+
+    /// slide_notes must return the notes of the requested slide only.
+    #[test]
+    fn slide_notes_returns_notes_for_slide() {
+        let md = "## Intro\nHello\n???\nFirst notes\nline two\n\n## Second\nWorld";
+        assert_eq!(slide_notes(md, 0), "First notes\nline two");
+        assert_eq!(slide_notes(md, 1), "");
+        assert_eq!(slide_notes(md, 7), "");
+    }
+
+    /// `--` and `++` must split a slide into steps revealed one at a time, and never
+    /// show up themselves.
+    #[test]
+    fn fragments_reveal_slide_step_by_step() {
+        let md = "## Quiz\nQuestion?\n\n--\n\nAnswer\n\n++\n\n- first\n- second\n\n--\n\n## Nested\n- --\n\n> ++\n\n## Trailing\nAll\n\n--\n";
+        let shown = |fragment| render_slide(md, 0, fragment, "", None);
+        assert!(shown(0).contains("Question?") && !shown(0).contains("Answer"));
+        assert!(shown(1).contains("Answer") && !shown(1).contains("<ul>"), "a stepped list appears with its first item");
+        assert!(shown(2).contains("first") && !shown(2).contains("second"));
+        assert!(shown(3).contains("second"));
+        assert_eq!(shown(3), shown(9));
+        assert_eq!(slide_steps(md, 0), 3);
+        for fragment in 0..4 {
+            assert!(!shown(fragment).contains("<p>\u{2013}") && !shown(fragment).contains("<p>--"), "marker shown: {}", shown(fragment));
         }
-        if sqlx::query("UPDATE recording SET video_path = ?, last_edited = strftime('%s', 'now') WHERE id = ?;")
-            .bind(&video_filename)
-            .bind(rid)
-            .execute(&db)
+        assert_eq!(slide_steps(md, 1), 0, "markers inside other blocks are content");
+        assert!(render_slide(md, 1, 0, "", None).contains("++"));
+        assert_eq!(slide_steps(md, 2), 0, "a marker with nothing after it adds no step");
+
+        let slides = render_all_slides(md);
+        assert!(slides[0].1.contains("Answer") && slides[0].1.contains("second"), "exports show every step");
+        assert!(!slides[0].1.contains("<p>\u{2013}</p>") && !slides[0].1.contains("<p>++</p>"));
+    }
+
+    /// Fragment steps must be clamped to the slide's steps, reset by moving slides, and
+    /// recorded as cues of their own.
+    #[tokio::test]
+    async fn fragment_steps_are_tracked_and_recorded() {
+        let (_server, mut state) = test_server().await;
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(uid, "Fragments", &state.db_pool).await;
+        DbPresentation::update_content(pid, "## Quiz\nQuestion?\n\n--\n\nAnswer\n\n## Next".to_string(), &state.db_pool)
             .await
-            .is_err()
+            .unwrap();
+        let pres = add_client_handler_channel(pid.to_string(), &mut state).await.unwrap();
+        handle_recording_message(RecordingMessage::RecordingStart, &pres, pid, &state.db_pool).await;
+        let key = pid.to_string();
+        let sent = update_slide(&key, SlideMessage::Fragment { slide: 0, fragment: 5 }, &mut state);
+        assert!(matches!(sent, Some(SlideMessage::Fragment { slide: 0, fragment: 1 })), "clamped to the one step");
+        assert!(matches!(state_messages(&pres.lock().unwrap(), false, true).last(), Some(SlideMessage::Fragment { fragment: 1, .. })));
+        update_slide(&key, SlideMessage::Slide(1), &mut state);
         {
-            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            let p = pres.lock().unwrap();
+            assert_eq!((p.slide, p.fragment), (1, 0));
         }
+
+        let Some(SlideMessage::RecordingStop { id, .. }) =
+            handle_recording_message(RecordingMessage::RecordingStop, &pres, pid, &state.db_pool).await
+        else {
+            panic!("the recording was not stopped");
+        };
+        let cues: Vec<String> = sqlx::query_scalar("SELECT content FROM recording_slide WHERE recording_id = ? ORDER BY id")
+            .bind(id)
+            .fetch_all(&state.db_pool)
+            .await
+            .unwrap();
+        assert_eq!(cues.len(), 3);
+        assert!(cues[0].contains("Question?") && !cues[0].contains("Answer"));
+        assert!(cues[1].contains("Answer"));
+        assert!(cues[2].contains("Next"));
     }
 
-    if let Some(captions_data) = captions_bytes {
-        if tokio::fs::write(format!("{dir}/captions.vtt"), &captions_data).await.is_err() {
-            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    /// Blocks must be counted in document order, in the same units the audience client
+    /// numbers, and a highlight must mark the text of exactly one of them.
+    #[test]
+    fn highlight_marks_blocks_in_document_order() {
+        let md = "## Table\nIntro *text*.\n\n| A | B |\n|---|---|\n| 1 | 2 |\n\n- one\n- two\n\n--\n\n![alt](x.png) `code`\n";
+        let deck = parse_deck(md);
+        let marked = |block| {
+            let mut html = String::new();
+            cmark_html::push_html(&mut html, deck.highlighted(0, block).reveal(0, u32::MAX).0.into_iter());
+            html
+        };
+        assert!(marked(0).contains("<h2><mark>Table</mark></h2>"));
+        assert!(marked(1).contains("<mark>Intro </mark><em><mark>text</mark></em>"));
+        assert!(marked(2).contains("<th><mark>A</mark></th><th><mark>B</mark></th>"), "the header row");
+        assert!(marked(4).contains("<th><mark>B</mark></th>") && !marked(4).contains("<mark>A"));
+        assert!(marked(7).contains("<td><mark>2</mark></td>"));
+        assert!(marked(9).contains("<li><mark>two</mark></li>") && !marked(9).contains("<mark>one"));
+        assert!(marked(10).contains("alt=\"alt\"") && marked(10).contains("<mark><code>code</code></mark>"));
+        assert!(!marked(11).contains("<mark>"));
+        assert!(slide_has_block(md, 0, 10));
+        assert!(!slide_has_block(md, 0, 11) && !slide_has_block(md, 1, 0));
+
+        let target = |tag: &str, text: &str, occurrence| BlockTarget { tag: tag.to_string(), text: text.to_string(), occurrence };
+        let targets = deck.targets(0);
+        assert_eq!(targets.len(), 11);
+        assert_eq!(targets[1], target("p", "Introtext", 0), "spacing and punctuation are dropped");
+        assert_eq!(targets[2], target("tr", "AB", 0), "the header row");
+        assert_eq!(targets[3], target("th", "A", 0));
+        assert_eq!(targets[7], target("td", "2", 0));
+        assert_eq!(targets[10], target("p", "code", 0), "image alt text is left out");
+        for (block, t) in targets.iter().enumerate() {
+            assert_eq!(deck.find_block(0, t), Some(block as u32));
         }
-        let _ = sqlx::query("UPDATE recording SET last_edited = strftime('%s', 'now') WHERE id = ?;")
-            .bind(rid)
-            .execute(&db)
-            .await;
+
+        // Raw HTML and footnote references are no blocks; repeated text is told apart by
+        // its occurrence.
+        let deck = parse_deck("## Raw\n<div>x</div>\n\nSee[^1] it.\n\n- Same\n- Same\n\n[^1]: A note.\n");
+        assert_eq!(deck.find_block(0, &target("p", "Seeit", 0)), Some(1));
+        assert_eq!(deck.find_block(0, &target("li", "Same", 1)), Some(3));
+        assert_eq!(deck.find_block(0, &target("li", "Same", 2)), None);
     }
 
-    StatusCode::OK.into_response()
-}
+    /// Highlights at another slide or a missing block must be dropped, and a kept one must
+    /// reach late joiners, survive fragment steps, clear on a slide change, and be recorded.
+    #[tokio::test]
+    async fn highlight_is_checked_cleared_and_recorded() {
+        let (_server, mut state) = test_server().await;
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(uid, "Pointer", &state.db_pool).await;
+        DbPresentation::update_content(pid, "## One\nFirst\n\n--\n\nSecond\n\n## Two\nOther".to_string(), &state.db_pool)
+            .await
+            .unwrap();
+        let pres = add_client_handler_channel(pid.to_string(), &mut state).await.unwrap();
+        handle_recording_message(RecordingMessage::RecordingStart, &pres, pid, &state.db_pool).await;
+        let key = pid.to_string();
+        let point = |slide, block| SlideMessage::Highlight { slide, block, target: None, variants: BTreeMap::new() };
+        assert!(update_slide(&key, point(1, Some(0)), &mut state).is_none(), "not the current slide");
+        assert!(update_slide(&key, point(0, Some(3)), &mut state).is_none(), "the slide has three blocks");
+        // Presenters name the block by what it shows; the server numbers it.
+        let first = BlockTarget { tag: "p".to_string(), text: "First".to_string(), occurrence: 0 };
+        let named = |target| SlideMessage::Highlight { slide: 0, block: None, target: Some(target), variants: BTreeMap::new() };
+        let again = BlockTarget { occurrence: 1, ..first.clone() };
+        assert!(update_slide(&key, named(again), &mut state).is_none(), "no second block shows the same");
+        let sent = update_slide(&key, named(first.clone()), &mut state).unwrap();
+        assert!(matches!(&sent, SlideMessage::Highlight { slide: 0, block: Some(1), target: Some(t), .. } if *t == first));
+        let audience = Viewer { connection_id: 1, is_presenter: false, is_audience: true, is_editor: false };
+        assert!(audience.view(&sent).is_some());
+        assert!(matches!(
+            state_messages(&pres.lock().unwrap(), false, true).last(),
+            Some(SlideMessage::Highlight { block: Some(1), .. })
+        ));
+
+        update_slide(&key, SlideMessage::Fragment { slide: 0, fragment: 1 }, &mut state);
+        assert_eq!(pres.lock().unwrap().highlight, Some(1), "stepping within the slide keeps the pointer");
+        update_slide(&key, SlideMessage::Slide(1), &mut state);
+        assert_eq!(pres.lock().unwrap().highlight, None);
+        assert!(!state_messages(&pres.lock().unwrap(), false, true).iter().any(|m| matches!(m, SlideMessage::Highlight { .. })));
+
+        let Some(SlideMessage::RecordingStop { id, .. }) =
+            handle_recording_message(RecordingMessage::RecordingStop, &pres, pid, &state.db_pool).await
+        else {
+            panic!("the recording was not stopped");
+        };
+        let cues: Vec<String> = sqlx::query_scalar("SELECT content FROM recording_slide WHERE recording_id = ? ORDER BY id")
+            .bind(id)
+            .fetch_all(&state.db_pool)
+            .await
+            .unwrap();
+        assert_eq!(cues.len(), 4);
+        assert!(!cues[0].contains("<mark>"));
+        assert!(cues[1].contains("<mark>First</mark>") && !cues[1].contains("Second"));
+        assert!(cues[2].contains("<mark>First</mark>") && cues[2].contains("Second"));
+        assert!(cues[3].contains("Other") && !cues[3].contains("<mark>"));
+    }
 
-async fn add_recording(
-    State(db): State<SqlitePool>,
-    auth_session: AuthSession,
-    Path(pid): Path<i64>,
-    mut multipart: Multipart,
-) -> impl IntoResponse {
-    let Some(user) = auth_session.user else {
-        return StatusCode::UNAUTHORIZED.into_response();
-    };
-    let has_access = sqlx::query_scalar::<_, i64>(
-        "SELECT COUNT(*) FROM (
-            SELECT 1 FROM presentation WHERE id = ? AND user_id = ?
-            UNION ALL
-            SELECT 1 FROM presentation_access WHERE presentation_id = ? AND user_id = ? AND role IN ('controller', 'editor')
-        )",
-    )
-    .bind(pid)
-    .bind(user.id)
-    .bind(pid)
-    .bind(user.id)
-    .fetch_one(&db)
-    .await;
-    if !matches!(has_access, Ok(n) if n > 0) {
-        return StatusCode::FORBIDDEN.into_response();
+    /// No client role may inject a Notes message into the broadcast channel.
+    #[tokio::test]
+    async fn ws_owner_cannot_send_notes_message() {
+        let (_server, state) = test_server().await;
+        let (tx, mut rx) = tokio::sync::broadcast::channel::<SlideMessage>(8);
+        let mut tx = tx;
+        let msg = axum::extract::ws::Message::text(
+            serde_json::to_string(&SlideMessage::Notes("spoofed".to_string())).unwrap(),
+        );
+        let mut state_clone = state.clone();
+        let (direct, mut replies) = tokio::sync::mpsc::unbounded_channel();
+        let result = handle_socket(Ok(msg), "1", &mut tx, &mut state_clone, &AccessResult::Owner, &direct);
+        assert!(matches!(result, Ok(true)), "Notes from a client must be dropped");
+        assert!(matches!(replies.try_recv(), Ok(SlideMessage::Error(ProtocolError { code: ErrorCode::Unauthorized, .. }))));
+        assert!(rx.try_recv().is_err(), "Notes from a client must not be broadcast");
     }
 
-    let mut name = String::new();
-    let mut video_bytes: Option<(Vec<u8>, String)> = None;
-    let mut slides_json: Option<String> = None;
-    let mut captions_bytes: Option<Vec<u8>> = None;
+    /// An audience question must be stored and returned for broadcast; anonymous
+    /// questions must not record their author.
+    #[tokio::test]
+    async fn question_ask_persists_question() {
+        let pool = setup_pool().await;
+        let owner = make_user(&pool, "qaowner").await;
+        let asker = make_user(&pool, "asker").await;
+        let pres = make_presentation(&owner, &pool).await;
+
+        let ask = |text: &str, anonymous| QuestionMessage::QuestionAsk { text: text.to_string(), anonymous };
+        let (direct, _replies) = tokio::sync::mpsc::unbounded_channel();
+        let msgs = handle_question_message(
+            ask("  Why?  ", false), pres.id, &AccessResult::Audience, Some(asker.id), &mut None, &pool, &direct,
+        )
+        .await
+        .unwrap();
+        assert!(matches!(&msgs[..], [SlideMessage::Question(q)] if q.text == "Why?" && q.author.as_deref() == Some("asker")));
 
-    while let Ok(Some(field)) = multipart.next_field().await {
-        match field.name().unwrap_or("") {
-            "name" => {
-                name = field.text().await.unwrap_or_default();
-            }
-            "video" => {
-                let ext = field
-                    .file_name()
-                    .and_then(|f| std::path::Path::new(f).extension())
-                    .and_then(std::ffi::OsStr::to_str)
-                    .unwrap_or("bin")
-                    .to_string();
-                if let Ok(b) = field.bytes().await {
-                    if !b.is_empty() {
-                        video_bytes = Some((b.to_vec(), ext));
-                    }
-                }
-            }
-            "slides" => {
-                if let Ok(text) = field.text().await {
-                    if !text.is_empty() {
-                        slides_json = Some(text);
-                    }
-                }
-            }
-            "captions" => {
-                if let Ok(b) = field.bytes().await {
-                    if !b.is_empty() {
-                        captions_bytes = Some(b.to_vec());
-                    }
-                }
-            }
-            _ => {}
-        }
+        let msgs = handle_question_message(
+            ask("Who?", true), pres.id, &AccessResult::Audience, Some(asker.id), &mut None, &pool, &direct,
+        )
+        .await
+        .unwrap();
+        assert!(matches!(&msgs[..], [SlideMessage::Question(q)] if q.author.is_none()));
+        assert_eq!(Question::get_for_presentation(&pool, pres.id).await.unwrap().len(), 2);
     }
 
-    let Some(slides_str) = slides_json else {
-        return StatusCode::BAD_REQUEST.into_response();
-    };
-    if name.is_empty() {
-        return StatusCode::BAD_REQUEST.into_response();
-    }
-    let Ok(slides) = serde_json::from_str::<Vec<RecordingSlideInput>>(&slides_str) else {
-        return StatusCode::BAD_REQUEST.into_response();
-    };
+    /// A connection must not ask again within the cooldown, and oversized questions disconnect;
+    /// the asker is told which happened.
+    #[tokio::test]
+    async fn question_ask_is_rate_limited_and_size_capped() {
+        let pool = setup_pool().await;
+        let owner = make_user(&pool, "qaowner").await;
+        let pres = make_presentation(&owner, &pool).await;
+        let mut last_asked = None;
+        let (direct, mut replies) = tokio::sync::mpsc::unbounded_channel();
+
+        for text in ["First", "Second"] {
+            let msg = QuestionMessage::QuestionAsk { text: text.to_string(), anonymous: true };
+            handle_question_message(msg, pres.id, &AccessResult::PublicOk, None, &mut last_asked, &pool, &direct)
+                .await
+                .unwrap();
+        }
+        let stored = Question::get_for_presentation(&pool, pres.id).await.unwrap();
+        assert_eq!(stored.len(), 1, "second question within the cooldown must be dropped");
+        assert_eq!(stored[0].text, "First");
+        assert!(matches!(
+            replies.try_recv(),
+            Ok(SlideMessage::Error(ProtocolError { code: ErrorCode::RateLimited, fatal: false, .. }))
+        ));
+
+        let msg = QuestionMessage::QuestionAsk { text: "x".repeat(2000), anonymous: true };
+        let result = handle_question_message(msg, pres.id, &AccessResult::PublicOk, None, &mut None, &pool, &direct).await;
+        assert!(result.is_err(), "oversized question must close the connection");
+        assert!(matches!(
+            replies.try_recv(),
+            Ok(SlideMessage::Error(ProtocolError { code: ErrorCode::TooLarge, fatal: true, .. }))
+        ));
+    }
+
+    /// Only presenters may moderate; pinning must broadcast the pinned list to everyone.
+    #[tokio::test]
+    async fn question_moderate_requires_presenter_and_broadcasts_pins() {
+        let pool = setup_pool().await;
+        let owner = make_user(&pool, "qaowner").await;
+        let pres = make_presentation(&owner, &pool).await;
+        let question = Question::create(&pool, pres.id, None, "Pin me").await.unwrap();
+        let pin = || QuestionMessage::QuestionModerate { id: question.id, status: QuestionStatus::Pinned };
+        let (direct, mut replies) = tokio::sync::mpsc::unbounded_channel();
 
-    let captions_data = captions_bytes.unwrap_or_else(|| b"WEBVTT\n".to_vec());
-    let (video_path, video_data) = match video_bytes {
-        Some((data, ext)) => (Some(format!("video.{ext}")), Some(data)),
-        None => (None, None),
-    };
+        let msgs = handle_question_message(pin(), pres.id, &AccessResult::Audience, None, &mut None, &pool, &direct)
+            .await
+            .unwrap();
+        assert!(msgs.is_empty(), "audience must not moderate questions");
+        assert!(matches!(replies.try_recv(), Ok(SlideMessage::Error(ProtocolError { code: ErrorCode::Unauthorized, .. }))));
+        assert!(Question::get_pinned(&pool, pres.id).await.unwrap().is_empty());
 
-    let rec = match Recording::create(pid, name, video_path.clone(), "captions.vtt".to_string(), &db).await {
-        Ok(r) => r,
-        Err(e) => { eprintln!("add_recording: Recording::create failed: {e:?}"); return StatusCode::INTERNAL_SERVER_ERROR.into_response(); }
-    };
+        let msgs = handle_question_message(pin(), pres.id, &AccessResult::Controller, None, &mut None, &pool, &direct)
+            .await
+            .unwrap();
+        assert!(matches!(&msgs[0], SlideMessage::Question(q) if q.status == QuestionStatus::Pinned));
+        assert!(matches!(&msgs[1], SlideMessage::PinnedQuestions(p) if p.len() == 1));
 
-    let dir = format!("assets/{}", rec.id);
-    if let Err(e) = tokio::fs::create_dir_all(&dir).await {
-        eprintln!("add_recording: create_dir_all {dir} failed: {e:?}");
-        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        let other = make_presentation(&owner, &pool).await;
+        let msgs = handle_question_message(pin(), other.id, &AccessResult::Owner, None, &mut None, &pool, &direct)
+            .await
+            .unwrap();
+        assert!(msgs.is_empty(), "questions of another presentation must not be moderated");
+        assert!(matches!(
+            replies.try_recv(),
+            Ok(SlideMessage::Error(ProtocolError { code: ErrorCode::Malformed, message_type: Some(t), .. })) if t == "question_moderate"
+        ));
     }
-    if let (Some(filename), Some(data)) = (video_path, video_data) {
-        if let Err(e) = tokio::fs::write(format!("{dir}/{filename}"), &data).await {
-            eprintln!("add_recording: write video failed: {e:?}");
-            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
-        }
+
+    /// Question messages are server-generated; a client must not be able to broadcast one.
+    #[tokio::test]
+    async fn ws_owner_cannot_send_question_message() {
+        let (_server, state) = test_server().await;
+        let (mut tx, mut rx) = tokio::sync::broadcast::channel::<SlideMessage>(8);
+        let msg = axum::extract::ws::Message::text(
+            r#"{"type":"pinned_questions","data":[]}"#.to_string(),
+        );
+        let mut state_clone = state.clone();
+        let (direct, _replies) = tokio::sync::mpsc::unbounded_channel();
+        let result = handle_socket(Ok(msg), "1", &mut tx, &mut state_clone, &AccessResult::Owner, &direct);
+        assert!(matches!(result, Ok(true)), "question messages from a client must be dropped");
+        assert!(rx.try_recv().is_err(), "question messages from a client must not be broadcast");
     }
-    if let Err(e) = tokio::fs::write(format!("{dir}/captions.vtt"), &captions_data).await {
-        eprintln!("add_recording: write captions failed: {e:?}");
-        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+
+    /// A `poll` fenced block must yield its question and `- ` options; other slides have none.
+    #[test]
+    fn slide_poll_parses_inline_block() {
+        let md = "## Intro\nHello\n\n## Vote\n```poll\nBest colour?\n- Red\n- Blue\n```\n";
+        assert_eq!(
+            slide_poll(md, 1),
+            Some(("Best colour?".to_string(), vec!["Red".to_string(), "Blue".to_string()]))
+        );
+        assert_eq!(slide_poll(md, 0), None);
+        assert_eq!(slide_poll(md, 5), None);
     }
-    if let Err(e) = RecordingSlide::create_batch(rec.id, slides, &db).await {
-        eprintln!("add_recording: create_batch failed: {e:?}");
-        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+
+    /// Polls need 2–10 distinct, non-empty options.
+    #[test]
+    fn poll_is_valid_checks_options() {
+        let opts = |o: &[&str]| o.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert!(poll_is_valid("Q?", &opts(&["A", "B"])));
+        assert!(!poll_is_valid("Q?", &opts(&["A"])));
+        assert!(!poll_is_valid("Q?", &opts(&["A", "A"])));
+        assert!(!poll_is_valid("Q?", &opts(&["A", ""])));
+        assert!(!poll_is_valid("", &opts(&["A", "B"])));
     }
 
-    Redirect::to("/user/presentations").into_response()
-}
+    /// Editors open polls, each audience connection votes once, and the tally is broadcast.
+    #[tokio::test]
+    async fn poll_open_and_vote_once_per_connection() {
+        let pool = setup_pool().await;
+        let owner = make_user(&pool, "pollowner").await;
+        let pres_db = make_presentation(&owner, &pool).await;
+        let pres = make_presentation_arc();
+        let open = || PollMessage::Open { question: "Q?".to_string(), options: vec!["A".to_string(), "B".to_string()] };
 
-/// Builds the application router and state from an already-migrated database pool.
-///
-/// Accepts any `SqlitePool` (file-based or in-memory). The caller is responsible
-/// for running migrations before passing the pool in. Returns both the router (for
-/// serving) and the app state (so the caller can retain it for signal handling).
-pub async fn build_app(db_pool: SqlitePool) -> (Router, AppState) {
-    let session_store = SqliteStore::new(db_pool.clone());
-    session_store.migrate().await.unwrap();
-    let session_layer = SessionManagerLayer::new(session_store)
-        // with_secure(false): Caddy terminates TLS; this binary binds to localhost:5002 only.
-        // Session cookies are never sent over plain HTTP in production.
-        .with_secure(false)
-        .with_expiry(Expiry::OnInactivity(Duration::days(1)));
-    let tera = Tera::new();
-    let backend = Backend::new(db_pool.clone());
-    let auth_layer = AuthManagerLayerBuilder::new(backend, session_layer).build();
-    let state = AppState {
-        tera,
-        slides: Arc::new(Mutex::new(HashMap::new())),
-        db_pool,
-    };
-    // Rate-limit POST /auth/login: 5 attempts per IP per minute (OWASP brute-force prevention).
-    // Burst of 5 with one token replenished every 12 s gives a sustained rate of 5/minute.
-    // ClientIpExtractor reads X-Forwarded-For (set by Caddy) and falls back to 127.0.0.1
-    // in dev/test environments where no proxy header is present.
-    let login_rate_limit = {
-        let mut builder = GovernorConfigBuilder::default();
-        builder.per_second(12);
-        builder.burst_size(5);
-        let conf = builder.key_extractor(ClientIpExtractor).finish().unwrap();
-        GovernorLayer::new(Arc::new(conf))
-    };
-    let router = Router::new()
-        .route("/", get(index))
-        .route("/auth/login", get(login))
-        .route("/auth/login", post(login_process).layer(login_rate_limit))
-        .route("/auth/logout", get(logout))
-        .route("/user/presentations", get(presentations))
-        .route("/user/recordings/{rid}/delete", post(delete_recording))
-        .route("/user/presentations/{pid}/delete", post(delete_presentation))
-        .route("/user/presentations/{pid}/access/add", post(add_access))
-        .route("/user/presentations/{pid}/access/remove", post(remove_access))
-        .route("/users/exists", get(user_exists))
-        .route(
-            "/user/presentations/{pid}/access/change-role",
-            post(change_access_role),
-        )
-        .route(
-            "/user/presentations/{pid}/access/mode",
-            post(set_presentation_access_mode),
-        )
-        .route(
-            "/user/recordings/{rid}/access/mode",
-            post(set_recording_access_mode),
-        )
-        .route(
-            "/user/recordings/{rid}/slides/{sid}/time",
-            post(update_slide_time),
-        )
-        .route("/user/recordings/{rid}/name", post(update_recording_name))
-        .route(
-            "/user/presentations/{pid}/name",
-            post(update_presentation_name),
-        )
-        .route("/user/change_pwd", get(change_pwd))
-        .route("/user/change_pwd", post(change_pwd_form))
-        .route("/user/new", get(new_user))
-        .route("/user/new", post(new_user_form))
-        .route("/join", get(join))
-        .route("/create", get(start))
-        .route("/create", post(start_pres))
-        .route("/{uname}/{pid}", get(present))
-        .route("/qr/{uname}/{pid}", get(qr_code))
-        .route("/ws/{pid}", get(broadcast_to_all))
-        .route("/demo", get(demo))
-        .route("/help", get(help))
-        .route("/{uname}/{pid}/edit", get(edit_pres))
-        .route("/{uname}/{pid}/{rid}", get(recording))
-        .route("/{uname}/{pid}/{rid}/edit", get(edit_recording))
-        .route("/{uname}/{pid}/{rid}/slides.vtt", get(slides_vtt))
-        .route("/{uname}/{pid}/{rid}/slides.html", get(slides_html))
-        .nest_service("/css", ServeDir::new("css/"))
-        .nest_service("/js", ServeDir::new("js/"))
-        .nest_service("/assets", ServeDir::new("assets/"))
-        .merge(
-            Router::new()
-                .route(
-                    "/user/presentations/{pid}/recordings",
-                    post(add_recording),
-                )
-                .route(
-                    "/user/recordings/{rid}/files",
-                    post(update_recording_files),
-                )
-                // 50 MB upload limit — matches common video-hosting constraints (Mux, Vimeo Basic).
-        // Chosen at the lower end of the ±50% range; prevents runaway memory use on the server.
-        .layer(DefaultBodyLimit::max(50 * 1024 * 1024)),
-        )
-        .with_state(state.clone())
-        .layer(auth_layer);
-    (router, state)
-}
+        let denied = handle_poll_message(open(), &pres, pres_db.id, &AccessResult::Controller, None, &mut HashSet::new(), &pool).await;
+        assert!(
+            matches!(denied, Err(ProtocolError { code: ErrorCode::Unauthorized, .. })),
+            "controllers must not open polls"
+        );
+
+        let Ok(Some(SlideMessage::Poll(poll))) =
+            handle_poll_message(open(), &pres, pres_db.id, &AccessResult::Editor, None, &mut HashSet::new(), &pool).await
+        else {
+            panic!("editor must open the poll");
+        };
+        assert!(poll.open);
+
+        let mut voted = HashSet::new();
+        let bad = PollMessage::Vote { poll_id: poll.id, option: 7 };
+        let bad = handle_poll_message(bad, &pres, pres_db.id, &AccessResult::PublicOk, None, &mut voted, &pool).await;
+        assert!(
+            matches!(bad, Err(ProtocolError { code: ErrorCode::Malformed, .. })),
+            "a vote for a missing option must be refused, leaving the real one"
+        );
+        let vote = || PollMessage::Vote { poll_id: poll.id, option: 1 };
+        let first = handle_poll_message(vote(), &pres, pres_db.id, &AccessResult::PublicOk, None, &mut voted, &pool).await;
+        assert!(matches!(first, Ok(Some(SlideMessage::Poll(ref p))) if p.counts == [0, 1]));
+        let again = handle_poll_message(vote(), &pres, pres_db.id, &AccessResult::PublicOk, None, &mut voted, &pool).await;
+        assert!(
+            matches!(again, Err(ProtocolError { code: ErrorCode::Malformed, .. })),
+            "a connection must vote only once"
+        );
+        let other = handle_poll_message(vote(), &pres, pres_db.id, &AccessResult::Owner, None, &mut HashSet::new(), &pool).await;
+        assert!(other.is_err(), "presenters must not vote");
+
+        let stored = Poll::get_for_presentation(&pool, pres_db.id).await.unwrap();
+        assert_eq!(stored[0].counts, [0, 1]);
+    }
+
+    /// A signed-in user must be counted once even when voting from several connections.
+    #[tokio::test]
+    async fn poll_vote_once_per_user() {
+        let pool = setup_pool().await;
+        let owner = make_user(&pool, "pollowner").await;
+        let voter = make_user(&pool, "voter").await;
+        let pres_db = make_presentation(&owner, &pool).await;
+        let pres = make_presentation_arc();
+        let poll = open_poll(&pres, pres_db.id, "Q?", &["A".to_string(), "B".to_string()], &pool).await;
+        let Some(SlideMessage::Poll(poll)) = poll else { panic!("poll must open") };
 
-/// Dynamic cleanup of still open presentations.
-fn cleanup(state: &mut AppState) {
-    let mut slides = state.slides.lock().unwrap();
-    slides.retain(|_k, v| Arc::strong_count(v) > 1);
-}
+        let vote = || PollMessage::Vote { poll_id: poll.id, option: 0 };
+        let first = handle_poll_message(vote(), &pres, pres_db.id, &AccessResult::Audience, Some(voter.id), &mut HashSet::new(), &pool).await;
+        assert!(matches!(first, Ok(Some(SlideMessage::Poll(_)))));
+        let again = handle_poll_message(vote(), &pres, pres_db.id, &AccessResult::Audience, Some(voter.id), &mut HashSet::new(), &pool).await;
+        assert!(
+            matches!(again, Err(ProtocolError { code: ErrorCode::Malformed, .. })),
+            "a second connection of the same user must be told it already voted"
+        );
+        assert_eq!(pres.lock().unwrap().poll.as_ref().unwrap().counts, [1, 0]);
+    }
 
-#[tokio::main(flavor = "current_thread")]
-async fn main() {
-    let port = std::env::var("APP_PORT").unwrap_or_else(|_| "5002".to_string());
-    let db_url = std::env::var("APP_DB").unwrap_or_else(|_| "sqlite://db.sqlite3".to_string());
-    let mut signals = Signals::new([SIGUSR1]).unwrap();
-    let sig_handle = signals.handle();
-    let migrate_pool = SqlitePool::connect_with(
-        SqliteConnectOptions::from_str(&db_url)
-            .unwrap()
-            .create_if_missing(true)
-            .foreign_keys(false),
-    )
-    .await
-    .unwrap();
-    sqlx::migrate!("./migrations").run(&migrate_pool).await.unwrap();
-    migrate_pool.close().await;
-    let db_pool = SqlitePool::connect_with(
-        SqliteConnectOptions::from_str(&db_url)
-            .unwrap()
-            .create_if_missing(true)
-            .foreign_keys(true),
-    )
-    .await
-    .unwrap();
-    let (app, state) = build_app(db_pool).await;
-    let listener = tokio::net::TcpListener::bind(format!("0.0.0.0:{port}"))
-        .await
-        .unwrap();
-    let mut state_for_signal = state;
-    let signal_task = tokio::spawn(async move {
-        use futures_util::StreamExt;
-        while let Some(_sig) = signals.next().await {
-            cleanup(&mut state_for_signal);
-        }
-    });
-    axum::serve(listener, app).await.unwrap();
-    sig_handle.close();
-    let _ = signal_task.await;
-}
+    /// A poll that cannot be opened is refused with an error rather than dropped.
+    #[tokio::test]
+    async fn poll_open_refuses_invalid_polls() {
+        let pool = setup_pool().await;
+        let owner = make_user(&pool, "pollrefused").await;
+        let pres_db = make_presentation(&owner, &pool).await;
+        let pres = make_presentation_arc();
+        let refused = |result: Result<Option<SlideMessage>, ProtocolError>, msg_type: &str| {
+            matches!(result, Err(ProtocolError { code: ErrorCode::Malformed, message_type: Some(t), .. }) if t == msg_type)
+        };
 
-#[cfg(test)]
-#[allow(clippy::pedantic, missing_docs)]
-mod tests {
-    use super::*;
-    use axum_test::TestServer;
-    use sqlx::sqlite::SqlitePoolOptions;
+        let one_option = PollMessage::Open { question: "Q?".to_string(), options: vec!["A".to_string()] };
+        let result = handle_poll_message(one_option, &pres, pres_db.id, &AccessResult::Owner, None, &mut HashSet::new(), &pool).await;
+        assert!(refused(result, "poll_open"), "a poll with one option must be refused");
+        let result = handle_poll_message(PollMessage::OpenInline, &pres, pres_db.id, &AccessResult::Owner, None, &mut HashSet::new(), &pool).await;
+        assert!(refused(result, "poll_open_inline"), "a slide without a poll must be refused");
+        assert!(pres.lock().unwrap().poll.is_none());
+    }
 
-    /// Creates a `TestServer` backed by a fresh isolated in-memory database.
-    ///
-    /// Uses `max_connections(1)` so that all queries share one SQLite connection
-    /// (and therefore one in-memory database). Migrations run with FK enforcement
-    /// off (some migrations DROP TABLE), then FK enforcement is enabled before
-    /// handing the pool to `build_app`.
-    ///
-    /// Returns both the server and the app state. The test has access to
-    /// `state.db_pool` for seeding data before making requests.
-    async fn test_server() -> (TestServer, AppState) {
+    /// Inline polls open from the current slide; open and close land in the recording timeline.
+    #[tokio::test]
+    async fn poll_events_are_saved_with_recording() {
+        let pool = setup_pool().await;
+        let owner = make_user(&pool, "pollowner").await;
+        let pres_db = make_presentation(&owner, &pool).await;
+        let pres = Arc::new(Mutex::new(Presentation::new(
+            "## Vote\n```poll\nQ?\n- Yes\n- No\n```".to_string(),
+        )));
+        handle_recording_message(RecordingMessage::RecordingStart, &pres, pres_db.id, &pool).await;
+        let rec_id = pres.lock().unwrap().recording.as_ref().unwrap().db_id;
+
+        let mut voted = HashSet::new();
+        let opened = handle_poll_message(PollMessage::OpenInline, &pres, pres_db.id, &AccessResult::Owner, None, &mut voted, &pool).await;
+        assert!(matches!(opened, Ok(Some(SlideMessage::Poll(ref p))) if p.options == ["Yes", "No"]));
+        let closed = handle_poll_message(PollMessage::Close, &pres, pres_db.id, &AccessResult::Owner, None, &mut voted, &pool).await;
+        assert!(matches!(closed, Ok(Some(SlideMessage::Poll(ref p))) if !p.open));
+        handle_recording_message(RecordingMessage::RecordingStop, &pres, pres_db.id, &pool).await;
+
+        let events: Vec<String> = sqlx::query_scalar("SELECT event FROM recording_poll WHERE recording_id = ? ORDER BY id")
+            .bind(rec_id)
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(events, ["open", "close"]);
+        assert!(!Poll::get_for_presentation(&pool, pres_db.id).await.unwrap()[0].open);
+    }
+
+    /// Creates an isolated in-memory pool with migrations applied.
+    async fn setup_pool() -> SqlitePool {
         let pool = SqlitePoolOptions::new()
             .max_connections(1)
             .connect_with(
@@ -2021,2001 +8226,3050 @@ mod tests {
             .await
             .unwrap();
         sqlx::migrate!("./migrations").run(&pool).await.unwrap();
-        // Enable FK enforcement on the single connection now that migrations are done.
         sqlx::query("PRAGMA foreign_keys = ON")
             .execute(&pool)
             .await
             .unwrap();
-        let (router, state) = build_app(pool).await;
-        // save_cookies() makes the TestServer persist Set-Cookie headers between
-        // requests, which is how session auth is maintained across test steps.
-        //
-        // API note: if this call does not compile for the installed version of
-        // axum-test, check the crate docs for the equivalent cookie persistence
-        // configuration (look for TestServerConfig, save_cookies, or similar).
-        let server = TestServer::builder()
-            .save_cookies()
-            .build(router)
-            .unwrap();
-        (server, state)
+        pool
     }
 
-    /// Seeds one user into the database using the same `User::new` path the app uses.
-    ///
-    /// The groups table row for id=1 ("admin") is created by migrations.
-    /// Do not re-insert it. This user is not added to any group; group membership
-    /// is not needed for basic login and session tests.
-    async fn seed_user(pool: &SqlitePool) {
+    /// Creates a user with the given name and returns the User struct.
+    async fn make_user(pool: &SqlitePool, name: &str) -> User {
         User::new(
             pool,
             AddUserForm {
-                name: "testuser".to_string(),
-                email: "test@example.com".to_string(),
+                name: name.to_string(),
+                email: format!("{name}@example.com"),
                 password: "testpass".to_string(),
             },
         )
         .await
         .unwrap();
+        User::get_by_name(name.to_string(), pool).await.unwrap().unwrap()
     }
 
-    async fn seed_admin_user(pool: &SqlitePool) {
-        User::new(
-            pool,
-            AddUserForm {
-                name: "adminuser".to_string(),
-                email: "admin2@example.com".to_string(),
-                password: "adminpass".to_string(),
-            },
-        )
-        .await
-        .unwrap();
-        sqlx::query("INSERT INTO group_users (user_id, group_id) VALUES ((SELECT id FROM users WHERE name = 'adminuser'), 1)")
-            .execute(pool)
+    /// Creates a presentation owned by the given user and returns the DbPresentation.
+    async fn make_presentation(owner: &User, pool: &SqlitePool) -> DbPresentation {
+        DbPresentation::new(owner, "Test Presentation".to_string(), pool)
             .await
-            .unwrap();
+            .unwrap()
     }
 
-    async fn login_as(server: &axum_test::TestServer, username: &str, password: &str) {
-        server
-            .post("/auth/login")
-            .form(&serde_json::json!({ "username": username, "password": password }))
-            .await;
+    /// Creates an in-memory Presentation arc for unit tests.
+    fn make_presentation_arc() -> Arc<Mutex<Presentation>> {
+        Arc::new(Mutex::new(Presentation::new(
+            "## Intro\nHello\n\n## Second\nWorld".to_string(),
+        )))
     }
 
-    async fn seed_presentation(user_id: i64, name: &str, pool: &SqlitePool) -> i64 {
-        sqlx::query_scalar::<_, i64>(
-            "INSERT INTO presentation (name, user_id, content) VALUES (?, ?, '') RETURNING id",
-        )
-        .bind(name)
-        .bind(user_id)
-        .fetch_one(pool)
-        .await
-        .unwrap()
-    }
+    /// recording_start must insert a recording row and set in-memory state.
+    #[tokio::test]
+    async fn recording_start_creates_db_row() {
+        let pool = setup_pool().await;
+        let owner = make_user(&pool, "owner").await;
+        let pres_db = make_presentation(&owner, &pool).await;
+        let pres = make_presentation_arc();
 
-    async fn get_user_id(name: &str, pool: &SqlitePool) -> i64 {
-        sqlx::query_scalar::<_, i64>("SELECT id FROM users WHERE name = ?")
-            .bind(name)
-            .fetch_one(pool)
-            .await
-            .unwrap()
+        let result = handle_recording_message(
+            RecordingMessage::RecordingStart,
+            &pres,
+            pres_db.id,
+            &pool,
+        ).await;
+
+        assert!(matches!(result, Some(SlideMessage::RecordingStart { elapsed_ms: 0 })));
+        assert!(pres.lock().unwrap().recording.is_some());
+        // DB row exists
+        let rec_id = pres.lock().unwrap().recording.as_ref().unwrap().db_id;
+        let row = Recording::get_by_id(rec_id, &pool).await.unwrap();
+        assert!(row.is_some());
+        assert!(row.unwrap().name.starts_with("Recording"));
     }
 
-    /// POST /create with a valid name must redirect to /{username}/{pid}/edit.
+    /// The resync snapshot must carry the full state, minus what the role may not see.
     #[tokio::test]
-    async fn create_presentation_redirects_to_edit() {
-        let (server, state) = test_server().await;
-        seed_user(&state.db_pool).await;
-        login_as(&server, "testuser", "testpass").await;
+    async fn state_messages_respect_role() {
+        let pool = setup_pool().await;
+        let owner = make_user(&pool, "snapshot").await;
+        let pres_db = make_presentation(&owner, &pool).await;
+        let pres = make_presentation_arc();
+        pres.lock().unwrap().content = "## One\nShown\n\n???\nSecret".to_string();
+        handle_recording_message(RecordingMessage::RecordingStart, &pres, pres_db.id, &pool).await;
 
-        let response = server
-            .post("/create")
-            .form(&serde_json::json!({ "name": "Test Pres" }))
-            .await;
+        let p = pres.lock().unwrap();
+        let audience = state_messages(&p, false, true);
+        let presenter = state_messages(&p, true, false);
 
-        assert_eq!(response.status_code(), 303);
-        let location = response.headers()["location"].to_str().unwrap();
-        assert!(
-            location.starts_with("/testuser/") && location.ends_with("/edit"),
-            "create must redirect to /{{username}}/{{pid}}/edit, got: {location}"
-        );
+        assert!(matches!(&audience[0], SlideMessage::Text(t) if t.contains("Shown") && !t.contains("Secret")));
+        assert!(matches!(audience[1], SlideMessage::Revision(0)));
+        assert!(matches!(audience[2], SlideMessage::Slide(0)));
+        assert_eq!(audience.len(), 3, "audience must get neither notes nor recording state");
+        assert!(matches!(&presenter[0], SlideMessage::Text(t) if t.contains("Secret")));
+        assert!(presenter.iter().any(|m| matches!(m, SlideMessage::Notes(n) if n.contains("Secret"))));
+        assert!(presenter.iter().any(|m| matches!(m, SlideMessage::RecordingStart { .. })));
     }
 
-    /// Deleting your own presentation must redirect to /user/presentations
-    /// and the row must be gone from the database.
-    #[tokio::test]
-    async fn delete_own_presentation_removes_it() {
-        let (server, state) = test_server().await;
-        seed_user(&state.db_pool).await;
-        let uid = get_user_id("testuser", &state.db_pool).await;
-        let pid = seed_presentation(uid, "To Delete", &state.db_pool).await;
-        login_as(&server, "testuser", "testpass").await;
+    #[test]
+    fn text_patch_round_trips_in_utf16_offsets() {
+        let old = "## Intro 👋\nHello\n## End";
+        let new = "## Intro 👋\nHello there\n## End";
+        let patch = TextPatch::between(old, new, 4);
+        // The emoji counts as two UTF-16 code units, as in JavaScript.
+        assert_eq!((patch.base, patch.start, patch.end, patch.text.as_str()), (4, 17, 17, " there"));
+        assert_eq!(patch.apply(old).as_deref(), Some(new));
 
-        let response = server
-            .post(&format!("/user/presentations/{pid}/delete"))
-            .await;
+        let replaced = TextPatch::between("a👋b", "a👍b", 0);
+        assert_eq!((replaced.start, replaced.end), (1, 3));
+        assert_eq!(replaced.apply("a👋b").as_deref(), Some("a👍b"));
+    }
 
-        assert_eq!(response.status_code(), 303);
-        assert_eq!(response.headers()["location"], "/user/presentations");
+    #[test]
+    fn text_patch_rejects_offsets_that_do_not_fit() {
+        assert_eq!(utf16_to_byte("a👋b", 2), None, "offset inside a surrogate pair");
+        assert_eq!(utf16_to_byte("a👋b", 3), Some(5));
+        assert_eq!(utf16_to_byte("ab", 3), None);
+        let patch = TextPatch { start: 1, end: 9, text: "x".to_string(), ..TextPatch::default() };
+        assert_eq!(patch.apply("abc"), None);
+    }
 
-        let count: i64 =
-            sqlx::query_scalar("SELECT COUNT(*) FROM presentation WHERE id = ?")
-                .bind(pid)
-                .fetch_one(&state.db_pool)
-                .await
-                .unwrap();
-        assert_eq!(count, 0, "deleted presentation must not exist in the database");
+    #[tokio::test]
+    async fn set_content_broadcasts_patches_with_notes_free_audience_copy() {
+        let pres = make_presentation_arc();
+        let mut rx = pres.lock().unwrap().channel.0.subscribe();
+        pres.lock().unwrap().set_content("## One\nShown\n\n???\nSecret".to_string(), None);
+        pres.lock().unwrap().set_content("## One\nShown\n\n???\nSecret plan".to_string(), None);
+
+        assert_eq!(pres.lock().unwrap().text_revision, 2);
+        let _ = rx.try_recv().unwrap();
+        let Ok(SlideMessage::Patch(patch)) = rx.try_recv() else { panic!("expected a patch") };
+        assert_eq!(patch.base, 1);
+        assert_eq!(patch.text, " plan");
+        let audience = patch.audience.expect("audience copy");
+        assert!(audience.text.is_empty() && audience.start == audience.end, "notes edits are invisible to the audience");
+        let wire = serde_json::to_string(&SlideMessage::Patch(TextPatch::between("ab", "abc", 1))).unwrap();
+        assert_eq!(wire, r#"{"type":"patch","data":{"base":1,"start":2,"end":2,"text":"c"}}"#);
     }
 
-    /// Attempting to delete another user's presentation must leave it intact.
-    /// The handler redirects (303) but the ownership check in the SQL means
-    /// no row is deleted when user_id does not match.
     #[tokio::test]
-    async fn delete_other_users_presentation_is_noop() {
-        let (server, state) = test_server().await;
-        seed_user(&state.db_pool).await;
-        seed_admin_user(&state.db_pool).await;
-        let owner_id = get_user_id("adminuser", &state.db_pool).await;
-        let pid = seed_presentation(owner_id, "Owner's Pres", &state.db_pool).await;
-        // Log in as a different user (testuser) and try to delete adminuser's presentation.
-        login_as(&server, "testuser", "testpass").await;
+    async fn stale_patch_gets_snapshot_instead_of_being_applied() {
+        let pres = make_presentation_arc();
+        pres.lock().unwrap().set_content("## One".to_string(), None);
+        pres.lock().unwrap().text_history.clear();
+        let mut rx = pres.lock().unwrap().channel.0.subscribe();
+        let (direct, mut direct_rx) = tokio::sync::mpsc::unbounded_channel();
+
+        // Revision 0 is no longer in the history, so the patch cannot be rebased.
+        let stale = TextPatch::between("", "## Zero", 0);
+        handle_patch(&pres, &stale, &AccessResult::Editor, "1", (1, "ed"), &direct).unwrap();
+        assert_eq!(pres.lock().unwrap().content, "## One");
+        assert!(rx.try_recv().is_err(), "a stale patch must not be broadcast");
+        assert!(matches!(direct_rx.try_recv(), Ok(SlideMessage::Text(t)) if t == "## One"));
+        assert!(matches!(direct_rx.try_recv(), Ok(SlideMessage::Revision(1))));
+
+        let fresh = TextPatch::between("## One", "## One!", 1);
+        handle_patch(&pres, &fresh, &AccessResult::Editor, "1", (1, "ed"), &direct).unwrap();
+        assert_eq!(pres.lock().unwrap().content, "## One!");
+        assert!(matches!(rx.try_recv(), Ok(SlideMessage::Patch(p)) if p.base == 1));
+
+        let audience = TextPatch::between("## One!", "", 2);
+        handle_patch(&pres, &audience, &AccessResult::Audience, "1", (1, "ed"), &direct).unwrap();
+        assert_eq!(pres.lock().unwrap().content, "## One!", "only editors may patch");
+    }
+
+    /// Reversed ranges and ranges past the deck are refused as malformed without touching
+    /// the presentation, which keeps taking edits; transforming over them cannot overflow.
+    #[tokio::test]
+    async fn invalid_patch_ranges_are_refused() {
+        let pres = make_presentation_arc();
+        pres.lock().unwrap().set_content("## One".to_string(), None);
+        pres.lock().unwrap().set_content("## One!".to_string(), None);
+        let (direct, mut direct_rx) = tokio::sync::mpsc::unbounded_channel();
+        let bad = [
+            TextPatch { base: 2, start: 5, end: 2, text: "x".to_string(), ..TextPatch::default() },
+            TextPatch { base: 2, start: 0, end: 8, ..TextPatch::default() },
+            // "## One" at revision 1 was 6 units long, though the deck is 7 now.
+            TextPatch { base: 1, start: 7, end: 7, text: "x".to_string(), ..TextPatch::default() },
+            TextPatch { base: 1, start: usize::MAX, end: usize::MAX, ..TextPatch::default() },
+        ];
+        for patch in &bad {
+            handle_patch(&pres, patch, &AccessResult::Editor, "1", (1, "ed"), &direct).unwrap();
+            assert!(
+                matches!(direct_rx.try_recv(), Ok(SlideMessage::Error(ProtocolError { code: ErrorCode::Malformed, .. }))),
+                "{patch:?}"
+            );
+        }
+        assert_eq!(pres.lock().unwrap().content, "## One!");
 
-        server
-            .post(&format!("/user/presentations/{pid}/delete"))
-            .await;
+        let huge = TextPatch { start: usize::MAX, end: 0, text: "x".to_string(), ..TextPatch::default() };
+        let normal = TextPatch { start: 1, end: 3, ..TextPatch::default() };
+        let _ = huge.transform(&normal, true);
+        let _ = normal.transform(&huge, false);
 
-        let count: i64 =
-            sqlx::query_scalar("SELECT COUNT(*) FROM presentation WHERE id = ?")
-                .bind(pid)
-                .fetch_one(&state.db_pool)
-                .await
-                .unwrap();
-        assert_eq!(count, 1, "another user's presentation must not be deleted");
+        let fresh = TextPatch::between("## One", "## Once", 1);
+        handle_patch(&pres, &fresh, &AccessResult::Editor, "1", (1, "ed"), &direct).unwrap();
+        assert_eq!(pres.lock().unwrap().content, "## Once!");
+        assert!(!pres.is_poisoned());
+    }
+
+    #[test]
+    fn transformed_patches_converge() {
+        // Whichever order two concurrent patches arrive in, both sides end with the same text.
+        let base = "abcd";
+        let mut patches = Vec::new();
+        for start in 0..=4 {
+            for end in start..=4 {
+                for text in ["", "X", "YZ"] {
+                    patches.push(TextPatch { start, end, text: text.to_string(), ..TextPatch::default() });
+                }
+            }
+        }
+        for late in &patches {
+            for early in &patches {
+                let server = late.transform(early, true).apply(&early.apply(base).unwrap()).unwrap();
+                let client = early.transform(late, false).apply(&late.apply(base).unwrap()).unwrap();
+                assert_eq!(server, client, "late={late:?} early={early:?}");
+            }
+        }
     }
 
-    /// POST /user/presentations/{pid}/name with a plain-text body must return 200.
     #[tokio::test]
-    async fn rename_presentation_returns_200() {
-        let (server, state) = test_server().await;
-        seed_user(&state.db_pool).await;
-        let uid = get_user_id("testuser", &state.db_pool).await;
-        let pid = seed_presentation(uid, "Old Name", &state.db_pool).await;
-        login_as(&server, "testuser", "testpass").await;
+    async fn concurrent_patches_on_different_slides_both_survive() {
+        let pres = make_presentation_arc();
+        let deck = "## One\nfirst\n## Two\nsecond";
+        pres.lock().unwrap().set_content(deck.to_string(), None);
+        let mut rx = pres.lock().unwrap().channel.0.subscribe();
+        let (direct, mut direct_rx) = tokio::sync::mpsc::unbounded_channel();
 
-        let response = server
-            .post(&format!("/user/presentations/{pid}/name"))
-            .text("New Name")
-            .await;
+        // Both editors saw revision 1 when they typed.
+        let alice = TextPatch::between(deck, "## One\nfirst draft\n## Two\nsecond", 1);
+        let bob = TextPatch::between(deck, "## One\nfirst\n## Two\nsecond take", 1);
+        handle_patch(&pres, &alice, &AccessResult::Owner, "1", (1, "alice"), &direct).unwrap();
+        handle_patch(&pres, &bob, &AccessResult::Editor, "1", (2, "bob"), &direct).unwrap();
+
+        assert_eq!(pres.lock().unwrap().content, "## One\nfirst draft\n## Two\nsecond take");
+        assert!(direct_rx.try_recv().is_err(), "a rebased patch needs no snapshot");
+        let Ok(SlideMessage::Patch(first)) = rx.try_recv() else { panic!("expected a patch") };
+        let Ok(SlideMessage::Patch(second)) = rx.try_recv() else { panic!("expected a patch") };
+        assert_eq!((first.author, first.by.as_deref()), (Some(1), Some("alice")));
+        assert_eq!((second.base, second.author, second.by.as_deref()), (2, Some(2), Some("bob")));
+    }
+
+    #[test]
+    fn replay_returns_only_what_was_missed() {
+        let mut replay = Replay::new();
+        for n in 0..3 {
+            replay.push(SlideMessage::Slide(n));
+        }
+        let missed = replay.since(replay.stream, 1).unwrap();
+        assert_eq!(missed.iter().map(|(seq, _)| *seq).collect::<Vec<_>>(), vec![2, 3]);
+        assert!(replay.since(replay.stream, 3).unwrap().is_empty());
+        assert!(replay.since(replay.stream + 1, 1).is_none(), "another run of the stream");
+        assert!(replay.since(replay.stream, 4).is_none(), "a sequence number not reached yet");
+
+        for n in 0..REPLAY_LEN as u32 {
+            replay.push(SlideMessage::Slide(n));
+        }
+        assert!(replay.since(replay.stream, 1).is_none(), "the gap is too big to replay");
+        assert_eq!(replay.since(replay.stream, 3).unwrap().len(), REPLAY_LEN);
+    }
 
-        assert_eq!(response.status_code(), 200);
+    /// Serves a presentation as process `node` of those sharing `bus`, as
+    /// `add_client_handler_channel` does.
+    fn serve_on(bus: &Arc<dyn PubSub>, node: u64, pid: i64, pool: &SqlitePool, content: &str) -> Arc<Mutex<Presentation>> {
+        let pres = Presentation::new(content.to_string());
+        let (raw, replay) = (pres.channel.0.subscribe(), Arc::clone(&pres.replay));
+        let pres = Arc::new(Mutex::new(pres));
+        let link = Link { pubsub: Arc::clone(bus), node, pid: pid.to_string(), db_pool: pool.clone() };
+        tokio::spawn(sequencer(raw, Arc::downgrade(&pres), replay, link, content.to_string()));
+        pres
+    }
+
+    /// Waits until `done` holds, letting the sequencers run.
+    async fn settle(done: impl Fn() -> bool) {
+        for _ in 0..100 {
+            if done() {
+                return;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        panic!("the processes did not settle");
     }
 
-    /// Successful login must redirect to `/` (HTTP 303 See Other).
     #[tokio::test]
-    async fn login_correct_credentials_redirects_to_home() {
-        let (server, state) = test_server().await;
-        seed_user(&state.db_pool).await;
+    async fn sequencer_stamps_broadcasts_in_order() {
+        let bus: Arc<dyn PubSub> = Arc::new(pubsub::InMemory::default());
+        let pres = serve_on(&bus, 1, 1, &setup_pool().await, "");
+        let (tx, mut rx) = {
+            let p = pres.lock().unwrap();
+            (p.channel.0.clone(), p.replay.lock().unwrap().channel.subscribe())
+        };
+        assert!(tx.send(SlideMessage::Slide(4)).is_ok());
+        assert!(tx.send(SlideMessage::Name("Talk".to_string())).is_ok());
 
-        let response = server
-            .post("/auth/login")
-            .form(&serde_json::json!({
-                "username": "testuser",
-                "password": "testpass"
-            }))
-            .await;
+        let (first, msg) = rx.recv().await.unwrap();
+        assert!(first == 1 && matches!(msg, SlideMessage::Slide(4)));
+        let (second, msg) = rx.recv().await.unwrap();
+        assert_eq!(sequenced_json(&msg, second), r#"{"data":"Talk","seq":2,"type":"name"}"#);
+        assert_eq!(pres.lock().unwrap().replay.lock().unwrap().messages.len(), 2);
+    }
 
-        assert_eq!(response.status_code(), 303);
-        assert_eq!(
-            response.headers()["location"],
-            "/",
-            "successful login must redirect to /"
+    /// Two processes sharing a broker relay each other's broadcasts to their own
+    /// connections, and concurrent edits settle on the same deck in both.
+    #[tokio::test]
+    async fn processes_sharing_a_broker_share_presentations() {
+        let bus: Arc<dyn PubSub> = Arc::new(pubsub::InMemory::default());
+        let pool = setup_pool().await;
+        let a = serve_on(&bus, 1, 1, &pool, "## One");
+        let b = serve_on(&bus, 2, 1, &pool, "## One");
+        let mut rx = b.lock().unwrap().replay.lock().unwrap().channel.subscribe();
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+
+        a.lock().unwrap().set_content("## One!".to_string(), Some((0, "ed")));
+        assert!(a.lock().unwrap().channel.0.send(SlideMessage::Slide(2)).is_ok());
+        let Ok((_, SlideMessage::Patch(patch))) = rx.recv().await else { panic!("expected a patch") };
+        assert_eq!((patch.base, patch.text.as_str()), (0, "!"));
+        assert!(matches!(rx.recv().await, Ok((_, SlideMessage::Slide(2)))));
+        assert!(
+            matches!(rx.recv().await, Ok((_, SlideMessage::Pacing(Pacing { slide: 2, .. })))),
+            "each process paces the moves it sees for its own presenters"
         );
+        assert_eq!(b.lock().unwrap().content, "## One!");
+        assert_eq!(b.lock().unwrap().slide, 2);
+
+        b.lock().unwrap().next_connection_id = 5;
+        let cursor = Cursor { id: 0, user: "ed".to_string(), start: 1, end: 2 };
+        assert!(a.lock().unwrap().channel.0.send(SlideMessage::Cursor(cursor)).is_ok());
+        let Ok((_, SlideMessage::Cursor(relayed))) = rx.recv().await else { panic!("expected a cursor") };
+        assert_eq!((relayed.user.as_str(), relayed.start), ("ed", 1));
+        assert_eq!(relayed.id, 5, "a remote cursor gets a connection id of this process");
+
+        // Edits made on both processes before either hears of the other's are merged.
+        for n in 0..3 {
+            let edited = format!("# A{n}\n{}", a.lock().unwrap().content);
+            a.lock().unwrap().set_content(edited, None);
+            let edited = format!("{}\nB{n}", b.lock().unwrap().content);
+            b.lock().unwrap().set_content(edited, None);
+        }
+        let merged = "# A2\n# A1\n# A0\n## One!\nB0\nB1\nB2";
+        settle(|| a.lock().unwrap().content == merged && b.lock().unwrap().content == merged).await;
+
+        // Until then, the first processes were waiting for a state nobody had to send.
+        tokio::time::sleep(SYNC_WAIT).await;
+        let late = serve_on(&bus, 3, 1, &pool, "## One");
+        settle(|| late.lock().unwrap().content == merged).await;
+        assert_eq!(late.lock().unwrap().slide, 2, "a process joining late is sent the state");
     }
 
-    /// Wrong password must re-render the login page (HTTP 200), not redirect.
     #[tokio::test]
-    async fn login_wrong_password_returns_login_page() {
-        let (server, state) = test_server().await;
-        seed_user(&state.db_pool).await;
+    async fn concurrent_edits_on_several_processes_converge() {
+        let bus: Arc<dyn PubSub> = Arc::new(pubsub::InMemory::default());
+        let pool = setup_pool().await;
+        let processes: Vec<_> = (1..=3).map(|node| serve_on(&bus, node, 1, &pool, "## One\n")).collect();
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+
+        for round in 0..20 {
+            for (n, p) in processes.iter().enumerate() {
+                let mut p = p.lock().unwrap();
+                // Each process types at its own end of the deck, or in the middle.
+                let at = [0, p.content.find("One").unwrap() + 3, p.content.len()][n];
+                let edited = format!("{}<{n}.{round}>{}", &p.content[..at], &p.content[at..]);
+                p.set_content(edited, None);
+            }
+            tokio::task::yield_now().await;
+        }
+        let converged = || {
+            let decks: Vec<String> = processes.iter().map(|p| p.lock().unwrap().content.clone()).collect();
+            decks.iter().all(|deck| *deck == decks[0])
+        };
+        settle(converged).await;
+        let deck = processes[0].lock().unwrap().content.clone();
+        for n in 0..3 {
+            for round in 0..20 {
+                assert!(deck.contains(&format!("<{n}.{round}>")), "edit {n}.{round} was lost: {deck}");
+            }
+        }
+    }
 
-        let response = server
-            .post("/auth/login")
-            .form(&serde_json::json!({
-                "username": "testuser",
-                "password": "wrongpass"
-            }))
-            .await;
+    /// The recording stops when the last presenter leaves, even if they were connected
+    /// to another process than the one recording.
+    #[tokio::test]
+    async fn recording_stops_when_presenters_leave_every_process() {
+        let bus: Arc<dyn PubSub> = Arc::new(pubsub::InMemory::default());
+        let pool = setup_pool().await;
+        let owner = make_user(&pool, "owner").await;
+        let pres_db = make_presentation(&owner, &pool).await;
+        let recording = serve_on(&bus, 1, pres_db.id, &pool, "## One");
+        handle_recording_message(RecordingMessage::RecordingStart, &recording, pres_db.id, &pool).await;
+        let rec_id = recording.lock().unwrap().recording.as_ref().unwrap().db_id;
+        let link = Link { pubsub: Arc::clone(&bus), node: 2, pid: pres_db.id.to_string(), db_pool: pool.clone() };
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+
+        assert_eq!(link.count_presenters(1).await.unwrap(), 1);
+        assert_eq!(link.count_presenters(0).await.unwrap(), 0);
+        link.publish(RemoteEvent::PresentersGone).await;
+        settle(|| recording.lock().unwrap().recording.is_none()).await;
+        let rec = Recording::get_by_id(rec_id, &pool).await.unwrap().unwrap();
+        assert!(rec.last_edited.is_some(), "the recording was saved");
+    }
 
-        assert_eq!(
-            response.status_code(),
-            200,
-            "wrong password should return 200 (re-render login page), not redirect"
-        );
-        assert!(
-            response.text().contains("Invalid username or password."),
-            "wrong password must show an error message (WCAG 3.3.1 Error Identification)"
-        );
+    /// A process that crashes stops counting its presenters once its share expires, so the
+    /// recording held elsewhere stops as if they had left.
+    #[tokio::test]
+    async fn recording_stops_when_the_process_of_its_presenters_crashes() {
+        let bus: Arc<dyn PubSub> = Arc::new(pubsub::InMemory::default());
+        let pool = setup_pool().await;
+        let owner = make_user(&pool, "owner").await;
+        let pres_db = make_presentation(&owner, &pool).await;
+        let recording = serve_on(&bus, 1, pres_db.id, &pool, "## One");
+        handle_recording_message(RecordingMessage::RecordingStart, &recording, pres_db.id, &pool).await;
+        let crashed = Link { pubsub: Arc::clone(&bus), node: 2, pid: pres_db.id.to_string(), db_pool: pool.clone() };
+        assert_eq!(crashed.count_presenters(1).await.unwrap(), 1);
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+
+        tokio::time::pause();
+        tokio::time::advance(HEARTBEAT).await;
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+        assert!(recording.lock().unwrap().recording.is_some(), "the presenter is still counted");
+        tokio::time::advance(PRESENTERS_TTL).await;
+        tokio::time::resume();
+        settle(|| recording.lock().unwrap().recording.is_none()).await;
     }
 
-    /// Accessing a protected route without a session must redirect to `/auth/login`.
+    /// Presenters on any process can pause and stop a recording held by another.
     #[tokio::test]
-    async fn presentations_without_session_redirects_to_login() {
-        let (server, _state) = test_server().await;
+    async fn recording_is_controlled_from_any_process() {
+        let bus: Arc<dyn PubSub> = Arc::new(pubsub::InMemory::default());
+        let pool = setup_pool().await;
+        let owner = make_user(&pool, "owner").await;
+        let pres_db = make_presentation(&owner, &pool).await;
+        let holder = serve_on(&bus, 1, pres_db.id, &pool, "## One");
+        let other = serve_on(&bus, 2, pres_db.id, &pool, "## One");
+        let link = Link { pubsub: Arc::clone(&bus), node: 2, pid: pres_db.id.to_string(), db_pool: pool.clone() };
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
 
-        let response = server.get("/user/presentations").await;
+        let started = control_recording(RecordingMessage::RecordingStart, &holder, pres_db.id, &link).await.unwrap();
+        assert!(holder.lock().unwrap().channel.0.send(started).is_ok());
+        settle(|| other.lock().unwrap().recording_elsewhere).await;
+        let second = control_recording(RecordingMessage::RecordingStart, &other, pres_db.id, &link).await;
+        assert!(second.is_none() && other.lock().unwrap().recording.is_none(), "one recording at a time");
 
-        assert_eq!(response.status_code(), 303);
+        assert!(control_recording(RecordingMessage::RecordingPause, &other, pres_db.id, &link).await.is_none());
+        settle(|| holder.lock().unwrap().recording.as_ref().is_some_and(|rec| rec.is_paused)).await;
+        control_recording(RecordingMessage::RecordingStop, &other, pres_db.id, &link).await;
+        settle(|| holder.lock().unwrap().recording.is_none() && !other.lock().unwrap().recording_elsewhere).await;
+    }
+
+    /// Presenters see the viewers of every process, and a process joining late paces the
+    /// session from its first move.
+    #[tokio::test]
+    async fn presence_and_pacing_are_shared_between_processes() {
+        let bus: Arc<dyn PubSub> = Arc::new(pubsub::InMemory::default());
+        let pool = setup_pool().await;
+        let a = serve_on(&bus, 1, 1, &pool, "## One\n## Two");
+        let b = serve_on(&bus, 2, 1, &pool, "## One\n## Two");
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+
+        a.lock().unwrap().attendees.insert(0, (AccessResult::Audience, Some("amy".to_string())));
+        b.lock().unwrap().attendees.insert(0, (AccessResult::PublicOk, None));
+        b.lock().unwrap().attendees.insert(1, (AccessResult::Owner, None));
+        announce_presence(&a);
+        announce_presence(&b);
+        let expected = Presence { viewers: 2, audience: 1, public: 1, presenters: 1, users: vec!["amy".to_string()], ..Presence::default() };
+        settle(|| a.lock().unwrap().presence() == expected && b.lock().unwrap().presence() == expected).await;
+
+        a.lock().unwrap().show(1, 0);
+        assert!(a.lock().unwrap().channel.0.send(SlideMessage::Slide(1)).is_ok());
+        tokio::time::sleep(SYNC_WAIT).await;
+        let late = serve_on(&bus, 3, 1, &pool, "## One\n## Two");
+        settle(|| late.lock().unwrap().clock.is_some()).await;
+        let late_ms = late.lock().unwrap().clock.as_ref().unwrap().elapsed_ms();
+        assert!(late_ms >= SYNC_WAIT.as_millis() as u64, "the clock started with the first move");
+        assert_eq!(late.lock().unwrap().presence(), expected);
+    }
+
+    #[test]
+    fn viewer_gets_its_own_form_of_each_broadcast() {
+        let audience = Viewer { connection_id: 1, is_presenter: false, is_audience: true, is_editor: false };
+        let editor = Viewer { connection_id: 2, is_presenter: true, is_audience: false, is_editor: true };
+        let text = SlideMessage::Text("## One\n???\nSecret".to_string());
+        assert!(matches!(audience.view(&text), Some(SlideMessage::Text(t)) if !t.contains("Secret")));
+        assert!(matches!(editor.view(&text), Some(SlideMessage::Text(t)) if t.contains("Secret")));
+
+        let patch = TextPatch { author: Some(2), ..TextPatch::default() };
+        assert!(matches!(editor.view(&SlideMessage::Patch(patch.clone())), Some(SlideMessage::Patch(p)) if p.mine));
+        assert!(matches!(audience.view(&SlideMessage::Patch(patch)), Some(SlideMessage::Patch(p)) if !p.mine));
+
+        let own_cursor = SlideMessage::Cursor(Cursor { id: 2, ..Cursor::default() });
+        assert!(editor.view(&own_cursor).is_none());
+        assert!(editor.view(&SlideMessage::CursorGone(3)).is_some());
+        assert!(audience.view(&SlideMessage::CursorGone(3)).is_none());
+        assert!(audience.view(&SlideMessage::RecordingPause { elapsed_ms: 1 }).is_none());
+    }
+
+    /// Presence must count connections by role, name signed-in viewers once, and reach
+    /// presenters only.
+    #[test]
+    fn presence_counts_connections_by_role() {
+        let mut p = Presentation::new(String::new());
+        p.attendees.insert(0, (AccessResult::Owner, None));
+        p.attendees.insert(1, (AccessResult::Audience, Some("bob".to_string())));
+        p.attendees.insert(2, (AccessResult::Audience, Some("bob".to_string())));
+        p.attendees.insert(3, (AccessResult::PublicOk, Some("alice".to_string())));
+        p.attendees.insert(4, (AccessResult::PublicOk, None));
+        p.attendees.insert(5, (AccessResult::Captioner, Some("carol".to_string())));
+        let presence = p.presence();
         assert_eq!(
-            response.headers()["location"],
-            "/auth/login",
-            "unauthenticated request must redirect to /auth/login"
+            presence,
+            Presence { viewers: 4, audience: 2, public: 2, presenters: 1, captioners: 1, users: vec!["alice".to_string(), "bob".to_string()] }
         );
-    }
 
-    /// Inserts a recording row for the given presentation and returns its id.
-    async fn seed_recording(presentation_id: i64, pool: &SqlitePool) -> i64 {
-        sqlx::query_scalar::<_, i64>(
-            "INSERT INTO recording (presentation_id, name, captions_path, start) VALUES (?, 'Test Recording', 'captions.vtt', '2026-01-01T00:00:00+00:00') RETURNING id",
-        )
-        .bind(presentation_id)
-        .fetch_one(pool)
-        .await
-        .unwrap()
+        let message = SlideMessage::Presence(presence);
+        let audience = Viewer { connection_id: 1, is_presenter: false, is_audience: true, is_editor: false };
+        let controller = Viewer { connection_id: 5, is_presenter: true, is_audience: false, is_editor: false };
+        assert!(audience.view(&message).is_none());
+        assert!(controller.view(&message).is_some());
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&json(&message)).unwrap(),
+            serde_json::json!({"type": "presence", "data": {"viewers": 4, "audience": 2, "public": 2, "presenters": 1, "captioners": 1, "users": ["alice", "bob"]}})
+        );
     }
 
-    /// Inserts a recording_slide row for the given recording and returns its id.
-    async fn seed_recording_slide(recording_id: i64, pool: &SqlitePool) -> i64 {
-        sqlx::query_scalar::<_, i64>(
-            "INSERT INTO recording_slide (recording_id, start_seconds, position, title, content) VALUES (?, 0.0, 0, 'Slide 1', 'content') RETURNING id",
-        )
-        .bind(recording_id)
-        .fetch_one(pool)
-        .await
-        .unwrap()
+    /// Connections coming and going within the interval must make one presence update.
+    #[tokio::test(start_paused = true)]
+    async fn presence_updates_are_throttled() {
+        let pres = make_presentation_arc();
+        let mut rx = pres.lock().unwrap().channel.0.subscribe();
+        pres.lock().unwrap().attendees.insert(0, (AccessResult::Audience, None));
+        announce_presence(&pres);
+        tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+        assert!(matches!(rx.try_recv(), Ok(SlideMessage::Presence(p)) if p.viewers == 1), "the first update is immediate");
+
+        for id in 1..4 {
+            pres.lock().unwrap().attendees.insert(id, (AccessResult::PublicOk, None));
+            announce_presence(&pres);
+        }
+        pres.lock().unwrap().attendees.remove(&3);
+        announce_presence(&pres);
+        tokio::time::sleep(PRESENCE_INTERVAL / 2).await;
+        assert!(rx.try_recv().is_err(), "nothing before the interval has passed");
+        tokio::time::sleep(PRESENCE_INTERVAL).await;
+        assert!(matches!(rx.try_recv(), Ok(SlideMessage::Presence(p)) if p.viewers == 3));
+        assert!(rx.try_recv().is_err(), "one update for the whole burst");
+
+        // A change undone before the update is due sends nothing.
+        pres.lock().unwrap().attendees.insert(9, (AccessResult::PublicOk, None));
+        announce_presence(&pres);
+        pres.lock().unwrap().attendees.remove(&9);
+        tokio::time::sleep(PRESENCE_INTERVAL * 2).await;
+        assert!(rx.try_recv().is_err());
     }
 
-    /// Deleting a presentation must remove all its recording_slide rows.
-    /// recording_slide has no ON DELETE CASCADE — DbPresentation::delete
-    /// performs the cleanup manually. If this ever breaks, recording_slide
-    /// rows become orphaned and FK enforcement will block future deletions.
     #[tokio::test]
-    async fn delete_presentation_removes_recording_slides() {
-        let (_server, state) = test_server().await;
-        // test_server seeds admin/admin from migrations; use that directly.
-        let uid = get_user_id("admin", &state.db_pool).await;
-        let pid = seed_presentation(uid, "With Recordings", &state.db_pool).await;
-        let rid = seed_recording(pid, &state.db_pool).await;
-        let sid = seed_recording_slide(rid, &state.db_pool).await;
-
-        DbPresentation::delete(pid, uid, &state.db_pool).await.unwrap();
+    async fn cursor_identity_comes_from_the_server() {
+        let msg: SlideMessage =
+            serde_json::from_str(r#"{"type":"cursor","data":{"id":7,"user":"mallory","start":3,"end":5}}"#).unwrap();
+        let SlideMessage::Cursor(cursor) = msg else { panic!("expected a cursor") };
+        assert_eq!(cursor, Cursor { id: 0, user: String::new(), start: 3, end: 5 });
 
-        let pres_count: i64 =
-            sqlx::query_scalar("SELECT COUNT(*) FROM presentation WHERE id = ?")
-                .bind(pid)
-                .fetch_one(&state.db_pool)
-                .await
-                .unwrap();
-        let rec_count: i64 =
-            sqlx::query_scalar("SELECT COUNT(*) FROM recording WHERE id = ?")
-                .bind(rid)
-                .fetch_one(&state.db_pool)
-                .await
-                .unwrap();
-        let slide_count: i64 =
-            sqlx::query_scalar("SELECT COUNT(*) FROM recording_slide WHERE id = ?")
-                .bind(sid)
-                .fetch_one(&state.db_pool)
-                .await
-                .unwrap();
+        let (_server, state) = test_server().await;
+        let (tx, mut rx) = tokio::sync::broadcast::channel::<SlideMessage>(8);
+        let mut tx = tx;
+        let msg = axum::extract::ws::Message::text(r#"{"type":"cursor_gone","data":7}"#);
+        let (direct, _replies) = tokio::sync::mpsc::unbounded_channel();
+        let result = handle_socket(Ok(msg), "1", &mut tx, &mut state.clone(), &AccessResult::Owner, &direct);
+        assert!(matches!(result, Ok(true)));
+        assert!(rx.try_recv().is_err(), "cursor_gone from a client must not be relayed");
+    }
 
-        assert_eq!(pres_count, 0, "presentation row must be deleted");
-        assert_eq!(rec_count, 0, "recording row must be deleted");
-        assert_eq!(slide_count, 0, "recording_slide row must be deleted (manual cascade)");
+    /// Each lag event must be counted per connection, with the messages it skipped.
+    #[test]
+    fn connection_stats_count_lag_events() {
+        let diagnostics = ConnectionStats::default();
+        assert_eq!(diagnostics.record_lag(5), 1);
+        assert_eq!(diagnostics.record_lag(7), 2);
+        assert_eq!(diagnostics.skipped_messages.load(Ordering::Relaxed), 12);
     }
 
-    /// POST /user/new by a non-admin authenticated user must return 404.
-    /// The handler explicitly returns NOT_FOUND (not 403) to avoid leaking
-    /// the existence of the admin-only endpoint to non-admin users.
+    /// recording_start when already active must return None (no-op).
     #[tokio::test]
-    async fn create_user_by_non_admin_returns_404() {
-        let (server, state) = test_server().await;
-        seed_user(&state.db_pool).await;
-        // testuser has no group membership — not in the admin group.
-        login_as(&server, "testuser", "testpass").await;
+    async fn recording_start_ignored_if_active() {
+        let pool = setup_pool().await;
+        let owner = make_user(&pool, "owner2").await;
+        let pres_db = make_presentation(&owner, &pool).await;
+        let pres = make_presentation_arc();
 
-        let response = server
-            .post("/user/new")
-            .form(&serde_json::json!({
-                "name": "newuser",
-                "email": "new@example.com",
-                "password": "password123"
-            }))
-            .await;
+        handle_recording_message(RecordingMessage::RecordingStart, &pres, pres_db.id, &pool).await;
+        let result = handle_recording_message(RecordingMessage::RecordingStart, &pres, pres_db.id, &pool).await;
 
-        assert_eq!(
-            response.status_code(),
-            404,
-            "non-admin must not be able to create users"
-        );
-        // The user must not have been created.
-        let count: i64 =
-            sqlx::query_scalar("SELECT COUNT(*) FROM users WHERE name = 'newuser'")
-                .fetch_one(&state.db_pool)
-                .await
-                .unwrap();
-        assert_eq!(count, 0, "new user must not exist after rejected request");
+        assert!(result.is_none());
+        // Only one DB row
+        let rows: Vec<Recording> = sqlx::query_as::<_, Recording>(
+            "SELECT * FROM recording WHERE presentation_id = ?",
+        )
+        .bind(pres_db.id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(rows.len(), 1);
     }
 
-    /// After a successful login, the session cookie must grant access to protected routes.
+    /// recording_stop must write recording_slide rows and clear in-memory state.
     #[tokio::test]
-    async fn presentations_with_valid_session_returns_200() {
-        let (server, state) = test_server().await;
-        seed_user(&state.db_pool).await;
+    async fn recording_stop_saves_slides() {
+        let pool = setup_pool().await;
+        let owner = make_user(&pool, "owner3").await;
+        let pres_db = make_presentation(&owner, &pool).await;
+        let pres = make_presentation_arc();
 
-        // Establish a session. The TestServer saves the Set-Cookie from this
-        // response and sends it on subsequent requests.
-        server
-            .post("/auth/login")
-            .form(&serde_json::json!({
-                "username": "testuser",
-                "password": "testpass"
-            }))
-            .await;
+        handle_recording_message(RecordingMessage::RecordingStart, &pres, pres_db.id, &pool).await;
 
-        let response = server.get("/user/presentations").await;
-        assert_eq!(
-            response.status_code(),
-            200,
-            "authenticated request should return 200"
-        );
-    }
+        // Simulate a slide change at 2000ms
+        {
+            let mut p = pres.lock().unwrap();
+            let rec = p.recording.as_mut().unwrap();
+            rec.slides.push(RecordingEvent { offset_ms: 2000, slide: 1, fragment: 0, highlight: None });
+        }
 
-    // --- FAQ merge tests ---
+        let result = handle_recording_message(RecordingMessage::RecordingStop, &pres, pres_db.id, &pool).await;
 
-    #[tokio::test]
-    async fn test_home_faq_presenter_section_removed() {
-        let (server, _state) = test_server().await;
-        let response = server.get("/").await;
-        response.assert_status_ok();
-        let body = response.text();
-        assert!(
-            !body.contains("For Presenters"),
-            "home page should not contain the For Presenters heading after merge"
-        );
+        assert!(matches!(result, Some(SlideMessage::RecordingStop { .. })));
+        assert!(pres.lock().unwrap().recording.is_none());
+
+        // recording_slide rows exist
+        let rows = sqlx::query_as::<_, RecordingSlide>(
+            "SELECT * FROM recording_slide WHERE recording_id = (SELECT id FROM recording WHERE presentation_id = ? ORDER BY id DESC LIMIT 1) ORDER BY position",
+        )
+        .bind(pres_db.id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].position, 0);
+        assert_eq!(rows[0].title, "Intro");
+        assert!((rows[0].start_seconds - 0.0).abs() < 0.001);
+        assert_eq!(rows[1].position, 1);
+        assert_eq!(rows[1].title, "Second");
+        assert!((rows[1].start_seconds - 2.0).abs() < 0.001);
     }
 
+    /// Elapsed time must account for paused periods.
     #[tokio::test]
-    async fn test_home_faq_audience_section_intact() {
-        let (server, _state) = test_server().await;
-        let response = server.get("/").await;
-        response.assert_status_ok();
-        let body = response.text();
-        assert!(
-            body.contains("How does an audience member follow along"),
-            "home page should still contain audience FAQ questions"
-        );
-    }
+    async fn recording_pause_resume_elapsed() {
+        let pool = setup_pool().await;
+        let owner = make_user(&pool, "owner").await;
+        let pres_db = make_presentation(&owner, &pool).await;
+        let pres = make_presentation_arc();
 
-    #[tokio::test]
-    async fn test_help_editing_slides_expanded() {
-        let (server, _state) = test_server().await;
-        let response = server.get("/help").await;
-        response.assert_status_ok();
-        let body = response.text();
-        assert!(
-            body.contains("KaTeX"),
-            "help page should mention KaTeX math support"
-        );
-        assert!(
-            body.contains("saved as you type"),
-            "help page should mention autosave"
-        );
-        assert!(
-            body.contains("pushed to all connected audience members instantly"),
-            "help page should mention live sync pushing edits instantly"
-        );
+        // Start
+        handle_recording_message(RecordingMessage::RecordingStart, &pres, pres_db.id, &pool).await;
+
+        // Pause immediately — elapsed should be very small (< 100ms)
+        let pause_msg = handle_recording_message(RecordingMessage::RecordingPause, &pres, pres_db.id, &pool).await;
+        let elapsed_at_pause = match pause_msg {
+            Some(SlideMessage::RecordingPause { elapsed_ms }) => elapsed_ms,
+            _ => panic!("expected RecordingPause"),
+        };
+        assert!(elapsed_at_pause < 200, "elapsed at pause was {elapsed_at_pause}ms, expected < 200ms");
+        assert!(pres.lock().unwrap().recording.as_ref().unwrap().is_paused);
+
+        // Resume — elapsed should still be small
+        let resume_msg = handle_recording_message(RecordingMessage::RecordingResume, &pres, pres_db.id, &pool).await;
+        let elapsed_at_resume = match resume_msg {
+            Some(SlideMessage::RecordingResume { elapsed_ms }) => elapsed_ms,
+            _ => panic!("expected RecordingResume"),
+        };
+        assert!(!pres.lock().unwrap().recording.as_ref().unwrap().is_paused);
+        // elapsed at resume should be >= elapsed at pause (not reset to 0)
+        assert!(elapsed_at_resume >= elapsed_at_pause,
+            "elapsed at resume ({elapsed_at_resume}) should be >= elapsed at pause ({elapsed_at_pause})");
     }
 
+    /// Pausing when already paused must return None.
     #[tokio::test]
-    async fn test_help_recording_expanded() {
-        let (server, _state) = test_server().await;
-        let response = server.get("/help").await;
-        response.assert_status_ok();
-        let body = response.text();
-        assert!(
-            body.contains("WebVTT"),
-            "help page should mention WebVTT export in the Recording section"
-        );
-        assert!(
-            body.contains("12.5s"),
-            "help page should show dropdown label format example including timestamp"
-        );
+    async fn recording_pause_noop_when_already_paused() {
+        let pool = setup_pool().await;
+        let owner = make_user(&pool, "owner").await;
+        let pres_db = make_presentation(&owner, &pool).await;
+        let pres = make_presentation_arc();
+        handle_recording_message(RecordingMessage::RecordingStart, &pres, pres_db.id, &pool).await;
+        handle_recording_message(RecordingMessage::RecordingPause, &pres, pres_db.id, &pool).await;
+        let result = handle_recording_message(RecordingMessage::RecordingPause, &pres, pres_db.id, &pool).await;
+        assert!(result.is_none());
     }
 
-    /// The presentation_access table must exist after migrations and accept
-    /// a valid (presentation_id, user_id, role) row.
+    /// When presenter_count drops to 0 with an active recording, the stop logic saves slides.
     #[tokio::test]
-    async fn presentation_access_table_exists_and_accepts_rows() {
-        let (_server, state) = test_server().await;
-        let uid = get_user_id("admin", &state.db_pool).await;
-        let pid = seed_presentation(uid, "Access Test", &state.db_pool).await;
+    async fn recording_auto_stop_on_last_presenter_disconnect() {
+        let pool = setup_pool().await;
+        let owner = make_user(&pool, "owner").await;
+        let pres_db = make_presentation(&owner, &pool).await;
+        let pres = make_presentation_arc();
 
-        // Insert a second user to be the co-presenter
-        User::new(
-            &state.db_pool,
-            AddUserForm {
-                name: "copresenter".to_string(),
-                email: "co@example.com".to_string(),
-                password: "copass".to_string(),
-            },
-        )
-        .await
-        .unwrap();
-        let co_uid = get_user_id("copresenter", &state.db_pool).await;
+        // Start recording
+        handle_recording_message(RecordingMessage::RecordingStart, &pres, pres_db.id, &pool).await;
+        let rec_id = pres.lock().unwrap().recording.as_ref().unwrap().db_id;
 
-        sqlx::query(
-            "INSERT INTO presentation_access (presentation_id, user_id, role) VALUES (?, ?, 'editor')",
-        )
-        .bind(pid)
-        .bind(co_uid)
-        .execute(&state.db_pool)
-        .await
-        .expect("presentation_access table must accept a valid row");
+        // Simulate auto-stop (same as stop, but called by disconnect handler)
+        let result = handle_recording_message(RecordingMessage::RecordingStop, &pres, pres_db.id, &pool).await;
+        assert!(matches!(result, Some(SlideMessage::RecordingStop { .. })));
+        assert!(pres.lock().unwrap().recording.is_none());
 
-        let role: String = sqlx::query_scalar(
-            "SELECT role FROM presentation_access WHERE presentation_id = ? AND user_id = ?",
-        )
-        .bind(pid)
-        .bind(co_uid)
-        .fetch_one(&state.db_pool)
-        .await
-        .unwrap();
-        assert_eq!(role, "editor");
+        // last_edited was set
+        let rec = Recording::get_by_id(rec_id, &pool).await.unwrap().unwrap();
+        assert!(rec.last_edited.is_some());
     }
 
-    /// A Controller role must not be permitted to send a Name message.
-    /// handle_socket must return Ok(true) (keep connection open, message dropped).
+    /// GET /auth/logout must clear the session and redirect to /.
     #[tokio::test]
-    async fn ws_controller_cannot_send_name_message() {
-        let (_server, state) = test_server().await;
-        let (tx, _rx) = tokio::sync::broadcast::channel::<SlideMessage>(8);
-        let mut tx = tx;
-        let msg = axum::extract::ws::Message::text(
-            serde_json::to_string(&SlideMessage::Name("hacked".to_string())).unwrap(),
-        );
-        let mut state_clone = state.clone();
-        let uid = get_user_id("admin", &state.db_pool).await;
-        let pid = seed_presentation(uid, "WS Test", &state.db_pool).await;
-        let result = handle_socket(Ok(msg), &pid.to_string(), &mut tx, &mut state_clone, &AccessResult::Controller);
-        assert!(
-            matches!(result, Ok(true)),
-            "Controller sending Name must be silently dropped (Ok(true)), not an error"
-        );
-    }
+    async fn logout_clears_session_and_redirects() {
+        let (server, _state) = test_server().await;
+        login_as(&server, "admin", "admin").await;
 
-    /// An Editor role must be permitted to send a Slide message.
-    #[tokio::test]
-    async fn ws_editor_can_send_slide_message() {
-        let (_server, state) = test_server().await;
-        let uid = get_user_id("admin", &state.db_pool).await;
-        let pid = seed_presentation(uid, "WS Test 2", &state.db_pool).await;
-        let mut state_clone = state.clone();
+        // Authenticated: presentations page is accessible.
+        let before = server.get("/user/presentations").await;
+        assert_eq!(before.status_code(), 200);
 
-        {
-            let (tx_inner, rx_inner) = tokio::sync::broadcast::channel::<SlideMessage>(8);
-            state_clone.slides.lock().unwrap().insert(
-                pid.to_string(),
-                Arc::new(Mutex::new(Presentation {
-                    content: String::new(),
-                    slide: 0,
-                    channel: (tx_inner, rx_inner),
-                    recording: None,
-                    presenter_count: 0,
-                })),
-            );
-        }
+        let resp = server.get("/auth/logout").await;
+        assert!(resp.status_code().is_redirection());
 
-        let (tx, mut rx) = tokio::sync::broadcast::channel::<SlideMessage>(8);
-        let mut tx_clone = tx;
-        let msg = axum::extract::ws::Message::text(
-            serde_json::to_string(&SlideMessage::Slide(2)).unwrap(),
-        );
-        let result = handle_socket(Ok(msg), &pid.to_string(), &mut tx_clone, &mut state_clone, &AccessResult::Editor);
-        assert!(matches!(result, Ok(true)), "Editor must be able to send Slide");
-        let received = rx.try_recv();
-        assert!(
-            matches!(received, Ok(SlideMessage::Slide(2))),
-            "Slide message must be broadcast when sent by Editor"
-        );
+        // After logout: presentations page redirects to login.
+        let after = server.get("/user/presentations").await;
+        assert!(after.status_code().is_redirection());
+        let loc = after.headers().get("location").unwrap().to_str().unwrap();
+        assert!(loc.contains("/auth/login"), "expected redirect to login, got {loc}");
     }
 
-    /// Audience connections must not receive recording control messages from the broadcast channel.
-    ///
-    /// This mirrors the server-side filtering introduced to prevent data exposure: recording
-    /// start/pause/resume/stop events are only meaningful to presenter connections (Owner /
-    /// Editor / Controller) and must be silently dropped before they reach audience or public
-    /// subscribers.  `Text`, `Slide`, and `Name` messages must still pass through so that the
-    /// audience view continues to update.
-    ///
-    /// Analogous real-world precedent: Google Docs broadcasts cursor and selection events only to
-    /// participants with edit access and never to public-link viewers.
+    /// POST /user/new by an admin must create the user and redirect.
     #[tokio::test]
-    async fn audience_connection_does_not_receive_recording_messages() {
-        use tokio::sync::broadcast;
+    async fn admin_can_create_user() {
+        let (server, state) = test_server().await;
+        login_as(&server, "admin", "admin").await;
 
-        let (tx, _rx_main) = broadcast::channel::<SlideMessage>(16);
+        let resp = server
+            .post("/user/new")
+            .form(&serde_json::json!({
+                "name": "newbie",
+                "email": "newbie@example.com",
+                "password": "newbiepass"
+            }))
+            .await;
+        assert!(resp.status_code().is_redirection());
 
-        // Simulate the audience subscriber's receiver.
-        let mut rx_audience = tx.subscribe();
+        let exists: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users WHERE name = 'newbie'")
+            .fetch_one(&state.db_pool)
+            .await
+            .unwrap();
+        assert_eq!(exists, 1);
+    }
 
-        // Publish each recording control variant as if a presenter had triggered them.
-        let recording_msgs: Vec<SlideMessage> = vec![
-            SlideMessage::RecordingStart { elapsed_ms: 0 },
-            SlideMessage::RecordingPause { elapsed_ms: 500 },
-            SlideMessage::RecordingResume { elapsed_ms: 500 },
-            SlideMessage::RecordingStop {
-                id: 1,
-                name: "test".to_string(),
-                start: "2026-01-01".to_string(),
-            },
-        ];
-        for m in &recording_msgs {
-            assert!(tx.send(m.clone()).is_ok(), "broadcast send must succeed");
-        }
+    /// POST /user/change_pwd with correct old password must update it.
+    #[tokio::test]
+    async fn change_pwd_succeeds_with_correct_old_password() {
+        let (server, state) = test_server().await;
+        login_as(&server, "admin", "admin").await;
 
-        // Also send allowed message types that the audience view needs.
-        assert!(tx.send(SlideMessage::Slide(3)).is_ok());
-        assert!(tx.send(SlideMessage::Text("# Hello".to_string())).is_ok());
-        assert!(tx.send(SlideMessage::Name("My Talk".to_string())).is_ok());
+        let resp = server
+            .post("/user/change_pwd")
+            .form(&serde_json::json!({
+                "old": "admin",
+                "new": "newpass123",
+                "confirm": "newpass123"
+            }))
+            .await;
+        assert!(resp.status_code().is_redirection());
+
+        // New password must work for login.
+        let login_resp = server
+            .post("/auth/login")
+            .form(&serde_json::json!({ "username": "admin", "password": "newpass123" }))
+            .await;
+        assert!(login_resp.status_code().is_redirection());
 
-        // Apply the same filter used in the server-side channel_handler for audience connections.
-        let mut allowed = vec![];
-        while let Ok(msg) = rx_audience.try_recv() {
-            if matches!(
-                msg,
-                SlideMessage::Text(_) | SlideMessage::Slide(_) | SlideMessage::Name(_)
-            ) {
-                allowed.push(msg);
-            }
-        }
+        // Drop pool to silence unused warning in test context.
+        let _ = &state.db_pool;
+    }
 
-        assert_eq!(allowed.len(), 3, "audience must receive exactly 3 non-recording messages");
-        assert!(
-            matches!(allowed[0], SlideMessage::Slide(3)),
-            "audience must receive Slide"
-        );
-        assert!(
-            matches!(allowed[1], SlideMessage::Text(_)),
-            "audience must receive Text"
-        );
-        assert!(
-            matches!(allowed[2], SlideMessage::Name(_)),
-            "audience must receive Name"
-        );
+    /// POST /user/change_pwd with wrong old password must redirect back with an error.
+    #[tokio::test]
+    async fn change_pwd_rejects_wrong_old_password() {
+        let (server, _state) = test_server().await;
+        login_as(&server, "admin", "admin").await;
+
+        let resp = server
+            .post("/user/change_pwd")
+            .form(&serde_json::json!({
+                "old": "wrongpassword",
+                "new": "newpass123",
+                "confirm": "newpass123"
+            }))
+            .await;
+        assert!(resp.status_code().is_redirection());
+        let loc = resp.headers().get("location").unwrap().to_str().unwrap();
+        assert!(loc.contains("error="), "expected error query param, got {loc}");
     }
 
-    /// POST /user/presentations/{pid}/access/add by the owner must insert the row
-    /// and redirect to /user/presentations.
+    /// POST /user/change_pwd with mismatched new/confirm must redirect with an error.
     #[tokio::test]
-    async fn add_access_as_owner_inserts_row() {
-        let (server, state) = test_server().await;
-        seed_user(&state.db_pool).await;
-        let uid = get_user_id("testuser", &state.db_pool).await;
-        let pid = seed_presentation(uid, "Shared Pres", &state.db_pool).await;
-        // Create a second user to add as co-presenter
-        User::new(
-            &state.db_pool,
-            AddUserForm {
-                name: "couser".to_string(),
-                email: "co@example.com".to_string(),
-                password: "copass".to_string(),
-            },
-        )
-        .await
-        .unwrap();
-        login_as(&server, "testuser", "testpass").await;
+    async fn change_pwd_rejects_mismatched_confirm() {
+        let (server, _state) = test_server().await;
+        login_as(&server, "admin", "admin").await;
 
-        let response = server
-            .post(&format!("/user/presentations/{pid}/access/add"))
-            .form(&serde_json::json!({ "username": "couser", "role": "editor" }))
+        let resp = server
+            .post("/user/change_pwd")
+            .form(&serde_json::json!({
+                "old": "admin",
+                "new": "newpass123",
+                "confirm": "differentpass"
+            }))
             .await;
+        assert!(resp.status_code().is_redirection());
+        let loc = resp.headers().get("location").unwrap().to_str().unwrap();
+        assert!(loc.contains("error="), "expected error query param, got {loc}");
+    }
 
-        assert_eq!(response.status_code(), 303);
-        let count: i64 = sqlx::query_scalar(
-            "SELECT COUNT(*) FROM presentation_access WHERE presentation_id = ?",
+    /// POST /user/recordings/{rid}/delete by owner must remove the row and redirect.
+    #[tokio::test]
+    async fn delete_recording_as_owner_removes_row() {
+        let (server, state) = test_server().await;
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(uid, "Rec Delete Test", &state.db_pool).await;
+        let rid: i64 = sqlx::query_scalar(
+            "INSERT INTO recording (presentation_id, name, captions_path) VALUES (?, 'Rec', 'r.vtt') RETURNING id"
         )
         .bind(pid)
         .fetch_one(&state.db_pool)
         .await
         .unwrap();
-        assert_eq!(count, 1, "access row must be inserted");
+
+        login_as(&server, "admin", "admin").await;
+        let resp = server.post(&format!("/user/recordings/{rid}/delete")).await;
+        assert!(resp.status_code().is_redirection());
+
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM recording WHERE id = ?")
+            .bind(rid)
+            .fetch_one(&state.db_pool)
+            .await
+            .unwrap();
+        assert_eq!(count, 0);
     }
 
-    /// POST .../access/add by a non-owner must return 404.
+    /// POST /user/recordings/{rid}/delete by non-owner must return 403.
     #[tokio::test]
-    async fn add_access_by_non_owner_returns_404() {
+    async fn delete_recording_as_non_owner_returns_403() {
         let (server, state) = test_server().await;
         seed_user(&state.db_pool).await;
-        seed_admin_user(&state.db_pool).await;
-        let owner_id = get_user_id("adminuser", &state.db_pool).await;
-        let pid = seed_presentation(owner_id, "Owner Pres", &state.db_pool).await;
-        login_as(&server, "testuser", "testpass").await;
-
-        let response = server
-            .post(&format!("/user/presentations/{pid}/access/add"))
-            .form(&serde_json::json!({ "username": "adminuser", "role": "editor" }))
-            .await;
+        let owner_uid = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(owner_uid, "Rec Delete Perm Test", &state.db_pool).await;
+        let rid: i64 = sqlx::query_scalar(
+            "INSERT INTO recording (presentation_id, name, captions_path) VALUES (?, 'Rec', 'r.vtt') RETURNING id"
+        )
+        .bind(pid)
+        .fetch_one(&state.db_pool)
+        .await
+        .unwrap();
 
-        assert_eq!(response.status_code(), 404);
+        login_as(&server, "testuser", "testpass").await;
+        let resp = server.post(&format!("/user/recordings/{rid}/delete")).await;
+        assert_eq!(resp.status_code(), 403);
     }
 
-    /// POST .../access/remove by the owner must delete the row.
+    /// POST /user/recordings/{rid}/name by owner must update the name.
     #[tokio::test]
-    async fn remove_access_as_owner_deletes_row() {
+    async fn update_recording_name_as_owner() {
         let (server, state) = test_server().await;
-        seed_user(&state.db_pool).await;
-        let uid = get_user_id("testuser", &state.db_pool).await;
-        let pid = seed_presentation(uid, "Rm Pres", &state.db_pool).await;
-        User::new(
-            &state.db_pool,
-            AddUserForm {
-                name: "couser2".to_string(),
-                email: "co2@example.com".to_string(),
-                password: "copass2".to_string(),
-            },
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(uid, "Rename Rec Test", &state.db_pool).await;
+        let rid: i64 = sqlx::query_scalar(
+            "INSERT INTO recording (presentation_id, name, captions_path) VALUES (?, 'OldName', 'r.vtt') RETURNING id"
         )
+        .bind(pid)
+        .fetch_one(&state.db_pool)
         .await
         .unwrap();
-        let co_uid = get_user_id("couser2", &state.db_pool).await;
-        PresentationAccess::add(&state.db_pool, pid, co_uid, "editor").await.unwrap();
-        login_as(&server, "testuser", "testpass").await;
 
-        let response = server
-            .post(&format!("/user/presentations/{pid}/access/remove"))
-            .form(&serde_json::json!({ "user_id": co_uid }))
+        login_as(&server, "admin", "admin").await;
+        let resp = server
+            .post(&format!("/user/recordings/{rid}/name"))
+            .text("NewName")
             .await;
+        assert_eq!(resp.status_code(), 200);
 
-        assert_eq!(response.status_code(), 303);
-        let count: i64 =
-            sqlx::query_scalar("SELECT COUNT(*) FROM presentation_access WHERE presentation_id = ?")
-                .bind(pid)
-                .fetch_one(&state.db_pool)
-                .await
-                .unwrap();
-        assert_eq!(count, 0, "access row must be deleted");
+        let name: String = sqlx::query_scalar("SELECT name FROM recording WHERE id = ?")
+            .bind(rid)
+            .fetch_one(&state.db_pool)
+            .await
+            .unwrap();
+        assert_eq!(name, "NewName");
     }
 
-    /// POST .../access/change-role by the owner must update the role in the DB.
+    /// POST /user/recordings/{rid}/name by non-owner must return 403.
     #[tokio::test]
-    async fn change_access_role_as_owner_updates_role() {
+    async fn update_recording_name_as_non_owner_returns_403() {
         let (server, state) = test_server().await;
         seed_user(&state.db_pool).await;
-        let uid = get_user_id("testuser", &state.db_pool).await;
-        let pid = seed_presentation(uid, "Change Role Pres", &state.db_pool).await;
-        User::new(
-            &state.db_pool,
-            AddUserForm {
-                name: "couser3".to_string(),
-                email: "co3@example.com".to_string(),
-                password: "copass3".to_string(),
-            },
+        let owner_uid = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(owner_uid, "Rename Rec Perm", &state.db_pool).await;
+        let rid: i64 = sqlx::query_scalar(
+            "INSERT INTO recording (presentation_id, name, captions_path) VALUES (?, 'OldName', 'r.vtt') RETURNING id"
         )
+        .bind(pid)
+        .fetch_one(&state.db_pool)
         .await
         .unwrap();
-        let co_uid = get_user_id("couser3", &state.db_pool).await;
-        PresentationAccess::add(&state.db_pool, pid, co_uid, "editor").await.unwrap();
+
         login_as(&server, "testuser", "testpass").await;
+        let resp = server
+            .post(&format!("/user/recordings/{rid}/name"))
+            .text("Hack")
+            .await;
+        assert_eq!(resp.status_code(), 403);
+    }
 
-        let response = server
-            .post(&format!("/user/presentations/{pid}/access/change-role"))
-            .form(&serde_json::json!({ "user_id": co_uid, "role": "controller" }))
+    /// POST /user/recordings/{rid}/files without authentication must return 401.
+    #[tokio::test]
+    async fn update_recording_files_requires_auth() {
+        let (server, _state) = test_server().await;
+        let form = axum_test::multipart::MultipartForm::new();
+        let resp = server
+            .post("/user/recordings/1/files")
+            .multipart(form)
             .await;
+        assert_eq!(resp.status_code(), axum::http::StatusCode::UNAUTHORIZED);
+    }
 
-        assert_eq!(response.status_code(), 303);
-        let role: String = sqlx::query_scalar(
-            "SELECT role FROM presentation_access WHERE presentation_id = ? AND user_id = ?",
+    /// POST /user/recordings/{rid}/files with an ID that does not belong to the
+    /// authenticated user must return 404.
+    #[tokio::test]
+    async fn update_recording_files_rejects_unowned_recording() {
+        let (server, state) = test_server().await;
+        seed_user(&state.db_pool).await;
+        // The recording is owned by admin; testuser tries to upload.
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(uid, "Files Perm Test", &state.db_pool).await;
+        let rid: i64 = sqlx::query_scalar(
+            "INSERT INTO recording (presentation_id, name, captions_path) VALUES (?, 'Rec', 'r.vtt') RETURNING id",
         )
         .bind(pid)
-        .bind(co_uid)
         .fetch_one(&state.db_pool)
         .await
         .unwrap();
-        assert_eq!(role, "controller", "role must be updated to controller");
+
+        login_as(&server, "testuser", "testpass").await;
+        let form = axum_test::multipart::MultipartForm::new();
+        let resp = server
+            .post(&format!("/user/recordings/{rid}/files"))
+            .multipart(form)
+            .await;
+        assert_eq!(resp.status_code(), axum::http::StatusCode::NOT_FOUND);
     }
 
-    /// GET /{uname}/{pid} by an editor must redirect to the stage (same as owner).
-    /// The response is 200 (stage.html is rendered, not a redirect — stage() renders directly).
-    /// stage.html does not contain the markdown editor; that is in edit.html.
-    #[tokio::test]
-    async fn editor_gets_stage_access() {
-        let (server, state) = test_server().await;
-        seed_user(&state.db_pool).await;
-        let uid = get_user_id("admin", &state.db_pool).await;
-        let pid = seed_presentation(uid, "Editor Stage Test", &state.db_pool).await;
-        User::new(
-            &state.db_pool,
-            AddUserForm {
-                name: "editoruser".to_string(),
-                email: "ed@example.com".to_string(),
-                password: "edpass".to_string(),
-            },
+    /// POST /user/recordings/{rid}/files by the owner with a video part must
+    /// write the file to disk and update the `video_path` column.
+    ///
+    /// The handler writes to the relative path `assets/{rid}/`, so the test
+    /// pre-creates that directory and removes it on exit to avoid polluting the
+    /// working tree between runs.
+    #[tokio::test]
+    async fn update_recording_files_owner_uploads_video() {
+        let (server, state) = test_server().await;
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(uid, "Files Upload Test", &state.db_pool).await;
+        let rid: i64 = sqlx::query_scalar(
+            "INSERT INTO recording (presentation_id, name, captions_path) VALUES (?, 'Rec', 'r.vtt') RETURNING id",
         )
+        .bind(pid)
+        .fetch_one(&state.db_pool)
         .await
         .unwrap();
-        let ed_uid = get_user_id("editoruser", &state.db_pool).await;
-        PresentationAccess::add(&state.db_pool, pid, ed_uid, "editor").await.unwrap();
-        login_as(&server, "editoruser", "edpass").await;
 
-        let response = server.get(&format!("/admin/{pid}")).await;
+        // The handler writes to assets/{rid}/ using a path relative to the
+        // process CWD. Pre-create the directory so the write succeeds.
+        let asset_dir = format!("assets/{rid}");
+        tokio::fs::create_dir_all(&asset_dir).await.unwrap();
 
-        assert_eq!(response.status_code(), 200);
-        assert!(
-            response.text().contains(r#"id="recordPause""#),
-            "editor must see the record button on stage"
-        );
-        assert!(
-            !response.text().contains(r#"id="markdown-input""#),
-            "editor must not see the markdown editor on stage"
+        login_as(&server, "admin", "admin").await;
+        let form = axum_test::multipart::MultipartForm::new().add_part(
+            "video",
+            axum_test::multipart::Part::bytes(b"\0\0\0\x18ftypisom-video-data".to_vec())
+                .file_name("video.mp4")
+                .mime_type("video/mp4"),
         );
-    }
+        let resp = server
+            .post(&format!("/user/recordings/{rid}/files"))
+            .multipart(form)
+            .await;
 
-    /// GET /user/presentations must include presentations shared with the user.
-    #[tokio::test]
-    async fn presentations_list_includes_shared() {
-        let (server, state) = test_server().await;
-        seed_user(&state.db_pool).await;
-        let admin_id = get_user_id("admin", &state.db_pool).await;
-        let pid = seed_presentation(admin_id, "Shared With Testuser", &state.db_pool).await;
-        let testuser_id = get_user_id("testuser", &state.db_pool).await;
-        PresentationAccess::add(&state.db_pool, pid, testuser_id, "editor").await.unwrap();
-        login_as(&server, "testuser", "testpass").await;
+        // Cleanup before asserting so the directory is removed even on failure.
+        let _ = tokio::fs::remove_dir_all(&asset_dir).await;
 
-        let response = server.get("/user/presentations").await;
+        assert_eq!(resp.status_code(), axum::http::StatusCode::OK);
 
-        assert_eq!(response.status_code(), 200);
-        assert!(
-            response.text().contains("Shared With Testuser"),
-            "shared presentation must appear in testuser's list"
+        let video_path: Option<String> =
+            sqlx::query_scalar("SELECT video_path FROM recording WHERE id = ?")
+                .bind(rid)
+                .fetch_one(&state.db_pool)
+                .await
+                .unwrap();
+        assert_eq!(
+            video_path.as_deref(),
+            Some("video.mp4"),
+            "video_path must be updated in the database after upload"
         );
     }
 
-    /// GET /user/presentations must link shared presentations using the owner's username, not the viewer's.
+    /// GET /{uname}/{pid}/{rid}/slides.vtt must return VTT content for accessible recordings.
     #[tokio::test]
-    async fn presentations_list_shared_link_uses_owner_name() {
+    async fn slides_vtt_returns_content_for_public_recording() {
         let (server, state) = test_server().await;
-        seed_user(&state.db_pool).await;
-        let admin_id = get_user_id("admin", &state.db_pool).await;
-        let pid = seed_presentation(admin_id, "Owner Link Test", &state.db_pool).await;
-        let testuser_id = get_user_id("testuser", &state.db_pool).await;
-        PresentationAccess::add(&state.db_pool, pid, testuser_id, "editor").await.unwrap();
-        login_as(&server, "testuser", "testpass").await;
-
-        let response = server.get("/user/presentations").await;
-        let body = response.text();
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(uid, "VTT Test", &state.db_pool).await;
+        let rid: i64 = sqlx::query_scalar(
+            "INSERT INTO recording (presentation_id, name, captions_path) VALUES (?, 'Rec', 'r.vtt') RETURNING id"
+        )
+        .bind(pid)
+        .fetch_one(&state.db_pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO recording_slide (recording_id, position, title, content, start_seconds) VALUES (?, 0, 'Intro', '<h2>Intro</h2>', 0.0)"
+        )
+        .bind(rid)
+        .execute(&state.db_pool)
+        .await
+        .unwrap();
 
-        assert!(
-            body.contains(&format!("/admin/{pid}")),
-            "shared presentation link must use owner's username (admin), not viewer's"
-        );
-        assert!(
-            !body.contains(&format!("/testuser/{pid}")),
-            "shared presentation link must not use viewer's username"
-        );
+        let resp = server.get(&format!("/admin/{pid}/{rid}/slides.vtt")).await;
+        assert_eq!(resp.status_code(), 200);
+        let body = resp.text();
+        assert!(body.starts_with("WEBVTT"), "response must be VTT");
+        assert!(body.contains("Intro"), "VTT must include slide title");
     }
 
-    /// GET /{uname}/{pid} by a controller must get stage access.
+    /// GET /{uname}/{pid}/{rid}/slides.html must return HTML for accessible recordings.
     #[tokio::test]
-    async fn controller_gets_stage_access() {
+    async fn slides_html_returns_content_for_public_recording() {
         let (server, state) = test_server().await;
-        seed_user(&state.db_pool).await;
         let uid = get_user_id("admin", &state.db_pool).await;
-        let pid = seed_presentation(uid, "Controller Stage Test", &state.db_pool).await;
-        User::new(
-            &state.db_pool,
-            AddUserForm {
-                name: "ctrluser".to_string(),
-                email: "ctrl@example.com".to_string(),
-                password: "ctrlpass".to_string(),
-            },
+        let pid = seed_presentation(uid, "HTML Test", &state.db_pool).await;
+        let rid: i64 = sqlx::query_scalar(
+            "INSERT INTO recording (presentation_id, name, captions_path) VALUES (?, 'Rec', 'r.vtt') RETURNING id"
         )
+        .bind(pid)
+        .fetch_one(&state.db_pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO recording_slide (recording_id, position, title, content, start_seconds) VALUES (?, 0, 'Intro', '<h2>Intro</h2>', 0.0)"
+        )
+        .bind(rid)
+        .execute(&state.db_pool)
         .await
         .unwrap();
-        let ctrl_uid = get_user_id("ctrluser", &state.db_pool).await;
-        PresentationAccess::add(&state.db_pool, pid, ctrl_uid, "controller").await.unwrap();
-        login_as(&server, "ctrluser", "ctrlpass").await;
 
-        let response = server.get(&format!("/admin/{pid}")).await;
-        assert_eq!(response.status_code(), 200);
-        assert!(
-            response.text().contains(r#"id="recordPause""#),
-            "controller must see the record button on stage"
-        );
-        assert!(
-            !response.text().contains(r#"id="markdown-input""#),
-            "controller must not see the markdown editor"
-        );
+        let resp = server.get(&format!("/admin/{pid}/{rid}/slides.html")).await;
+        assert_eq!(resp.status_code(), 200);
+        let body = resp.text();
+        assert!(body.contains("<!DOCTYPE html>"), "response must be HTML");
+        assert!(body.contains("<section>"), "response must contain slide sections");
     }
 
-    /// GET /{editor_name}/{pid} must redirect 308 (permanent) to /{owner_name}/{pid}.
+    /// GET /{uname}/{pid}/transcript must render every slide for a public presentation.
     #[tokio::test]
-    async fn non_owner_uname_redirects_to_canonical_url() {
+    async fn transcript_renders_all_slides_for_public_presentation() {
         let (server, state) = test_server().await;
-        seed_user(&state.db_pool).await;
-        let owner_uid = get_user_id("admin", &state.db_pool).await;
-        let pid = seed_presentation(owner_uid, "Canon Test", &state.db_pool).await;
-        let editor_uid = get_user_id("testuser", &state.db_pool).await;
-        PresentationAccess::add(&state.db_pool, pid, editor_uid, "editor").await.unwrap();
-        login_as(&server, "testuser", "testpass").await;
-
-        let response = server.get(&format!("/testuser/{pid}")).await;
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(uid, "Transcript Test", &state.db_pool).await;
+        DbPresentation::update_content(pid, "## Intro\nHello\n\n## Second\nWorld".to_string(), &state.db_pool)
+            .await
+            .unwrap();
 
-        assert_eq!(response.status_code(), 308);
-        let location = response.headers()["location"].to_str().unwrap();
-        assert_eq!(location, &format!("/admin/{pid}"));
+        let resp = server.get(&format!("/admin/{pid}/transcript")).await;
+        assert_eq!(resp.status_code(), 200);
+        let body = resp.text();
+        assert!(body.contains(r#"id="slide-1""#) && body.contains(r#"id="slide-2""#), "transcript must contain one article per slide");
+        assert!(body.contains("Hello") && body.contains("World"), "transcript must include slide content");
+        assert!(!body.contains("<h2>Intro</h2>"), "slide headings must be rendered by the template, not duplicated");
     }
 
-    /// GET /{nonexistent_name}/{pid} must still return generic audience (no change).
+    /// GET /{uname}/{pid}/transcript must be refused when check_access denies the viewer.
     #[tokio::test]
-    async fn nonexistent_uname_returns_audience() {
+    async fn transcript_denied_for_private_presentation() {
         let (server, state) = test_server().await;
-        let owner_uid = get_user_id("admin", &state.db_pool).await;
-        let pid = seed_presentation(owner_uid, "Uname Test", &state.db_pool).await;
-
-        let response = server.get(&format!("/nobody/{pid}")).await;
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(uid, "Private Transcript", &state.db_pool).await;
+        DbPresentation::set_access_mode(pid, "private", &state.db_pool).await.unwrap();
 
-        assert_eq!(response.status_code(), 200);
+        let resp = server.get(&format!("/admin/{pid}/transcript")).await;
+        assert_eq!(resp.status_code(), 403);
     }
 
-    /// GET /users/exists?username=testuser returns 200 when user exists and caller is authenticated.
+    /// The HTML export must be a download holding every slide and inlined `KaTeX` fonts.
     #[tokio::test]
-    async fn user_exists_returns_200_for_known_user() {
+    async fn export_html_renders_all_slides_offline() {
         let (server, state) = test_server().await;
-        seed_user(&state.db_pool).await;
-        login_as(&server, "testuser", "testpass").await;
-        let response = server
-            .get("/users/exists")
-            .add_query_param("username", "testuser")
-            .await;
-        assert_eq!(response.status_code(), 200);
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(uid, "Offline Deck", &state.db_pool).await;
+        DbPresentation::update_content(pid, "## First\nOne\n\n## Second\nTwo\n???\nSecret".to_string(), &state.db_pool)
+            .await
+            .unwrap();
+
+        let resp = server.get(&format!("/admin/{pid}/export.html")).await;
+        assert_eq!(resp.status_code(), 200);
+        let disposition = resp.header("content-disposition");
+        assert_eq!(disposition.to_str().unwrap(), "attachment; filename=\"Offline_Deck.html\"");
+        let body = resp.text();
+        assert!(body.contains("1: First") && body.contains("2: Second"), "export must list every slide");
+        assert!(body.contains("data:font/woff2;base64,"), "KaTeX fonts must be inlined");
+        assert!(!body.contains("url(fonts/"), "no font may be loaded from the server");
+        assert!(!body.contains("Secret"), "speaker notes must not be exported");
     }
 
-    /// GET /users/exists?username=nobody returns 404 when user does not exist.
+    /// The zip export must hold the page plus the local images it references.
     #[tokio::test]
-    async fn user_exists_returns_404_for_unknown_user() {
+    async fn export_zip_bundles_referenced_assets() {
         let (server, state) = test_server().await;
-        seed_user(&state.db_pool).await;
-        login_as(&server, "testuser", "testpass").await;
-        let response = server
-            .get("/users/exists")
-            .add_query_param("username", "nobody")
-            .await;
-        assert_eq!(response.status_code(), 404);
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = 9805;
+        seed_presentation_with_id(pid, uid, &state.db_pool).await;
+        let dir = format!("{PRESENTATION_ASSET_DIR}/{pid}");
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        tokio::fs::write(format!("{dir}/demo.png"), b"png-bytes").await.unwrap();
+        DbPresentation::update_content(pid, format!("## Pic\n![demo](/assets/presentations/{pid}/demo.png)"), &state.db_pool)
+            .await
+            .unwrap();
+
+        let resp = server.get(&format!("/admin/{pid}/export.zip")).await;
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+        assert_eq!(resp.status_code(), 200);
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(resp.as_bytes().to_vec())).unwrap();
+        let bundled = format!("assets/presentations/{pid}/demo.png");
+        assert!(archive.by_name(&bundled).is_ok(), "referenced asset must be bundled");
+        let mut html = String::new();
+        std::io::Read::read_to_string(&mut archive.by_name("index.html").unwrap(), &mut html).unwrap();
+        assert!(html.contains(&format!(r#"src="{bundled}""#)), "image must point into the bundle");
     }
 
-    /// GET /users/exists without the username query param must return 400 Bad Request.
+    /// Exports must go through `check_access` like the live view.
     #[tokio::test]
-    async fn user_exists_returns_400_when_username_missing() {
+    async fn export_denied_for_private_presentation() {
         let (server, state) = test_server().await;
-        seed_user(&state.db_pool).await;
-        login_as(&server, "testuser", "testpass").await;
-        let response = server.get("/users/exists").await;
-        assert_eq!(response.status_code(), 400);
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(uid, "Private Export", &state.db_pool).await;
+        DbPresentation::set_access_mode(pid, "private", &state.db_pool).await.unwrap();
+
+        for ext in ["html", "zip"] {
+            let resp = server.get(&format!("/admin/{pid}/export.{ext}")).await;
+            assert_eq!(resp.status_code(), 403);
+        }
     }
 
-    /// GET /users/exists without a session must return 401 Unauthorized.
-    #[tokio::test]
-    async fn user_exists_requires_auth() {
-        let (server, _state) = test_server().await;
-        // axum-test does not follow redirects — the raw response status is returned.
-        let response = server
-            .get("/users/exists")
-            .add_query_param("username", "admin")
-            .await;
-        assert_eq!(response.status_code(), 401u16);
+    /// Only WOFF2 sources remain, inlined as `data:` URIs.
+    #[test]
+    fn inline_katex_fonts_keeps_only_woff2() {
+        let css = r#"@font-face{src:url(fonts/A.woff2) format("woff2"),url(fonts/A.woff) format("woff"),url(fonts/A.ttf) format("truetype")}.x{}"#;
+        let fonts = HashMap::from([("A.woff2".to_string(), b"abc".to_vec())]);
+        assert_eq!(
+            inline_katex_fonts(css, &fonts),
+            r#"@font-face{src:url(data:font/woff2;base64,YWJj) format("woff2")}.x{}"#
+        );
     }
 
-    /// GET /users/exists without session AND without username param must return 401, not 400.
-    #[tokio::test]
-    async fn user_exists_unauthenticated_missing_param_returns_401() {
-        let (server, _state) = test_server().await;
-        let response = server.get("/users/exists").await;
-        assert_eq!(response.status_code(), 401u16);
+    /// Only plain paths to the presentation's own uploads map to local files.
+    #[test]
+    fn local_asset_path_rejects_traversal() {
+        assert_eq!(
+            local_asset_path(7, "/assets/presentations/7/a.png"),
+            Some(std::path::PathBuf::from("presentation_assets/7/a.png"))
+        );
+        assert_eq!(local_asset_path(7, "/assets/presentations/8/a.png"), None);
+        assert_eq!(local_asset_path(7, "/assets/presentations/7/../8/a.png"), None);
+        assert_eq!(local_asset_path(7, "/assets/1/a.png"), None);
+        assert_eq!(local_asset_path(7, "https://example.com/a.png"), None);
     }
 
-    /// POST /user/presentations/{pid}/access/mode must save the mode and redirect.
+    /// The history page must list saved revisions with their author for the owner.
     #[tokio::test]
-    async fn set_presentation_access_mode_as_owner() {
+    async fn history_lists_revisions_for_owner() {
         let (server, state) = test_server().await;
-        seed_user(&state.db_pool).await;
-        let uid = get_user_id("testuser", &state.db_pool).await;
-        let pid = seed_presentation(uid, "Mode Test", &state.db_pool).await;
-        login_as(&server, "testuser", "testpass").await;
-
-        let resp = server
-            .post(&format!("/user/presentations/{pid}/access/mode"))
-            .form(&serde_json::json!({ "mode": "private" }))
-            .await;
-        assert_eq!(resp.status_code(), 303);
-
-        let mode: String = sqlx::query_scalar("SELECT access_mode FROM presentation WHERE id = ?")
-            .bind(pid)
-            .fetch_one(&state.db_pool)
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(uid, "History Test", &state.db_pool).await;
+        PresentationRevision::create(&state.db_pool, pid, Some(uid), "owner", "## One".to_string())
             .await
             .unwrap();
-        assert_eq!(mode, "private");
+        login_as(&server, "admin", "admin").await;
+
+        let resp = server.get(&format!("/admin/{pid}/history")).await;
+        assert_eq!(resp.status_code(), 200);
+        let body = resp.text();
+        assert!(body.contains("Restore revision 1"), "history must offer a restore button, got: {body}");
+        assert!(body.contains("<td>admin</td>"), "history must name the author");
     }
 
-    /// POST /user/presentations/{pid}/access/mode by a non-owner must return 404.
+    /// Users without edit rights must not see a presentation's history.
     #[tokio::test]
-    async fn set_presentation_access_mode_non_owner_returns_404() {
+    async fn history_forbidden_for_non_editor() {
         let (server, state) = test_server().await;
         seed_user(&state.db_pool).await;
-        let admin_uid = get_user_id("admin", &state.db_pool).await;
-        let pid = seed_presentation(admin_uid, "Admin Pres", &state.db_pool).await;
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(uid, "History Perm", &state.db_pool).await;
         login_as(&server, "testuser", "testpass").await;
 
-        let resp = server
-            .post(&format!("/user/presentations/{pid}/access/mode"))
-            .form(&serde_json::json!({ "mode": "private" }))
-            .await;
-        assert_eq!(resp.status_code(), 404);
+        let resp = server.get(&format!("/admin/{pid}/history")).await;
+        assert_eq!(resp.status_code(), 403);
     }
 
-    /// POST /user/recordings/{rid}/access/mode must save the mode.
+    /// Identical consecutive snapshots must be stored only once.
     #[tokio::test]
-    async fn set_recording_access_mode_as_owner() {
-        let (server, state) = test_server().await;
-        seed_user(&state.db_pool).await;
-        let uid = get_user_id("testuser", &state.db_pool).await;
-        let pid = seed_presentation(uid, "Rec Mode Test", &state.db_pool).await;
-        login_as(&server, "testuser", "testpass").await;
-
-        let rid: i64 = sqlx::query_scalar(
-            "INSERT INTO recording (presentation_id, name, captions_path)
-             VALUES (?, 'Rec', 'rec.vtt') RETURNING id"
-        )
-        .bind(pid)
-        .fetch_one(&state.db_pool)
-        .await
-        .unwrap();
+    async fn revision_create_skips_unchanged_content() {
+        let pool = setup_pool().await;
+        let owner = make_user(&pool, "revowner").await;
+        let pres = make_presentation(&owner, &pool).await;
+        for content in ["## A", "## A", "## B", "## A"] {
+            PresentationRevision::create(&pool, pres.id, Some(owner.id), "owner", content.to_string())
+                .await
+                .unwrap();
+        }
+        let revisions = PresentationRevision::get_for_presentation(&pool, pres.id).await.unwrap();
+        let contents: Vec<&str> = revisions.iter().map(|r| r.content.as_str()).collect();
+        assert_eq!(contents, ["## A", "## B", "## A"], "newest first, consecutive duplicates dropped");
+    }
 
-        let resp = server
-            .post(&format!("/user/recordings/{rid}/access/mode"))
-            .form(&serde_json::json!({ "action": "set", "mode": "private" }))
-            .await;
-        assert_eq!(resp.status_code(), 303);
+    /// A burst of edits must save the content from before it, then a single revision
+    /// holding the final content.
+    #[tokio::test]
+    async fn schedule_revision_debounces_edits() {
+        let pool = setup_pool().await;
+        let owner = make_user(&pool, "debouncer").await;
+        let pres_db = make_presentation(&owner, &pool).await;
+        let pres = make_presentation_arc();
+        let original = pres.lock().unwrap().content.clone();
 
-        let mode: Option<String> = sqlx::query_scalar("SELECT access_mode FROM recording WHERE id = ?")
-            .bind(rid)
-            .fetch_one(&state.db_pool)
-            .await
-            .unwrap();
-        assert_eq!(mode, Some("private".to_string()));
+        tokio::time::pause();
+        for text in ["## A", "## AB", "## ABC"] {
+            schedule_revision(&pres, pres_db.id, Some(owner.id), &AccessResult::Owner, &pool);
+            pres.lock().unwrap().content = text.to_string();
+        }
+        // Let the spawned tasks register their timers before the clock jumps.
+        tokio::task::yield_now().await;
+        tokio::time::advance(REVISION_DEBOUNCE + std::time::Duration::from_secs(1)).await;
+        tokio::time::resume();
+
+        let mut revisions = vec![];
+        for _ in 0..100 {
+            revisions = PresentationRevision::get_for_presentation(&pool, pres_db.id).await.unwrap();
+            if revisions.len() >= 2 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        let contents: Vec<&str> = revisions.iter().map(|r| r.content.as_str()).collect();
+        assert_eq!(contents, ["## ABC", original.as_str()], "the deck before the burst, then once after it");
+        assert_eq!(revisions[0].author.as_deref(), Some("debouncer"));
     }
 
-    /// POST /user/recordings/{rid}/access/mode with action=inherit must set NULL.
+    /// Edits that never pause still produce a revision every [`REVISION_MAX_DELAY`].
     #[tokio::test]
-    async fn set_recording_access_mode_inherit() {
-        let (server, state) = test_server().await;
-        seed_user(&state.db_pool).await;
-        let uid = get_user_id("testuser", &state.db_pool).await;
-        let pid = seed_presentation(uid, "Rec Inherit Test", &state.db_pool).await;
-        login_as(&server, "testuser", "testpass").await;
+    async fn schedule_revision_saves_continuous_edits() {
+        let pool = setup_pool().await;
+        let owner = make_user(&pool, "typist").await;
+        let pres_db = make_presentation(&owner, &pool).await;
+        let pres = make_presentation_arc();
 
-        let rid: i64 = sqlx::query_scalar(
-            "INSERT INTO recording (presentation_id, name, captions_path, access_mode)
-             VALUES (?, 'Rec', 'rec.vtt', 'private') RETURNING id"
-        )
-        .bind(pid)
-        .fetch_one(&state.db_pool)
-        .await
-        .unwrap();
+        tokio::time::pause();
+        let step = std::time::Duration::from_secs(2);
+        let mut elapsed = std::time::Duration::ZERO;
+        let mut n = 0;
+        while elapsed < REVISION_MAX_DELAY + REVISION_DEBOUNCE {
+            schedule_revision(&pres, pres_db.id, Some(owner.id), &AccessResult::Owner, &pool);
+            n += 1;
+            pres.lock().unwrap().content = format!("## Typing {n}");
+            tokio::task::yield_now().await;
+            tokio::time::advance(step).await;
+            elapsed += step;
+        }
+        tokio::time::resume();
 
-        let resp = server
-            .post(&format!("/user/recordings/{rid}/access/mode"))
-            .form(&serde_json::json!({ "action": "inherit" }))
-            .await;
-        assert_eq!(resp.status_code(), 303);
+        let mut revisions = vec![];
+        for _ in 0..100 {
+            revisions = PresentationRevision::get_for_presentation(&pool, pres_db.id).await.unwrap();
+            if revisions.len() >= 2 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert!(
+            revisions.iter().any(|r| r.content.starts_with("## Typing")),
+            "continuous typing must still be saved"
+        );
+    }
 
-        let mode: Option<String> = sqlx::query_scalar("SELECT access_mode FROM recording WHERE id = ?")
-            .bind(rid)
-            .fetch_one(&state.db_pool)
-            .await
-            .unwrap();
-        assert!(mode.is_none(), "inherit action must set access_mode to NULL");
+    /// Reads a presentation's saved content.
+    async fn saved_content(id: i64, pool: &SqlitePool) -> String {
+        DbPresentation::get_by_id(id, pool).await.unwrap().unwrap().content
     }
 
-    /// POST /user/presentations/{pid}/access/add with role=audience must insert the row.
+    /// A burst of edits must be saved once, with the final content, after the pause.
     #[tokio::test]
-    async fn add_audience_member_as_owner() {
-        let (server, state) = test_server().await;
-        seed_user(&state.db_pool).await;
-        let uid = get_user_id("testuser", &state.db_pool).await;
-        let pid = seed_presentation(uid, "Audience Test", &state.db_pool).await;
-        User::new(
-            &state.db_pool,
-            AddUserForm {
-                name: "vieweruser".to_string(),
-                email: "viewer@example.com".to_string(),
-                password: "viewerpass".to_string(),
-            },
-        ).await.unwrap();
-        login_as(&server, "testuser", "testpass").await;
+    async fn content_writer_debounces_edits() {
+        let pool = setup_pool().await;
+        let owner = make_user(&pool, "writer1").await;
+        let pres_db = make_presentation(&owner, &pool).await;
 
-        let resp = server
-            .post(&format!("/user/presentations/{pid}/access/add"))
-            .form(&serde_json::json!({ "username": "vieweruser", "role": "audience" }))
-            .await;
-        assert_eq!(resp.status_code(), 303);
+        tokio::time::pause();
+        let writer = ContentWriter::spawn(pres_db.id, 0, pool.clone());
+        for text in ["## A", "## AB", "## ABC"] {
+            writer.edit(text.to_string());
+        }
+        tokio::task::yield_now().await;
+        tokio::time::advance(PERSIST_DEBOUNCE + std::time::Duration::from_millis(100)).await;
+        tokio::task::yield_now().await;
+        tokio::time::resume();
 
-        let count: i64 = sqlx::query_scalar(
-            "SELECT COUNT(*) FROM presentation_access WHERE presentation_id = ? AND role = 'audience'"
-        )
-        .bind(pid)
-        .fetch_one(&state.db_pool)
-        .await
-        .unwrap();
-        assert_eq!(count, 1);
+        assert_eq!(saved_content(pres_db.id, &pool).await, "## ABC");
     }
 
-    /// GET /{uname}/{pid}/{rid} must return 403 for unauthenticated access on a private presentation.
+    /// Typing without a pause must still be saved within the latency bound.
     #[tokio::test]
-    async fn recording_handler_denies_access_in_private_mode() {
-        let (server, state) = test_server().await;
-        let uid = get_user_id("admin", &state.db_pool).await;
-        let pid = seed_presentation(uid, "Private Rec Test", &state.db_pool).await;
+    async fn content_writer_flushes_after_max_latency() {
+        let pool = setup_pool().await;
+        let owner = make_user(&pool, "writer2").await;
+        let pres_db = make_presentation(&owner, &pool).await;
+        let original = pres_db.content.clone();
+
+        tokio::time::pause();
+        let writer = ContentWriter::spawn(pres_db.id, 0, pool.clone());
+        let step = PERSIST_DEBOUNCE / 2;
+        let mut elapsed = std::time::Duration::ZERO;
+        let mut n = 0;
+        while elapsed <= PERSIST_MAX_LATENCY {
+            n += 1;
+            writer.edit(format!("## Draft {n}"));
+            tokio::task::yield_now().await;
+            tokio::time::advance(step).await;
+            tokio::task::yield_now().await;
+            elapsed += step;
+        }
+        tokio::time::resume();
 
-        sqlx::query("UPDATE presentation SET access_mode = 'private' WHERE id = ?")
-            .bind(pid)
-            .execute(&state.db_pool)
-            .await
-            .unwrap();
+        let saved = saved_content(pres_db.id, &pool).await;
+        assert_ne!(saved, original, "edits must be saved while typing continues");
+        assert!(saved.starts_with("## Draft "), "got: {saved}");
+    }
 
-        let rid: i64 = sqlx::query_scalar(
-            "INSERT INTO recording (presentation_id, name, captions_path)
-             VALUES (?, 'Rec', 'rec.vtt') RETURNING id"
-        )
-        .bind(pid)
-        .fetch_one(&state.db_pool)
-        .await
-        .unwrap();
+    /// `flush` and dropping the last handle must save pending edits without waiting.
+    #[tokio::test]
+    async fn content_writer_flushes_on_demand_and_on_drop() {
+        let pool = setup_pool().await;
+        let owner = make_user(&pool, "writer3").await;
+        let pres_db = make_presentation(&owner, &pool).await;
 
-        let resp = server
-            .get(&format!("/admin/{pid}/{rid}"))
-            .await;
-        assert_eq!(resp.status_code(), 403);
+        let writer = ContentWriter::spawn(pres_db.id, 0, pool.clone());
+        writer.edit("## Flushed".to_string());
+        writer.flush().await;
+        assert_eq!(saved_content(pres_db.id, &pool).await, "## Flushed");
+
+        writer.edit("## Dropped".to_string());
+        drop(writer);
+        let mut saved = String::new();
+        for _ in 0..20 {
+            saved = saved_content(pres_db.id, &pool).await;
+            if saved == "## Dropped" {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(saved, "## Dropped", "dropping the writer must save without the debounce");
     }
 
-    /// GET /{uname}/{pid}/{rid}/edit by the owner must return 200 with the edit-rec-heading.
+    /// A text edit over the WebSocket must be handed to the presentation's writer
+    /// rather than written by each connection that relays it.
     #[tokio::test]
-    async fn owner_gets_edit_recording_page() {
+    async fn ws_text_edit_goes_through_content_writer() {
         let (server, state) = test_server().await;
         let uid = get_user_id("admin", &state.db_pool).await;
-        let pid = seed_presentation(uid, "Edit Rec Test", &state.db_pool).await;
-        let rid = seed_recording(pid, &state.db_pool).await;
-        login_as(&server, "admin", "admin").await;
+        let pid = seed_presentation(uid, "Writer WS", &state.db_pool).await;
+        let mut state_clone = state.clone();
+        let pres = add_client_handler_channel(pid.to_string(), &mut state_clone).await.unwrap();
+        let mut rx = pres.lock().unwrap().channel.0.subscribe();
+        drop(server);
 
-        let resp = server.get(&format!("/admin/{pid}/{rid}/edit")).await;
-        assert_eq!(resp.status_code(), 200);
-        assert!(
-            resp.text().contains(r#"id="edit-rec-heading""#),
-            "edit recording page must have edit-rec-heading"
+        let (tx, _rx) = tokio::sync::broadcast::channel::<SlideMessage>(8);
+        let mut tx_clone = tx;
+        let msg = axum::extract::ws::Message::text(
+            serde_json::to_string(&SlideMessage::Text("## Live edit".to_string())).unwrap(),
         );
+        let (direct, _replies) = tokio::sync::mpsc::unbounded_channel();
+        handle_socket(Ok(msg), &pid.to_string(), &mut tx_clone, &mut state_clone, &AccessResult::Owner, &direct)
+            .unwrap();
+        let writer = pres.lock().unwrap().writer.clone().unwrap();
+        writer.flush().await;
+
+        assert_eq!(saved_content(pid, &state.db_pool).await, "## Live edit");
+        assert!(matches!(rx.try_recv(), Ok(SlideMessage::Patch(p)) if p.base == 0 && p.text == "## Live edit"));
     }
 
-    /// GET /{uname}/{pid}/{rid}/edit without login must redirect to /auth/login.
+    /// Idle presentations must be saved, have their recording stopped and be evicted;
+    /// those a connection still holds must stay.
     #[tokio::test]
-    async fn unauthenticated_edit_recording_redirects() {
+    async fn idle_presentations_are_saved_and_evicted() {
+        let (_server, state) = test_server().await;
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let idle = seed_presentation(uid, "Idle", &state.db_pool).await;
+        let busy = seed_presentation(uid, "Busy", &state.db_pool).await;
+        let mut state_clone = state.clone();
+        let pres = add_client_handler_channel(idle.to_string(), &mut state_clone).await.unwrap();
+        pres.lock().unwrap().set_content("## Unsaved".to_string(), None);
+        handle_recording_message(RecordingMessage::RecordingStart, &pres, idle, &state.db_pool).await;
+        let rec_id = pres.lock().unwrap().recording.as_ref().unwrap().db_id;
+        drop(pres);
+        let held = add_client_handler_channel(busy.to_string(), &mut state_clone).await.unwrap();
+
+        assert_eq!(evict_idle(&state, std::time::Duration::from_secs(60)).await, 0, "not idle for long enough");
+        assert_eq!(evict_idle(&state, std::time::Duration::ZERO).await, 1);
+        assert_eq!(saved_content(idle, &state.db_pool).await, "## Unsaved");
+        let rec = Recording::get_by_id(rec_id, &state.db_pool).await.unwrap().unwrap();
+        assert!(rec.last_edited.is_some(), "the orphaned recording was saved");
+        let slides = state.slides.lock().unwrap();
+        assert!(!slides.contains_key(&idle.to_string()));
+        assert!(slides.contains_key(&busy.to_string()), "a held presentation must stay");
+        assert_eq!(state.evictions.presentations.load(Ordering::Relaxed), 1);
+        assert_eq!(state.evictions.recordings_stopped.load(Ordering::Relaxed), 1);
+        drop(held);
+    }
+
+    /// After a restart the slide shown and the recording in progress must come back,
+    /// the recording paused where it was checkpointed.
+    #[tokio::test]
+    async fn restart_restores_slide_and_recording() {
+        let (_server, mut state) = test_server().await;
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(uid, "Restart", &state.db_pool).await;
+        let pres = add_client_handler_channel(pid.to_string(), &mut state).await.unwrap();
+        handle_recording_message(RecordingMessage::RecordingStart, &pres, pid, &state.db_pool).await;
+        update_slide(&pid.to_string(), SlideMessage::Slide(2), &mut state);
+        let rec_id = pres.lock().unwrap().recording.as_ref().unwrap().db_id;
+        // The process dies with the checkpoint written but the recording unsaved.
+        let writer = pres.lock().unwrap().writer.clone().unwrap();
+        writer.flush().await;
+        state.slides.lock().unwrap().clear();
+        drop(pres);
+        assert_eq!(Recording::mark_interrupted(Some(state.node), &state.db_pool).await.unwrap(), 1);
+
+        let pres = add_client_handler_channel(pid.to_string(), &mut state).await.unwrap();
+        let p = pres.lock().unwrap();
+        assert_eq!(p.slide, 2);
+        let rec = p.recording.as_ref().expect("the recording was restored");
+        assert_eq!(rec.db_id, rec_id);
+        assert!(rec.is_paused, "a restored recording waits for the presenter to resume");
+        assert_eq!(rec.slides.iter().map(|ev| ev.slide).collect::<Vec<_>>(), vec![0, 2]);
+        assert!(matches!(state_messages(&p, true, false).last(), Some(SlideMessage::RecordingPause { .. })));
+    }
+
+    /// POST /user/recordings/{rid}/finalise must save an interrupted recording's
+    /// checkpointed timeline, and only for the owner.
+    #[tokio::test]
+    async fn finalise_saves_interrupted_recording() {
         let (server, state) = test_server().await;
         let uid = get_user_id("admin", &state.db_pool).await;
-        let pid = seed_presentation(uid, "Edit Rec Auth Test", &state.db_pool).await;
-        let rid = seed_recording(pid, &state.db_pool).await;
+        let pid = seed_presentation(uid, "Interrupted", &state.db_pool).await;
+        DbPresentation::update_content(pid, "## One\n\n## Two".to_string(), &state.db_pool).await.unwrap();
+        let rec = Recording::create(pid, "Rec".to_string(), None, String::new(), &state.db_pool).await.unwrap();
+        let checkpoint = RecordingCheckpoint { recording_id: rec.id, active_ms: 3000, slides: vec![(0, 0, 0, None), (1500, 1, 0, None)], polls: vec![], captions: vec![] };
+        LiveState { slide: 1, fragment: 0, recording: Some(checkpoint), clock: None }.save(pid, state.node, &state.db_pool).await.unwrap();
+        Recording::mark_interrupted(Some(state.node), &state.db_pool).await.unwrap();
+        let edit_url = format!("/admin/{pid}/{}/edit", rec.id);
 
-        let resp = server.get(&format!("/admin/{pid}/{rid}/edit")).await;
-        assert_eq!(resp.status_code(), 303);
+        seed_user(&state.db_pool).await;
+        login_as(&server, "testuser", "testpass").await;
+        assert_eq!(server.post(&format!("/user/recordings/{}/finalise", rec.id)).await.status_code(), 403);
+
+        login_as(&server, "admin", "admin").await;
+        let page = server.get(&edit_url).await.text();
+        assert!(page.contains("interrupted-heading") && page.contains("Finalise recording"));
+        let resp = server.post(&format!("/user/recordings/{}/finalise", rec.id)).await;
+        assert_eq!(resp.header("location"), edit_url.as_str());
+        let titles: Vec<String> = sqlx::query_scalar("SELECT title FROM recording_slide WHERE recording_id = ? ORDER BY start_seconds")
+            .bind(rec.id)
+            .fetch_all(&state.db_pool)
+            .await
+            .unwrap();
+        assert_eq!(titles, vec!["One", "Two"]);
+        assert_eq!(LiveState::get_for_recording(rec.id, &state.db_pool).await.unwrap(), None);
+        let page = server.get(&edit_url).await.text();
+        assert!(page.contains("interrupted-heading") && !page.contains("Finalise recording"));
+        assert_eq!(server.post(&format!("/user/recordings/{}/finalise", rec.id)).await.status_code(), 404);
     }
 
-    /// GET /{uname}/{pid}/edit by the owner must serve the edit page.
+    /// GET /{uname}/{pid}/presence must report the live connections to presenters only.
     #[tokio::test]
-    async fn owner_gets_edit_page() {
+    async fn presence_endpoint_is_for_presenters() {
         let (server, state) = test_server().await;
-        seed_user(&state.db_pool).await;
         let uid = get_user_id("admin", &state.db_pool).await;
-        let pid = seed_presentation(uid, "Edit Page Test", &state.db_pool).await;
-        login_as(&server, "admin", "admin").await;
+        let pid = seed_presentation(uid, "Presence", &state.db_pool).await;
+        let url = format!("/admin/{pid}/presence");
+        assert_eq!(server.get(&url).await.status_code(), 401);
+        seed_user(&state.db_pool).await;
+        login_as(&server, "testuser", "testpass").await;
+        assert_eq!(server.get(&url).await.status_code(), 403);
 
-        let response = server.get(&format!("/admin/{pid}/edit")).await;
-        assert_eq!(response.status_code(), 200);
-        assert!(
-            response.text().contains(r#"id="edit-heading""#),
-            "edit page must have the edit-heading H1"
-        );
-        assert!(
-            response.text().contains(r#"id="markdown-input""#),
-            "edit page must have the markdown textarea"
-        );
-        assert!(
-            !response.text().contains(r#"id="recordPause""#),
-            "edit page must not have the record button"
+        login_as(&server, "admin", "admin").await;
+        let idle = server.get(&url).await;
+        assert_eq!(idle.status_code(), 200);
+        assert_eq!(idle.json::<serde_json::Value>()["viewers"], 0);
+        let live = Arc::new(Mutex::new(Presentation::new(String::new())));
+        live.lock().unwrap().attendees.insert(0, (AccessResult::Audience, Some("testuser".to_string())));
+        state.slides.lock().unwrap().insert(pid.to_string(), live);
+        assert_eq!(
+            server.get(&url).await.json::<serde_json::Value>(),
+            serde_json::json!({"viewers": 1, "audience": 1, "public": 0, "presenters": 0, "captioners": 0, "users": ["testuser"]})
         );
+        assert_eq!(server.get(&format!("/testuser/{pid}/presence")).await.status_code(), 404);
     }
 
-    /// GET /{uname}/{pid}/edit by a controller must redirect to the stage page.
+    #[test]
+    fn planned_times_come_from_speaker_notes() {
+        for (text, seconds) in [("90s", Some(90)), ("2m30s", Some(150)), ("1h 5 min", Some(3900)), ("2:30", Some(150)), ("1:02:30", Some(3750)), ("3", Some(180))] {
+            assert_eq!(pacing::parse_duration(text), seconds, "{text}");
+        }
+        for text in ["", "0", "soon", "2x", "1:2:3:4", "5m later"] {
+            assert_eq!(pacing::parse_duration(text), None, "{text}");
+        }
+        let md = "## A\nTime: 9m is on the slide, not in the notes\n\n???\nSay hi\nTime: 2m30s\n\n## B\nNo plan\n\n## C\n\n???\ntime: 1:00";
+        assert_eq!(planned_times(md), vec![Some(150), None, Some(60)]);
+    }
+
+    /// Presenters must be told where the session stands on each move, warned once when a
+    /// slide and when the whole session run over, and not warned again on a revisit.
+    #[tokio::test(start_paused = true)]
+    async fn pacing_warns_when_slides_and_session_run_over() {
+        let md = "## A\n\n???\nTime: 1m\n\n## B\n\n???\nTime: 1m\n\n## C";
+        let pres = Arc::new_cyclic(|this| {
+            let mut p = Presentation::new(md.to_string());
+            p.this = this.clone();
+            Mutex::new(p)
+        });
+        let mut rx = pres.lock().unwrap().channel.0.subscribe();
+        let mut next = || match rx.try_recv() {
+            Ok(SlideMessage::Pacing(pacing)) => Some(pacing),
+            _ => None,
+        };
+        let minute = std::time::Duration::from_secs(60);
+        assert!(pres.lock().unwrap().clock.is_none(), "the clock waits for the first move");
+        pres.lock().unwrap().show(0, 0);
+        let started = next().unwrap();
+        assert_eq!((started.slide_planned, started.session_planned, started.over), (Some(60), Some(120), None));
+
+        tokio::time::sleep(minute + std::time::Duration::from_secs(1)).await;
+        assert!(matches!(next(), Some(Pacing { slide: 0, over: Some(Overrun::Slide), .. })));
+        pres.lock().unwrap().show(0, 1);
+        assert!(next().is_none(), "a fragment step is no move to another slide");
+        pres.lock().unwrap().show(1, 0);
+        assert!(matches!(next(), Some(Pacing { slide: 1, slide_seconds: 0, session_seconds: 61, over: None, .. })));
+
+        tokio::time::sleep(minute + std::time::Duration::from_secs(1)).await;
+        let warnings: Vec<Pacing> = std::iter::from_fn(&mut next).collect();
+        assert_eq!(warnings.iter().map(|w| w.over).collect::<Vec<_>>(), vec![Some(Overrun::Session), Some(Overrun::Slide)]);
+
+        pres.lock().unwrap().show(0, 0);
+        assert!(next().is_some());
+        tokio::time::sleep(minute * 10).await;
+        assert!(next().is_none(), "each overrun is warned about once");
+
+        let p = pres.lock().unwrap();
+        let titles = vec!["A".to_string(), "B".to_string(), "C".to_string()];
+        let report = pacing::Report::new(p.clock.as_ref().map(SessionClock::state).as_ref(), titles, &planned_times(&p.content));
+        let actual: Vec<u64> = report.slides.iter().map(|s| s.actual_seconds).collect();
+        assert_eq!(actual, vec![661, 61, 0]);
+        assert_eq!((report.session_seconds, report.planned_seconds), (722, Some(120)));
+    }
+
+    /// GET /{user}/{pid}/pacing must report planned against actual times to presenters only.
     #[tokio::test]
-    async fn controller_edit_redirects_to_stage() {
+    async fn pacing_report_is_for_presenters() {
         let (server, state) = test_server().await;
-        seed_user(&state.db_pool).await;
         let uid = get_user_id("admin", &state.db_pool).await;
-        let pid = seed_presentation(uid, "Edit Redirect Test", &state.db_pool).await;
-        User::new(
-            &state.db_pool,
-            AddUserForm {
-                name: "ctrluser2".to_string(),
-                email: "ctrl2@example.com".to_string(),
-                password: "ctrlpass2".to_string(),
-            },
-        )
-        .await
-        .unwrap();
-        let ctrl_uid = get_user_id("ctrluser2", &state.db_pool).await;
-        PresentationAccess::add(&state.db_pool, pid, ctrl_uid, "controller").await.unwrap();
-        login_as(&server, "ctrluser2", "ctrlpass2").await;
+        let pid = seed_presentation(uid, "Pacing", &state.db_pool).await;
+        DbPresentation::update_content(pid, "## Intro\n\n???\nTime: 2m\n\n## End".to_string(), &state.db_pool)
+            .await
+            .unwrap();
+        let url = format!("/admin/{pid}/pacing");
+        assert_eq!(server.get(&url).await.status_code(), 401);
+        seed_user(&state.db_pool).await;
+        login_as(&server, "testuser", "testpass").await;
+        assert_eq!(server.get(&url).await.status_code(), 403);
 
-        // axum-test does not follow redirects; verify the redirect then follow manually.
-        let redirect_resp = server.get(&format!("/admin/{pid}/edit")).await;
-        assert!(
-            redirect_resp.status_code().is_redirection(),
-            "edit route must redirect a controller"
+        login_as(&server, "admin", "admin").await;
+        let report = server.get(&url).await;
+        assert_eq!(report.status_code(), 200);
+        assert_eq!(
+            report.json::<serde_json::Value>(),
+            serde_json::json!({
+                "started": false,
+                "session_seconds": 0,
+                "planned_seconds": 120,
+                "slides": [
+                    {"slide": 0, "title": "Intro", "planned_seconds": 120, "actual_seconds": 0},
+                    {"slide": 1, "title": "End", "planned_seconds": null, "actual_seconds": 0}
+                ]
+            })
         );
-        let location = redirect_resp
-            .headers()
-            .get("location")
-            .expect("redirect must have a Location header")
-            .to_str()
-            .unwrap()
-            .to_string();
-        let stage_resp = server.get(&location).await;
-        assert_eq!(stage_resp.status_code(), 200);
-        assert!(
-            stage_resp.text().contains(r#"id="recordPause""#),
-            "controller redirected to stage should see recordPause"
+        let pres = add_client_handler_channel(pid.to_string(), &mut state.clone()).await.unwrap();
+        pres.lock().unwrap().show(1, 0);
+        assert_eq!(server.get(&url).await.json::<serde_json::Value>()["started"], true);
+
+        // The report outlives the session: the clock saved when everyone left still tells.
+        {
+            let mut p = pres.lock().unwrap();
+            p.clock = Some(SessionClock::resume(ClockState { elapsed_ms: 125_000, steps: vec![(0, 0), (65_000, 1)] }));
+            p.idle_since = Some(std::time::Instant::now());
+            p.checkpoint();
+        }
+        drop(pres);
+        assert_eq!(evict_idle(&state, std::time::Duration::ZERO).await, 1);
+        let report = server.get(&url).await.json::<serde_json::Value>();
+        assert_eq!(report["started"], true);
+        let actual: Vec<&serde_json::Value> = report["slides"].as_array().unwrap().iter().map(|s| &s["actual_seconds"]).collect();
+        assert_eq!(actual, [65, 60]);
+        assert_eq!(report["session_seconds"], 125);
+    }
+
+    #[test]
+    fn caption_cues_last_until_the_next_caption() {
+        let captions = [(0, "One".to_string()), (2500, "Two".to_string()), (3_700_000, "Last".to_string())];
+        assert_eq!(
+            captions::webvtt(&captions, 3_700_400),
+            "WEBVTT\n\n00:00:00.000 --> 00:00:02.500\nOne\n\n00:00:02.500 --> 00:00:08.500\nTwo\n\n\
+             01:01:40.000 --> 01:01:40.400\nLast\n"
         );
+        assert_eq!(captions::clean(" Fish\r\n &\u{7} chips\t"), Some("Fish & chips".to_string()));
+        assert_eq!(captions::clean(" \n"), None);
+        assert_eq!(captions::clean(&"x".repeat(captions::MAX_CAPTION_BYTES + 1)), None);
     }
 
-    /// A controller must be able to POST to add_recording.
+    /// Captioners and editors may send captions over the socket; other roles may not,
+    /// and captioners may send nothing else.
     #[tokio::test]
-    async fn controller_can_add_recording() {
-        let (server, state) = test_server().await;
-        seed_user(&state.db_pool).await;
+    async fn ws_captions_come_from_captioners_and_editors() {
+        let (_server, mut state) = test_server().await;
         let uid = get_user_id("admin", &state.db_pool).await;
-        let pid = seed_presentation(uid, "Recording Perm Test", &state.db_pool).await;
-        User::new(
-            &state.db_pool,
-            AddUserForm {
-                name: "ctrlrec".to_string(),
-                email: "ctrlrec@example.com".to_string(),
-                password: "ctrlrecpass".to_string(),
-            },
-        )
-        .await
-        .unwrap();
-        let ctrl_uid = get_user_id("ctrlrec", &state.db_pool).await;
-        PresentationAccess::add(&state.db_pool, pid, ctrl_uid, "controller").await.unwrap();
-        login_as(&server, "ctrlrec", "ctrlrecpass").await;
+        let key = seed_presentation(uid, "Captions", &state.db_pool).await.to_string();
+        let pres = add_client_handler_channel(key.clone(), &mut state).await.unwrap();
+        let mut tx = pres.lock().unwrap().channel.0.clone();
+        let mut rx = tx.subscribe();
+        let text = |msg: &SlideMessage| axum::extract::ws::Message::text(serde_json::to_string(msg).unwrap());
+        let caption = SlideMessage::Caption("Hello".to_string());
+        let (direct, _replies) = tokio::sync::mpsc::unbounded_channel();
+        for (role, sent) in [
+            (AccessResult::Captioner, true),
+            (AccessResult::Editor, true),
+            (AccessResult::Controller, false),
+            (AccessResult::Audience, false),
+        ] {
+            assert!(matches!(handle_socket(Ok(text(&caption)), &key, &mut tx, &mut state, &role, &direct), Ok(true)));
+            assert_eq!(matches!(rx.try_recv(), Ok(SlideMessage::Caption(t)) if t == "Hello"), sent, "{role:?}");
+        }
+        let slide = text(&SlideMessage::Slide(1));
+        assert!(matches!(handle_socket(Ok(slide), &key, &mut tx, &mut state, &AccessResult::Captioner, &direct), Ok(true)));
+        assert!(rx.try_recv().is_err(), "captioners must not move slides");
+        let viewer = Viewer { connection_id: 1, is_presenter: false, is_audience: true, is_editor: false };
+        assert!(viewer.view(&caption).is_some(), "the audience must see captions");
+    }
 
-        // Multipart form with required fields: name + slides JSON
-        let form = axum_test::multipart::MultipartForm::new()
-            .add_text("name", "Test Recording")
-            .add_text("slides", "[]");
-        let response = server
-            .post(&format!("/user/presentations/{pid}/recordings"))
-            .multipart(form)
-            .await;
-        // Must not be 403
-        assert_ne!(
-            response.status_code(),
-            axum::http::StatusCode::FORBIDDEN,
-            "controller must not be forbidden from adding a recording"
-        );
+    /// Captions must be timed against the recording only while it runs, and saved as
+    /// its captions file when it stops.
+    #[tokio::test]
+    async fn captions_are_saved_with_the_recording() {
+        let (_server, mut state) = test_server().await;
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(uid, "Captions", &state.db_pool).await;
+        let rid = 9811;
+        sqlx::query("INSERT INTO recording (id, presentation_id, name, captions_path) VALUES (?, ?, 'Live', '')")
+            .bind(rid)
+            .bind(pid)
+            .execute(&state.db_pool)
+            .await
+            .unwrap();
+        let key = pid.to_string();
+        let pres = add_client_handler_channel(key.clone(), &mut state).await.unwrap();
+        let checkpoint =
+            RecordingCheckpoint { recording_id: rid, active_ms: 0, slides: vec![(0, 0, 0, None)], polls: vec![], captions: vec![] };
+        pres.lock().unwrap().recording = Some(RecordingState::restore(checkpoint, "Live".to_string()));
+
+        let paused = update_slide(&key, SlideMessage::Caption("While paused".to_string()), &mut state);
+        assert!(matches!(paused, Some(SlideMessage::Caption(_))), "captions show while paused");
+        handle_recording_message(RecordingMessage::RecordingResume, &pres, pid, &state.db_pool).await;
+        assert!(update_slide(&key, SlideMessage::Caption(" \n ".to_string()), &mut state).is_none());
+        update_slide(&key, SlideMessage::Caption("Fish &\n<chips>".to_string()), &mut state);
+        let recorded: Vec<String> = {
+            let p = pres.lock().unwrap();
+            p.recording.as_ref().unwrap().captions.iter().map(|(_, text)| text.clone()).collect()
+        };
+        assert_eq!(recorded, ["Fish & <chips>"]);
+
+        handle_recording_message(RecordingMessage::RecordingStop, &pres, pid, &state.db_pool).await;
+        let dir = format!("assets/{rid}");
+        let vtt = tokio::fs::read_to_string(format!("{dir}/captions.vtt")).await;
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+        let vtt = vtt.unwrap();
+        assert!(vtt.starts_with("WEBVTT\n\n00:00:00.") && vtt.ends_with("\nFish &amp; &lt;chips&gt;\n"), "{vtt}");
+        let rec = Recording::get_by_id(rid, &state.db_pool).await.unwrap().unwrap();
+        assert_eq!(rec.captions_path, "captions.vtt");
     }
 
-    /// render_all_slides must return one (title, html) pair per ## heading.
-    #[test]
-    fn render_all_slides_splits_on_h2() {
-        let md = "## Intro\nHello world\n\n## Second\nContent here";
-        let slides = render_all_slides(md);
-        assert_eq!(slides.len(), 2);
-        assert_eq!(slides[0].0, "Intro");
-        assert!(slides[0].1.contains("Hello world"), "got: {}", slides[0].1);
-        assert_eq!(slides[1].0, "Second");
-        assert!(slides[1].1.contains("Content here"), "got: {}", slides[1].1);
+    /// POST /{uname}/{pid}/captions must show each line to everyone, for captioners only.
+    #[tokio::test]
+    async fn captions_can_be_posted_as_plain_text() {
+        let (server, mut state) = test_server().await;
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(uid, "Captions", &state.db_pool).await;
+        let url = format!("/admin/{pid}/captions");
+        assert_eq!(server.post(&url).text("Hi").await.status_code(), 401);
+        seed_user(&state.db_pool).await;
+        login_as(&server, "testuser", "testpass").await;
+        assert_eq!(server.post(&url).text("Hi").await.status_code(), 403);
+        assert!(!server.get(&format!("/admin/{pid}")).await.text().contains("id=\"caption-form\""));
+
+        let tuid = get_user_id("testuser", &state.db_pool).await;
+        PresentationAccess::add(&state.db_pool, pid, tuid, "captioner").await.unwrap();
+        assert!(server.get(&format!("/admin/{pid}")).await.text().contains("id=\"caption-form\""));
+        let pres = add_client_handler_channel(pid.to_string(), &mut state).await.unwrap();
+        let mut rx = pres.lock().unwrap().channel.0.subscribe();
+        let resp = server.post(&url).text("Hello there\r\n\nGeneral Kenobi").await;
+        assert_eq!(resp.status_code(), 204);
+        let mut shown = Vec::new();
+        while let Ok(msg) = rx.try_recv() {
+            if let SlideMessage::Caption(text) = msg {
+                shown.push(text);
+            }
+        }
+        assert_eq!(shown, ["Hello there", "General Kenobi"]);
+
+        assert_eq!(server.post(&url).bytes(vec![0xff, b'\n'].into()).await.status_code(), 400);
+        let long = "x".repeat(captions::MAX_CAPTION_BYTES + 1);
+        assert_eq!(server.post(&url).text(long).await.status_code(), 413);
     }
 
-    /// render_all_slides must return empty vec for content with no ## headings.
     #[test]
-    fn render_all_slides_empty_for_no_headings() {
-        let slides = render_all_slides("just some text");
-        assert_eq!(slides.len(), 0);
+    fn idle_eviction_period_reads_minutes() {
+        assert_eq!(idle_eviction_after(Some("5")), std::time::Duration::from_secs(300));
+        assert_eq!(idle_eviction_after(Some("0")), std::time::Duration::from_secs(15 * 60));
+        assert_eq!(idle_eviction_after(None), std::time::Duration::from_secs(15 * 60));
     }
 
-    /// Raw HTML in Markdown content must be stripped by render_slide and render_all_slides
-    /// to prevent stored XSS (OWASP A03).
-    #[test]
-    fn render_slide_strips_raw_html() {
-        let md = "## Slide 1\n<script>alert('xss')</script>\n\nSafe text";
-        // render_slide must not include the <script> tag
-        let html = render_slide(md, 0, "");
-        assert!(
-            !html.contains("<script"),
-            "render_slide must strip <script> tags, got: {html}"
+    /// GET /admin/live must report eviction counts to admins only.
+    #[tokio::test]
+    async fn live_stats_are_for_admins_only() {
+        let (server, state) = test_server().await;
+        assert_eq!(server.get("/admin/live").await.status_code(), 401);
+        seed_user(&state.db_pool).await;
+        login_as(&server, "testuser", "testpass").await;
+        assert_eq!(server.get("/admin/live").await.status_code(), 404);
+        login_as(&server, "admin", "admin").await;
+        let response = server.get("/admin/live").await;
+        assert_eq!(response.status_code(), 200);
+        assert_eq!(
+            response.json::<serde_json::Value>(),
+            serde_json::json!({"live": 0, "evicted": 0, "recordings_stopped": 0, "connections": []})
         );
-        assert!(html.contains("Safe text"), "safe content must still render");
+    }
 
-        // render_all_slides must also strip raw HTML
-        let slides = render_all_slides(md);
-        assert_eq!(slides.len(), 1);
-        assert!(
-            !slides[0].1.contains("<script"),
-            "render_all_slides must strip <script> tags, got: {}",
-            slides[0].1
+    /// GET /admin/live must list the lag counters of every open connection.
+    #[tokio::test]
+    async fn live_stats_show_connection_lag() {
+        let (server, state) = test_server().await;
+        let pres = Arc::new(Mutex::new(Presentation::new(String::new())));
+        let stats = Arc::new(ConnectionStats::default());
+        stats.record_lag(5);
+        stats.record_lag(2);
+        pres.lock().unwrap().connections.insert(3, Arc::clone(&stats));
+        state.slides.lock().unwrap().insert("9909".to_string(), pres);
+        login_as(&server, "admin", "admin").await;
+
+        let body = server.get("/admin/live").await.json::<serde_json::Value>();
+        assert_eq!(body["live"], 1);
+        assert_eq!(
+            body["connections"],
+            serde_json::json!([{"presentation": "9909", "connection": 3, "lag_events": 2, "skipped_messages": 7}])
         );
     }
 
-    /// Creates an isolated in-memory pool with migrations applied.
-    async fn setup_pool() -> SqlitePool {
-        let pool = SqlitePoolOptions::new()
-            .max_connections(1)
-            .connect_with(
-                SqliteConnectOptions::from_str("sqlite::memory:")
-                    .unwrap()
-                    .foreign_keys(false),
-            )
+    /// Restoring a revision must update the DB, keep the replaced content as a revision,
+    /// and broadcast the restored text to live clients.
+    #[tokio::test]
+    async fn restore_revision_updates_content_and_broadcasts() {
+        let (server, state) = test_server().await;
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(uid, "Restore Test", &state.db_pool).await;
+        PresentationRevision::create(&state.db_pool, pid, Some(uid), "owner", "## Good".to_string())
             .await
             .unwrap();
-        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
-        sqlx::query("PRAGMA foreign_keys = ON")
-            .execute(&pool)
+        let rev_id: i64 = sqlx::query_scalar("SELECT id FROM presentation_revision WHERE presentation_id = ?")
+            .bind(pid)
+            .fetch_one(&state.db_pool)
             .await
             .unwrap();
-        pool
-    }
+        let live = Arc::new(Mutex::new(Presentation::new("## Bad paste".to_string())));
+        let mut rx = live.lock().unwrap().channel.0.subscribe();
+        state.slides.lock().unwrap().insert(pid.to_string(), Arc::clone(&live));
+        login_as(&server, "admin", "admin").await;
 
-    /// Creates a user with the given name and returns the User struct.
-    async fn make_user(pool: &SqlitePool, name: &str) -> User {
-        User::new(
-            pool,
-            AddUserForm {
-                name: name.to_string(),
-                email: format!("{name}@example.com"),
-                password: "testpass".to_string(),
-            },
-        )
-        .await
-        .unwrap();
-        User::get_by_name(name.to_string(), pool).await.unwrap().unwrap()
-    }
+        let resp = server
+            .post(&format!("/user/presentations/{pid}/revisions/{rev_id}/restore"))
+            .await;
+        assert_eq!(resp.status_code(), 303);
 
-    /// Creates a presentation owned by the given user and returns the DbPresentation.
-    async fn make_presentation(owner: &User, pool: &SqlitePool) -> DbPresentation {
-        DbPresentation::new(owner, "Test Presentation".to_string(), pool)
+        let content: String = sqlx::query_scalar("SELECT content FROM presentation WHERE id = ?")
+            .bind(pid)
+            .fetch_one(&state.db_pool)
             .await
-            .unwrap()
-    }
-
-    /// Creates an in-memory Presentation arc for unit tests.
-    fn make_presentation_arc() -> Arc<Mutex<Presentation>> {
-        let (tx, _rx) = broadcast::channel(8);
-        let (_, rx_inner) = broadcast::channel(8);
-        Arc::new(Mutex::new(Presentation {
-            content: "## Intro\nHello\n\n## Second\nWorld".to_string(),
-            slide: 0,
-            channel: (tx, rx_inner),
-            recording: None,
-            presenter_count: 0,
-        }))
+            .unwrap();
+        assert_eq!(content, "## Good");
+        assert_eq!(live.lock().unwrap().content, "## Good");
+        let Ok(SlideMessage::Patch(patch)) = rx.try_recv() else { panic!("restore must broadcast a patch") };
+        assert_eq!(patch.apply("## Bad paste").as_deref(), Some("## Good"));
+        let revisions = PresentationRevision::get_for_presentation(&state.db_pool, pid).await.unwrap();
+        assert!(
+            revisions.iter().any(|r| r.content == "## Bad paste"),
+            "the replaced content must be kept as a revision"
+        );
     }
 
-    /// recording_start must insert a recording row and set in-memory state.
+    /// The diff page must mark lines only present in the revision as removed.
     #[tokio::test]
-    async fn recording_start_creates_db_row() {
-        let pool = setup_pool().await;
-        let owner = make_user(&pool, "owner").await;
-        let pres_db = make_presentation(&owner, &pool).await;
-        let pres = make_presentation_arc();
-
-        let result = handle_recording_message(
-            RecordingMessage::RecordingStart,
-            &pres,
-            pres_db.id,
-            &pool,
-        ).await;
+    async fn revision_diff_marks_changed_lines() {
+        let (server, state) = test_server().await;
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(uid, "Diff Test", &state.db_pool).await;
+        PresentationRevision::create(&state.db_pool, pid, Some(uid), "owner", "## Old title\n".to_string())
+            .await
+            .unwrap();
+        DbPresentation::update_content(pid, "## New title\n".to_string(), &state.db_pool).await.unwrap();
+        let rev_id: i64 = sqlx::query_scalar("SELECT id FROM presentation_revision WHERE presentation_id = ?")
+            .bind(pid)
+            .fetch_one(&state.db_pool)
+            .await
+            .unwrap();
+        login_as(&server, "admin", "admin").await;
 
-        assert!(matches!(result, Some(SlideMessage::RecordingStart { elapsed_ms: 0 })));
-        assert!(pres.lock().unwrap().recording.is_some());
-        // DB row exists
-        let rec_id = pres.lock().unwrap().recording.as_ref().unwrap().db_id;
-        let row = Recording::get_by_id(rec_id, &pool).await.unwrap();
-        assert!(row.is_some());
-        assert!(row.unwrap().name.starts_with("Recording"));
+        let resp = server.get(&format!("/admin/{pid}/history/{rev_id}")).await;
+        assert_eq!(resp.status_code(), 200);
+        let body = resp.text();
+        assert!(body.contains("<del>## Old title</del>"), "got: {body}");
+        assert!(body.contains("<ins>## New title</ins>"), "got: {body}");
     }
 
-    /// recording_start when already active must return None (no-op).
+    /// The diff page compares against a live presentation's content, which may
+    /// not have been written to the database yet.
     #[tokio::test]
-    async fn recording_start_ignored_if_active() {
-        let pool = setup_pool().await;
-        let owner = make_user(&pool, "owner2").await;
-        let pres_db = make_presentation(&owner, &pool).await;
-        let pres = make_presentation_arc();
-
-        handle_recording_message(RecordingMessage::RecordingStart, &pres, pres_db.id, &pool).await;
-        let result = handle_recording_message(RecordingMessage::RecordingStart, &pres, pres_db.id, &pool).await;
+    async fn revision_diff_uses_live_content() {
+        let (server, state) = test_server().await;
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(uid, "Live Diff Test", &state.db_pool).await;
+        PresentationRevision::create(&state.db_pool, pid, Some(uid), "owner", "## Old title\n".to_string())
+            .await
+            .unwrap();
+        DbPresentation::update_content(pid, "## Saved title\n".to_string(), &state.db_pool).await.unwrap();
+        state
+            .slides
+            .lock()
+            .unwrap()
+            .insert(pid.to_string(), Arc::new(Mutex::new(Presentation::new("## Live title\n".to_string()))));
+        let rev_id: i64 = sqlx::query_scalar("SELECT id FROM presentation_revision WHERE presentation_id = ?")
+            .bind(pid)
+            .fetch_one(&state.db_pool)
+            .await
+            .unwrap();
+        login_as(&server, "admin", "admin").await;
 
-        assert!(result.is_none());
-        // Only one DB row
-        let rows: Vec<Recording> = sqlx::query_as::<_, Recording>(
-            "SELECT * FROM recording WHERE presentation_id = ?",
-        )
-        .bind(pres_db.id)
-        .fetch_all(&pool)
-        .await
-        .unwrap();
-        assert_eq!(rows.len(), 1);
+        let resp = server.get(&format!("/admin/{pid}/history/{rev_id}")).await;
+        assert_eq!(resp.status_code(), 200);
+        let body = resp.text();
+        assert!(body.contains("<ins>## Live title</ins>"), "got: {body}");
+        assert!(!body.contains("Saved title"), "got: {body}");
     }
 
-    /// recording_stop must write recording_slide rows and clear in-memory state.
-    #[tokio::test]
-    async fn recording_stop_saves_slides() {
-        let pool = setup_pool().await;
-        let owner = make_user(&pool, "owner3").await;
-        let pres_db = make_presentation(&owner, &pool).await;
-        let pres = make_presentation_arc();
-
-        handle_recording_message(RecordingMessage::RecordingStart, &pres, pres_db.id, &pool).await;
-
-        // Simulate a slide change at 2000ms
-        {
-            let mut p = pres.lock().unwrap();
-            let rec = p.recording.as_mut().unwrap();
-            rec.slides.push(RecordingEvent { offset_ms: 2000, slide: 1 });
-        }
+    // --- QR code route tests ---
 
-        let result = handle_recording_message(RecordingMessage::RecordingStop, &pres, pres_db.id, &pool).await;
+    /// GET /qr/{uname}/{pid} must return 200 with content-type image/svg+xml.
+    #[tokio::test]
+    async fn qr_code_returns_correct_content_type() {
+        let (server, _state) = test_server().await;
 
-        assert!(matches!(result, Some(SlideMessage::RecordingStop { .. })));
-        assert!(pres.lock().unwrap().recording.is_none());
+        let response = server.get("/qr/testuser/42").await;
 
-        // recording_slide rows exist
-        let rows = sqlx::query_as::<_, RecordingSlide>(
-            "SELECT * FROM recording_slide WHERE recording_id = (SELECT id FROM recording WHERE presentation_id = ? ORDER BY id DESC LIMIT 1) ORDER BY position",
-        )
-        .bind(pres_db.id)
-        .fetch_all(&pool)
-        .await
-        .unwrap();
-        assert_eq!(rows.len(), 2);
-        assert_eq!(rows[0].position, 0);
-        assert_eq!(rows[0].title, "Intro");
-        assert!((rows[0].start_seconds - 0.0).abs() < 0.001);
-        assert_eq!(rows[1].position, 1);
-        assert_eq!(rows[1].title, "Second");
-        assert!((rows[1].start_seconds - 2.0).abs() < 0.001);
+        assert_eq!(response.status_code(), 200);
+        let ct = response
+            .headers()
+            .get("content-type")
+            .expect("content-type header must be present")
+            .to_str()
+            .unwrap();
+        assert!(
+            ct.starts_with("image/svg+xml"),
+            "content-type must be image/svg+xml, got: {ct}"
+        );
     }
 
-    /// Elapsed time must account for paused periods.
+    /// GET /qr/{uname}/{pid} body must be a well-formed SVG document.
     #[tokio::test]
-    async fn recording_pause_resume_elapsed() {
-        let pool = setup_pool().await;
-        let owner = make_user(&pool, "owner").await;
-        let pres_db = make_presentation(&owner, &pool).await;
-        let pres = make_presentation_arc();
-
-        // Start
-        handle_recording_message(RecordingMessage::RecordingStart, &pres, pres_db.id, &pool).await;
+    async fn qr_code_returns_svg_body() {
+        let (server, _state) = test_server().await;
 
-        // Pause immediately — elapsed should be very small (< 100ms)
-        let pause_msg = handle_recording_message(RecordingMessage::RecordingPause, &pres, pres_db.id, &pool).await;
-        let elapsed_at_pause = match pause_msg {
-            Some(SlideMessage::RecordingPause { elapsed_ms }) => elapsed_ms,
-            _ => panic!("expected RecordingPause"),
-        };
-        assert!(elapsed_at_pause < 200, "elapsed at pause was {elapsed_at_pause}ms, expected < 200ms");
-        assert!(pres.lock().unwrap().recording.as_ref().unwrap().is_paused);
+        let response = server.get("/qr/testuser/42").await;
+        let body = response.text();
 
-        // Resume — elapsed should still be small
-        let resume_msg = handle_recording_message(RecordingMessage::RecordingResume, &pres, pres_db.id, &pool).await;
-        let elapsed_at_resume = match resume_msg {
-            Some(SlideMessage::RecordingResume { elapsed_ms }) => elapsed_ms,
-            _ => panic!("expected RecordingResume"),
-        };
-        assert!(!pres.lock().unwrap().recording.as_ref().unwrap().is_paused);
-        // elapsed at resume should be >= elapsed at pause (not reset to 0)
-        assert!(elapsed_at_resume >= elapsed_at_pause,
-            "elapsed at resume ({elapsed_at_resume}) should be >= elapsed at pause ({elapsed_at_pause})");
+        assert!(body.contains("<svg"), "body must contain <svg element, got: {}", &body[..body.len().min(80)]);
+        assert!(body.contains("</svg>"), "body must contain closing </svg>");
     }
 
-    /// Pausing when already paused must return None.
+    /// GET /qr/{uname}/{pid} must succeed even when the username and presentation
+    /// ID do not correspond to any real database rows — the handler generates the
+    /// QR code from the path params without a DB lookup.
     #[tokio::test]
-    async fn recording_pause_noop_when_already_paused() {
-        let pool = setup_pool().await;
-        let owner = make_user(&pool, "owner").await;
-        let pres_db = make_presentation(&owner, &pool).await;
-        let pres = make_presentation_arc();
-        handle_recording_message(RecordingMessage::RecordingStart, &pres, pres_db.id, &pool).await;
-        handle_recording_message(RecordingMessage::RecordingPause, &pres, pres_db.id, &pool).await;
-        let result = handle_recording_message(RecordingMessage::RecordingPause, &pres, pres_db.id, &pool).await;
-        assert!(result.is_none());
+    async fn qr_code_succeeds_for_nonexistent_presentation() {
+        let (server, _state) = test_server().await;
+
+        let response = server.get("/qr/nobody/99999").await;
+
+        assert_eq!(
+            response.status_code(),
+            200,
+            "QR route must succeed even for unknown username/pid"
+        );
+        let ct = response
+            .headers()
+            .get("content-type")
+            .unwrap()
+            .to_str()
+            .unwrap();
+        assert!(ct.starts_with("image/svg+xml"));
     }
 
-    /// When presenter_count drops to 0 with an active recording, the stop logic saves slides.
-    #[tokio::test]
-    async fn recording_auto_stop_on_last_presenter_disconnect() {
-        let pool = setup_pool().await;
-        let owner = make_user(&pool, "owner").await;
-        let pres_db = make_presentation(&owner, &pool).await;
-        let pres = make_presentation_arc();
+    // --- Demo redirect tests ---
 
-        // Start recording
-        handle_recording_message(RecordingMessage::RecordingStart, &pres, pres_db.id, &pool).await;
-        let rec_id = pres.lock().unwrap().recording.as_ref().unwrap().db_id;
+    /// GET /demo when the admin user has at least one presentation must redirect
+    /// to /{admin.name}/{pres.id}.  Migrations seed admin (id=1) and the Demo
+    /// presentation (id=1), so the Location header must be /admin/1.
+    #[tokio::test]
+    async fn demo_with_admin_presentation_redirects_to_presentation() {
+        let (server, _state) = test_server().await;
+        // Migrations seed admin user + Demo presentation; no extra seeding needed.
 
-        // Simulate auto-stop (same as stop, but called by disconnect handler)
-        let result = handle_recording_message(RecordingMessage::RecordingStop, &pres, pres_db.id, &pool).await;
-        assert!(matches!(result, Some(SlideMessage::RecordingStop { .. })));
-        assert!(pres.lock().unwrap().recording.is_none());
+        let response = server.get("/demo").await;
 
-        // last_edited was set
-        let rec = Recording::get_by_id(rec_id, &pool).await.unwrap().unwrap();
-        assert!(rec.last_edited.is_some());
+        assert_eq!(
+            response.status_code(),
+            303,
+            "demo must redirect (303) to the admin's first presentation"
+        );
+        let location = response
+            .headers()
+            .get("location")
+            .expect("Location header must be present")
+            .to_str()
+            .unwrap();
+        assert_eq!(
+            location, "/admin/1",
+            "demo must redirect to /admin/1 (seeded presentation)"
+        );
     }
 
-    /// GET /auth/logout must clear the session and redirect to /.
+    /// GET /demo when the admin user has no presentations must redirect to /.
     #[tokio::test]
-    async fn logout_clears_session_and_redirects() {
-        let (server, _state) = test_server().await;
-        login_as(&server, "admin", "admin").await;
+    async fn demo_without_admin_presentations_redirects_to_home() {
+        let (_server, state) = test_server().await;
+        // Delete the seeded Demo presentation so the admin has none.
+        // Must delete recording rows first — recording has no ON DELETE CASCADE FK.
+        sqlx::query("DELETE FROM recording WHERE presentation_id IN (SELECT id FROM presentation WHERE user_id = (SELECT id FROM users WHERE name = 'admin'))")
+            .execute(&state.db_pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM presentation WHERE user_id = (SELECT id FROM users WHERE name = 'admin')")
+            .execute(&state.db_pool)
+            .await
+            .unwrap();
 
-        // Authenticated: presentations page is accessible.
-        let before = server.get("/user/presentations").await;
-        assert_eq!(before.status_code(), 200);
+        // Build a fresh server against the same pool state.
+        let (router, _) = build_app(state.db_pool, state.upload_limit, state.pubsub).await;
+        let server = TestServer::builder().save_cookies().build(router).unwrap();
 
-        let resp = server.get("/auth/logout").await;
-        assert!(resp.status_code().is_redirection());
+        let response = server.get("/demo").await;
 
-        // After logout: presentations page redirects to login.
-        let after = server.get("/user/presentations").await;
-        assert!(after.status_code().is_redirection());
-        let loc = after.headers().get("location").unwrap().to_str().unwrap();
-        assert!(loc.contains("/auth/login"), "expected redirect to login, got {loc}");
+        assert_eq!(
+            response.status_code(),
+            303,
+            "demo with no admin presentations must still redirect"
+        );
+        let location = response
+            .headers()
+            .get("location")
+            .unwrap()
+            .to_str()
+            .unwrap();
+        assert_eq!(location, "/", "demo with no presentations must redirect to /");
     }
 
-    /// POST /user/new by an admin must create the user and redirect.
+    /// A poisoned K/V store mutex must return `Err` from `add_client_handler_channel`
+    /// rather than crashing the process with a panic.
     #[tokio::test]
-    async fn admin_can_create_user() {
-        let (server, state) = test_server().await;
-        login_as(&server, "admin", "admin").await;
-
-        let resp = server
-            .post("/user/new")
-            .form(&serde_json::json!({
-                "name": "newbie",
-                "email": "newbie@example.com",
-                "password": "newbiepass"
-            }))
-            .await;
-        assert!(resp.status_code().is_redirection());
-
-        let exists: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users WHERE name = 'newbie'")
-            .fetch_one(&state.db_pool)
+    async fn kvstore_lock_poison_returns_err_not_panic() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(
+                SqliteConnectOptions::from_str("sqlite::memory:")
+                    .unwrap()
+                    .foreign_keys(false),
+            )
             .await
             .unwrap();
-        assert_eq!(exists, 1);
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        // Poison the slides mutex by panicking while a guard is held.
+        let slides: Arc<Mutex<HashMap<String, Arc<Mutex<Presentation>>>>> =
+            Arc::new(Mutex::new(HashMap::new()));
+        let slides_clone = Arc::clone(&slides);
+        let _ = std::thread::spawn(move || {
+            let _guard = slides_clone.lock().unwrap();
+            panic!("intentional poison");
+        })
+        .join();
+        assert!(slides.lock().is_err(), "mutex should be poisoned after panic");
+
+        let mut state = AppState {
+            tera: Tera::new(),
+            slides,
+            db_pool: pool,
+            upload_limit: UploadLimit(1024),
+            pubsub: Arc::new(pubsub::InMemory::default()),
+            node: 1,
+            evictions: Arc::new(EvictionStats::default()),
+        };
+
+        let result = add_client_handler_channel("1".to_string(), &mut state).await;
+        assert!(
+            result.is_err(),
+            "poisoned K/V store must return Err, not panic"
+        );
     }
 
-    /// POST /user/change_pwd with correct old password must update it.
+    /// After 5 login attempts from the same IP, the 6th must be rejected with HTTP 429.
     #[tokio::test]
-    async fn change_pwd_succeeds_with_correct_old_password() {
+    async fn login_rate_limiting_returns_429_after_limit() {
         let (server, state) = test_server().await;
-        login_as(&server, "admin", "admin").await;
+        seed_user(&state.db_pool).await;
+
+        // Send 5 attempts — all should be handled normally (not rate-limited).
+        for i in 0..5_u8 {
+            let resp = server
+                .post("/auth/login")
+                .form(&serde_json::json!({
+                    "username": "testuser",
+                    "password": "wrongpass"
+                }))
+                .await;
+            assert_ne!(
+                resp.status_code().as_u16(),
+                429,
+                "attempt {i} should not be rate-limited"
+            );
+        }
 
+        // The 6th attempt from the same IP must be rejected.
         let resp = server
-            .post("/user/change_pwd")
+            .post("/auth/login")
             .form(&serde_json::json!({
-                "old": "admin",
-                "new": "newpass123",
-                "confirm": "newpass123"
+                "username": "testuser",
+                "password": "wrongpass"
             }))
             .await;
-        assert!(resp.status_code().is_redirection());
+        assert_eq!(
+            resp.status_code().as_u16(),
+            429,
+            "6th attempt must return 429 Too Many Requests"
+        );
+    }
 
-        // New password must work for login.
-        let login_resp = server
-            .post("/auth/login")
-            .form(&serde_json::json!({ "username": "admin", "password": "newpass123" }))
-            .await;
-        assert!(login_resp.status_code().is_redirection());
+    /// Packs `files` into an in-memory zip, the container both deck formats use.
+    fn zip_bytes(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut zip = ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        for (name, data) in files {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(data).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
 
-        // Drop pool to silence unused warning in test context.
-        let _ = &state.db_pool;
+    fn sample_pptx() -> Vec<u8> {
+        const NS: &str = r#"xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" xmlns:p="http://schemas.openxmlformats.org/presentationml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships""#;
+        const REL: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+        let presentation = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?><p:presentation {NS}><p:sldIdLst><p:sldId id="256" r:id="rId2"/><p:sldId id="257" r:id="rId3"/></p:sldIdLst></p:presentation>"#
+        );
+        let presentation_rels = format!(
+            r#"<Relationships><Relationship Id="rId2" Type="{REL}/slide" Target="slides/slide1.xml"/><Relationship Id="rId3" Type="{REL}/slide" Target="slides/slide2.xml"/></Relationships>"#
+        );
+        let slide1 = format!(
+            r#"<p:sld {NS}><p:cSld><p:spTree>
+<p:nvGrpSpPr/><p:grpSpPr/>
+<p:sp><p:nvSpPr><p:cNvPr id="2" name="Title"/><p:cNvSpPr/><p:nvPr><p:ph type="title"/></p:nvPr></p:nvSpPr><p:txBody><a:p><a:r><a:t>Intro &amp; Goals</a:t></a:r></a:p></p:txBody></p:sp>
+<p:sp><p:nvSpPr><p:cNvPr id="3" name="Body"/><p:cNvSpPr/><p:nvPr><p:ph idx="1"/></p:nvPr></p:nvSpPr><p:txBody>
+<a:p><a:r><a:t>Point one</a:t></a:r></a:p>
+<a:p><a:pPr lvl="1"/><a:r><a:t>Sub point</a:t></a:r></a:p>
+<a:p><a:pPr><a:buAutoNum type="arabicPeriod"/></a:pPr><a:r><a:t>First step</a:t></a:r></a:p>
+<a:p><a:endParaRPr/></a:p>
+</p:txBody></p:sp>
+<p:pic><p:nvPicPr><p:cNvPr id="4" name="Picture 1" descr="A red square"/><p:cNvPicPr/><p:nvPr/></p:nvPicPr><p:blipFill><a:blip r:embed="rId2"/></p:blipFill></p:pic>
+<p:graphicFrame><p:nvGraphicFramePr/><a:graphic><a:graphicData uri="http://schemas.openxmlformats.org/drawingml/2006/chart"/></a:graphic></p:graphicFrame>
+</p:spTree></p:cSld></p:sld>"#
+        );
+        let slide1_rels = format!(
+            r#"<Relationships><Relationship Id="rId2" Type="{REL}/image" Target="../media/image1.png"/><Relationship Id="rId3" Type="{REL}/notesSlide" Target="../notesSlides/notesSlide1.xml"/></Relationships>"#
+        );
+        let notes = format!(
+            r#"<p:notes {NS}><p:cSld><p:spTree>
+<p:sp><p:nvSpPr><p:cNvPr id="2" name="Image"/><p:cNvSpPr/><p:nvPr><p:ph type="sldImg"/></p:nvPr></p:nvSpPr></p:sp>
+<p:sp><p:nvSpPr><p:cNvPr id="3" name="Notes"/><p:cNvSpPr/><p:nvPr><p:ph type="body" idx="1"/></p:nvPr></p:nvSpPr><p:txBody><a:p><a:r><a:t>Mention the budget</a:t></a:r></a:p></p:txBody></p:sp>
+</p:spTree></p:cSld></p:notes>"#
+        );
+        let slide2 = format!(
+            r#"<p:sld {NS}><p:cSld><p:spTree>
+<p:graphicFrame><p:nvGraphicFramePr/><a:graphic><a:graphicData uri="http://schemas.openxmlformats.org/drawingml/2006/table"><a:tbl>
+<a:tr><a:tc><a:txBody><a:p><a:r><a:t>Name</a:t></a:r></a:p></a:txBody></a:tc><a:tc><a:txBody><a:p><a:r><a:t>Value | more</a:t></a:r></a:p></a:txBody></a:tc></a:tr>
+<a:tr><a:tc><a:txBody><a:p><a:r><a:t>a</a:t></a:r></a:p></a:txBody></a:tc><a:tc><a:txBody><a:p><a:r><a:t>1</a:t></a:r></a:p></a:txBody></a:tc></a:tr>
+</a:tbl></a:graphicData></a:graphic></p:graphicFrame>
+<p:pic><p:nvPicPr><p:cNvPr id="5" name="Picture 2"/><p:cNvPicPr/><p:nvPr/></p:nvPicPr><p:blipFill><a:blip r:embed="rId2"/></p:blipFill></p:pic>
+</p:spTree></p:cSld></p:sld>"#
+        );
+        let slide2_rels = format!(
+            r#"<Relationships><Relationship Id="rId2" Type="{REL}/image" Target="../media/image1.png"/></Relationships>"#
+        );
+        zip_bytes(&[
+            ("[Content_Types].xml", b"<Types/>".as_slice()),
+            ("ppt/presentation.xml", presentation.as_bytes()),
+            ("ppt/_rels/presentation.xml.rels", presentation_rels.as_bytes()),
+            ("ppt/slides/slide1.xml", slide1.as_bytes()),
+            ("ppt/slides/_rels/slide1.xml.rels", slide1_rels.as_bytes()),
+            ("ppt/slides/slide2.xml", slide2.as_bytes()),
+            ("ppt/slides/_rels/slide2.xml.rels", slide2_rels.as_bytes()),
+            ("ppt/notesSlides/notesSlide1.xml", notes.as_bytes()),
+            ("ppt/media/image1.png", b"png-bytes".as_slice()),
+        ])
+    }
+
+    fn sample_odp() -> Vec<u8> {
+        let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document-content xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" xmlns:style="urn:oasis:names:tc:opendocument:xmlns:style:1.0" xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0" xmlns:table="urn:oasis:names:tc:opendocument:xmlns:table:1.0" xmlns:draw="urn:oasis:names:tc:opendocument:xmlns:drawing:1.0" xmlns:xlink="http://www.w3.org/1999/xlink" xmlns:svg="urn:oasis:names:tc:opendocument:xmlns:svg-compatible:1.0" xmlns:presentation="urn:oasis:names:tc:opendocument:xmlns:presentation:1.0">
+<office:automatic-styles>
+  <text:list-style style:name="L2"><text:list-level-style-number text:level="1"/></text:list-style>
+</office:automatic-styles>
+<office:body><office:presentation>
+<draw:page draw:name="page1">
+  <draw:frame presentation:class="title"><draw:text-box><text:p>Welcome</text:p></draw:text-box></draw:frame>
+  <draw:frame presentation:class="outline"><draw:text-box>
+    <text:list><text:list-item><text:p>Alpha</text:p>
+      <text:list><text:list-item><text:p>Beta<text:s/>version</text:p></text:list-item></text:list>
+    </text:list-item></text:list>
+  </draw:text-box></draw:frame>
+  <draw:frame><draw:image xlink:href="Pictures/pic.png"/><svg:title>Logo</svg:title></draw:frame>
+  <presentation:notes><draw:page-thumbnail/><draw:frame presentation:class="notes"><draw:text-box><text:p>Say hi</text:p></draw:text-box></draw:frame></presentation:notes>
+</draw:page>
+<draw:page draw:name="page2">
+  <draw:frame presentation:class="title"><draw:text-box><text:p>Data</text:p></draw:text-box></draw:frame>
+  <draw:frame><table:table>
+    <table:table-row><table:table-cell><text:p>H1</text:p></table:table-cell><table:table-cell><text:p>H2</text:p></table:table-cell></table:table-row>
+    <table:table-row><table:table-cell><text:p>x</text:p></table:table-cell><table:covered-table-cell/></table:table-row>
+  </table:table></draw:frame>
+  <draw:frame><draw:object xlink:href="./Object 1"/><draw:image xlink:href="./ObjectReplacements/Object 1"/></draw:frame>
+  <draw:frame><draw:text-box><text:list text:style-name="L2"><text:list-item><text:p>Step</text:p></text:list-item></text:list></draw:text-box></draw:frame>
+</draw:page>
+</office:presentation></office:body></office:document-content>"#;
+        zip_bytes(&[
+            ("mimetype", b"application/vnd.oasis.opendocument.presentation".as_slice()),
+            ("content.xml", content.as_bytes()),
+            ("Pictures/pic.png", b"png-bytes".as_slice()),
+        ])
+    }
+
+    /// A .pptx import keeps titles, nesting, tables, notes and images, and
+    /// reports the chart and the missing alt text.
+    #[test]
+    fn import_pptx_converts_slides() {
+        let deck = import::import_deck(&sample_pptx()).unwrap();
+        assert_eq!(
+            deck.markdown("/p/"),
+            "## Intro \\& Goals\n\n- Point one\n  - Sub point\n1. First step\n\n![A red square](/p/image1.png)\n\n???\nMention the budget\n\
+             \n## Slide 2\n\n| Name | Value \\| more |\n| --- | --- |\n| a | 1 |\n\n![](/p/image1.png)\n"
+        );
+        assert_eq!(deck.images, vec![("image1.png".to_string(), b"png-bytes".to_vec())]);
+        assert_eq!(deck.alt_text("image1.png"), "A red square");
+        assert_eq!(
+            deck.warnings,
+            vec![
+                "Slide 1: A chart could not be imported.".to_string(),
+                "Slide 2: The image image1.png has no alt text; add a description.".to_string(),
+            ]
+        );
     }
 
-    /// POST /user/change_pwd with wrong old password must redirect back with an error.
-    #[tokio::test]
-    async fn change_pwd_rejects_wrong_old_password() {
-        let (server, _state) = test_server().await;
-        login_as(&server, "admin", "admin").await;
+    /// An .odp import converts the same structures from OpenDocument markup.
+    #[test]
+    fn import_odp_converts_slides() {
+        let deck = import::import_deck(&sample_odp()).unwrap();
+        assert_eq!(
+            deck.markdown("/p/"),
+            "## Welcome\n\n- Alpha\n  - Beta version\n\n![Logo](/p/pic.png)\n\n???\nSay hi\n\
+             \n## Data\n\n| H1 | H2 |\n| --- | --- |\n| x |  |\n\n1. Step\n"
+        );
+        assert_eq!(deck.images.len(), 1);
+        assert_eq!(deck.warnings, vec!["Slide 2: A chart or embedded object could not be imported.".to_string()]);
+    }
 
-        let resp = server
-            .post("/user/change_pwd")
-            .form(&serde_json::json!({
-                "old": "wrongpassword",
-                "new": "newpass123",
-                "confirm": "newpass123"
-            }))
-            .await;
-        assert!(resp.status_code().is_redirection());
-        let loc = resp.headers().get("location").unwrap().to_str().unwrap();
-        assert!(loc.contains("error="), "expected error query param, got {loc}");
+    /// Deeply nested XML is refused as damaged instead of overflowing the stack.
+    #[test]
+    fn import_rejects_deeply_nested_xml() {
+        let depth = 200_000;
+        let content = format!(
+            "<office:document-content>{}{}</office:document-content>",
+            "<text:span>".repeat(depth),
+            "</text:span>".repeat(depth)
+        );
+        let bytes = zip_bytes(&[
+            ("mimetype", b"application/vnd.oasis.opendocument.presentation".as_slice()),
+            ("content.xml", content.as_bytes()),
+        ]);
+        assert!(matches!(import::import_deck(&bytes), Err(import::ImportError::Damaged(_))));
     }
 
-    /// POST /user/change_pwd with mismatched new/confirm must redirect with an error.
-    #[tokio::test]
-    async fn change_pwd_rejects_mismatched_confirm() {
-        let (server, _state) = test_server().await;
-        login_as(&server, "admin", "admin").await;
+    /// Imported speaker notes stay presenter-only.
+    #[test]
+    fn import_notes_are_not_rendered_for_audience() {
+        let deck = import::import_deck(&sample_pptx()).unwrap();
+        let slides = render_all_slides(&deck.markdown("/p/"));
+        assert_eq!(slides.len(), 2);
+        assert!(!slides.iter().any(|(_, html)| html.contains("budget")), "notes leaked: {slides:?}");
+    }
 
-        let resp = server
-            .post("/user/change_pwd")
-            .form(&serde_json::json!({
-                "old": "admin",
-                "new": "newpass123",
-                "confirm": "differentpass"
-            }))
-            .await;
-        assert!(resp.status_code().is_redirection());
-        let loc = resp.headers().get("location").unwrap().to_str().unwrap();
-        assert!(loc.contains("error="), "expected error query param, got {loc}");
+    /// Files that are not decks are rejected without creating anything.
+    #[test]
+    fn import_rejects_unknown_files() {
+        assert!(matches!(import::import_deck(b"not a zip"), Err(import::ImportError::UnknownFormat)));
+        let other = zip_bytes(&[("word/document.xml", b"<w:document/>".as_slice())]);
+        assert!(matches!(import::import_deck(&other), Err(import::ImportError::UnknownFormat)));
     }
 
-    /// POST /user/recordings/{rid}/delete by owner must remove the row and redirect.
+    /// POST /create/import creates the presentation, writes its images and lists
+    /// what could not be converted.
     #[tokio::test]
-    async fn delete_recording_as_owner_removes_row() {
+    async fn import_presentation_creates_deck_and_images() {
         let (server, state) = test_server().await;
+        login_as(&server, "admin", "admin").await;
+        let form = axum_test::multipart::MultipartForm::new().add_part(
+            "file",
+            axum_test::multipart::Part::bytes(sample_odp())
+                .file_name("Quarterly review.odp")
+                .mime_type("application/vnd.oasis.opendocument.presentation"),
+        );
+        let resp = server.post("/create/import").multipart(form).await;
+
         let uid = get_user_id("admin", &state.db_pool).await;
-        let pid = seed_presentation(uid, "Rec Delete Test", &state.db_pool).await;
-        let rid: i64 = sqlx::query_scalar(
-            "INSERT INTO recording (presentation_id, name, captions_path) VALUES (?, 'Rec', 'r.vtt') RETURNING id"
+        let pres = sqlx::query_as::<_, (i64, String, String)>(
+            "SELECT id, name, content FROM presentation WHERE user_id = ? ORDER BY id DESC LIMIT 1",
         )
-        .bind(pid)
+        .bind(uid)
         .fetch_one(&state.db_pool)
         .await
         .unwrap();
+        let asset_dir = format!("{PRESENTATION_ASSET_DIR}/{}", pres.0);
+        let image = tokio::fs::read(format!("{asset_dir}/pic.png")).await;
+        let _ = tokio::fs::remove_dir_all(&asset_dir).await;
+        let assets = PresentationAsset::get_for_presentation(&state.db_pool, pres.0).await.unwrap();
 
+        assert_eq!(resp.status_code(), StatusCode::OK);
+        assert!(resp.text().contains("A chart or embedded object could not be imported."));
+        assert_eq!(pres.1, "Quarterly review");
+        assert!(pres.2.contains(&format!("![Logo](/assets/presentations/{}/pic.png)", pres.0)));
+        assert_eq!(image.unwrap(), b"png-bytes");
+        assert_eq!(
+            assets.iter().map(|a| (a.file_name.as_str(), a.alt_text.as_str())).collect::<Vec<_>>(),
+            vec![("pic.png", "Logo")]
+        );
+    }
+
+    /// An import that fails part way, here writing its image, leaves neither the
+    /// presentation nor any of its files behind.
+    #[tokio::test]
+    async fn import_presentation_cleans_up_after_a_failed_write() {
+        let (server, state) = test_server().await;
         login_as(&server, "admin", "admin").await;
-        let resp = server.post(&format!("/user/recordings/{rid}/delete")).await;
-        assert!(resp.status_code().is_redirection());
+        // The import gets the next id, kept clear of other tests' directories.
+        let uid = get_user_id("admin", &state.db_pool).await;
+        seed_presentation_with_id(9908, uid, &state.db_pool).await;
+        // A directory where the image should go makes writing it fail.
+        let asset_dir = format!("{PRESENTATION_ASSET_DIR}/9909");
+        tokio::fs::create_dir_all(format!("{asset_dir}/pic.png")).await.unwrap();
+        let form = axum_test::multipart::MultipartForm::new()
+            .add_part("file", axum_test::multipart::Part::bytes(sample_odp()).file_name("Doomed.odp"));
+        let resp = server.post("/create/import").multipart(form).await;
+        let left = tokio::fs::try_exists(&asset_dir).await.unwrap();
+        let _ = tokio::fs::remove_dir_all(&asset_dir).await;
 
-        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM recording WHERE id = ?")
-            .bind(rid)
+        assert_eq!(resp.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+        let count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM presentation WHERE name = 'Doomed'")
             .fetch_one(&state.db_pool)
             .await
             .unwrap();
-        assert_eq!(count, 0);
+        assert_eq!(count, 0, "the presentation row is removed");
+        assert!(!left, "the asset directory is removed");
     }
 
-    /// POST /user/recordings/{rid}/delete by non-owner must return 403.
+    /// A file that is not a deck re-renders the create page with an error.
     #[tokio::test]
-    async fn delete_recording_as_non_owner_returns_403() {
+    async fn import_presentation_rejects_other_files() {
         let (server, state) = test_server().await;
-        seed_user(&state.db_pool).await;
-        let owner_uid = get_user_id("admin", &state.db_pool).await;
-        let pid = seed_presentation(owner_uid, "Rec Delete Perm Test", &state.db_pool).await;
-        let rid: i64 = sqlx::query_scalar(
-            "INSERT INTO recording (presentation_id, name, captions_path) VALUES (?, 'Rec', 'r.vtt') RETURNING id"
-        )
-        .bind(pid)
-        .fetch_one(&state.db_pool)
-        .await
-        .unwrap();
+        login_as(&server, "admin", "admin").await;
+        let form = axum_test::multipart::MultipartForm::new().add_part(
+            "file",
+            axum_test::multipart::Part::bytes(b"hello".to_vec()).file_name("notes.txt"),
+        );
+        let resp = server.post("/create/import").multipart(form).await;
+        assert_eq!(resp.status_code(), StatusCode::BAD_REQUEST);
+        assert!(resp.text().contains("role=\"alert\""));
+        let count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM presentation WHERE name = 'notes'")
+            .fetch_one(&state.db_pool)
+            .await
+            .unwrap();
+        assert_eq!(count, 0);
+    }
 
-        login_as(&server, "testuser", "testpass").await;
-        let resp = server.post(&format!("/user/recordings/{rid}/delete")).await;
-        assert_eq!(resp.status_code(), 403);
+    /// Seeds a presentation with a fixed id, so tests running in parallel never
+    /// share a `presentation_assets/{pid}` directory.
+    async fn seed_presentation_with_id(pid: i64, user_id: i64, pool: &SqlitePool) {
+        sqlx::query("INSERT INTO presentation (id, name, user_id, content) VALUES (?, 'Assets', ?, '')")
+            .bind(pid)
+            .bind(user_id)
+            .execute(pool)
+            .await
+            .unwrap();
     }
 
-    /// POST /user/recordings/{rid}/name by owner must update the name.
+    fn asset_form(file_name: &str, alt: &str) -> axum_test::multipart::MultipartForm {
+        axum_test::multipart::MultipartForm::new()
+            .add_text("alt", alt.to_string())
+            .add_part(
+                "file",
+                axum_test::multipart::Part::bytes(b"png-bytes".to_vec()).file_name(file_name.to_string()),
+            )
+    }
+
+    /// An upload with alt text is stored, listed on the edit page with its Markdown,
+    /// and served back; a second file with the same name does not overwrite it.
     #[tokio::test]
-    async fn update_recording_name_as_owner() {
+    async fn add_asset_stores_lists_and_serves_upload() {
         let (server, state) = test_server().await;
         let uid = get_user_id("admin", &state.db_pool).await;
-        let pid = seed_presentation(uid, "Rename Rec Test", &state.db_pool).await;
-        let rid: i64 = sqlx::query_scalar(
-            "INSERT INTO recording (presentation_id, name, captions_path) VALUES (?, 'OldName', 'r.vtt') RETURNING id"
-        )
-        .bind(pid)
-        .fetch_one(&state.db_pool)
-        .await
-        .unwrap();
+        let pid = 9801;
+        seed_presentation_with_id(pid, uid, &state.db_pool).await;
+        login_as(&server, "admin", "admin").await;
 
+        let first = server
+            .post(&format!("/user/presentations/{pid}/assets"))
+            .multipart(asset_form("my chart.png", "Sales [2026]"))
+            .await;
+        let second = server
+            .post(&format!("/user/presentations/{pid}/assets"))
+            .multipart(asset_form("my chart.png", "Sales again"))
+            .await;
+        // Tera escapes `/` in text as `&#x2F;`.
+        let edit = server.get(&format!("/admin/{pid}/edit")).await.text().replace("&#x2F;", "/");
+        let served = server.get(&format!("/assets/presentations/{pid}/my_chart.png")).await;
+        let stored = std::fs::read_dir(format!("{PRESENTATION_ASSET_DIR}/{pid}"))
+            .map(|dir| dir.count())
+            .unwrap_or(0);
+        let _ = tokio::fs::remove_dir_all(format!("{PRESENTATION_ASSET_DIR}/{pid}")).await;
+
+        assert_eq!(first.status_code(), StatusCode::SEE_OTHER);
+        assert_eq!(second.status_code(), StatusCode::SEE_OTHER);
+        assert_eq!(stored, 2, "the second upload must not overwrite the first");
+        assert!(edit.contains(&format!("![Sales \\[2026\\]](/assets/presentations/{pid}/my_chart.png)")), "{edit}");
+        assert!(edit.contains(&format!("/assets/presentations/{pid}/2-my_chart.png")));
+        assert_eq!(served.status_code(), StatusCode::OK);
+        assert_eq!(served.header("content-type"), "image/png");
+        assert_eq!(served.as_bytes().as_ref(), b"png-bytes");
+    }
+
+    /// Uploads without alt text, or of types slides cannot show, are refused.
+    #[tokio::test]
+    async fn add_asset_requires_alt_text_and_known_type() {
+        let (server, state) = test_server().await;
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = 9802;
+        seed_presentation_with_id(pid, uid, &state.db_pool).await;
         login_as(&server, "admin", "admin").await;
-        let resp = server
-            .post(&format!("/user/recordings/{rid}/name"))
-            .text("NewName")
+
+        let no_alt = server
+            .post(&format!("/user/presentations/{pid}/assets"))
+            .multipart(asset_form("a.png", "   "))
             .await;
-        assert_eq!(resp.status_code(), 200);
+        let bad_type = server
+            .post(&format!("/user/presentations/{pid}/assets"))
+            .multipart(asset_form("a.html", "A page"))
+            .await;
+        let dir_exists = std::path::Path::new(&format!("{PRESENTATION_ASSET_DIR}/{pid}")).exists();
+        let _ = tokio::fs::remove_dir_all(format!("{PRESENTATION_ASSET_DIR}/{pid}")).await;
 
-        let name: String = sqlx::query_scalar("SELECT name FROM recording WHERE id = ?")
-            .bind(rid)
+        assert_eq!(no_alt.status_code(), StatusCode::BAD_REQUEST);
+        assert_eq!(bad_type.status_code(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert!(!dir_exists, "refused uploads must not be written");
+        let rows = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM presentation_asset")
             .fetch_one(&state.db_pool)
             .await
             .unwrap();
-        assert_eq!(name, "NewName");
+        assert_eq!(rows, 0);
     }
 
-    /// POST /user/recordings/{rid}/name by non-owner must return 403.
+    /// Only owners and editors may upload.
     #[tokio::test]
-    async fn update_recording_name_as_non_owner_returns_403() {
+    async fn add_asset_by_non_editor_returns_403() {
         let (server, state) = test_server().await;
         seed_user(&state.db_pool).await;
-        let owner_uid = get_user_id("admin", &state.db_pool).await;
-        let pid = seed_presentation(owner_uid, "Rename Rec Perm", &state.db_pool).await;
-        let rid: i64 = sqlx::query_scalar(
-            "INSERT INTO recording (presentation_id, name, captions_path) VALUES (?, 'OldName', 'r.vtt') RETURNING id"
-        )
-        .bind(pid)
-        .fetch_one(&state.db_pool)
-        .await
-        .unwrap();
-
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = 9803;
+        seed_presentation_with_id(pid, uid, &state.db_pool).await;
         login_as(&server, "testuser", "testpass").await;
-        let resp = server
-            .post(&format!("/user/recordings/{rid}/name"))
-            .text("Hack")
-            .await;
-        assert_eq!(resp.status_code(), 403);
-    }
 
-    /// POST /user/recordings/{rid}/files without authentication must return 401.
-    #[tokio::test]
-    async fn update_recording_files_requires_auth() {
-        let (server, _state) = test_server().await;
-        let form = axum_test::multipart::MultipartForm::new();
         let resp = server
-            .post("/user/recordings/1/files")
-            .multipart(form)
+            .post(&format!("/user/presentations/{pid}/assets"))
+            .multipart(asset_form("a.png", "A picture"))
             .await;
-        assert_eq!(resp.status_code(), axum::http::StatusCode::UNAUTHORIZED);
+        assert_eq!(resp.status_code(), StatusCode::FORBIDDEN);
     }
 
-    /// POST /user/recordings/{rid}/files with an ID that does not belong to the
-    /// authenticated user must return 404.
+    /// Uploads of a private presentation are hidden from visitors, including
+    /// through a percent-encoded path, which is one segment and so reaches the
+    /// `/{uname}/{pid}` route rather than any file.
     #[tokio::test]
-    async fn update_recording_files_rejects_unowned_recording() {
+    async fn presentation_asset_checks_access() {
         let (server, state) = test_server().await;
-        seed_user(&state.db_pool).await;
-        // The recording is owned by admin; testuser tries to upload.
         let uid = get_user_id("admin", &state.db_pool).await;
-        let pid = seed_presentation(uid, "Files Perm Test", &state.db_pool).await;
-        let rid: i64 = sqlx::query_scalar(
-            "INSERT INTO recording (presentation_id, name, captions_path) VALUES (?, 'Rec', 'r.vtt') RETURNING id",
-        )
-        .bind(pid)
-        .fetch_one(&state.db_pool)
-        .await
-        .unwrap();
+        let pid = 9804;
+        seed_presentation_with_id(pid, uid, &state.db_pool).await;
+        DbPresentation::set_access_mode(pid, "private", &state.db_pool).await.unwrap();
+        let dir = format!("{PRESENTATION_ASSET_DIR}/{pid}");
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        tokio::fs::write(format!("{dir}/a.png"), b"png-bytes").await.unwrap();
+
+        let direct = server.get(&format!("/assets/presentations/{pid}/a.png")).await;
+        let encoded = server.get(&format!("/assets/presentations%2F{pid}%2Fa.png")).await;
+        login_as(&server, "admin", "admin").await;
+        let owner = server.get(&format!("/assets/presentations/{pid}/a.png")).await;
+        let _ = tokio::fs::remove_dir_all(&dir).await;
 
-        login_as(&server, "testuser", "testpass").await;
-        let form = axum_test::multipart::MultipartForm::new();
-        let resp = server
-            .post(&format!("/user/recordings/{rid}/files"))
-            .multipart(form)
-            .await;
-        assert_eq!(resp.status_code(), axum::http::StatusCode::NOT_FOUND);
+        assert_eq!(direct.status_code(), StatusCode::FORBIDDEN);
+        assert_eq!(encoded.status_code(), StatusCode::BAD_REQUEST);
+        assert_eq!(owner.status_code(), StatusCode::OK);
     }
 
-    /// POST /user/recordings/{rid}/files by the owner with a video part must
-    /// write the file to disk and update the `video_path` column.
-    ///
-    /// The handler writes to the relative path `assets/{rid}/`, so the test
-    /// pre-creates that directory and removes it on exit to avoid polluting the
-    /// working tree between runs.
+    /// A presentation path whose id is not a number is rejected before any
+    /// lookup, whatever the first segment is.
     #[tokio::test]
-    async fn update_recording_files_owner_uploads_video() {
-        let (server, state) = test_server().await;
-        let uid = get_user_id("admin", &state.db_pool).await;
-        let pid = seed_presentation(uid, "Files Upload Test", &state.db_pool).await;
-        let rid: i64 = sqlx::query_scalar(
-            "INSERT INTO recording (presentation_id, name, captions_path) VALUES (?, 'Rec', 'r.vtt') RETURNING id",
+    async fn presentation_path_rejects_non_numeric_id() {
+        let (server, _state) = test_server().await;
+        let named = server.get("/admin/not-a-number").await;
+        let assets = server.get("/assets/presentations%2F1%2Fa.png").await;
+        assert_eq!(named.status_code(), StatusCode::BAD_REQUEST);
+        assert_eq!(assets.status_code(), StatusCode::BAD_REQUEST);
+    }
+
+    /// `Range` headers map to inclusive byte spans; anything unusable falls back
+    /// to the whole file.
+    #[test]
+    fn byte_range_parses_single_ranges() {
+        assert_eq!(byte_range(None, 10), ByteRange::Full);
+        assert_eq!(byte_range(Some("bytes=2-4"), 10), ByteRange::Partial { start: 2, end: 4 });
+        assert_eq!(byte_range(Some("bytes=5-"), 10), ByteRange::Partial { start: 5, end: 9 });
+        assert_eq!(byte_range(Some("bytes=8-100"), 10), ByteRange::Partial { start: 8, end: 9 });
+        assert_eq!(byte_range(Some("bytes=-3"), 10), ByteRange::Partial { start: 7, end: 9 });
+        assert_eq!(byte_range(Some("bytes=-30"), 10), ByteRange::Partial { start: 0, end: 9 });
+        assert_eq!(byte_range(Some("bytes=10-"), 10), ByteRange::Unsatisfiable);
+        assert_eq!(byte_range(Some("bytes=-0"), 10), ByteRange::Unsatisfiable);
+        assert_eq!(byte_range(Some("bytes=0-1,4-5"), 10), ByteRange::Full);
+        assert_eq!(byte_range(Some("bytes=4-2"), 10), ByteRange::Full);
+        assert_eq!(byte_range(Some("items=0-1"), 10), ByteRange::Full);
+    }
+
+    /// Seeds a recording with a fixed id and files in `assets/{rid}/`, so tests
+    /// running in parallel never share the directory.
+    async fn seed_recording_files(rid: i64, pid: i64, pool: &SqlitePool) -> String {
+        sqlx::query(
+            "INSERT INTO recording (id, presentation_id, name, video_path, captions_path) \
+             VALUES (?, ?, 'Media', 'video.mp4', 'captions.vtt')",
         )
+        .bind(rid)
         .bind(pid)
-        .fetch_one(&state.db_pool)
+        .execute(pool)
         .await
         .unwrap();
-
-        // The handler writes to assets/{rid}/ using a path relative to the
-        // process CWD. Pre-create the directory so the write succeeds.
-        let asset_dir = format!("assets/{rid}");
-        tokio::fs::create_dir_all(&asset_dir).await.unwrap();
-
-        login_as(&server, "admin", "admin").await;
-        let form = axum_test::multipart::MultipartForm::new().add_part(
-            "video",
-            axum_test::multipart::Part::bytes(b"fake-video-data".to_vec())
-                .file_name("video.mp4")
-                .mime_type("video/mp4"),
-        );
-        let resp = server
-            .post(&format!("/user/recordings/{rid}/files"))
-            .multipart(form)
-            .await;
-
-        // Cleanup before asserting so the directory is removed even on failure.
-        let _ = tokio::fs::remove_dir_all(&asset_dir).await;
-
-        assert_eq!(resp.status_code(), axum::http::StatusCode::OK);
-
-        let video_path: Option<String> =
-            sqlx::query_scalar("SELECT video_path FROM recording WHERE id = ?")
-                .bind(rid)
-                .fetch_one(&state.db_pool)
-                .await
-                .unwrap();
-        assert_eq!(
-            video_path.as_deref(),
-            Some("video.mp4"),
-            "video_path must be updated in the database after upload"
-        );
+        let dir = format!("assets/{rid}");
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        tokio::fs::write(format!("{dir}/video.mp4"), b"0123456789").await.unwrap();
+        tokio::fs::write(format!("{dir}/captions.vtt"), b"WEBVTT\n").await.unwrap();
+        tokio::fs::write(format!("{dir}/other.txt"), b"secret").await.unwrap();
+        dir
     }
 
-    /// GET /{uname}/{pid}/{rid}/slides.vtt must return VTT content for accessible recordings.
+    /// Recording media is served through the recording's access check, with
+    /// Range support for seeking, and no longer through the open `/assets` mount.
     #[tokio::test]
-    async fn slides_vtt_returns_content_for_public_recording() {
+    async fn recording_file_serves_media_with_ranges() {
         let (server, state) = test_server().await;
         let uid = get_user_id("admin", &state.db_pool).await;
-        let pid = seed_presentation(uid, "VTT Test", &state.db_pool).await;
-        let rid: i64 = sqlx::query_scalar(
-            "INSERT INTO recording (presentation_id, name, captions_path) VALUES (?, 'Rec', 'r.vtt') RETURNING id"
-        )
-        .bind(pid)
-        .fetch_one(&state.db_pool)
-        .await
-        .unwrap();
-        sqlx::query(
-            "INSERT INTO recording_slide (recording_id, position, title, content, start_seconds) VALUES (?, 0, 'Intro', '<h2>Intro</h2>', 0.0)"
-        )
-        .bind(rid)
-        .execute(&state.db_pool)
-        .await
-        .unwrap();
+        let pid = 9901;
+        seed_presentation_with_id(pid, uid, &state.db_pool).await;
+        let rid = 9901;
+        let dir = seed_recording_files(rid, pid, &state.db_pool).await;
+        let base = format!("/admin/{pid}/{rid}/files");
+
+        let full = server.get(&format!("{base}/video.mp4")).await;
+        let part = server
+            .get(&format!("{base}/video.mp4"))
+            .add_header(axum::http::header::RANGE, axum::http::HeaderValue::from_static("bytes=2-5"))
+            .await;
+        let past_end = server
+            .get(&format!("{base}/video.mp4"))
+            .add_header(axum::http::header::RANGE, axum::http::HeaderValue::from_static("bytes=50-"))
+            .await;
+        let captions = server.get(&format!("{base}/captions.vtt")).await;
+        let other = server.get(&format!("{base}/other.txt")).await;
+        let open_mount = server.get(&format!("/assets/{rid}/video.mp4")).await;
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+
+        assert_eq!(full.status_code(), StatusCode::OK);
+        assert_eq!(full.header("accept-ranges"), "bytes");
+        assert_eq!(full.as_bytes().as_ref(), b"0123456789");
+        assert_eq!(part.status_code(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(part.header("content-range"), "bytes 2-5/10");
+        assert_eq!(part.as_bytes().as_ref(), b"2345");
+        assert_eq!(past_end.status_code(), StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(past_end.header("content-range"), "bytes */10");
+        assert_eq!(captions.status_code(), StatusCode::OK);
+        assert_eq!(captions.header("content-type"), "text/vtt; charset=utf-8");
+        assert_eq!(other.status_code(), StatusCode::NOT_FOUND);
+        assert!(open_mount.status_code().is_client_error());
+        assert_ne!(open_mount.as_bytes().as_ref(), b"0123456789");
+    }
+
+    /// A private recording's media is refused to visitors, like its page.
+    #[tokio::test]
+    async fn recording_file_denied_for_private_recording() {
+        let (server, state) = test_server().await;
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = 9902;
+        seed_presentation_with_id(pid, uid, &state.db_pool).await;
+        let rid = 9902;
+        let dir = seed_recording_files(rid, pid, &state.db_pool).await;
+        db::Recording::set_access_mode(rid, Some("private"), &state.db_pool).await.unwrap();
+
+        let anonymous = server.get(&format!("/admin/{pid}/{rid}/files/video.mp4")).await;
+        login_as(&server, "admin", "admin").await;
+        let owner = server.get(&format!("/admin/{pid}/{rid}/files/video.mp4")).await;
+        let _ = tokio::fs::remove_dir_all(&dir).await;
 
-        let resp = server.get(&format!("/admin/{pid}/{rid}/slides.vtt")).await;
-        assert_eq!(resp.status_code(), 200);
-        let body = resp.text();
-        assert!(body.starts_with("WEBVTT"), "response must be VTT");
-        assert!(body.contains("Intro"), "VTT must include slide title");
+        assert_eq!(anonymous.status_code(), StatusCode::FORBIDDEN);
+        assert_eq!(owner.status_code(), StatusCode::OK);
     }
 
-    /// GET /{uname}/{pid}/{rid}/slides.html must return HTML for accessible recordings.
+    /// A new recording's files are named after their sniffed content, not the
+    /// client's file name, and land in `assets/{rid}/`.
     #[tokio::test]
-    async fn slides_html_returns_content_for_public_recording() {
+    async fn add_recording_streams_sniffed_files() {
         let (server, state) = test_server().await;
         let uid = get_user_id("admin", &state.db_pool).await;
-        let pid = seed_presentation(uid, "HTML Test", &state.db_pool).await;
-        let rid: i64 = sqlx::query_scalar(
-            "INSERT INTO recording (presentation_id, name, captions_path) VALUES (?, 'Rec', 'r.vtt') RETURNING id"
-        )
-        .bind(pid)
-        .fetch_one(&state.db_pool)
-        .await
-        .unwrap();
-        sqlx::query(
-            "INSERT INTO recording_slide (recording_id, position, title, content, start_seconds) VALUES (?, 0, 'Intro', '<h2>Intro</h2>', 0.0)"
-        )
-        .bind(rid)
-        .execute(&state.db_pool)
-        .await
-        .unwrap();
+        let pid = 9903;
+        seed_presentation_with_id(pid, uid, &state.db_pool).await;
+        login_as(&server, "admin", "admin").await;
 
-        let resp = server.get(&format!("/admin/{pid}/{rid}/slides.html")).await;
-        assert_eq!(resp.status_code(), 200);
-        let body = resp.text();
-        assert!(body.contains("<!DOCTYPE html>"), "response must be HTML");
-        assert!(body.contains("<section>"), "response must contain slide sections");
+        let form = axum_test::multipart::MultipartForm::new()
+            .add_text("name", "Lecture")
+            .add_text("slides", "[]")
+            .add_part(
+                "video",
+                axum_test::multipart::Part::bytes(b"\x1A\x45\xDF\xA3\x9F\x42\x82\x84webm-data".to_vec())
+                    .file_name("lecture.mp4"),
+            )
+            .add_part(
+                "captions",
+                axum_test::multipart::Part::bytes(b"WEBVTT\n\n00:00.000 --> 00:01.000\nHi\n".to_vec())
+                    .file_name("lecture.txt"),
+            );
+        let resp = server.post(&format!("/user/presentations/{pid}/recordings")).multipart(form).await;
+        let rec: Option<Recording> =
+            sqlx::query_as("SELECT * FROM recording WHERE presentation_id = ?")
+                .bind(pid)
+                .fetch_optional(&state.db_pool)
+                .await
+                .unwrap();
+        let files = match &rec {
+            Some(rec) => {
+                let dir = format!("assets/{}", rec.id);
+                let video = tokio::fs::read(format!("{dir}/video.webm")).await.ok();
+                let captions = tokio::fs::read_to_string(format!("{dir}/captions.vtt")).await.ok();
+                let _ = tokio::fs::remove_dir_all(&dir).await;
+                Some((video, captions))
+            }
+            None => None,
+        };
+
+        assert_eq!(resp.status_code(), StatusCode::SEE_OTHER);
+        assert_eq!(rec.unwrap().video_path.as_deref(), Some("video.webm"));
+        let (video, captions) = files.unwrap();
+        assert!(video.unwrap().ends_with(b"webm-data"));
+        assert!(captions.unwrap().contains("Hi"));
     }
 
-    // --- QR code route tests ---
+    /// Files that are not a video container or WebVTT are refused, and no
+    /// recording row is left behind.
+    #[tokio::test]
+    async fn add_recording_rejects_unrecognised_files() {
+        let (server, state) = test_server().await;
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = 9904;
+        seed_presentation_with_id(pid, uid, &state.db_pool).await;
+        login_as(&server, "admin", "admin").await;
 
-    /// GET /qr/{uname}/{pid} must return 200 with content-type image/svg+xml.
+        let video = axum_test::multipart::MultipartForm::new()
+            .add_text("name", "Lecture")
+            .add_text("slides", "[]")
+            .add_part(
+                "video",
+                axum_test::multipart::Part::bytes(b"<html><script>alert(1)</script>".to_vec())
+                    .file_name("video.mp4")
+                    .mime_type("video/mp4"),
+            );
+        let video_resp = server.post(&format!("/user/presentations/{pid}/recordings")).multipart(video).await;
+        let captions = axum_test::multipart::MultipartForm::new()
+            .add_text("name", "Lecture")
+            .add_text("slides", "[]")
+            .add_part(
+                "captions",
+                axum_test::multipart::Part::bytes(b"\0\0\0\x18ftypisom".to_vec()).file_name("captions.vtt"),
+            );
+        let captions_resp =
+            server.post(&format!("/user/presentations/{pid}/recordings")).multipart(captions).await;
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM recording WHERE presentation_id = ?")
+            .bind(pid)
+            .fetch_one(&state.db_pool)
+            .await
+            .unwrap();
+
+        assert_eq!(video_resp.status_code(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert_eq!(captions_resp.status_code(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert_eq!(count, 0);
+    }
+
+    /// Uploads over the configured limit are refused and leave nothing behind.
     #[tokio::test]
-    async fn qr_code_returns_correct_content_type() {
-        let (server, _state) = test_server().await;
+    async fn update_recording_files_enforces_upload_limit() {
+        let (server, state) = test_server_with_limit(UploadLimit(1024)).await;
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = 9905;
+        seed_presentation_with_id(pid, uid, &state.db_pool).await;
+        let rid = 9905;
+        let dir = seed_recording_files(rid, pid, &state.db_pool).await;
+        login_as(&server, "admin", "admin").await;
 
-        let response = server.get("/qr/testuser/42").await;
+        let mut big = b"\0\0\0\x18ftypisom".to_vec();
+        big.resize(4096, 0);
+        let form = axum_test::multipart::MultipartForm::new()
+            .add_part("video", axum_test::multipart::Part::bytes(big).file_name("video.mp4"));
+        let resp = server.post(&format!("/user/recordings/{rid}/files")).multipart(form).await;
+        let video = tokio::fs::read(format!("{dir}/video.mp4")).await.unwrap();
+        let _ = tokio::fs::remove_dir_all(&dir).await;
 
-        assert_eq!(response.status_code(), 200);
-        let ct = response
-            .headers()
-            .get("content-type")
-            .expect("content-type header must be present")
-            .to_str()
-            .unwrap();
-        assert!(
-            ct.starts_with("image/svg+xml"),
-            "content-type must be image/svg+xml, got: {ct}"
-        );
+        assert_eq!(resp.status_code(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(video, b"0123456789", "the existing video must be untouched");
     }
 
-    /// GET /qr/{uname}/{pid} body must be a well-formed SVG document.
+    /// Assets are read into memory whole, so a higher recording limit leaves theirs fixed.
     #[tokio::test]
-    async fn qr_code_returns_svg_body() {
-        let (server, _state) = test_server().await;
+    async fn add_asset_keeps_its_limit_when_recordings_allow_more() {
+        let (server, state) = test_server_with_limit(UploadLimit(200 * 1024 * 1024)).await;
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = 9907;
+        seed_presentation_with_id(pid, uid, &state.db_pool).await;
+        login_as(&server, "admin", "admin").await;
 
-        let response = server.get("/qr/testuser/42").await;
-        let body = response.text();
+        let form = axum_test::multipart::MultipartForm::new()
+            .add_text("alt", "Huge")
+            .add_part("file", axum_test::multipart::Part::bytes(vec![0; IN_MEMORY_UPLOAD_LIMIT + 1]).file_name("huge.png"));
+        let resp = server.post(&format!("/user/presentations/{pid}/assets")).multipart(form).await;
+        let stored = std::fs::read_dir(format!("{PRESENTATION_ASSET_DIR}/{pid}")).map(|dir| dir.count()).unwrap_or(0);
+        let _ = tokio::fs::remove_dir_all(format!("{PRESENTATION_ASSET_DIR}/{pid}")).await;
 
-        assert!(body.contains("<svg"), "body must contain <svg element, got: {}", &body[..body.len().min(80)]);
-        assert!(body.contains("</svg>"), "body must contain closing </svg>");
+        assert_eq!(resp.status_code(), StatusCode::BAD_REQUEST);
+        assert_eq!(stored, 0);
     }
 
-    /// GET /qr/{uname}/{pid} must succeed even when the username and presentation
-    /// ID do not correspond to any real database rows — the handler generates the
-    /// QR code from the path params without a DB lookup.
+    /// Replacing a video with one in another container updates the path and
+    /// removes the old file.
     #[tokio::test]
-    async fn qr_code_succeeds_for_nonexistent_presentation() {
-        let (server, _state) = test_server().await;
+    async fn update_recording_files_replaces_video_container() {
+        let (server, state) = test_server().await;
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = 9906;
+        seed_presentation_with_id(pid, uid, &state.db_pool).await;
+        let rid = 9906;
+        let dir = seed_recording_files(rid, pid, &state.db_pool).await;
+        login_as(&server, "admin", "admin").await;
 
-        let response = server.get("/qr/nobody/99999").await;
+        let form = axum_test::multipart::MultipartForm::new()
+            .add_part("video", axum_test::multipart::Part::bytes(b"OggS\0\x02theora".to_vec()).file_name("v.mp4"));
+        let resp = server.post(&format!("/user/recordings/{rid}/files")).multipart(form).await;
+        let old_exists = tokio::fs::try_exists(format!("{dir}/video.mp4")).await.unwrap();
+        let new_exists = tokio::fs::try_exists(format!("{dir}/video.ogv")).await.unwrap();
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+        let rec = Recording::get_by_id(rid, &state.db_pool).await.unwrap().unwrap();
 
-        assert_eq!(
-            response.status_code(),
-            200,
-            "QR route must succeed even for unknown username/pid"
-        );
-        let ct = response
-            .headers()
-            .get("content-type")
-            .unwrap()
-            .to_str()
-            .unwrap();
-        assert!(ct.starts_with("image/svg+xml"));
+        assert_eq!(resp.status_code(), StatusCode::OK);
+        assert_eq!(rec.video_path.as_deref(), Some("video.ogv"));
+        assert!(new_exists);
+        assert!(!old_exists);
     }
 
-    // --- Demo redirect tests ---
+    /// Language tags must be shaped like BCP 47 tags, and a slide rendered in a language
+    /// must say so.
+    #[test]
+    fn language_tags_are_checked_and_marked_on_slides() {
+        for tag in ["en", "fr-CA", "zh-Hant-TW", "gsw", "es-419"] {
+            assert_eq!(language_tag(tag), Some(tag));
+        }
+        for tag in ["", "e", "english", "fr_CA", "fr-", "en-\"x\"", "<b>", &format!("en-{}", "a".repeat(40))] {
+            assert_eq!(language_tag(tag), None, "{tag}");
+        }
+        let md = "## Bonjour\nTout le monde";
+        assert_eq!(render_slide(md, 0, 0, "", Some("fr")), format!("<div lang=\"fr\">{}</div>", render_slide(md, 0, 0, "", None)));
+    }
 
-    /// GET /demo when the admin user has at least one presentation must redirect
-    /// to /{admin.name}/{pres.id}.  Migrations seed admin (id=1) and the Demo
-    /// presentation (id=1), so the Location header must be /admin/1.
+    /// Translations saved over HTTP must be checked against the deck, reach live viewers
+    /// without speaker notes, and be shown to viewers who ask for them.
     #[tokio::test]
-    async fn demo_with_admin_presentation_redirects_to_presentation() {
-        let (server, _state) = test_server().await;
-        // Migrations seed admin user + Demo presentation; no extra seeding needed.
+    async fn translations_are_checked_and_sent_to_viewers() {
+        let (server, mut state) = test_server().await;
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(uid, "Bilingual", &state.db_pool).await;
+        DbPresentation::update_content(pid, "## Hello\nWelcome\n\n## Bye\nThanks".to_string(), &state.db_pool).await.unwrap();
+        let url = format!("/user/presentations/{pid}/variants");
+        let variant = |lang: &str, content: &str| serde_json::json!({ "lang": lang, "content": content });
+        assert_eq!(server.post(&url).form(&variant("fr", "")).await.status_code(), 401);
+        login_as(&server, "admin", "admin").await;
+        let pres = add_client_handler_channel(pid.to_string(), &mut state).await.unwrap();
+        let mut rx = pres.lock().unwrap().channel.0.subscribe();
+
+        let lang = server.post(&format!("/user/presentations/{pid}/language")).form(&serde_json::json!({ "lang": "en" })).await;
+        assert_eq!(lang.status_code(), StatusCode::SEE_OTHER);
+        assert!(matches!(rx.try_recv(), Ok(SlideMessage::Language(Some(l))) if l == "en"));
+        assert_eq!(server.post(&url).form(&variant("en", "")).await.status_code(), 400, "the deck is already in English");
+        assert_eq!(server.post(&url).form(&variant("French", "")).await.status_code(), 400);
+        let short = server.post(&url).form(&variant("fr", "## Bonjour")).await;
+        assert_eq!(short.status_code(), 400);
+        assert!(short.text().contains("1 slides but the presentation has 2"), "{}", short.text());
+
+        let french = "## Bonjour\nBienvenue\n\n???\nSecret\n\n## Au revoir\nMerci";
+        assert_eq!(server.post(&url).form(&variant("fr", french)).await.status_code(), StatusCode::SEE_OTHER);
+        assert!(matches!(rx.try_recv(), Ok(SlideMessage::Variant { lang, text: Some(t) }) if lang == "fr" && t == french));
+        let audience = Viewer { connection_id: 0, is_presenter: false, is_audience: true, is_editor: false };
+        let sent = state_messages(&pres.lock().unwrap(), false, true)
+            .into_iter()
+            .filter_map(|m| match audience.view(&m) {
+                Some(SlideMessage::Variant { text: Some(text), .. }) => Some(text),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(sent.len(), 1);
+        assert!(!sent[0].contains("Secret") && sent[0].contains("Merci"));
+
+        let edit = server.get(&format!("/admin/{pid}/edit")).await.text();
+        assert!(edit.contains("Bienvenue") && edit.contains("value=\"en\""));
+        server.get("/auth/logout").await;
+        let page = server.get(&format!("/admin/{pid}?lang=fr")).await.text();
+        assert!(page.contains("<div lang=\"fr\"><h1>Bilingual</h1><h2>Bonjour</h2>"), "{page}");
+        let page = server.get(&format!("/admin/{pid}?lang=de")).await.text();
+        assert!(page.contains("<div lang=\"en\"><h1>Bilingual</h1><h2>Hello</h2>"), "{page}");
+        login_as(&server, "admin", "admin").await;
 
-        let response = server.get("/demo").await;
+        let removed = server.post(&format!("/user/presentations/{pid}/variants/fr/delete")).await;
+        assert_eq!(removed.status_code(), StatusCode::SEE_OTHER);
+        assert!(matches!(rx.try_recv(), Ok(SlideMessage::Variant { lang, text: None }) if lang == "fr"));
+        assert!(PresentationVariant::get_for_presentation(&state.db_pool, pid).await.unwrap().is_empty());
+    }
 
-        assert_eq!(
-            response.status_code(),
-            303,
-            "demo must redirect (303) to the admin's first presentation"
-        );
-        let location = response
-            .headers()
-            .get("location")
-            .expect("Location header must be present")
-            .to_str()
-            .unwrap();
-        assert_eq!(
-            location, "/admin/1",
-            "demo must redirect to /admin/1 (seeded presentation)"
-        );
+    /// The transcript and downloads must follow `?lang=`, marking the language of the slides.
+    #[tokio::test]
+    async fn transcript_and_exports_follow_lang() {
+        let (server, state) = test_server().await;
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(uid, "Bilingual", &state.db_pool).await;
+        DbPresentation::update_content(pid, "## Hello\nWelcome".to_string(), &state.db_pool).await.unwrap();
+        PresentationVariant::save(&state.db_pool, pid, "fr", "## Bonjour\nBienvenue").await.unwrap();
+
+        let transcript = server.get(&format!("/admin/{pid}/transcript?lang=fr")).await.text();
+        assert!(transcript.contains("Bienvenue") && !transcript.contains("Welcome"));
+        assert!(transcript.contains("<div class=\"slide__content\" lang=\"fr\">"));
+        let transcript = server.get(&format!("/admin/{pid}/transcript")).await.text();
+        assert!(transcript.contains("Welcome") && !transcript.contains("lang=\"fr\""));
+        let export = server.get(&format!("/admin/{pid}/export.html?lang=fr")).await.text();
+        assert!(export.contains("Bienvenue") && export.contains("id=\"currentSlide\" lang=\"fr\""));
+
+        // Edits not yet written to the database must be in what is downloaded.
+        let mut state = state;
+        let pres = add_client_handler_channel(pid.to_string(), &mut state).await.unwrap();
+        {
+            let mut p = pres.lock().unwrap();
+            p.content = "## Hello\nStill typing".to_string();
+            p.variants.insert("fr".to_string(), "## Bonjour\nEn cours".to_string());
+        }
+        let export = server.get(&format!("/admin/{pid}/export.html")).await.text();
+        assert!(export.contains("Still typing") && !export.contains("Welcome"));
+        let export = server.get(&format!("/admin/{pid}/export.html?lang=fr")).await.text();
+        assert!(export.contains("En cours") && !export.contains("Bienvenue"));
+        let transcript = server.get(&format!("/admin/{pid}/transcript")).await.text();
+        assert!(transcript.contains("Still typing"));
     }
 
-    /// GET /demo when the admin user has no presentations must redirect to /.
+    /// A stopped recording must save its slides in every translation, served with `?lang=`.
     #[tokio::test]
-    async fn demo_without_admin_presentations_redirects_to_home() {
-        let (_server, state) = test_server().await;
-        // Delete the seeded Demo presentation so the admin has none.
-        // Must delete recording rows first — recording has no ON DELETE CASCADE FK.
-        sqlx::query("DELETE FROM recording WHERE presentation_id IN (SELECT id FROM presentation WHERE user_id = (SELECT id FROM users WHERE name = 'admin'))")
-            .execute(&state.db_pool)
-            .await
-            .unwrap();
-        sqlx::query("DELETE FROM presentation WHERE user_id = (SELECT id FROM users WHERE name = 'admin')")
+    async fn recordings_keep_each_translation() {
+        let (server, mut state) = test_server().await;
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let pid = seed_presentation(uid, "Bilingual", &state.db_pool).await;
+        DbPresentation::update_content(pid, "## Hello\nWelcome\n\n## Bye\nThanks".to_string(), &state.db_pool).await.unwrap();
+        DbPresentation::set_language(pid, Some("en"), &state.db_pool).await.unwrap();
+        PresentationVariant::save(&state.db_pool, pid, "fr", "## Bonjour\nBienvenue\n\n## Au revoir\nMerci").await.unwrap();
+        let rid = 9812;
+        sqlx::query("INSERT INTO recording (id, presentation_id, name, captions_path) VALUES (?, ?, 'Live', '')")
+            .bind(rid)
+            .bind(pid)
             .execute(&state.db_pool)
             .await
             .unwrap();
+        let key = pid.to_string();
+        let pres = add_client_handler_channel(key.clone(), &mut state).await.unwrap();
+        let checkpoint = RecordingCheckpoint {
+            recording_id: rid,
+            active_ms: 0,
+            slides: vec![(0, 0, 0, None), (1000, 7, 0, None), (2000, 1, 0, None)],
+            polls: vec![],
+            captions: vec![],
+        };
+        pres.lock().unwrap().recording = Some(RecordingState::restore(checkpoint, "Live".to_string()));
+        handle_recording_message(RecordingMessage::RecordingStop, &pres, pid, &state.db_pool).await;
 
-        // Build a fresh server against the same pool state.
-        let (router, _) = build_app(state.db_pool).await;
-        let server = TestServer::builder().save_cookies().build(router).unwrap();
-
-        let response = server.get("/demo").await;
+        let titles = |slides: Vec<RecordingSlide>| slides.into_iter().map(|s| (s.position, s.title)).collect::<Vec<_>>();
+        let recorded = RecordingSlide::get_by_recording(rid, &state.db_pool).await.unwrap();
+        assert_eq!(titles(recorded), [(0, "Hello".to_string()), (1, "Bye".to_string())]);
+        let french = RecordingSlide::get_by_recording_in(rid, "fr", &state.db_pool).await.unwrap();
+        assert_eq!(titles(french), [(0, "Bonjour".to_string()), (1, "Au revoir".to_string())]);
 
-        assert_eq!(
-            response.status_code(),
-            303,
-            "demo with no admin presentations must still redirect"
-        );
-        let location = response
-            .headers()
-            .get("location")
-            .unwrap()
-            .to_str()
-            .unwrap();
-        assert_eq!(location, "/", "demo with no presentations must redirect to /");
+        login_as(&server, "admin", "admin").await;
+        let vtt = server.get(&format!("/admin/{pid}/{rid}/slides.vtt?lang=fr")).await.text();
+        assert!(vtt.contains("Au revoir") && !vtt.contains("Bye"));
+        let html = server.get(&format!("/admin/{pid}/{rid}/slides.html?lang=fr")).await.text();
+        assert!(html.starts_with("<!DOCTYPE html><html lang=\"fr\">") && html.contains("Merci"));
+        let html = server.get(&format!("/admin/{pid}/{rid}/slides.html?lang=de")).await.text();
+        assert!(html.starts_with("<!DOCTYPE html><html lang=\"en\">") && html.contains("Thanks"));
+        let page = server.get(&format!("/admin/{pid}/{rid}?lang=fr")).await.text();
+        assert!(page.contains("slides.vtt?lang=fr") && page.contains("id=\"currentSlide\" lang=\"fr\""));
+    }
+
+    /// Every connection is greeted first with the protocol version, its role and what it
+    /// may send; the socket accepts exactly those types and refuses the rest with an error.
+    #[tokio::test]
+    async fn hello_capabilities_are_what_the_socket_accepts() {
+        let (_server, mut state) = test_server().await;
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let key = seed_presentation(uid, "Protocol", &state.db_pool).await.to_string();
+        let pres = add_client_handler_channel(key.clone(), &mut state).await.unwrap();
+        let mut tx = pres.lock().unwrap().channel.0.clone();
+        let mut rx = tx.subscribe();
+        let (direct, mut replies) = tokio::sync::mpsc::unbounded_channel();
+        let sent = [
+            SlideMessage::Text("## One\n\n## Two".to_string()),
+            SlideMessage::Slide(1),
+            SlideMessage::Fragment { slide: 1, fragment: 0 },
+            SlideMessage::Highlight { slide: 1, block: None, target: None, variants: BTreeMap::new() },
+            SlideMessage::Caption("Hello".to_string()),
+            SlideMessage::Name("Renamed".to_string()),
+            SlideMessage::Notes("spoofed".to_string()),
+            SlideMessage::Error(ProtocolError::unauthorized("text")),
+        ];
+        for role in [
+            AccessResult::Owner,
+            AccessResult::Editor,
+            AccessResult::Controller,
+            AccessResult::Captioner,
+            AccessResult::Audience,
+            AccessResult::PublicOk,
+            AccessResult::Denied,
+        ] {
+            let hello = Hello::new(&role);
+            assert_eq!((hello.protocol, hello.role.as_str()), (protocol::PROTOCOL_VERSION, role.as_str()));
+            for msg in &sent {
+                let msg_type = slide_msg_type_name(msg);
+                let text = axum::extract::ws::Message::text(json(msg));
+                assert!(matches!(handle_socket(Ok(text), &key, &mut tx, &mut state, &role, &direct), Ok(true)));
+                let allowed = hello.capabilities.iter().any(|c| c == msg_type);
+                // An accepted message may be broadcast along with others, such as a Pacing update.
+                let broadcast = std::iter::from_fn(|| rx.try_recv().ok()).count() > 0;
+                assert_eq!(broadcast, allowed, "{role:?} {msg_type}");
+                let refused = matches!(
+                    replies.try_recv(),
+                    Ok(SlideMessage::Error(ProtocolError { code: ErrorCode::Unauthorized, message_type: Some(t), .. }))
+                        if t == msg_type
+                );
+                assert_eq!(refused, !allowed, "{role:?} {msg_type}");
+            }
+        }
+        let viewer = Viewer { connection_id: 1, is_presenter: false, is_audience: true, is_editor: false };
+        assert!(viewer.view(&SlideMessage::Hello(Hello::new(&AccessResult::PublicOk))).is_some());
+        assert!(viewer.view(&SlideMessage::Error(ProtocolError::unauthorized("slide"))).is_some());
     }
 
-    /// A poisoned K/V store mutex must return `Err` from `add_client_handler_channel`
-    /// rather than crashing the process with a panic.
+    /// Unreadable messages are answered with `malformed` and the connection stays open;
+    /// oversized ones are answered with a fatal `too_large` before it closes.
     #[tokio::test]
-    async fn kvstore_lock_poison_returns_err_not_panic() {
-        let pool = SqlitePoolOptions::new()
-            .max_connections(1)
-            .connect_with(
-                SqliteConnectOptions::from_str("sqlite::memory:")
-                    .unwrap()
-                    .foreign_keys(false),
-            )
-            .await
-            .unwrap();
-        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
-
-        // Poison the slides mutex by panicking while a guard is held.
-        let slides: Arc<Mutex<HashMap<String, Arc<Mutex<Presentation>>>>> =
-            Arc::new(Mutex::new(HashMap::new()));
-        let slides_clone = Arc::clone(&slides);
-        let _ = std::thread::spawn(move || {
-            let _guard = slides_clone.lock().unwrap();
-            panic!("intentional poison");
-        })
-        .join();
-        assert!(slides.lock().is_err(), "mutex should be poisoned after panic");
-
-        let mut state = AppState {
-            tera: Tera::new(),
-            slides,
-            db_pool: pool,
+    async fn malformed_and_oversized_messages_are_answered() {
+        let (_server, mut state) = test_server().await;
+        let uid = get_user_id("admin", &state.db_pool).await;
+        let key = seed_presentation(uid, "Protocol", &state.db_pool).await.to_string();
+        let pres = add_client_handler_channel(key.clone(), &mut state).await.unwrap();
+        let mut tx = pres.lock().unwrap().channel.0.clone();
+        let (direct, mut replies) = tokio::sync::mpsc::unbounded_channel();
+        let mut send = |text: String| {
+            let result = handle_socket(Ok(axum::extract::ws::Message::text(text)), &key, &mut tx, &mut state, &AccessResult::Owner, &direct);
+            (result, replies.try_recv())
         };
 
-        let result = add_client_handler_channel("1".to_string(), &mut state).await;
-        assert!(
-            result.is_err(),
-            "poisoned K/V store must return Err, not panic"
+        let (result, reply) = send(r#"{"type":"slide","data":"two"}"#.to_string());
+        assert!(matches!(result, Ok(true)));
+        assert!(matches!(
+            reply,
+            Ok(SlideMessage::Error(ProtocolError { code: ErrorCode::Malformed, message_type: Some(t), fatal: false, .. })) if t == "slide"
+        ));
+        let (result, reply) = send("not json".to_string());
+        assert!(matches!(result, Ok(true)));
+        assert!(matches!(reply, Ok(SlideMessage::Error(ProtocolError { code: ErrorCode::Malformed, message_type: None, .. }))));
+
+        let (result, reply) = send(json(&SlideMessage::Caption("x".repeat(captions::MAX_CAPTION_BYTES + 1))));
+        assert!(matches!(result, Ok(true)), "an oversized caption is refused, not fatal");
+        assert!(matches!(reply, Ok(SlideMessage::Error(ProtocolError { code: ErrorCode::TooLarge, fatal: false, .. }))));
+
+        let (result, reply) = send(json(&SlideMessage::Text("x".repeat(200 * 1024))));
+        assert!(result.is_err(), "an oversized deck must close the connection");
+        assert!(matches!(
+            reply,
+            Ok(SlideMessage::Error(ProtocolError { code: ErrorCode::TooLarge, fatal: true, message_type: Some(t), .. })) if t == "text"
+        ));
+        assert_eq!(
+            serde_json::to_value(SlideMessage::Error(ProtocolError::unauthorized("name"))).unwrap(),
+            serde_json::json!({"type": "error", "data": {
+                "code": "unauthorized", "message_type": "name", "message": "Your role may not send name messages.", "fatal": false
+            }})
         );
     }
 
-    /// After 5 login attempts from the same IP, the 6th must be rejected with HTTP 429.
+    /// The published schema must be a valid JSON Schema that accepts every message of either
+    /// side, broadcasts with their sequence number included, and rejects malformed ones.
     #[tokio::test]
-    async fn login_rate_limiting_returns_429_after_limit() {
-        let (server, state) = test_server().await;
-        seed_user(&state.db_pool).await;
-
-        // Send 5 attempts — all should be handled normally (not rate-limited).
-        for i in 0..5_u8 {
-            let resp = server
-                .post("/auth/login")
-                .form(&serde_json::json!({
-                    "username": "testuser",
-                    "password": "wrongpass"
-                }))
-                .await;
-            assert_ne!(
-                resp.status_code().as_u16(),
-                429,
-                "attempt {i} should not be rate-limited"
-            );
+    async fn schema_validates_every_message() {
+        let schema = protocol::schema();
+        assert!(jsonschema::meta::is_valid(&schema));
+        let validator = jsonschema::validator_for(&schema).unwrap();
+        let question = Question {
+            id: 1,
+            presentation_id: 1,
+            text: "Why?".to_string(),
+            status: QuestionStatus::Pinned,
+            created_at: time::OffsetDateTime::UNIX_EPOCH,
+            author: None,
+        };
+        let poll = Poll { id: 1, question: "Q?".to_string(), options: vec!["A".to_string()], counts: vec![2], open: true };
+        let pacing = Pacing {
+            slide: 1,
+            slide_seconds: 30,
+            slide_planned: Some(20),
+            session_seconds: 60,
+            session_planned: None,
+            over: Some(Overrun::Slide),
+        };
+        let server = [
+            SlideMessage::Hello(Hello::new(&AccessResult::Editor)),
+            SlideMessage::Error(ProtocolError::unauthorized("name").fatal()),
+            SlideMessage::Text("## One".to_string()),
+            SlideMessage::Patch(TextPatch { by: Some("ed".to_string()), mine: true, ..TextPatch::between("a", "ab", 3) }),
+            SlideMessage::Revision(4),
+            SlideMessage::Cursor(Cursor { id: 2, user: "ed".to_string(), start: 1, end: 2 }),
+            SlideMessage::CursorGone(2),
+            SlideMessage::Resume { stream: 7, seq: 9 },
+            SlideMessage::Slide(1),
+            SlideMessage::Fragment { slide: 1, fragment: 2 },
+            SlideMessage::Highlight {
+                slide: 1,
+                block: Some(0),
+                target: Some(BlockTarget { tag: "h2".to_string(), text: "Two".to_string(), occurrence: 0 }),
+                variants: BTreeMap::from([("fr".to_string(), BlockTarget { tag: "h2".to_string(), text: "Deux".to_string(), occurrence: 0 })]),
+            },
+            SlideMessage::Caption("Hello".to_string()),
+            SlideMessage::Name("Deck".to_string()),
+            SlideMessage::Language(None),
+            SlideMessage::Variant { lang: "fr".to_string(), text: Some("## Un".to_string()) },
+            SlideMessage::Notes("Say hi".to_string()),
+            SlideMessage::Question(question.clone()),
+            SlideMessage::Questions(vec![question.clone()]),
+            SlideMessage::PinnedQuestions(vec![question]),
+            SlideMessage::Poll(poll),
+            SlideMessage::RecordingStart { elapsed_ms: 0 },
+            SlideMessage::RecordingPause { elapsed_ms: 10 },
+            SlideMessage::RecordingResume { elapsed_ms: 10 },
+            SlideMessage::RecordingStop { id: 1, name: "Live".to_string(), start: "2026-10-17".to_string() },
+            SlideMessage::Presence(Presence { viewers: 1, users: vec!["amy".to_string()], ..Presence::default() }),
+            SlideMessage::Pacing(pacing),
+        ];
+        let types: HashSet<&str> = server.iter().map(slide_msg_type_name).collect();
+        assert_eq!(types.len(), schema["$defs"]["SlideMessage"]["oneOf"].as_array().unwrap().len(), "a sample per variant");
+        for (seq, msg) in (1..).zip(&server) {
+            for text in [json(msg), sequenced_json(msg, seq)] {
+                let value: serde_json::Value = serde_json::from_str(&text).unwrap();
+                assert!(validator.is_valid(&value), "{text}");
+            }
+        }
+        let client = [
+            r#"{"type":"question_ask","text":"Why?","anonymous":true}"#,
+            r#"{"type":"question_moderate","id":1,"status":"answered"}"#,
+            r#"{"type":"poll_open","question":"Q?","options":["A","B"]}"#,
+            r#"{"type":"poll_open_inline"}"#,
+            r#"{"type":"poll_close"}"#,
+            r#"{"type":"poll_vote","poll_id":1,"option":0}"#,
+            r#"{"type":"recording_start"}"#,
+            r#"{"type":"recording_stop"}"#,
+            r#"{"type":"cursor","data":{"start":1,"end":2}}"#,
+            r#"{"type":"patch","data":{"base":0,"start":0,"end":0,"text":"a"}}"#,
+        ];
+        for text in client {
+            assert!(validator.is_valid(&serde_json::from_str(text).unwrap()), "{text}");
+        }
+        for text in [r#"{"type":"slide","data":"two"}"#, r#"{"type":"bogus"}"#, r#"{"type":"poll_vote","poll_id":1}"#, "[]"] {
+            assert!(!validator.is_valid(&serde_json::from_str(text).unwrap()), "{text}");
         }
+    }
 
-        // The 6th attempt from the same IP must be rejected.
-        let resp = server
-            .post("/auth/login")
-            .form(&serde_json::json!({
-                "username": "testuser",
-                "password": "wrongpass"
-            }))
-            .await;
-        assert_eq!(
-            resp.status_code().as_u16(),
-            429,
-            "6th attempt must return 429 Too Many Requests"
-        );
+    /// The schema is published for other clients to check themselves against.
+    #[tokio::test]
+    async fn schema_is_published() {
+        let (server, _state) = test_server().await;
+        let res = server.get("/protocol/schema.json").await;
+        res.assert_status_ok();
+        assert_eq!(res.header(axum::http::header::CONTENT_TYPE), "application/schema+json");
+        assert_eq!(res.json::<serde_json::Value>(), protocol::schema());
     }
 }
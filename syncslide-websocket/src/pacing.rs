@@ -0,0 +1,254 @@
+//! Planned slide timings and the session clock they are compared with.
+//!
+//! A slide's planned time is a `Time:` line in its speaker notes, such as `Time: 2m30s`,
+//! so it moves with the slide and never reaches the audience. The [`SessionClock`] of a
+//! live presentation starts at the first slide move and notes every move after it;
+//! presenters are told when the slide shown, or the whole deck, runs over its plan, and
+//! can compare planned and actual time per slide in a [`Report`], which the saved clock
+//! keeps available once the session is over.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tokio::time::Instant;
+
+/// Seconds planned by the first `Time:` line of a slide's speaker notes, if it has one.
+#[must_use]
+pub fn planned_time(notes: &str) -> Option<u64> {
+    notes.lines().find_map(|line| {
+        let (key, value) = line.trim().split_once(':')?;
+        if key.trim().eq_ignore_ascii_case("time") { parse_duration(value) } else { None }
+    })
+}
+
+/// Parses a duration such as `90s`, `2m`, `2m30s`, `1h 5min`, `2:30` or `1:02:30` into
+/// seconds; a bare number is minutes. `None` if it is not one, or is zero.
+#[must_use]
+pub fn parse_duration(text: &str) -> Option<u64> {
+    let text = text.trim();
+    let seconds = if text.contains(':') {
+        let parts: Vec<u64> = text.split(':').map(|p| p.trim().parse().ok()).collect::<Option<_>>()?;
+        if !(2..=3).contains(&parts.len()) {
+            return None;
+        }
+        parts.iter().try_fold(0u64, |total, &part| total.checked_mul(60)?.checked_add(part))?
+    } else if let Ok(minutes) = text.parse::<u64>() {
+        minutes.checked_mul(60)?
+    } else {
+        let mut total = 0u64;
+        let mut rest = text;
+        while !rest.is_empty() {
+            let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+            let value: u64 = rest[..digits].parse().ok()?;
+            rest = rest[digits..].trim_start();
+            let letters = rest.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(rest.len());
+            let scale = match rest[..letters].to_ascii_lowercase().as_str() {
+                "h" | "hr" | "hrs" | "hour" | "hours" => 3600,
+                "m" | "min" | "mins" | "minute" | "minutes" => 60,
+                "s" | "sec" | "secs" | "second" | "seconds" => 1,
+                _ => return None,
+            };
+            total = total.checked_add(value.checked_mul(scale)?)?;
+            rest = rest[letters..].trim_start();
+        }
+        total
+    };
+    (seconds > 0).then_some(seconds)
+}
+
+/// Seconds planned for the whole deck, if any slide has a planned time.
+fn total_planned(plan: &[Option<u64>]) -> Option<u64> {
+    plan.iter().flatten().copied().reduce(u64::saturating_add)
+}
+
+/// What ran over its planned time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Overrun {
+    /// The slide shown.
+    Slide,
+    /// The whole session.
+    Session,
+}
+
+/// How the session keeps to its planned timings, sent to presenters.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Pacing {
+    /// The slide shown.
+    pub slide: u32,
+    /// Seconds spent on the slide this session so far.
+    pub slide_seconds: u64,
+    /// Seconds planned for the slide, if it has a planned time.
+    pub slide_planned: Option<u64>,
+    /// Seconds since the first slide move started the clock.
+    pub session_seconds: u64,
+    /// Seconds planned for the whole deck, if any slide has a planned time.
+    pub session_planned: Option<u64>,
+    /// What has just run over its planned time; `None` for an update after a move.
+    pub over: Option<Overrun>,
+}
+
+/// The clock of one live session, from the first slide move on.
+pub struct SessionClock {
+    started_at: Instant,
+    /// `(offset_ms, slide)` of each move, oldest first.
+    steps: Vec<(u64, u32)>,
+    /// Bumped on each move to another slide.
+    pub visit: u64,
+    /// The visit a warning about the slide running over is scheduled for.
+    pub slide_warning: Option<u64>,
+    /// Whether a check of the whole session's time is scheduled.
+    pub session_check: bool,
+    /// Whether presenters were told the whole session runs over.
+    pub session_warned: bool,
+}
+
+/// A [`SessionClock`] as sent to another process serving the presentation, or saved
+/// for the report once the session is over.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ClockState {
+    /// Milliseconds since the clock started.
+    pub elapsed_ms: u64,
+    /// `(offset_ms, slide)` of each move, oldest first.
+    pub steps: Vec<(u64, u32)>,
+}
+
+impl ClockState {
+    /// Milliseconds spent on each slide, up to `elapsed_ms`.
+    #[must_use]
+    pub fn actual_ms(&self) -> BTreeMap<u32, u64> {
+        actual_ms(&self.steps, self.elapsed_ms)
+    }
+}
+
+/// Milliseconds spent on each slide by `steps`, the last one lasting until `now`.
+fn actual_ms(steps: &[(u64, u32)], now: u64) -> BTreeMap<u32, u64> {
+    let mut actual = BTreeMap::new();
+    for (i, &(offset_ms, slide)) in steps.iter().enumerate() {
+        let until = steps.get(i + 1).map_or(now, |&(next, _)| next);
+        *actual.entry(slide).or_insert(0) += until.saturating_sub(offset_ms);
+    }
+    actual
+}
+
+impl SessionClock {
+    /// Starts the clock now.
+    #[must_use]
+    pub fn start() -> Self {
+        SessionClock {
+            started_at: Instant::now(),
+            steps: Vec::new(),
+            visit: 0,
+            slide_warning: None,
+            session_check: false,
+            session_warned: false,
+        }
+    }
+
+    /// Carries on a clock started elsewhere, as `state` describes it.
+    #[must_use]
+    pub fn resume(state: ClockState) -> Self {
+        let elapsed = std::time::Duration::from_millis(state.elapsed_ms);
+        let mut clock = SessionClock::start();
+        clock.started_at = clock.started_at.checked_sub(elapsed).unwrap_or(clock.started_at);
+        for pair in state.steps.windows(2) {
+            if pair[0].1 != pair[1].1 {
+                clock.visit += 1;
+            }
+        }
+        clock.visit += u64::from(!state.steps.is_empty());
+        clock.steps = state.steps;
+        clock
+    }
+
+    /// What another process needs to carry on this clock.
+    #[must_use]
+    pub fn state(&self) -> ClockState {
+        ClockState { elapsed_ms: self.elapsed_ms(), steps: self.steps.clone() }
+    }
+
+    /// Milliseconds since the clock started.
+    #[must_use]
+    pub fn elapsed_ms(&self) -> u64 {
+        u64::try_from(self.started_at.elapsed().as_millis()).unwrap_or(u64::MAX)
+    }
+
+    /// Notes that `slide` is shown from now on.
+    pub fn step(&mut self, slide: u32) {
+        if self.steps.last().is_none_or(|&(_, last)| last != slide) {
+            self.visit += 1;
+        }
+        self.steps.push((self.elapsed_ms(), slide));
+    }
+
+    /// Milliseconds spent on each slide so far.
+    #[must_use]
+    pub fn actual_ms(&self) -> BTreeMap<u32, u64> {
+        actual_ms(&self.steps, self.elapsed_ms())
+    }
+
+    /// Where the session stands on `slide` against `plan`, the seconds planned per slide.
+    #[must_use]
+    pub fn pacing(&self, slide: u32, plan: &[Option<u64>]) -> Pacing {
+        Pacing {
+            slide,
+            slide_seconds: self.actual_ms().get(&slide).copied().unwrap_or(0) / 1000,
+            slide_planned: plan.get(slide as usize).copied().flatten(),
+            session_seconds: self.elapsed_ms() / 1000,
+            session_planned: total_planned(plan),
+            over: None,
+        }
+    }
+
+    /// Milliseconds until `slide` and the session run over `plan`: zero once they have,
+    /// `None` for either without a planned time.
+    #[must_use]
+    pub fn time_left(&self, slide: u32, plan: &[Option<u64>]) -> (Option<u64>, Option<u64>) {
+        let spent = self.actual_ms().get(&slide).copied().unwrap_or(0);
+        let slide_left = plan.get(slide as usize).copied().flatten().map(|s| (s * 1000).saturating_sub(spent));
+        let session_left = total_planned(plan).map(|s| (s * 1000).saturating_sub(self.elapsed_ms()));
+        (slide_left, session_left)
+    }
+}
+
+/// Planned and actual time of one slide.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct SlideTiming {
+    pub slide: u32,
+    pub title: String,
+    pub planned_seconds: Option<u64>,
+    pub actual_seconds: u64,
+}
+
+/// Planned against actual time, per slide and for the whole session.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Report {
+    /// Whether the clock has started; every actual time is zero until it has.
+    pub started: bool,
+    pub session_seconds: u64,
+    pub planned_seconds: Option<u64>,
+    pub slides: Vec<SlideTiming>,
+}
+
+impl Report {
+    /// The report for a deck with slide `titles` and `plan`, timed by `clock` if it started.
+    #[must_use]
+    pub fn new(clock: Option<&ClockState>, titles: Vec<String>, plan: &[Option<u64>]) -> Self {
+        let actual = clock.map(ClockState::actual_ms).unwrap_or_default();
+        let slides = (0u32..)
+            .zip(titles)
+            .map(|(slide, title)| SlideTiming {
+                slide,
+                title,
+                planned_seconds: plan.get(slide as usize).copied().flatten(),
+                actual_seconds: actual.get(&slide).copied().unwrap_or(0) / 1000,
+            })
+            .collect();
+        Report {
+            started: clock.is_some(),
+            session_seconds: clock.map_or(0, |c| c.elapsed_ms / 1000),
+            planned_seconds: total_planned(plan),
+            slides,
+        }
+    }
+}
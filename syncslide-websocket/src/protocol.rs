@@ -0,0 +1,144 @@
+//! The versioned WebSocket protocol spoken on `/ws/{pid}`.
+//!
+//! Every connection is first sent a [`Hello`] naming the protocol version, the role it was
+//! granted and the types of message that role may send. A message the server does not act
+//! on is answered with a [`ProtocolError`] carrying a machine-readable [`ErrorCode`] rather
+//! than dropped in silence. The [`schema`] of every message is published so that other
+//! clients can be checked against it.
+
+use crate::db::AccessResult;
+use crate::{PollMessage, QuestionMessage, RecordingMessage, SlideMessage};
+use schemars::{JsonSchema, generate::SchemaSettings};
+use serde::{Deserialize, Serialize};
+
+/// Version of the protocol, raised whenever a change would break existing clients.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Why a message was refused.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// The connection's role may not send this type of message.
+    Unauthorized,
+    /// The message is over a size limit.
+    TooLarge,
+    /// The message is not JSON, has no known type, or lacks fields its type needs.
+    Malformed,
+    /// The connection sent this type of message too soon after the last one.
+    RateLimited,
+}
+
+/// The first message of every connection.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Hello {
+    /// The [`PROTOCOL_VERSION`] the server speaks.
+    pub protocol: u32,
+    /// The role granted to the connection, named as [`AccessResult::as_str`] names it.
+    pub role: String,
+    /// Types of message the connection may send; any other is refused as `unauthorized`.
+    pub capabilities: Vec<String>,
+}
+
+impl Hello {
+    /// The greeting for a connection granted `role`.
+    pub fn new(role: &AccessResult) -> Self {
+        Hello {
+            protocol: PROTOCOL_VERSION,
+            role: role.as_str().to_string(),
+            capabilities: capabilities(role).iter().map(ToString::to_string).collect(),
+        }
+    }
+}
+
+/// A message from the connection that the server did not act on.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ProtocolError {
+    /// Why it was refused.
+    pub code: ErrorCode,
+    /// The `type` of the refused message, if it had one.
+    pub message_type: Option<String>,
+    /// What went wrong, fit to show to the user.
+    pub message: String,
+    /// Whether the server closes the connection after sending this.
+    pub fatal: bool,
+}
+
+impl ProtocolError {
+    /// A refusal of a message of type `message_type`; the connection stays open.
+    pub fn new(code: ErrorCode, message_type: Option<&str>, message: &str) -> Self {
+        ProtocolError { code, message_type: message_type.map(String::from), message: message.to_string(), fatal: false }
+    }
+
+    /// A refusal of a message of type `message_type` sent by a role that may not send it.
+    pub fn unauthorized(message_type: &str) -> Self {
+        Self::new(
+            ErrorCode::Unauthorized,
+            Some(message_type),
+            &format!("Your role may not send {message_type} messages."),
+        )
+    }
+
+    /// The same refusal, after which the connection is closed.
+    #[must_use]
+    pub fn fatal(self) -> Self {
+        ProtocolError { fatal: true, ..self }
+    }
+}
+
+/// Recording controls, open to every presenter.
+const RECORDING: [&str; 4] = ["recording_start", "recording_pause", "recording_resume", "recording_stop"];
+
+/// Types of message a connection granted `role` may send.
+///
+/// The server refuses every other type with [`ErrorCode::Unauthorized`], so this is both
+/// what [`Hello`] announces and what the socket handlers check.
+pub fn capabilities(role: &AccessResult) -> &'static [&'static str] {
+    const OWNER: [&str; 17] = [
+        "text", "patch", "cursor", "slide", "fragment", "highlight", "caption", "name",
+        "question_ask", "question_moderate", "poll_open", "poll_open_inline", "poll_close",
+        RECORDING[0], RECORDING[1], RECORDING[2], RECORDING[3],
+    ];
+    const EDITOR: [&str; 16] = [
+        "text", "patch", "cursor", "slide", "fragment", "highlight", "caption",
+        "question_ask", "question_moderate", "poll_open", "poll_open_inline", "poll_close",
+        RECORDING[0], RECORDING[1], RECORDING[2], RECORDING[3],
+    ];
+    const CONTROLLER: [&str; 9] = [
+        "slide", "fragment", "highlight", "question_ask", "question_moderate",
+        RECORDING[0], RECORDING[1], RECORDING[2], RECORDING[3],
+    ];
+    match role {
+        AccessResult::Owner => &OWNER,
+        AccessResult::Editor => &EDITOR,
+        AccessResult::Controller => &CONTROLLER,
+        AccessResult::Captioner => &["caption", "question_ask"],
+        AccessResult::Audience | AccessResult::PublicOk => &["question_ask", "poll_vote"],
+        AccessResult::Denied => &[],
+    }
+}
+
+/// Whether a connection granted `role` may send messages of type `message_type`.
+pub fn may_send(role: &AccessResult, message_type: &str) -> bool {
+    capabilities(role).contains(&message_type)
+}
+
+/// JSON Schema (draft 2020-12) of every message of the protocol.
+///
+/// A message the server sends is a `SlideMessage`; one a client sends is a `SlideMessage`,
+/// `QuestionMessage`, `PollMessage` or `RecordingMessage`. Broadcasts also carry a `seq`
+/// number next to `type` and `data`.
+pub fn schema() -> serde_json::Value {
+    let mut generator = SchemaSettings::draft2020_12().into_generator();
+    let messages = [
+        generator.subschema_for::<SlideMessage>(),
+        generator.subschema_for::<QuestionMessage>(),
+        generator.subschema_for::<PollMessage>(),
+        generator.subschema_for::<RecordingMessage>(),
+    ];
+    serde_json::json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": format!("SyncSlide WebSocket protocol, version {PROTOCOL_VERSION}"),
+        "anyOf": messages,
+        "$defs": generator.take_definitions(true),
+    })
+}
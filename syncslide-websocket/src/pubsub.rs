@@ -0,0 +1,345 @@
+//! Shares live presentations between server processes through a message broker.
+//!
+//! Each process keeps its own [`Presentation`](crate::Presentation) state and
+//! WebSocket connections. What one process broadcasts is published on the
+//! presentation's topic, and every other process serving it applies and relays it to
+//! its own connections. [`InMemory`] is the default for a single process; [`Redis`]
+//! connects several processes through a Redis server named by `APP_PUBSUB_URL`.
+//! Counts, such as the presenters connected to a presentation, are kept by the
+//! broker as one share per process that expires unless refreshed, so that every
+//! process sees the total and a process that crashed stops counting.
+
+use futures_util::future::BoxFuture;
+use futures_util::stream::BoxStream;
+use futures_util::{FutureExt, StreamExt};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::time::Instant;
+
+/// The payloads published on a topic after subscribing, in order.
+pub type Payloads = BoxStream<'static, String>;
+
+/// A broker to publish presentation broadcasts through.
+pub trait PubSub: Send + Sync {
+    /// Identifies this process among those sharing the broker.
+    fn node(&self) -> u64;
+    /// Sends `payload` to every current subscriber of `topic`.
+    fn publish<'a>(&'a self, topic: &'a str, payload: String) -> BoxFuture<'a, Result<(), PubSubError>>;
+    /// Starts receiving what is published on `topic`.
+    fn subscribe<'a>(&'a self, topic: &'a str) -> BoxFuture<'a, Result<Payloads, PubSubError>>;
+    /// Sets process `node`'s share of the count `key`, kept for `ttl` unless set again;
+    /// returns the sum of the shares of every process.
+    fn set_share<'a>(&'a self, key: &'a str, node: u64, share: i64, ttl: Duration) -> BoxFuture<'a, Result<i64, PubSubError>>;
+}
+
+/// Why the broker could not be reached or refused a command.
+#[derive(Debug, thiserror::Error)]
+pub enum PubSubError {
+    #[error(transparent)]
+    Redis(#[from] redis::RedisError),
+}
+
+/// Connects to the broker at `url`, or keeps everything in this process when `None`.
+pub async fn connect(url: Option<&str>) -> Result<Arc<dyn PubSub>, PubSubError> {
+    Ok(match url {
+        Some(url) => Arc::new(Redis::connect(url).await?),
+        None => Arc::new(InMemory::default()),
+    })
+}
+
+/// Identifies this process among those sharing a broker.
+pub fn node_id() -> u64 {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.subsec_nanos());
+    (u64::from(std::process::id()) << 32) | u64::from(nanos)
+}
+
+/// Each process's share of a count, with when it expires.
+type Shares = HashMap<u64, (i64, Instant)>;
+
+/// Topics and counts within one process, for a server that is not shared.
+#[derive(Default)]
+pub struct InMemory {
+    topics: Mutex<HashMap<String, broadcast::Sender<String>>>,
+    shares: Mutex<HashMap<String, Shares>>,
+}
+
+impl PubSub for InMemory {
+    /// Always the same, as no other process shares the broker, so a restarted server
+    /// knows what it left behind.
+    fn node(&self) -> u64 {
+        0
+    }
+
+    fn publish<'a>(&'a self, topic: &'a str, payload: String) -> BoxFuture<'a, Result<(), PubSubError>> {
+        if let Ok(topics) = self.topics.lock()
+            && let Some(sender) = topics.get(topic)
+        {
+            // No receivers only means nobody subscribed.
+            let _ = sender.send(payload);
+        }
+        async { Ok(()) }.boxed()
+    }
+
+    fn subscribe<'a>(&'a self, topic: &'a str) -> BoxFuture<'a, Result<Payloads, PubSubError>> {
+        let receiver = match self.topics.lock() {
+            Ok(mut topics) => {
+                // Forget the topics of presentations no longer served.
+                topics.retain(|_, sender| sender.receiver_count() > 0);
+                Some(topics.entry(topic.to_string()).or_insert_with(|| broadcast::channel(1024).0).subscribe())
+            }
+            Err(_) => None,
+        };
+        let payloads = futures_util::stream::unfold(receiver, |receiver| async move {
+            let mut receiver = receiver?;
+            loop {
+                match receiver.recv().await {
+                    Ok(payload) => return Some((payload, Some(receiver))),
+                    Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        });
+        async move { Ok(payloads.boxed()) }.boxed()
+    }
+
+    fn set_share<'a>(&'a self, key: &'a str, node: u64, share: i64, ttl: Duration) -> BoxFuture<'a, Result<i64, PubSubError>> {
+        let total = self.shares.lock().map_or(share, |mut counts| {
+            let now = Instant::now();
+            let shares = counts.entry(key.to_string()).or_default();
+            shares.insert(node, (share, now + ttl));
+            shares.retain(|_, (_, expires)| *expires > now);
+            shares.values().map(|(share, _)| share).sum()
+        });
+        async move { Ok(total) }.boxed()
+    }
+}
+
+/// A Redis server shared by several processes: `PUBLISH`/`SUBSCRIBE` for topics, and
+/// for counts a key per process share set to expire, listed in a set named after the count.
+pub struct Redis {
+    client: redis::Client,
+    connection: redis::aio::MultiplexedConnection,
+    node: u64,
+}
+
+impl Redis {
+    /// Connects to the Redis server at `url`, such as `redis://127.0.0.1:6379`.
+    pub async fn connect(url: &str) -> Result<Self, PubSubError> {
+        let client = redis::Client::open(url)?;
+        let connection = client.get_multiplexed_async_connection().await?;
+        Ok(Redis { client, connection, node: node_id() })
+    }
+}
+
+impl PubSub for Redis {
+    fn node(&self) -> u64 {
+        self.node
+    }
+
+    fn publish<'a>(&'a self, topic: &'a str, payload: String) -> BoxFuture<'a, Result<(), PubSubError>> {
+        let mut connection = self.connection.clone();
+        async move {
+            redis::cmd("PUBLISH").arg(topic).arg(payload).query_async::<i64>(&mut connection).await?;
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn subscribe<'a>(&'a self, topic: &'a str) -> BoxFuture<'a, Result<Payloads, PubSubError>> {
+        async move {
+            // Subscribing takes a connection of its own, closed when the stream is dropped.
+            let mut pubsub = self.client.get_async_pubsub().await?;
+            pubsub.subscribe(topic).await?;
+            let payloads = pubsub
+                .into_on_message()
+                .filter_map(|msg| futures_util::future::ready(msg.get_payload::<String>().ok()));
+            Ok(payloads.boxed())
+        }
+        .boxed()
+    }
+
+    fn set_share<'a>(&'a self, key: &'a str, node: u64, share: i64, ttl: Duration) -> BoxFuture<'a, Result<i64, PubSubError>> {
+        let mut connection = self.connection.clone();
+        let ttl_ms = u64::try_from(ttl.as_millis()).unwrap_or(u64::MAX);
+        async move {
+            redis::cmd("SET").arg(format!("{key}:{node}")).arg(share).arg("PX").arg(ttl_ms).query_async::<()>(&mut connection).await?;
+            redis::cmd("SADD").arg(key).arg(node).query_async::<i64>(&mut connection).await?;
+            redis::cmd("PEXPIRE").arg(key).arg(ttl_ms).query_async::<i64>(&mut connection).await?;
+            let nodes: Vec<String> = redis::cmd("SMEMBERS").arg(key).query_async(&mut connection).await?;
+            let keys: Vec<String> = nodes.iter().map(|node| format!("{key}:{node}")).collect();
+            let shares: Vec<Option<i64>> = redis::cmd("MGET").arg(&keys).query_async(&mut connection).await?;
+            // Processes whose share expired are forgotten.
+            let gone: Vec<&String> = nodes.iter().zip(&shares).filter(|(_, share)| share.is_none()).map(|(node, _)| node).collect();
+            if !gone.is_empty() {
+                redis::cmd("SREM").arg(key).arg(gone).query_async::<i64>(&mut connection).await?;
+            }
+            Ok(shares.into_iter().flatten().sum())
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::pedantic, missing_docs)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+    use tokio::sync::mpsc;
+
+    /// Subscribers by topic, values with their expiry, and sets, shared by the
+    /// connections to [`fake_redis`].
+    #[derive(Default)]
+    struct Broker {
+        subscribers: HashMap<String, Vec<mpsc::UnboundedSender<Vec<u8>>>>,
+        values: HashMap<String, (String, Instant)>,
+        sets: HashMap<String, Vec<String>>,
+    }
+
+    fn bulk(s: &str) -> String {
+        format!("${}\r\n{s}\r\n", s.len())
+    }
+
+    /// Reads one command, an array of bulk strings, or `None` at the end of the stream.
+    async fn read_command<R: AsyncBufReadExt + Unpin>(reader: &mut R) -> Option<Vec<String>> {
+        let mut line = String::new();
+        reader.read_line(&mut line).await.ok().filter(|&n| n > 0)?;
+        let count: usize = line.trim().strip_prefix('*')?.parse().ok()?;
+        let mut args = Vec::with_capacity(count);
+        for _ in 0..count {
+            line.clear();
+            reader.read_line(&mut line).await.ok()?;
+            let len: usize = line.trim().strip_prefix('$')?.parse().ok()?;
+            let mut arg = vec![0; len + 2];
+            reader.read_exact(&mut arg).await.ok()?;
+            arg.truncate(len);
+            args.push(String::from_utf8(arg).ok()?);
+        }
+        Some(args)
+    }
+
+    /// A local stand-in for a Redis server that speaks just enough RESP2 for [`Redis`].
+    async fn fake_redis() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("redis://{}", listener.local_addr().unwrap());
+        let broker = Arc::new(Mutex::new(Broker::default()));
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                let broker = Arc::clone(&broker);
+                let (read, mut write) = socket.into_split();
+                let (out, mut out_rx) = mpsc::unbounded_channel::<Vec<u8>>();
+                tokio::spawn(async move {
+                    while let Some(bytes) = out_rx.recv().await {
+                        if write.write_all(&bytes).await.is_err() {
+                            return;
+                        }
+                    }
+                });
+                tokio::spawn(async move {
+                    let mut reader = BufReader::new(read);
+                    while let Some(args) = read_command(&mut reader).await {
+                        let reply = {
+                            let mut broker = broker.lock().unwrap();
+                            match args[0].to_uppercase().as_str() {
+                                "SUBSCRIBE" => {
+                                    broker.subscribers.entry(args[1].clone()).or_default().push(out.clone());
+                                    format!("*3\r\n{}{}:1\r\n", bulk("subscribe"), bulk(&args[1]))
+                                }
+                                "PUBLISH" => {
+                                    let message = format!("*3\r\n{}{}{}", bulk("message"), bulk(&args[1]), bulk(&args[2]));
+                                    let subscribers = broker.subscribers.entry(args[1].clone()).or_default();
+                                    subscribers.retain(|s| s.send(message.clone().into_bytes()).is_ok());
+                                    format!(":{}\r\n", subscribers.len())
+                                }
+                                "SET" => {
+                                    let ttl = Duration::from_millis(args[4].parse().unwrap());
+                                    broker.values.insert(args[1].clone(), (args[2].clone(), Instant::now() + ttl));
+                                    "+OK\r\n".to_string()
+                                }
+                                "MGET" => {
+                                    let now = Instant::now();
+                                    let values: String = args[1..]
+                                        .iter()
+                                        .map(|key| match broker.values.get(key) {
+                                            Some((value, expires)) if *expires > now => bulk(value),
+                                            _ => "$-1\r\n".to_string(),
+                                        })
+                                        .collect();
+                                    format!("*{}\r\n{values}", args.len() - 1)
+                                }
+                                "SADD" | "SREM" => {
+                                    let set = broker.sets.entry(args[1].clone()).or_default();
+                                    set.retain(|member| !args[2..].contains(member));
+                                    if args[0].eq_ignore_ascii_case("SADD") {
+                                        set.extend(args[2..].iter().cloned());
+                                    }
+                                    ":1\r\n".to_string()
+                                }
+                                "SMEMBERS" => {
+                                    let set = broker.sets.get(&args[1]).cloned().unwrap_or_default();
+                                    format!("*{}\r\n{}", set.len(), set.iter().map(|m| bulk(m)).collect::<String>())
+                                }
+                                "PEXPIRE" => ":1\r\n".to_string(),
+                                _ => "+OK\r\n".to_string(),
+                            }
+                        };
+                        if out.send(reply.into_bytes()).is_err() {
+                            return;
+                        }
+                    }
+                });
+            }
+        });
+        url
+    }
+
+    async fn next(payloads: &mut Payloads) -> String {
+        tokio::time::timeout(std::time::Duration::from_secs(5), payloads.next()).await.unwrap().unwrap()
+    }
+
+    #[tokio::test]
+    async fn in_memory_delivers_to_subscribers_of_the_topic() {
+        let bus = InMemory::default();
+        bus.publish("t", "before".to_string()).await.unwrap();
+        let mut first = bus.subscribe("t").await.unwrap();
+        let mut second = bus.subscribe("t").await.unwrap();
+        let mut other = bus.subscribe("u").await.unwrap();
+        bus.publish("t", "hello".to_string()).await.unwrap();
+        assert_eq!(next(&mut first).await, "hello", "only what was published after subscribing");
+        assert_eq!(next(&mut second).await, "hello");
+        assert!(other.next().now_or_never().is_none());
+
+        let ttl = Duration::from_secs(60);
+        assert_eq!(bus.set_share("n", 1, 1, ttl).await.unwrap(), 1);
+        assert_eq!(bus.set_share("n", 2, 2, ttl).await.unwrap(), 3);
+        assert_eq!(bus.set_share("n", 1, 0, ttl).await.unwrap(), 2);
+        assert_eq!(bus.set_share("n", 1, 1, Duration::ZERO).await.unwrap(), 2, "an expired share is not counted");
+    }
+
+    /// Two processes connected to one Redis see each other's messages and counts.
+    #[tokio::test]
+    async fn redis_shares_topics_and_counters_between_connections() {
+        let url = fake_redis().await;
+        let (a, b) = (Redis::connect(&url).await.unwrap(), Redis::connect(&url).await.unwrap());
+        let mut payloads = b.subscribe("syncslide:1").await.unwrap();
+        a.publish("syncslide:1", "{\"slide\":2}".to_string()).await.unwrap();
+        a.publish("syncslide:2", "elsewhere".to_string()).await.unwrap();
+        a.publish("syncslide:1", "second".to_string()).await.unwrap();
+        assert_eq!(next(&mut payloads).await, "{\"slide\":2}");
+        assert_eq!(next(&mut payloads).await, "second");
+
+        let ttl = Duration::from_secs(60);
+        assert_eq!(a.set_share("presenters:1", 1, 1, ttl).await.unwrap(), 1);
+        assert_eq!(b.set_share("presenters:1", 2, 2, ttl).await.unwrap(), 3);
+        assert_eq!(a.set_share("presenters:1", 1, 0, ttl).await.unwrap(), 2);
+
+        // A process that stops refreshing its share, having crashed, stops counting.
+        a.set_share("presenters:1", 1, 4, Duration::from_millis(20)).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        assert_eq!(b.set_share("presenters:1", 2, 2, ttl).await.unwrap(), 2);
+    }
+}
@@ -0,0 +1,225 @@
+//! Streams recording uploads to disk with size limits and content sniffing.
+//!
+//! A multipart field is written chunk by chunk to a staging file under
+//! [`STAGING_DIR`], so a large lecture video never sits in memory. Only files whose
+//! leading bytes match an accepted [`MediaKind`] are kept; the client's file name
+//! and `Content-Type` are ignored. A [`StagedFile`] that is never persisted is
+//! removed when it is dropped, so every early return cleans up after itself.
+
+use axum::extract::multipart::{Field, MultipartError};
+use axum::http::StatusCode;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::io::AsyncWriteExt;
+
+/// Staging area for uploads in progress; inside `assets/` so that moving a
+/// finished upload into `assets/{rid}/` is a rename on the same file system.
+pub const STAGING_DIR: &str = "assets/.uploads";
+
+/// Upload limit used when `APP_UPLOAD_LIMIT_MB` is unset or invalid.
+const DEFAULT_LIMIT_MB: u64 = 50;
+
+/// Leading bytes kept in memory for sniffing.
+const SNIFF_LEN: usize = 64;
+
+/// Largest accepted upload in bytes, for a single file and for a whole request.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UploadLimit(pub u64);
+
+impl UploadLimit {
+    /// Reads the limit in megabytes from `APP_UPLOAD_LIMIT_MB`, defaulting to 50 MB.
+    pub fn from_env() -> Self {
+        Self::from_megabytes(std::env::var("APP_UPLOAD_LIMIT_MB").ok().as_deref())
+    }
+
+    fn from_megabytes(value: Option<&str>) -> Self {
+        let mb = value
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .filter(|&mb| mb > 0)
+            .unwrap_or(DEFAULT_LIMIT_MB);
+        Self(mb.saturating_mul(1024 * 1024))
+    }
+
+    /// The limit as a request body size for `DefaultBodyLimit`.
+    pub fn body_limit(self) -> usize {
+        usize::try_from(self.0).unwrap_or(usize::MAX)
+    }
+}
+
+/// File types accepted for recordings, identified by their leading bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MediaKind {
+    Mp4,
+    WebM,
+    Ogg,
+    WebVtt,
+}
+
+impl MediaKind {
+    /// Video containers a recording may use.
+    pub const VIDEO: &[Self] = &[Self::Mp4, Self::WebM, Self::Ogg];
+    /// Caption formats a recording may use.
+    pub const CAPTIONS: &[Self] = &[Self::WebVtt];
+
+    /// The extension stored files of this kind are given.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Mp4 => "mp4",
+            Self::WebM => "webm",
+            Self::Ogg => "ogv",
+            Self::WebVtt => "vtt",
+        }
+    }
+
+    /// Identifies a file from its first bytes.
+    pub fn sniff(head: &[u8]) -> Option<Self> {
+        if head.len() >= 8 && &head[4..8] == b"ftyp" {
+            return Some(Self::Mp4);
+        }
+        // Matroska and WebM share the EBML header; only the `webm` doctype is accepted.
+        if head.starts_with(&[0x1A, 0x45, 0xDF, 0xA3]) && head.windows(4).any(|w| w == b"webm") {
+            return Some(Self::WebM);
+        }
+        if head.starts_with(b"OggS") {
+            return Some(Self::Ogg);
+        }
+        let text = head.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(head);
+        if let Some(rest) = text.strip_prefix(b"WEBVTT")
+            && rest.first().is_none_or(|b| matches!(b, b' ' | b'\t' | b'\n' | b'\r'))
+        {
+            return Some(Self::WebVtt);
+        }
+        None
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum UploadError {
+    #[error("upload exceeds the size limit")]
+    TooLarge,
+    #[error("upload is not an accepted file type")]
+    Unsupported,
+    #[error("upload interrupted: {0}")]
+    Multipart(#[from] MultipartError),
+    #[error("upload could not be stored: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl UploadError {
+    /// The status code reported to the client.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::TooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            Self::Unsupported => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            Self::Multipart(e) => e.status(),
+            Self::Io(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// A fully received upload waiting in the staging area.
+///
+/// Removed from disk on drop unless [`persist`](Self::persist) moved it first.
+#[derive(Debug)]
+pub struct StagedFile {
+    path: PathBuf,
+    kind: MediaKind,
+    persisted: bool,
+}
+
+impl StagedFile {
+    pub fn kind(&self) -> MediaKind {
+        self.kind
+    }
+
+    /// Atomically moves the upload to `dest`, replacing any file already there.
+    pub async fn persist(mut self, dest: &Path) -> std::io::Result<()> {
+        tokio::fs::rename(&self.path, dest).await?;
+        self.persisted = true;
+        Ok(())
+    }
+}
+
+impl Drop for StagedFile {
+    fn drop(&mut self) {
+        if !self.persisted {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// A unique staging path; uploads may run concurrently, also across processes.
+fn staging_path() -> PathBuf {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    let n = NEXT.fetch_add(1, Ordering::Relaxed);
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.subsec_nanos());
+    Path::new(STAGING_DIR).join(format!("{}-{nanos}-{n}.part", std::process::id()))
+}
+
+/// Streams `field` into the staging area, enforcing `limit` as chunks arrive.
+///
+/// Returns `Ok(None)` for an empty field (no file chosen), and
+/// [`UploadError::Unsupported`] unless the content sniffs as one of `accept`.
+pub async fn stage(
+    mut field: Field<'_>,
+    limit: UploadLimit,
+    accept: &[MediaKind],
+) -> Result<Option<StagedFile>, UploadError> {
+    tokio::fs::create_dir_all(STAGING_DIR).await?;
+    let path = staging_path();
+    let mut file = tokio::fs::File::create(&path).await?;
+    // From here on the guard owns the file; any `?` below removes it.
+    let mut staged = StagedFile { path, kind: MediaKind::Mp4, persisted: false };
+    let mut head = Vec::with_capacity(SNIFF_LEN);
+    let mut written: u64 = 0;
+    while let Some(chunk) = field.chunk().await? {
+        written += chunk.len() as u64;
+        if written > limit.0 {
+            return Err(UploadError::TooLarge);
+        }
+        if head.len() < SNIFF_LEN {
+            let take = (SNIFF_LEN - head.len()).min(chunk.len());
+            head.extend_from_slice(&chunk[..take]);
+        }
+        file.write_all(&chunk).await?;
+    }
+    if written == 0 {
+        return Ok(None);
+    }
+    file.sync_all().await?;
+    staged.kind = MediaKind::sniff(&head)
+        .filter(|kind| accept.contains(kind))
+        .ok_or(UploadError::Unsupported)?;
+    Ok(Some(staged))
+}
+
+#[cfg(test)]
+#[allow(clippy::pedantic, missing_docs)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sniff_recognises_containers_and_captions() {
+        assert_eq!(MediaKind::sniff(b"\0\0\0\x18ftypisom"), Some(MediaKind::Mp4));
+        assert_eq!(
+            MediaKind::sniff(b"\x1A\x45\xDF\xA3\x9F\x42\x86\x81\x01\x42\x82\x84webm"),
+            Some(MediaKind::WebM)
+        );
+        assert_eq!(MediaKind::sniff(b"\x1A\x45\xDF\xA3\x9F\x42\x82\x88matroska"), None);
+        assert_eq!(MediaKind::sniff(b"OggS\0\x02"), Some(MediaKind::Ogg));
+        assert_eq!(MediaKind::sniff(b"WEBVTT\n\n00:00.000"), Some(MediaKind::WebVtt));
+        assert_eq!(MediaKind::sniff(b"\xEF\xBB\xBFWEBVTT - Lecture"), Some(MediaKind::WebVtt));
+        assert_eq!(MediaKind::sniff(b"WEBVTTX"), None);
+        assert_eq!(MediaKind::sniff(b"<html><script>"), None);
+    }
+
+    #[test]
+    fn limit_reads_megabytes_with_default() {
+        assert_eq!(UploadLimit::from_megabytes(None), UploadLimit(50 * 1024 * 1024));
+        assert_eq!(UploadLimit::from_megabytes(Some("2048")), UploadLimit(2048 * 1024 * 1024));
+        assert_eq!(UploadLimit::from_megabytes(Some("0")), UploadLimit(50 * 1024 * 1024));
+        assert_eq!(UploadLimit::from_megabytes(Some("lots")), UploadLimit(50 * 1024 * 1024));
+    }
+}